@@ -1,11 +1,11 @@
 use ::mpc_ristretto::{Visibility, Visible};
 use mpc_ristretto::{
     authenticated_ristretto::AuthenticatedRistretto, authenticated_scalar::AuthenticatedScalar,
-    mpc_ristretto::MpcRistrettoPoint, network::QuicTwoPartyNet,
+    mpc_ristretto::MpcRistrettoPoint,
 };
 
 use crate::{
-    mpc_ristretto::is_equal_u64, mpc_scalar::PartyIDBeaverSource, IntegrationTest,
+    mpc_ristretto::is_equal_u64, mpc_scalar::PartyIDBeaverSource, IntegrationNet, IntegrationTest,
     IntegrationTestArgs,
 };
 
@@ -112,7 +112,7 @@ fn test_authenticated_open_failure(test_args: &IntegrationTestArgs) -> Result<()
 
     // Party 1 attempts to corrupt the result
     if test_args.party_id == 1 {
-        res += MpcRistrettoPoint::<QuicTwoPartyNet, PartyIDBeaverSource>::base_point_mul_u64(5);
+        res += MpcRistrettoPoint::<IntegrationNet, PartyIDBeaverSource>::base_point_mul_u64(5);
     }
 
     // Open and verify that an error is returned
@@ -382,7 +382,7 @@ fn test_multiscalar_mul(test_args: &IntegrationTestArgs) -> Result<(), String> {
 
     // Party 0 now tries to corrupt the multiscalar multiplication; open and validate authentication fails
     if test_args.party_id == 0 {
-        res += MpcRistrettoPoint::<QuicTwoPartyNet, PartyIDBeaverSource>::base_point_mul_u64(5);
+        res += MpcRistrettoPoint::<IntegrationNet, PartyIDBeaverSource>::base_point_mul_u64(5);
     }
 
     res.open_and_authenticate().map_or(Ok(()), |_| {
@@ -392,6 +392,40 @@ fn test_multiscalar_mul(test_args: &IntegrationTestArgs) -> Result<(), String> {
     Ok(())
 }
 
+/// Tests that a shared point detached from the network and attached again keeps its MAC
+fn test_detach_and_attach(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_value = AuthenticatedRistretto::from_private_u64(
+        42,
+        test_args.mac_key.clone(),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(1 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let share = shared_value.detach();
+    if share.visibility != Visibility::Shared || share.mac_share.is_none() {
+        return Err(format!(
+            "Expected a shared point with a MAC, got {:?}",
+            share
+        ));
+    }
+
+    let opened_value = AuthenticatedRistretto::attach(
+        share,
+        test_args.mac_key.clone(),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .open_and_authenticate()
+    .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if !is_equal_u64(opened_value.to_ristretto(), 42) {
+        return Err(format!("Expected {}, got {:?}", 42, opened_value.value()));
+    }
+
+    Ok(())
+}
+
 inventory::submit!(IntegrationTest {
     name: "authenticated-ristretto::test_share_and_open",
     test_fn: test_share_and_open
@@ -431,3 +465,8 @@ inventory::submit!(IntegrationTest {
     name: "authenticated-ristretto::test_multiscalar_mul",
     test_fn: test_multiscalar_mul
 });
+
+inventory::submit!(IntegrationTest {
+    name: "authenticated-ristretto::test_detach_and_attach",
+    test_fn: test_detach_and_attach,
+});
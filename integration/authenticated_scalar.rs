@@ -1,12 +1,15 @@
 use ::mpc_ristretto::{Visibility, Visible};
 use curve25519_dalek::scalar::Scalar;
 use mpc_ristretto::{
-    authenticated_scalar::AuthenticatedScalar, error::MpcNetworkError, mpc_scalar::scalar_to_u64,
-    network::QuicTwoPartyNet,
+    authenticated_scalar::AuthenticatedScalar,
+    error::{MpcError, MpcNetworkError},
+    mpc_scalar::scalar_to_u64,
 };
 use rand::{thread_rng, RngCore};
 
-use crate::{mpc_scalar::PartyIDBeaverSource, IntegrationTest, IntegrationTestArgs};
+use crate::{
+    mpc_scalar::PartyIDBeaverSource, IntegrationNet, IntegrationTest, IntegrationTestArgs,
+};
 
 fn test_share_and_open(test_args: &IntegrationTestArgs) -> Result<(), String> {
     // Party 0 hold 2 and party 1 holds 3
@@ -105,7 +108,7 @@ fn test_authenticated_open(test_args: &IntegrationTestArgs) -> Result<(), String
 /// Tests the ability to batch open and authenticate values
 fn test_batch_open_and_authenticate(test_args: &IntegrationTestArgs) -> Result<(), String> {
     // Party 0 shares a vector of values with party 1
-    let values: Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>> =
+    let values: Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>> =
         vec![1u64, 2u64, 3u64]
             .into_iter()
             .map(|value| {
@@ -177,6 +180,49 @@ fn test_authenticated_open_failure(test_args: &IntegrationTestArgs) -> Result<()
     Ok(())
 }
 
+/// Tests that the plaintext is only returned once the MAC check passes
+fn test_open_authenticated(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 holds 2 and party 1 holds 3
+    let value = if test_args.party_id == 0 { 2 } else { 3 };
+    let my_value = AuthenticatedScalar::from_private_u64(
+        value,
+        test_args.mac_key.clone(),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+
+    let shared_value1 = my_value
+        .share_secret(0 /* party_id */)
+        .map_err(|err| format!("Error sharing value: {:?}", err))?;
+    let shared_value2 = my_value
+        .share_secret(1 /* party_id */)
+        .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    // An honest opening authenticates and returns the value
+    let opened_value = (&shared_value1 * &shared_value2)
+        .open_authenticated()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if !opened_value.value().value().eq(&Scalar::from(6u64)) {
+        return Err(format!(
+            "Expected {}, got {}",
+            6,
+            scalar_to_u64(&opened_value.value().value())
+        ));
+    }
+
+    // Party 1 attempts to corrupt the result, the error should carry no value
+    let corrupted_value = if test_args.party_id == 1 {
+        shared_value1 + Scalar::from(5u64)
+    } else {
+        shared_value1
+    };
+
+    match corrupted_value.open_authenticated() {
+        Err(MpcError::AuthenticationError) => Ok(()),
+        res => Err(format!("Expected AuthenticationError, got {:?}", res)),
+    }
+}
+
 fn test_add(test_args: &IntegrationTestArgs) -> Result<(), String> {
     // Parties each hold a secret value and add them together, result is authenticated
     let value = if test_args.party_id == 0 { 2 } else { 3 };
@@ -470,21 +516,21 @@ fn test_product(test_args: &IntegrationTestArgs) -> Result<(), String> {
                 test_args.beaver_source.clone(),
             )
         })
-        .collect::<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>>();
+        .collect::<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>>();
 
     // Share the values
     let shared_values1 = my_values.iter()
         .map(|value| value.share_secret(0 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     let shared_values2 = my_values.iter()
         .map(|value| value.share_secret(1 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     // Take product, open and authenticate, then enforce equality
-    let product: AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource> =
+    let product: AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource> =
         shared_values1.iter().chain(shared_values2.iter()).product();
 
     let product_open = product
@@ -517,21 +563,21 @@ fn test_sum(test_args: &IntegrationTestArgs) -> Result<(), String> {
                 test_args.beaver_source.clone(),
             )
         })
-        .collect::<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>>();
+        .collect::<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>>();
 
     // Share the values
     let shared_values1 = my_values.iter()
         .map(|value| value.share_secret(0 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     let shared_values2 = my_values.iter()
         .map(|value| value.share_secret(1 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     // Take the sum, open and authenticate, then enforce equality
-    let sum: AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource> =
+    let sum: AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource> =
         shared_values1.iter().chain(shared_values2.iter()).sum();
 
     let sum_open = sum
@@ -564,17 +610,17 @@ fn test_linear_combination(test_args: &IntegrationTestArgs) -> Result<(), String
                 test_args.beaver_source.clone(),
             )
         })
-        .collect::<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>>();
+        .collect::<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>>();
 
     // Share the values
     let shared_values = my_values.iter()
         .map(|value| value.share_secret(0 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     let shared_coefficients = my_values.iter()
         .map(|value| value.share_secret(1 /* party_id */))
-        .collect::<Result<Vec<AuthenticatedScalar<QuicTwoPartyNet, PartyIDBeaverSource>>, MpcNetworkError>>()
+        .collect::<Result<Vec<AuthenticatedScalar<IntegrationNet, PartyIDBeaverSource>>, MpcNetworkError>>()
         .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
     // Correctly open the linear combination
@@ -680,6 +726,43 @@ fn test_random_linear_comb(test_args: &IntegrationTestArgs) -> Result<(), String
     Ok(())
 }
 
+/// Tests that a shared value detached from the network and attached again keeps its MAC
+fn test_detach_and_attach(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_value = AuthenticatedScalar::from_private_u64(
+        42,
+        test_args.mac_key.clone(),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let share = shared_value.detach();
+    if share.visibility != Visibility::Shared || share.mac_share.is_none() {
+        return Err(format!(
+            "Expected a shared value with a MAC, got {:?}",
+            share
+        ));
+    }
+
+    let opened_value = AuthenticatedScalar::attach(
+        share,
+        test_args.mac_key.clone(),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .open_and_authenticate()
+    .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if opened_value.to_scalar() != Scalar::from(42u64) {
+        return Err(format!(
+            "Expected 42, got {}",
+            scalar_to_u64(&opened_value.to_scalar())
+        ));
+    }
+
+    Ok(())
+}
+
 inventory::submit!(IntegrationTest {
     name: "authenticated-scalar::test_share_and_open",
     test_fn: test_share_and_open,
@@ -744,3 +827,13 @@ inventory::submit!(IntegrationTest {
     name: "authenticated-scalar::test_random_linear_comb",
     test_fn: test_random_linear_comb,
 });
+
+inventory::submit!(IntegrationTest {
+    name: "authenticated-scalar::test_open_authenticated",
+    test_fn: test_open_authenticated,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "authenticated-scalar::test_detach_and_attach",
+    test_fn: test_detach_and_attach,
+});
@@ -1,10 +1,16 @@
 mod authenticated_ristretto;
 mod authenticated_scalar;
+mod mpc_binary;
+mod mpc_bool;
+mod mpc_fixed_point;
+mod mpc_matrix;
 mod mpc_ristretto;
 mod mpc_scalar;
+mod mpc_u64;
 mod network;
+mod schnorr;
 
-use std::{borrow::Borrow, cell::RefCell, net::SocketAddr, process::exit, rc::Rc};
+use std::{borrow::Borrow, cell::RefCell, net::SocketAddr, process::exit, rc::Rc, time::Duration};
 
 use clap::Parser;
 use colored::Colorize;
@@ -13,17 +19,23 @@ use dns_lookup::lookup_host;
 
 use ::mpc_ristretto::{
     mpc_scalar::MpcScalar,
-    network::{MpcNetwork, QuicTwoPartyNet},
+    network::{
+        simulated_network::{SimulatedNetwork, SimulationConfig},
+        MpcNetwork, QuicTwoPartyNet,
+    },
 };
 use mpc_scalar::PartyIDBeaverSource;
 
+/// The network the integration tests run over, link conditions are simulated if requested
+type IntegrationNet = SimulatedNetwork<QuicTwoPartyNet>;
+
 /// Integration test arguments, common to all tests
 #[derive(Clone, Debug)]
 struct IntegrationTestArgs {
     party_id: u64,
-    net_ref: Rc<RefCell<QuicTwoPartyNet>>,
+    net_ref: Rc<RefCell<IntegrationNet>>,
     beaver_source: Rc<RefCell<PartyIDBeaverSource>>,
-    mac_key: MpcScalar<QuicTwoPartyNet, PartyIDBeaverSource>,
+    mac_key: MpcScalar<IntegrationNet, PartyIDBeaverSource>,
 }
 
 /// Integration test format
@@ -54,6 +66,15 @@ struct Args {
     /// Whether running in docker or not, used for peer lookup
     #[clap(long, takes_value = false, value_parser)]
     docker: bool,
+    /// The simulated one way latency of each message, in milliseconds
+    #[clap(long = "latency-ms", value_parser, default_value_t = 0)]
+    latency_ms: u64,
+    /// The simulated jitter on top of the latency, in milliseconds
+    #[clap(long = "jitter-ms", value_parser, default_value_t = 0)]
+    jitter_ms: u64,
+    /// The simulated bandwidth, in bytes per second; unlimited if not set
+    #[clap(long, value_parser)]
+    bandwidth: Option<u64>,
 }
 
 #[allow(unused_doc_comments, clippy::await_holding_refcell_ref)]
@@ -92,9 +113,18 @@ async fn main() {
     println!("Lookup successful, found peer at {:?}", peer_addr);
 
     // Build and connect to the network
-    let mut net = QuicTwoPartyNet::new(args.party, local_addr, peer_addr);
+    let mut net = QuicTwoPartyNet::new(args.party, local_addr, peer_addr).with_output_digest();
 
     net.connect().await.unwrap();
+    let net = SimulatedNetwork::new(
+        net,
+        SimulationConfig {
+            latency: Duration::from_millis(args.latency_ms),
+            jitter: Duration::from_millis(args.jitter_ms),
+            bandwidth: args.bandwidth,
+        },
+    )
+    .unwrap();
 
     // Share the global mac key (hardcoded to Scalar(15))
     let net_ref = Rc::new(RefCell::new(net));
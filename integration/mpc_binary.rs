@@ -0,0 +1,73 @@
+use mpc_ristretto::{
+    beaver::DaBitGenerator,
+    error::MpcError,
+    mpc_binary::MpcBinary,
+    mpc_scalar::{scalar_to_u64, MpcScalar},
+};
+
+use crate::{IntegrationTest, IntegrationTestArgs};
+
+/// Returns the integer with the given little endian bits
+fn bits_to_u64(bits: &[bool]) -> u64 {
+    bits.iter()
+        .enumerate()
+        .fold(0, |acc, (i, bit)| acc | ((*bit as u64) << i))
+}
+
+/// Tests converting values between the arithmetic and binary domains, with a shift in between
+fn test_arithmetic_binary_conversion(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let mut dabits =
+        DaBitGenerator::new(test_args.net_ref.clone(), test_args.beaver_source.clone());
+    let mut values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[13, 200],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    values.push(MpcScalar::from_public_u64(
+        7,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    ));
+
+    // Arithmetic to binary
+    let binary = MpcBinary::batch_from_arithmetic(&values, 8 /* num_bits */, &mut dabits)
+        .map_err(|err| format!("Error converting to binary: {:?}", err))?;
+    let mut res = Vec::new();
+    for value in binary.iter() {
+        let bits = value
+            .open()
+            .map_err(|err| format!("Error opening bits: {:?}", err))?;
+        res.push(bits_to_u64(&bits));
+    }
+    let expected = vec![13u64, 200, 7];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    // Shift in the binary domain, the high bit of 200 is dropped, and convert back
+    let shifted = binary
+        .iter()
+        .map(|value| value.shift_left(1))
+        .collect::<Vec<_>>();
+    let arithmetic = MpcBinary::batch_to_arithmetic(&shifted, &mut dabits)
+        .and_then(|values| MpcScalar::batch_open(&values).map_err(MpcError::NetworkError))
+        .map_err(|err| format!("Error converting to arithmetic: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![26u64, 144, 14];
+    if arithmetic != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, arithmetic));
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-binary::test_arithmetic_binary_conversion",
+    test_fn: test_arithmetic_binary_conversion,
+});
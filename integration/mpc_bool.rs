@@ -0,0 +1,100 @@
+use mpc_ristretto::{
+    mpc_bool::MpcBool,
+    mpc_scalar::{scalar_to_u64, MpcScalar},
+};
+
+use crate::{IntegrationTest, IntegrationTestArgs};
+
+/// Tests the logical operators on shared booleans, and selecting with a comparison's result
+fn test_logic(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 inputs the left hand booleans and party 1 the right hand booleans
+    let inputs = [false, false, true, true]
+        .iter()
+        .zip([false, true, false, true].iter())
+        .map(|(lhs, rhs)| {
+            let value = if test_args.party_id == 0 { *lhs } else { *rhs };
+            MpcBool::from_private_bool(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut lhs = Vec::new();
+    let mut rhs = Vec::new();
+    for input in inputs.iter() {
+        lhs.push(
+            input
+                .share_secret(0 /* party_id */)
+                .map_err(|err| format!("Error sharing boolean: {:?}", err))?,
+        );
+        rhs.push(
+            input
+                .share_secret(1 /* party_id */)
+                .map_err(|err| format!("Error sharing boolean: {:?}", err))?,
+        );
+    }
+
+    let open_all = |bits: Vec<MpcBool<_, _>>| -> Result<Vec<bool>, String> {
+        bits.iter()
+            .map(|bit| bit.open())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("Error opening booleans: {:?}", err))
+    };
+    let and = open_all(
+        MpcBool::batch_and(&lhs, &rhs).map_err(|err| format!("Error in AND: {:?}", err))?,
+    )?;
+    let xor = open_all(
+        MpcBool::batch_xor(&lhs, &rhs).map_err(|err| format!("Error in XOR: {:?}", err))?,
+    )?;
+    let or = open_all(lhs.iter().zip(rhs.iter()).map(|(a, b)| a | b).collect())?;
+    let not = open_all(lhs.iter().map(|a| !a).collect())?;
+
+    for (name, res, expected) in [
+        ("AND", and, vec![false, false, false, true]),
+        ("XOR", xor, vec![false, true, true, false]),
+        ("OR", or, vec![false, true, true, true]),
+        ("NOT", not, vec![true, true, false, false]),
+    ] {
+        if res != expected {
+            return Err(format!(
+                "{}: Expected: {:?}, Got: {:?}",
+                name, expected, res
+            ));
+        }
+    }
+
+    // (3 < 5) AND NOT (5 < 3) selects the first value
+    let values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[3, 5],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let less = values[0]
+        .less_than(&values[1])
+        .map(MpcBool::from_scalar)
+        .map_err(|err| format!("Error comparing values: {:?}", err))?;
+    let greater = values[1]
+        .less_than(&values[0])
+        .map(MpcBool::from_scalar)
+        .map_err(|err| format!("Error comparing values: {:?}", err))?;
+    let res = (&less & &!&greater)
+        .select(&values[0], &values[1])
+        .and_then(|selected| selected.commit_and_open())
+        .map_err(|err| format!("Error selecting value: {:?}", err))?;
+    if scalar_to_u64(&res.value()) != 3 {
+        return Err(format!("Expected: 3, Got: {}", scalar_to_u64(&res.value())));
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-bool::test_logic",
+    test_fn: test_logic,
+});
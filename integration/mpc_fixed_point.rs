@@ -0,0 +1,88 @@
+use mpc_ristretto::mpc_fixed_point::MpcFixedPoint;
+
+use crate::{IntegrationTest, IntegrationTestArgs};
+
+/// Tests the square root of shared fixed-point numbers across several orders of magnitude
+fn test_sqrt(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let values = [2.0, 0.25, 12345.678, 3e-5, 0.0];
+    for value in values.iter() {
+        // Party 0 inputs the value, party 1 only follows along
+        let input = MpcFixedPoint::from_private_f64(
+            if test_args.party_id == 0 { *value } else { 0.0 },
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        )
+        .share_secret(0 /* party_id */)
+        .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+        let res = input
+            .sqrt(6 /* iterations */)
+            .and_then(|root| root.open())
+            .map_err(|err| format!("Error computing square root: {:?}", err))?;
+
+        let expected = value.sqrt();
+        if (res - expected).abs() > 1e-6 * expected.max(1e-3) {
+            return Err(format!(
+                "sqrt({}): Expected: {}, Got: {}",
+                value, expected, res
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Tests the approximations of exp, ln and sigmoid on shared fixed-point numbers
+fn test_exp_ln_sigmoid(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    type Function = fn(f64) -> f64;
+    let cases: [(&str, Function, &[f64]); 3] = [
+        ("exp", f64::exp, &[0.0, 1.0, -3.5, 10.0, -20.0]),
+        ("ln", f64::ln, &[1.0, 2.0, 0.001, 12345.678]),
+        (
+            "sigmoid",
+            |x| 1.0 / (1.0 + (-x).exp()),
+            &[0.0, 2.0, -2.0, 40.0, -40.0],
+        ),
+    ];
+
+    for (name, function, values) in cases.iter() {
+        for value in values.iter() {
+            // Party 0 inputs the value, party 1 only follows along
+            let input = MpcFixedPoint::from_private_f64(
+                if test_args.party_id == 0 { *value } else { 0.0 },
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+            .share_secret(0 /* party_id */)
+            .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+            let res = match *name {
+                "exp" => input.exp(),
+                "ln" => input.ln(),
+                _ => input.sigmoid(),
+            }
+            .and_then(|res| res.open())
+            .map_err(|err| format!("Error computing {}: {:?}", name, err))?;
+
+            let expected = function(*value);
+            if (res - expected).abs() > 1e-8 + 1e-8 * expected.abs() {
+                return Err(format!(
+                    "{}({}): Expected: {}, Got: {}",
+                    name, value, expected, res
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-fixed-point::test_sqrt",
+    test_fn: test_sqrt,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-fixed-point::test_exp_ln_sigmoid",
+    test_fn: test_exp_ln_sigmoid,
+});
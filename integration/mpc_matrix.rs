@@ -0,0 +1,179 @@
+use curve25519_dalek::scalar::Scalar;
+use mpc_ristretto::{
+    mpc_matrix::MpcMatrix,
+    mpc_scalar::{scalar_to_u64, MpcScalar},
+};
+
+use crate::{
+    mpc_scalar::PartyIDBeaverSource, IntegrationNet, IntegrationTest, IntegrationTestArgs,
+};
+
+/// Shares a matrix of u64 entries input by the given party
+fn share_matrix(
+    rows: usize,
+    cols: usize,
+    entries: &[u64],
+    party_id: u64,
+    test_args: &IntegrationTestArgs,
+) -> Result<MpcMatrix<IntegrationNet, PartyIDBeaverSource>, String> {
+    let entries = MpcScalar::batch_share_secrets(
+        party_id,
+        &MpcScalar::from_private_u64_slice(
+            entries,
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing matrix: {:?}", err))?;
+
+    Ok(MpcMatrix::new(rows, cols, entries))
+}
+
+/// Tests the product of a 2x3 matrix shared by party 0 with a 3x2 matrix shared by party 1
+fn test_matrix_mul(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let lhs = share_matrix(2, 3, &[1, 2, 3, 4, 5, 6], 0 /* party_id */, test_args)?;
+    let rhs = share_matrix(
+        3,
+        2,
+        &[7, 8, 9, 10, 11, 12],
+        1, /* party_id */
+        test_args,
+    )?;
+
+    let product = lhs
+        .mul(&rhs)
+        .map_err(|err| format!("Error multiplying matrices: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening matrix: {:?}", err))?;
+    let expected = [58u64, 64, 139, 154]
+        .iter()
+        .map(|value| Scalar::from(*value))
+        .collect::<Vec<_>>();
+    let res = product
+        .entries()
+        .iter()
+        .map(|entry| entry.value())
+        .collect::<Vec<_>>();
+    if (product.rows(), product.cols()) != (2, 2) || res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    // A product with a public matrix is computed locally
+    let identity = MpcMatrix::new(
+        2,
+        2,
+        MpcScalar::from_public_u64_slice(
+            &[1, 0, 0, 1],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    );
+    let res = identity
+        .mul(&lhs)
+        .map_err(|err| format!("Error multiplying matrices: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening matrix: {:?}", err))?
+        .entries()
+        .iter()
+        .map(|entry| entry.value())
+        .collect::<Vec<_>>();
+    let expected = (1u64..=6).map(Scalar::from).collect::<Vec<_>>();
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    // Matrices of mismatched shapes cannot be multiplied
+    if lhs.mul(&lhs).is_ok() {
+        return Err("Expected multiplying a 2x3 matrix by a 2x3 matrix to fail".to_string());
+    }
+
+    Ok(())
+}
+
+/// Opens a matrix and returns its entries as u64s
+fn open_matrix(
+    matrix: &MpcMatrix<IntegrationNet, PartyIDBeaverSource>,
+) -> Result<Vec<u64>, String> {
+    Ok(matrix
+        .open()
+        .map_err(|err| format!("Error opening matrix: {:?}", err))?
+        .entries()
+        .iter()
+        .map(|entry| scalar_to_u64(&entry.value()))
+        .collect())
+}
+
+/// Tests the entry-wise arithmetic and the transpose of shared matrices
+fn test_matrix_arithmetic(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let lhs = share_matrix(2, 3, &[1, 2, 3, 4, 5, 6], 0 /* party_id */, test_args)?;
+    let rhs = share_matrix(
+        2,
+        3,
+        &[7, 8, 9, 10, 11, 12],
+        1, /* party_id */
+        test_args,
+    )?;
+    let scalar = MpcScalar::from_private_u64(
+        2,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let transpose = lhs.transpose();
+    let results = vec![
+        rhs.sub(&lhs),
+        lhs.add(&rhs),
+        lhs.hadamard(&rhs),
+        lhs.scale(&scalar),
+        Ok(transpose.clone()),
+    ]
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| format!("Error computing on matrices: {:?}", err))?;
+
+    let expected = [
+        vec![6u64, 6, 6, 6, 6, 6],
+        vec![8, 10, 12, 14, 16, 18],
+        vec![7, 16, 27, 40, 55, 72],
+        vec![2, 4, 6, 8, 10, 12],
+        vec![1, 4, 2, 5, 3, 6],
+    ];
+    for (res, expected) in results.iter().zip(expected.iter()) {
+        let res = open_matrix(res)?;
+        if &res != expected {
+            return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+        }
+    }
+
+    // The transpose is 3x2, and its rows convert back to the same matrix
+    if (transpose.rows(), transpose.cols()) != (3, 2) {
+        return Err(format!(
+            "Expected a 3x2 transpose, Got: {}x{}",
+            transpose.rows(),
+            transpose.cols()
+        ));
+    }
+    let entries: Vec<_> = MpcMatrix::from_rows(transpose.to_rows()).into();
+    if entries.len() != 6 {
+        return Err(format!("Expected 6 entries, Got: {}", entries.len()));
+    }
+
+    // Matrices of mismatched shapes cannot be added
+    if lhs.add(&transpose).is_ok() {
+        return Err("Expected adding a 2x3 matrix and a 3x2 matrix to fail".to_string());
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-matrix::test_matrix_mul",
+    test_fn: test_matrix_mul,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-matrix::test_matrix_arithmetic",
+    test_fn: test_matrix_arithmetic,
+});
@@ -247,7 +247,7 @@ fn test_sub(test_args: &IntegrationTestArgs) -> Result<(), String> {
         .open()
         .map_err(|err| format!("Error opening value: {:?}", err))?;
     if !is_equal_u64(public_shared.value(), 16) {
-        return Err(format!("Expected {}, got {:?}", 16, public_shared.value()))?;
+        return Err(format!("Expected {}, got {:?}", 16, public_shared.value()));
     }
 
     // Public value - public value
@@ -364,6 +364,151 @@ fn test_multiscalar_mul(test_args: &IntegrationTestArgs) -> Result<(), String> {
     Ok(())
 }
 
+fn test_multiscalar_mul_difference(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 holds the scalars, party 1 holds the points
+    // Computing (2 * 5 + 3 * 7) - 31 * 1 == 0
+    let a_values = [2u64, 3u64];
+    let b_values = [31u64];
+    let p_values = [5u64, 7u64];
+
+    let a = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &a_values
+            .iter()
+            .map(|value| {
+                MpcScalar::from_private_u64(
+                    *value,
+                    test_args.net_ref.clone(),
+                    test_args.beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let b = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &b_values
+            .iter()
+            .map(|value| {
+                MpcScalar::from_private_u64(
+                    *value,
+                    test_args.net_ref.clone(),
+                    test_args.beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let p = MpcRistrettoPoint::batch_share_secrets(
+        1, /* party_id */
+        &p_values
+            .iter()
+            .map(|value| {
+                MpcRistrettoPoint::from_private_u64(
+                    *value,
+                    test_args.net_ref.clone(),
+                    test_args.beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    // Mix in a public point on the subtracted side
+    let q = vec![MpcRistrettoPoint::from_public_u64(
+        1,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )];
+
+    let res = MpcRistrettoPoint::multiscalar_mul_difference(&a, &p, &b, &q)
+        .map_err(|err| format!("Error computing multiscalar_mul_difference: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+
+    if !is_equal_u64(res.value(), 0) {
+        return Err(format!("Expected identity, got {:?}", res.value()));
+    }
+
+    // An unsatisfied equation should open to a non-identity point; (2 * 5 + 3 * 7) - 30 * 1 == 1
+    let b_unsatisfied = vec![
+        &b[0]
+            - MpcScalar::from_public_u64(
+                1,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            ),
+    ];
+    let res = MpcRistrettoPoint::multiscalar_mul_difference(&a, &p, &b_unsatisfied, &q)
+        .map_err(|err| format!("Error computing multiscalar_mul_difference: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+
+    if !is_equal_u64(res.value(), 1) {
+        return Err(format!("Expected {}, got {:?}", 1, res.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests that the inner product of shared scalars with public weights opens to the inner product
+/// times the base point
+fn test_inner_product_to_group(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 holds the scalars, the last scalar is public
+    // Computing (2 * 5 + 3 * 7 + 4 * 11) * G == 75 * G
+    let shared_values = [2u64, 3u64];
+    let mut scalars = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &shared_values
+            .iter()
+            .map(|value| {
+                MpcScalar::from_private_u64(
+                    *value,
+                    test_args.net_ref.clone(),
+                    test_args.beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    scalars.push(MpcScalar::from_public_u64(
+        4,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    ));
+    let weights = [5u64, 7u64, 11u64].map(Scalar::from);
+
+    let res = MpcRistrettoPoint::inner_product_to_group(&scalars, &weights)
+        .map_err(|err| format!("Error computing inner product: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+
+    if !is_equal_u64(res.value(), 75) {
+        return Err(format!("Expected {}, got {:?}", 75, res.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests that a public point shared trivially between the parties opens back to the point
+fn test_shared_from_public(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared = MpcRistrettoPoint::shared_from_public(
+        RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64),
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+
+    let res = shared
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+
+    if !is_equal_u64(res.value(), 7) {
+        return Err(format!("Expected {}, got {:?}", 7, res.value()));
+    }
+
+    Ok(())
+}
+
 inventory::submit!(IntegrationTest {
     name: "mpc-ristretto::test_share_and_open",
     test_fn: test_share_and_open,
@@ -403,3 +548,18 @@ inventory::submit!(IntegrationTest {
     name: "mpc-ristretto::test_multiscalar_mul",
     test_fn: test_multiscalar_mul,
 });
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-ristretto::test_multiscalar_mul_difference",
+    test_fn: test_multiscalar_mul_difference,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-ristretto::test_inner_product_to_group",
+    test_fn: test_inner_product_to_group,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-ristretto::test_shared_from_public",
+    test_fn: test_shared_from_public,
+});
@@ -1,14 +1,14 @@
 use curve25519_dalek::scalar::Scalar;
 
 use mpc_ristretto::{
-    beaver::SharedValueSource,
-    error::MpcNetworkError,
-    mpc_scalar::{scalar_to_u64, MpcScalar},
-    network::QuicTwoPartyNet,
+    beaver::{MatrixTriplet, PermutationCorrelation, SharedValueSource},
+    error::{MpcError, MpcNetworkError},
+    mpc_scalar::{lazy::LazyGraph, scalar_to_u64, MpcScalar},
+    Visibility, Visible,
 };
 use rand::{thread_rng, RngCore};
 
-use crate::{IntegrationTest, IntegrationTestArgs};
+use crate::{IntegrationNet, IntegrationTest, IntegrationTestArgs};
 
 /// Returns beaver triples (0, 0, 0) for party 0 and (1, 1, 1) for party 1
 #[derive(Debug)]
@@ -43,6 +43,57 @@ impl SharedValueSource<Scalar> for PartyIDBeaverSource {
         (Scalar::one(), Scalar::one())
     }
 
+    /// a = 2 and a^2 = 4 are each split evenly between the parties
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        (Scalar::from(1u64), Scalar::from(2u64))
+    }
+
+    /// Every entry of A is 2 and of B is 3, shared as the entries of a triplet, so every entry
+    /// of C is 6 * m, shared as (2 * m, 4 * m)
+    fn next_matrix_triplet(
+        &mut self,
+        n: usize,
+        m: usize,
+        k: usize,
+    ) -> Result<MatrixTriplet<Scalar>, MpcError> {
+        let (a, b, c) = if self.party_id == 0 {
+            (1u64, 3u64, 2 * m as u64)
+        } else {
+            (1u64, 0u64, 4 * m as u64)
+        };
+
+        Ok((
+            vec![Scalar::from(a); n * m],
+            vec![Scalar::from(b); m * k],
+            vec![Scalar::from(c); n * k],
+        ))
+    }
+
+    /// r = 2 and each of its powers 2^i are split evenly between the parties
+    fn next_exp_tuple(&mut self, k: usize) -> Result<Vec<Scalar>, MpcError> {
+        Ok((0..k).map(|i| Scalar::from(1u64 << i)).collect())
+    }
+
+    /// Each party rotates the vector by one, with a = 1 and b = 2 in every entry, so that
+    /// delta = pi(a) - b = -1 in every entry
+    fn next_permutation_correlation(
+        &mut self,
+        n: usize,
+        permuting_party: u64,
+    ) -> Result<PermutationCorrelation<Scalar>, MpcError> {
+        Ok(if self.party_id == permuting_party {
+            PermutationCorrelation::Permuter {
+                permutation: (0..n).map(|i| (i + 1) % n).collect(),
+                delta: vec![-Scalar::one(); n],
+            }
+        } else {
+            PermutationCorrelation::Masker {
+                a: vec![Scalar::one(); n],
+                b: vec![Scalar::from(2u64); n],
+            }
+        })
+    }
+
     fn next_shared_value(&mut self) -> Scalar {
         Scalar::from(self.party_id)
     }
@@ -349,7 +400,7 @@ fn test_commit_and_open(test_args: &IntegrationTestArgs) -> Result<(), String> {
 /// Test that sharing a batch of values works properly
 fn test_open_batch(test_args: &IntegrationTestArgs) -> Result<(), String> {
     // Party 0 shares values with party 1
-    let values: Vec<MpcScalar<QuicTwoPartyNet, PartyIDBeaverSource>> = vec![1u64, 2u64, 3u64]
+    let values: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = vec![1u64, 2u64, 3u64]
         .into_iter()
         .map(|value| {
             MpcScalar::from_private_u64(
@@ -377,7 +428,7 @@ fn test_open_batch(test_args: &IntegrationTestArgs) -> Result<(), String> {
 /// Tests that committing and opening in a batch works properly
 fn test_commit_and_open_batch(test_args: &IntegrationTestArgs) -> Result<(), String> {
     // Party 0 shares a vector of values, both parties commit and open
-    let values: Vec<MpcScalar<QuicTwoPartyNet, PartyIDBeaverSource>> = vec![1u64, 2u64, 3u64]
+    let values: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = vec![1u64, 2u64, 3u64]
         .into_iter()
         .map(|value| {
             MpcScalar::from_private_u64(
@@ -716,72 +767,1427 @@ fn test_simple_mpc(test_args: &IntegrationTestArgs) -> Result<(), String> {
 }
 
 // Register the tests
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_add",
-    test_fn: test_add,
-});
+/// Tests comparing shared values, including equal values
+fn test_less_than(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the lhs values, party 1 shares the rhs values
+    let my_values = if test_args.party_id == 0 {
+        vec![3u64, 9u64, 5u64, 0u64]
+    } else {
+        vec![4u64, 2u64, 5u64, u64::MAX]
+    };
+    let my_values: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = my_values
+        .into_iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect();
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_sub",
-    test_fn: test_sub,
-});
+    let lhs = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let rhs = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_mul",
-    test_fn: test_mul
-});
+    let res = MpcScalar::batch_open(
+        &MpcScalar::batch_less_than(&lhs, &rhs)
+            .map_err(|err| format!("Error comparing values: {:?}", err))?,
+    )
+    .map_err(|err| format!("Error opening values: {:?}", err))?
+    .iter()
+    .map(|value| scalar_to_u64(&value.value()))
+    .collect::<Vec<_>>();
+
+    let expected = vec![1u64, 0u64, 0u64, 1u64];
+    if res.ne(&expected) {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_batch_mul",
-    test_fn: test_batch_mul,
-});
+    Ok(())
+}
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_open_value",
-    test_fn: test_open_value,
-});
+/// Tests the order comparisons of a shared value with smaller, equal, and larger values
+fn test_order_comparisons(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &[4u64, 5, 6].map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        }),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let value = &values[1];
+
+    let mut bits = Vec::new();
+    for other in values.iter() {
+        bits.extend(
+            [
+                value.lt(other),
+                value.le(other),
+                value.gt(other),
+                value.ge(other),
+            ]
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("Error comparing values: {:?}", err))?,
+        );
+    }
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_commit_and_open",
-    test_fn: test_commit_and_open,
-});
+    let res = MpcScalar::batch_open(&bits)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|bit| scalar_to_u64(&bit.value()))
+        .collect::<Vec<_>>();
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_open_batch",
-    test_fn: test_open_batch,
-});
+    // (lt, le, gt, ge) of 5 against 4, 5, and 6
+    let expected = vec![0u64, 0, 1, 1, 0, 1, 0, 1, 1, 1, 0, 0];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_commit_and_open_batch",
-    test_fn: test_commit_and_open_batch,
-});
+    Ok(())
+}
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_receive_value",
-    test_fn: test_receive_value,
-});
+/// Tests the secret equality of shared values against equal and unequal values
+fn test_eq_secret(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the lhs values, party 1 shares the rhs values
+    let my_values = if test_args.party_id == 0 {
+        vec![3u64, 7u64, 0u64]
+    } else {
+        vec![4u64, 7u64, u64::MAX]
+    };
+    let my_values = my_values
+        .into_iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_sum",
-    test_fn: test_sum,
-});
+    let lhs = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let rhs = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_product",
-    test_fn: test_product,
-});
+    let mut bits = MpcScalar::batch_eq_secret(&lhs, &rhs)
+        .map_err(|err| format!("Error comparing values: {:?}", err))?;
+    bits.push(
+        lhs[1]
+            .eq_secret(&rhs[1])
+            .map_err(|err| format!("Error comparing values: {:?}", err))?,
+    );
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_linear_combination",
-    test_fn: test_linear_combination,
-});
+    let res = MpcScalar::batch_open(&bits)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|bit| scalar_to_u64(&bit.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![0u64, 1u64, 0u64, 1u64];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_random_linear_comb",
-    test_fn: test_random_linear_comb
-});
+    Ok(())
+}
 
-inventory::submit!(IntegrationTest {
-    name: "mpc-scalar::test_simple_mpc",
-    test_fn: test_simple_mpc,
+/// Tests that the payload is revealed only when enough predicates are satisfied
+fn test_reveal_if_count_at_least(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the values and their predicate bits, party 1 shares the payload
+    let shared_values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[10u64, 20u64, 30u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let predicate_bits = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[1u64, 0u64, 1u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing predicate bits: {:?}", err))?;
+    let payload = MpcScalar::from_private_u64(
+        42,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(1 /* party_id */)
+    .map_err(|err| format!("Error sharing payload: {:?}", err))?;
+
+    // Two of the predicates are satisfied, the payload is revealed at a threshold of two
+    let res = MpcScalar::reveal_if_count_at_least(&shared_values, &predicate_bits, 2, &payload)
+        .map_err(|err| format!("Error revealing payload: {:?}", err))?
+        .map(|value| scalar_to_u64(&value.value()));
+    if res != Some(42) {
+        return Err(format!("Expected: {:?}, Got: {:?}", Some(42), res));
+    }
+
+    // The payload is withheld at a threshold of three
+    let res = MpcScalar::reveal_if_count_at_least(&shared_values, &predicate_bits, 3, &payload)
+        .map_err(|err| format!("Error revealing payload: {:?}", err))?;
+    if let Some(value) = res {
+        return Err(format!("Expected no payload, Got: {:?}", value.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests reading from a shared table at a shared index
+fn test_oblivious_read(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the table, party 1 shares the index
+    let table = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[10u64, 20u64, 30u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing table: {:?}", err))?;
+    let indices = MpcScalar::batch_share_secrets(
+        1, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[1u64, 3u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing indices: {:?}", err))?;
+
+    let res = MpcScalar::oblivious_read(&table, &indices[0])
+        .map_err(|err| format!("Error reading table: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if res.value() != Scalar::from(20u64) {
+        return Err(format!("Expected: 20, Got: {:?}", res.value()));
+    }
+
+    // An index past the end of the table should fail the range assertion
+    match MpcScalar::oblivious_read(&table, &indices[1]) {
+        Err(MpcError::AssertionError(_)) => Ok(()),
+        Err(err) => Err(format!("Expected an assertion error, Got: {:?}", err)),
+        Ok(_) => Err("Expected an out of range read to fail".to_string()),
+    }
+}
+
+/// Tests writing to a shared table at a shared index
+fn test_oblivious_write(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the table and the value, party 1 shares the index
+    let mut table = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[10u64, 20u64, 30u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing table: {:?}", err))?;
+    let value = MpcScalar::from_private_u64(
+        99,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+    let index = MpcScalar::from_private_u64(
+        2,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(1 /* party_id */)
+    .map_err(|err| format!("Error sharing index: {:?}", err))?;
+
+    MpcScalar::oblivious_write(&mut table, &index, &value)
+        .map_err(|err| format!("Error writing table: {:?}", err))?;
+
+    let res = MpcScalar::oblivious_read(&table, &index)
+        .map_err(|err| format!("Error reading table: {:?}", err))?
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if res.value() != Scalar::from(99u64) {
+        return Err(format!("Expected: 99, Got: {:?}", res.value()));
+    }
+
+    // The other entries are unchanged
+    let res = MpcScalar::batch_open(&table)
+        .map_err(|err| format!("Error opening table: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![10u64, 20u64, 99u64];
+    if res.ne(&expected) {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests merging two sorted shared sequences into a single sorted sequence
+fn test_bitonic_merge(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the first sorted half, party 1 shares the second
+    let my_values = if test_args.party_id == 0 {
+        vec![1u64, 4u64, 6u64, 9u64]
+    } else {
+        vec![2u64, 3u64, 7u64, 8u64]
+    };
+    let my_values = MpcScalar::from_private_u64_slice(
+        &my_values,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+
+    let a = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let b = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let res = MpcScalar::batch_open(
+        &MpcScalar::bitonic_merge(&a, &b)
+            .map_err(|err| format!("Error merging values: {:?}", err))?,
+    )
+    .map_err(|err| format!("Error opening values: {:?}", err))?
+    .iter()
+    .map(|value| scalar_to_u64(&value.value()))
+    .collect::<Vec<_>>();
+
+    let expected = vec![1u64, 2u64, 3u64, 4u64, 6u64, 7u64, 8u64, 9u64];
+    if res.ne(&expected) {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests that a normalized shared vector sums to one
+fn test_normalize(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the vector
+    let values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[1u64, 2u64, 5u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let normalized =
+        MpcScalar::normalize(&values).map_err(|err| format!("Error normalizing: {:?}", err))?;
+    let opened = MpcScalar::batch_open(&normalized)
+        .map_err(|err| format!("Error opening values: {:?}", err))?;
+
+    let sum = opened
+        .iter()
+        .fold(Scalar::zero(), |acc, value| acc + value.value());
+    if sum != Scalar::one() {
+        return Err(format!("Expected: 1, Got: {:?}", sum));
+    }
+
+    let expected = Scalar::from(2u64) * Scalar::from(8u64).invert();
+    if opened[1].value() != expected {
+        return Err(format!(
+            "Expected: {:?}, Got: {:?}",
+            expected,
+            opened[1].value()
+        ));
+    }
+
+    // A vector that sums to zero cannot be normalized
+    let zeros = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[0u64, 0u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    match MpcScalar::normalize(&zeros) {
+        Err(MpcError::ArithmeticError(_)) => Ok(()),
+        Err(err) => Err(format!("Expected an arithmetic error, Got: {:?}", err)),
+        Ok(_) => Err("Expected normalizing a zero sum to fail".to_string()),
+    }
+}
+
+/// Tests squaring shared and public values with square pairs
+fn test_square(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_value = MpcScalar::from_private_u64(
+        5,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let res = shared_value
+        .square()
+        .and_then(|square| square.open())
+        .map_err(|err| format!("Error squaring value: {:?}", err))?;
+    if res.value() != Scalar::from(25u64) {
+        return Err(format!("Expected: 25, Got: {:?}", res.value()));
+    }
+
+    // A batch mixing shared and public values
+    let public_value = MpcScalar::from_public_u64(
+        6,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let squares = MpcScalar::batch_square(&[public_value, shared_value])
+        .and_then(|squares| MpcScalar::batch_open(&squares))
+        .map_err(|err| format!("Error squaring values: {:?}", err))?;
+    let expected = vec![Scalar::from(36u64), Scalar::from(25u64)];
+    let res = squares
+        .iter()
+        .map(|square| square.value())
+        .collect::<Vec<_>>();
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests raising shared and public values to a public power and evaluating a polynomial with
+/// an exponentiation tuple
+fn test_pow(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_value = MpcScalar::from_private_u64(
+        3,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let exp = MpcScalar::from_public_u64(
+        5,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let res = shared_value
+        .pow(&exp)
+        .and_then(|power| power.try_open())
+        .map_err(|err| format!("Error raising value to a power: {:?}", err))?;
+    if res.value() != Scalar::from(243u64) {
+        return Err(format!("Expected: 243, Got: {:?}", res.value()));
+    }
+
+    // 2 + 3x + x^3 at x = 3
+    let coeffs = [2u64, 3, 0, 1].map(Scalar::from);
+    let res = shared_value
+        .evaluate_polynomial(&coeffs)
+        .and_then(|value| value.try_open())
+        .map_err(|err| format!("Error evaluating polynomial: {:?}", err))?;
+    if res.value() != Scalar::from(38u64) {
+        return Err(format!("Expected: 38, Got: {:?}", res.value()));
+    }
+
+    let public_value = MpcScalar::from_public_u64(
+        2,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let exp = MpcScalar::from_public_u64(
+        10,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let res = public_value
+        .pow(&exp)
+        .map_err(|err| format!("Error raising value to a power: {:?}", err))?;
+    if res.visibility() != Visibility::Public || res.value() != Scalar::from(1024u64) {
+        return Err(format!("Expected: public 1024, Got: {:?}", res.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests raising shared values to public and shared exponents
+fn test_pow_public_and_secret(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let my_value = MpcScalar::from_private_u64(
+        if test_args.party_id == 0 { 3 } else { 5 },
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let base = my_value
+        .share_secret(0 /* party_id */)
+        .map_err(|err| format!("Error sharing value: {:?}", err))?;
+    let exp = my_value
+        .share_secret(1 /* party_id */)
+        .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let public_base = MpcScalar::from_public_u64(
+        2,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let public_exp = |exp: Scalar| {
+        MpcScalar::from_public_scalar(
+            exp,
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        )
+    };
+    // An exponent of 2^64 + 1, which does not fit in a u64
+    let large_exp = Scalar::from(u64::MAX) + Scalar::from(2u64);
+    let results = vec![
+        base.pow(&public_exp(Scalar::from(13u64))),
+        base.pow(&public_exp(Scalar::zero())),
+        base.pow(&exp),
+        public_base.pow(&exp),
+        base.pow(&public_exp(large_exp)),
+    ]
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| format!("Error raising value to a power: {:?}", err))?;
+
+    let res = MpcScalar::batch_open(&results)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| value.value())
+        .collect::<Vec<_>>();
+    let three_pow_large = (0..64).fold(Scalar::from(3u64), |acc, _| acc * acc) * Scalar::from(3u64);
+    let expected = vec![
+        Scalar::from(1_594_323u64),
+        Scalar::one(),
+        Scalar::from(243u64),
+        Scalar::from(32u64),
+        three_pow_large,
+    ];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests the inner product of shared vectors, with a public entry in one of them
+fn test_dot(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the lhs vector, party 1 shares the rhs vector
+    let my_values = if test_args.party_id == 0 {
+        vec![1u64, 2u64, 3u64]
+    } else {
+        vec![4u64, 5u64, 6u64]
+    };
+    let my_values = my_values
+        .into_iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut lhs = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let mut rhs = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    lhs.push(lhs[2].clone());
+    rhs.push(MpcScalar::from_public_u64(
+        7,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    ));
+
+    let res = MpcScalar::dot(&lhs, &rhs)
+        .and_then(|value| value.try_open())
+        .map_err(|err| format!("Error taking inner product: {:?}", err))?;
+    if res.value() != Scalar::from(53u64) {
+        return Err(format!("Expected: 53, Got: {:?}", res.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests evaluating a polynomial with shared coefficients at shared and public points
+fn test_eval_poly(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the coefficients of 2 + 3x + x^3, party 1 shares the point x = 3
+    let my_values = if test_args.party_id == 0 {
+        vec![2u64, 3, 0, 1]
+    } else {
+        vec![3u64, 0, 0, 0]
+    };
+    let my_values = MpcScalar::from_private_u64_slice(
+        &my_values,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let coeffs = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let x = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values[..1])
+        .map_err(|err| format!("Error sharing value: {:?}", err))?
+        .remove(0);
+    let public_x = MpcScalar::from_public_u64(
+        2,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+
+    let results = vec![
+        MpcScalar::eval_poly(&coeffs, &x),
+        MpcScalar::eval_poly(&coeffs, &public_x),
+        MpcScalar::eval_poly(&coeffs[..1], &x),
+    ]
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| format!("Error evaluating polynomial: {:?}", err))?;
+
+    let res = MpcScalar::batch_open(&results)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![38u64, 16, 2];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests the prefix sums and prefix products of a shared vector, which may hold a zero
+fn test_prefixes(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[2, 3, 4, 5, 0],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let products = MpcScalar::prefix_prod(&values)
+        .map_err(|err| format!("Error computing prefix products: {:?}", err))?;
+    let sums = MpcScalar::prefix_sum(&values);
+
+    let res = MpcScalar::batch_open(&[products, sums].concat())
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![2u64, 6, 24, 120, 0, 2, 5, 9, 14, 14];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests the oblivious minimum and maximum of shared values
+fn test_min_max(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the lhs values, party 1 shares the rhs values
+    let my_values = if test_args.party_id == 0 {
+        vec![3u64, 9u64, 5u64]
+    } else {
+        vec![4u64, 2u64, 5u64]
+    };
+    let my_values = MpcScalar::from_private_u64_slice(
+        &my_values,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let lhs = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let rhs = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let mut results = MpcScalar::batch_min(&lhs, &rhs)
+        .map_err(|err| format!("Error computing minimums: {:?}", err))?;
+    results.extend(
+        MpcScalar::batch_max(&lhs, &rhs)
+            .map_err(|err| format!("Error computing maximums: {:?}", err))?,
+    );
+    results.push(
+        lhs[0]
+            .min(&rhs[0])
+            .map_err(|err| format!("Error computing minimum: {:?}", err))?,
+    );
+    results.push(
+        lhs[1]
+            .max(&rhs[1])
+            .map_err(|err| format!("Error computing maximum: {:?}", err))?,
+    );
+
+    let res = MpcScalar::batch_open(&results)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![3u64, 2, 5, 4, 9, 5, 3, 9];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests sorting a shared vector whose length is not a power of two
+fn test_sort(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[7, 3, 9, 3, 1],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let sorted =
+        MpcScalar::sort(&values).map_err(|err| format!("Error sorting values: {:?}", err))?;
+    let res = MpcScalar::batch_open(&sorted)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+    let expected = vec![1u64, 3, 3, 7, 9];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests shuffling a vector of shared and public values
+fn test_shuffle(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let mut values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[7, 3, 9],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    values.push(MpcScalar::from_public_u64(
+        5,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    ));
+
+    let shuffled =
+        MpcScalar::shuffle(&values).map_err(|err| format!("Error shuffling values: {:?}", err))?;
+    let res = MpcScalar::batch_open(&shuffled)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+
+    // Each party rotates the vector by one in the test's value source
+    let expected = vec![9u64, 5, 7, 3];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests the fused multiply-add over shared, public and mixed operands
+fn test_batch_mul_add(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[2, 3, 4, 5, 6, 7],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let public = |value: u64| {
+        MpcScalar::from_public_u64(
+            value,
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        )
+    };
+
+    // shared * shared + shared, shared * public + public, public * public + shared
+    let a = [shared[0].clone(), shared[1].clone(), public(8)];
+    let b = [shared[2].clone(), public(9), public(10)];
+    let c = [shared[3].clone(), public(11), shared[4].clone()];
+    let res = MpcScalar::batch_mul_add(&a, &b, &c)
+        .and_then(|res| MpcScalar::batch_open(&res).map_err(MpcError::NetworkError))
+        .map_err(|err| format!("Error in batch_mul_add: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+
+    let expected = vec![2 * 4 + 5, 3 * 9 + 11, 8 * 10 + 6];
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests that a lazy graph coalesces independent multiplications and openings into layers
+fn test_lazy_graph(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[3, 4, 5],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let mut graph = LazyGraph::new(test_args.net_ref.clone(), test_args.beaver_source.clone());
+    let (x, y, z) = (
+        graph.input(&shared[0]),
+        graph.input(&shared[1]),
+        graph.input(&shared[2]),
+    );
+
+    // Layer one: x * y, y * z and the opening of x; layer two: (x * y) * (y * z)
+    let xy = graph.mul(x, y);
+    let yz = graph.mul(y, z);
+    let opened_x = graph.open(x);
+    let product = graph.mul(xy, yz);
+    let res = graph.add(product, x);
+    // A product with an opened value is local
+    let scaled = graph.mul(opened_x, z);
+    let sum = graph.add_constant(scaled, Scalar::from(1u64));
+
+    if graph.num_rounds(&[res, sum, opened_x]) != 2 {
+        return Err(format!(
+            "Expected 2 rounds, Got: {}",
+            graph.num_rounds(&[res, sum, opened_x])
+        ));
+    }
+
+    let values = graph
+        .evaluate(&[res, sum, opened_x])
+        .and_then(|values| MpcScalar::batch_open(&values).map_err(MpcError::NetworkError))
+        .map_err(|err| format!("Error evaluating graph: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+
+    let expected = vec![3 * 4 * 4 * 5 + 3, 3 * 5 + 1, 3];
+    if values != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, values));
+    }
+
+    Ok(())
+}
+
+/// Tests dividing shared values by shared and public divisors, and inverting a shared value
+fn test_div(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_values = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &[12u64, 4, 0].map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        }),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let (x, y, zero) = (&shared_values[0], &shared_values[1], &shared_values[2]);
+
+    let res = (x / y)
+        .open()
+        .map_err(|err| format!("Error opening quotient: {:?}", err))?;
+    if res.value() != Scalar::from(3u64) {
+        return Err(format!("Expected: 3, Got: {:?}", res.value()));
+    }
+
+    let res = (x / Scalar::from(3u64))
+        .open()
+        .map_err(|err| format!("Error opening quotient: {:?}", err))?;
+    if res.value() != Scalar::from(4u64) {
+        return Err(format!("Expected: 4, Got: {:?}", res.value()));
+    }
+
+    let res = y
+        .invert()
+        .and_then(|inverse| inverse.try_open())
+        .map_err(|err| format!("Error inverting value: {:?}", err))?;
+    if res.value() != Scalar::from(4u64).invert() {
+        return Err(format!("Expected: 4^-1, Got: {:?}", res.value()));
+    }
+
+    match x.try_div(zero) {
+        Err(MpcError::ArithmeticError(_)) => Ok(()),
+        res => Err(format!(
+            "Expected dividing by zero to fail, got: {:?}",
+            res.map(|value| value.value())
+        )),
+    }
+}
+
+/// Tests inverting a batch of shared and public values together
+fn test_batch_invert(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let mut values = MpcScalar::batch_share_secrets(
+        1, /* party_id */
+        &[4u64, 8].map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        }),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    values.insert(
+        1,
+        MpcScalar::from_public_u64(
+            5,
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    );
+
+    let inverses = MpcScalar::batch_invert(&values)
+        .and_then(|inverses| MpcScalar::batch_open(&inverses).map_err(MpcError::NetworkError))
+        .map_err(|err| format!("Error inverting values: {:?}", err))?;
+    let expected = [4u64, 5, 8]
+        .iter()
+        .map(|value| Scalar::from(*value).invert())
+        .collect::<Vec<_>>();
+    let res = inverses
+        .iter()
+        .map(|inverse| inverse.value())
+        .collect::<Vec<_>>();
+    if res != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests that adding a public constant to a shared value opens to the sum
+fn test_add_public_constant(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let shared_value = MpcScalar::from_private_u64(
+        3,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(1 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let res = shared_value
+        .add_public_constant(Scalar::from(4u64))
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if res.value() != Scalar::from(7u64) {
+        return Err(format!("Expected: 7, Got: {:?}", res.value()));
+    }
+
+    Ok(())
+}
+
+/// Tests the one-hot encoded and the shared index argmin and argmax of a shared vector
+fn test_argmin_argmax(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares a vector of values
+    let values: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = vec![3u64, 9u64, 2u64]
+        .into_iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect();
+
+    let shared_values = MpcScalar::batch_share_secrets(0 /* party_id */, &values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let argmin = MpcScalar::batch_open(
+        &MpcScalar::argmin(&shared_values)
+            .map_err(|err| format!("Error computing argmin: {:?}", err))?,
+    )
+    .map_err(|err| format!("Error opening values: {:?}", err))?
+    .iter()
+    .map(|value| scalar_to_u64(&value.value()))
+    .collect::<Vec<_>>();
+
+    if argmin.ne(&vec![0u64, 0u64, 1u64]) {
+        return Err(format!("Expected: {:?}, Got: {:?}", vec![0, 0, 1], argmin));
+    }
+
+    let argmax = MpcScalar::batch_open(
+        &MpcScalar::argmax(&shared_values)
+            .map_err(|err| format!("Error computing argmax: {:?}", err))?,
+    )
+    .map_err(|err| format!("Error opening values: {:?}", err))?
+    .iter()
+    .map(|value| scalar_to_u64(&value.value()))
+    .collect::<Vec<_>>();
+
+    if argmax.ne(&vec![0u64, 1u64, 0u64]) {
+        return Err(format!("Expected: {:?}, Got: {:?}", vec![0, 1, 0], argmax));
+    }
+
+    let indices = vec![
+        MpcScalar::argmin_index(&shared_values),
+        MpcScalar::argmax_index(&shared_values),
+    ]
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| format!("Error computing indices: {:?}", err))?;
+    let indices = MpcScalar::batch_open(&indices)
+        .map_err(|err| format!("Error opening values: {:?}", err))?
+        .iter()
+        .map(|value| scalar_to_u64(&value.value()))
+        .collect::<Vec<_>>();
+
+    if indices.ne(&vec![2u64, 1u64]) {
+        return Err(format!("Expected: {:?}, Got: {:?}", vec![2, 1], indices));
+    }
+
+    Ok(())
+}
+
+/// Tests splitting a shared value into its low and high bits
+fn test_split_at_bit(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares a value with both low and high bits set
+    let value = 0xdead_beef_1234u64;
+    let shared_value = MpcScalar::from_private_u64(
+        value,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    let (low, high) = shared_value
+        .split_at_bit(16)
+        .map_err(|err| format!("Error splitting value: {:?}", err))?;
+
+    let opened = MpcScalar::batch_open(&[low.clone(), high.clone()])
+        .map_err(|err| format!("Error opening values: {:?}", err))?;
+    let (opened_low, opened_high) = (
+        scalar_to_u64(&opened[0].value()),
+        scalar_to_u64(&opened[1].value()),
+    );
+
+    if opened_low != 0x1234 || opened_high != 0xdead_beef {
+        return Err(format!(
+            "Expected: ({}, {}), Got: ({}, {})",
+            0x1234, 0xdead_beefu64, opened_low, opened_high
+        ));
+    }
+
+    // Reconstruct the original value from the halves
+    let reconstructed = (low + high * Scalar::from(1u64 << 16))
+        .open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    if scalar_to_u64(&reconstructed.value()) != value {
+        return Err(format!(
+            "Expected: {}, Got: {}",
+            value,
+            scalar_to_u64(&reconstructed.value())
+        ));
+    }
+
+    Ok(())
+}
+
+/// Tests opening a value and applying a different post-processing on each party
+fn test_open_then(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the value 21
+    let shared_value = MpcScalar::from_private_u64(
+        21,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing value: {:?}", err))?;
+
+    // Party 0 doubles the opened value, party 1 squares it
+    let res = shared_value
+        .open_then(
+            |value| scalar_to_u64(&value) * 2,
+            |value| scalar_to_u64(&value).pow(2),
+        )
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+
+    let expected = if test_args.party_id == 0 { 42 } else { 441 };
+    if res != expected {
+        return Err(format!("Expected: {}, Got: {}", expected, res));
+    }
+
+    Ok(())
+}
+
+/// Tests rotating and shifting a shared bit vector by public amounts
+fn test_rotate_and_shift_bits(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares a bit vector
+    let plaintext_bits = vec![1u64, 1, 0, 1, 0, 0, 1, 0];
+    let bits: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = plaintext_bits
+        .iter()
+        .map(|bit| {
+            MpcScalar::from_private_u64(
+                *bit,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect();
+
+    let shared_bits = MpcScalar::batch_share_secrets(0 /* party_id */, &bits)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    for by in [0, 3, 8, 11] {
+        let rotated = MpcScalar::batch_open(&MpcScalar::rotate_bits(&shared_bits, by))
+            .map_err(|err| format!("Error opening values: {:?}", err))?
+            .iter()
+            .map(|bit| scalar_to_u64(&bit.value()))
+            .collect::<Vec<_>>();
+
+        let mut expected_rotated = plaintext_bits.clone();
+        expected_rotated.rotate_right(by % plaintext_bits.len());
+        if rotated.ne(&expected_rotated) {
+            return Err(format!(
+                "Expected: {:?}, Got: {:?}",
+                expected_rotated, rotated
+            ));
+        }
+
+        let shifted = MpcScalar::batch_open(&MpcScalar::shift_bits(&shared_bits, by))
+            .map_err(|err| format!("Error opening values: {:?}", err))?
+            .iter()
+            .map(|bit| scalar_to_u64(&bit.value()))
+            .collect::<Vec<_>>();
+
+        // Plaintext shift of the bits as an integer
+        let plaintext_value = plaintext_bits
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, bit)| acc | (bit << i));
+        let shifted_value = plaintext_value.checked_shl(by as u32).unwrap_or(0);
+        let expected_shifted = (0..plaintext_bits.len())
+            .map(|i| (shifted_value >> i) & 1)
+            .collect::<Vec<_>>();
+        if shifted.ne(&expected_shifted) {
+            return Err(format!(
+                "Expected: {:?}, Got: {:?}",
+                expected_shifted, shifted
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Tests asserting that two shared vectors are permutations of one another
+fn test_assert_permutation(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 shares the original vector, party 1 shares a candidate permutation
+    let my_values = if test_args.party_id == 0 {
+        vec![1u64, 2u64, 3u64, 4u64, 5u64]
+    } else {
+        vec![3u64, 5u64, 1u64, 4u64, 2u64]
+    };
+    let my_values: Vec<MpcScalar<IntegrationNet, PartyIDBeaverSource>> = my_values
+        .into_iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect();
+
+    let original = MpcScalar::batch_share_secrets(0 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    let permuted = MpcScalar::batch_share_secrets(1 /* party_id */, &my_values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    MpcScalar::assert_permutation(&original, &permuted)
+        .map_err(|err| format!("Expected permutation to pass, got: {:?}", err))?;
+
+    // Replace one of the permuted values, the assertion should now fail
+    let mut not_permuted = permuted.clone();
+    not_permuted[0] = &not_permuted[0] + Scalar::from(1u64);
+    match MpcScalar::assert_permutation(&original, &not_permuted) {
+        Err(MpcError::AssertionError(_)) => {}
+        res => return Err(format!("Expected AssertionError, got: {:?}", res)),
+    }
+
+    // Mismatched lengths are rejected
+    match MpcScalar::assert_permutation(&original, &permuted[1..]) {
+        Err(MpcError::AssertionError(_)) => Ok(()),
+        res => Err(format!("Expected AssertionError, got: {:?}", res)),
+    }
+}
+
+/// Tests the exact and probabilistic truncation of shared values
+fn test_truncate(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let values = [1000u64, 1023u64, 1u64 << 40]
+        .iter()
+        .map(|value| {
+            MpcScalar::from_private_u64(
+                *value,
+                test_args.net_ref.clone(),
+                test_args.beaver_source.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let shared = MpcScalar::batch_share_secrets(0 /* party_id */, &values)
+        .map_err(|err| format!("Error sharing values: {:?}", err))?;
+
+    let exact = MpcScalar::batch_truncate(&shared, 4 /* k */)
+        .map_err(|err| format!("Error truncating values: {:?}", err))?;
+    let probabilistic = MpcScalar::batch_truncate_probabilistic(&shared, 4 /* k */)
+        .map_err(|err| format!("Error truncating values: {:?}", err))?;
+
+    let open = |values: &[MpcScalar<_, _>]| -> Result<Vec<u64>, String> {
+        Ok(MpcScalar::batch_open(values)
+            .map_err(|err| format!("Error opening values: {:?}", err))?
+            .iter()
+            .map(|value| scalar_to_u64(&value.value()))
+            .collect())
+    };
+
+    let expected = vec![62u64, 63u64, 1u64 << 36];
+    let exact = open(&exact)?;
+    if exact != expected {
+        return Err(format!("Expected: {:?}, Got: {:?}", expected, exact));
+    }
+
+    // The probabilistic result may exceed the exact result by one
+    let probabilistic = open(&probabilistic)?;
+    if probabilistic
+        .iter()
+        .zip(expected.iter())
+        .any(|(res, expected)| res < expected || res - expected > 1)
+    {
+        return Err(format!(
+            "Expected within one of: {:?}, Got: {:?}",
+            expected, probabilistic
+        ));
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_add",
+    test_fn: test_add,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_sub",
+    test_fn: test_sub,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_mul",
+    test_fn: test_mul
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_batch_mul",
+    test_fn: test_batch_mul,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_open_value",
+    test_fn: test_open_value,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_commit_and_open",
+    test_fn: test_commit_and_open,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_open_batch",
+    test_fn: test_open_batch,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_commit_and_open_batch",
+    test_fn: test_commit_and_open_batch,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_receive_value",
+    test_fn: test_receive_value,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_sum",
+    test_fn: test_sum,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_product",
+    test_fn: test_product,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_linear_combination",
+    test_fn: test_linear_combination,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_random_linear_comb",
+    test_fn: test_random_linear_comb
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_simple_mpc",
+    test_fn: test_simple_mpc,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_less_than",
+    test_fn: test_less_than,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_argmin_argmax",
+    test_fn: test_argmin_argmax,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_split_at_bit",
+    test_fn: test_split_at_bit,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_open_then",
+    test_fn: test_open_then,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_rotate_and_shift_bits",
+    test_fn: test_rotate_and_shift_bits,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_assert_permutation",
+    test_fn: test_assert_permutation,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_reveal_if_count_at_least",
+    test_fn: test_reveal_if_count_at_least,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_oblivious_read",
+    test_fn: test_oblivious_read,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_oblivious_write",
+    test_fn: test_oblivious_write,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_bitonic_merge",
+    test_fn: test_bitonic_merge,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_normalize",
+    test_fn: test_normalize,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_add_public_constant",
+    test_fn: test_add_public_constant,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_square",
+    test_fn: test_square,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_pow",
+    test_fn: test_pow,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_pow_public_and_secret",
+    test_fn: test_pow_public_and_secret,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_dot",
+    test_fn: test_dot,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_eval_poly",
+    test_fn: test_eval_poly,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_prefixes",
+    test_fn: test_prefixes,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_min_max",
+    test_fn: test_min_max,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_sort",
+    test_fn: test_sort,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_shuffle",
+    test_fn: test_shuffle,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_batch_mul_add",
+    test_fn: test_batch_mul_add,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_lazy_graph",
+    test_fn: test_lazy_graph,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_div",
+    test_fn: test_div,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_batch_invert",
+    test_fn: test_batch_invert,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_order_comparisons",
+    test_fn: test_order_comparisons,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_eq_secret",
+    test_fn: test_eq_secret,
+});
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-scalar::test_truncate",
+    test_fn: test_truncate,
 });
@@ -0,0 +1,77 @@
+use mpc_ristretto::mpc_u64::MpcU64;
+
+use crate::{IntegrationTest, IntegrationTestArgs};
+
+/// Tests that arithmetic on shared 64-bit integers wraps around as the native integers do
+fn test_wrapping_arithmetic(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 inputs 2^64 - 2 and party 1 inputs 5
+    let value = if test_args.party_id == 0 {
+        u64::MAX - 1
+    } else {
+        5
+    };
+    let input = MpcU64::from_private_u64(
+        value,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    );
+    let a = input
+        .share_secret(0 /* party_id */)
+        .map_err(|err| format!("Error sharing integer: {:?}", err))?;
+    let b = input
+        .share_secret(1 /* party_id */)
+        .map_err(|err| format!("Error sharing integer: {:?}", err))?;
+
+    let lhs = [a.clone(), b.clone(), a.clone()];
+    let rhs = [b.clone(), a.clone(), b.clone()];
+    let sums = MpcU64::batch_wrapping_add(&lhs, &rhs)
+        .map_err(|err| format!("Error adding integers: {:?}", err))?;
+    let res = vec![
+        ("a + b", sums[0].clone(), (u64::MAX - 1).wrapping_add(5)),
+        ("b - a", &b - &a, 5u64.wrapping_sub(u64::MAX - 1)),
+        ("a * b", &a * &b, (u64::MAX - 1).wrapping_mul(5)),
+        (
+            "-b",
+            b.wrapping_neg()
+                .map_err(|err| format!("Error negating integer: {:?}", err))?,
+            5u64.wrapping_neg(),
+        ),
+        (
+            "b << 62",
+            b.wrapping_shl(62)
+                .map_err(|err| format!("Error shifting integer: {:?}", err))?,
+            5u64 << 62,
+        ),
+        (
+            "a >> 3",
+            a.shr(3)
+                .map_err(|err| format!("Error shifting integer: {:?}", err))?,
+            (u64::MAX - 1) >> 3,
+        ),
+    ];
+
+    for (name, value, expected) in res {
+        let opened = value
+            .open()
+            .map_err(|err| format!("Error opening {}: {:?}", name, err))?;
+        if opened != expected {
+            return Err(format!("{}: Expected: {}, Got: {}", name, expected, opened));
+        }
+    }
+
+    // The wrapped sum is less than either summand
+    let less = sums[0]
+        .lt(&b)
+        .and_then(|res| res.open())
+        .map_err(|err| format!("Error comparing integers: {:?}", err))?;
+    if !less {
+        return Err("Expected the wrapped sum to be less than 5".to_string());
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "mpc-u64::test_wrapping_arithmetic",
+    test_fn: test_wrapping_arithmetic,
+});
@@ -1,6 +1,6 @@
 use curve25519_dalek::scalar::Scalar;
 use futures::executor::block_on;
-use mpc_ristretto::network::MpcNetwork;
+use mpc_ristretto::{mpc_ristretto::MpcRistrettoPoint, mpc_scalar::MpcScalar, network::MpcNetwork};
 
 use crate::{base_point_mul, IntegrationTest, IntegrationTestArgs};
 
@@ -42,6 +42,61 @@ fn test_send_scalar(test_args: &IntegrationTestArgs) -> Result<(), String> {
     }
 }
 
+/// Tests that both parties compute the same output digest over a sequence of openings
+fn test_output_digest(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    let initial_digest = test_args
+        .net_ref
+        .borrow()
+        .output_digest()
+        .ok_or_else(|| "Expected the network to maintain an output digest".to_string())?;
+
+    // Open a sequence of scalars and points
+    let shared_scalars = MpcScalar::batch_share_secrets(
+        0, /* party_id */
+        &MpcScalar::from_private_u64_slice(
+            &[1u64, 2u64, 3u64],
+            test_args.net_ref.clone(),
+            test_args.beaver_source.clone(),
+        ),
+    )
+    .map_err(|err| format!("Error sharing values: {:?}", err))?;
+    MpcScalar::batch_open(&shared_scalars)
+        .map_err(|err| format!("Error opening values: {:?}", err))?;
+    shared_scalars[0]
+        .commit_and_open()
+        .map_err(|err| format!("Error opening value: {:?}", err))?;
+    MpcRistrettoPoint::from_private_u64(
+        4,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(1 /* party_id */)
+    .map_err(|err| format!("Error sharing point: {:?}", err))?
+    .open()
+    .map_err(|err| format!("Error opening point: {:?}", err))?;
+
+    let digest = test_args.net_ref.borrow().output_digest().unwrap();
+    if digest == initial_digest {
+        return Err("Expected the openings to update the digest".to_string());
+    }
+
+    // Exchange the digests and compare them
+    let digest_scalar = Scalar::from_bytes_mod_order(digest);
+    let peer_digest_scalar = block_on(
+        test_args
+            .net_ref
+            .borrow_mut()
+            .broadcast_single_scalar(digest_scalar),
+    )
+    .map_err(|err| format!("{:?}", err))?;
+
+    if digest_scalar != peer_digest_scalar {
+        return Err("Expected the parties' output digests to match".to_string());
+    }
+
+    Ok(())
+}
+
 // Take inventory
 inventory::submit!(IntegrationTest {
     name: "network::test_send_ristretto",
@@ -52,3 +107,8 @@ inventory::submit!(IntegrationTest {
     name: "network::test_send_scalar",
     test_fn: test_send_scalar,
 });
+
+inventory::submit!(IntegrationTest {
+    name: "network::test_output_digest",
+    test_fn: test_output_digest,
+});
@@ -0,0 +1,39 @@
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use mpc_ristretto::{mpc_scalar::MpcScalar, schnorr};
+
+use crate::{IntegrationTest, IntegrationTestArgs};
+
+/// Tests that a proof over a shared witness verifies against its statement, and that a proof
+/// over a different witness does not
+fn test_prove_shared(test_args: &IntegrationTestArgs) -> Result<(), String> {
+    // Party 0 holds the witness
+    let witness = MpcScalar::from_private_u64(
+        5,
+        test_args.net_ref.clone(),
+        test_args.beaver_source.clone(),
+    )
+    .share_secret(0 /* party_id */)
+    .map_err(|err| format!("Error sharing witness: {:?}", err))?;
+    let statement = RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64);
+
+    let proof = schnorr::prove_shared(&witness, RISTRETTO_BASEPOINT_POINT)
+        .map_err(|err| format!("Error proving knowledge: {:?}", err))?;
+    if !schnorr::verify(&proof, statement) {
+        return Err("Expected proof to verify".to_string());
+    }
+
+    // A proof of a different witness should not verify against the statement
+    let wrong_witness = &witness + Scalar::one();
+    let proof = schnorr::prove_shared(&wrong_witness, RISTRETTO_BASEPOINT_POINT)
+        .map_err(|err| format!("Error proving knowledge: {:?}", err))?;
+    if schnorr::verify(&proof, statement) {
+        return Err("Expected proof of a wrong witness to fail verification".to_string());
+    }
+
+    Ok(())
+}
+
+inventory::submit!(IntegrationTest {
+    name: "schnorr::test_prove_shared",
+    test_fn: test_prove_shared,
+});
@@ -12,16 +12,21 @@ use curve25519_dalek::{
 };
 use rand_core::{CryptoRng, RngCore};
 use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize-on-drop")]
+use zeroize::ZeroizeOnDrop;
 
 use crate::{
     authenticated_scalar::AuthenticatedScalar,
     beaver::SharedValueSource,
+    block_on,
     error::{MpcError, MpcNetworkError},
     macros,
     mpc_ristretto::{MpcCompressedRistretto, MpcRistrettoPoint},
     mpc_scalar::MpcScalar,
-    network::MpcNetwork,
-    BeaverSource, SharedNetwork, Visibility, Visible,
+    network::{DynMpcNetwork, MpcNetwork},
+    share::PointShare,
+    BeaverSource, SessionId, SharedNetwork, Visibility, Visible,
 };
 
 /// An authenticated Ristretto point, wrapper around an MPC-capable Ristretto point
@@ -43,6 +48,9 @@ pub struct AuthenticatedRistretto<N: MpcNetwork + Send, S: SharedValueSource<Sca
     visibility: Visibility,
 }
 
+/// An `AuthenticatedRistretto` over a network chosen at runtime
+pub type DynAuthenticatedRistretto<S> = AuthenticatedRistretto<DynMpcNetwork, S>;
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for AuthenticatedRistretto<N, S> {
     fn clone(&self) -> Self {
         Self {
@@ -86,6 +94,12 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
         self.value().network()
     }
 
+    /// Returns the ID of the session the value was allocated in
+    #[inline]
+    pub fn session_id(&self) -> SessionId {
+        self.value.session_id()
+    }
+
     #[inline]
     fn beaver_source(&self) -> BeaverSource<S> {
         self.value().beaver_source()
@@ -159,6 +173,41 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
         }
     }
 
+    /// Detaches the party's shares of the point and its MAC from the network and value source,
+    /// e.g. to persist them or route them over another transport
+    pub fn detach(&self) -> PointShare {
+        PointShare {
+            value: self.to_ristretto(),
+            visibility: self.visibility,
+            mac_share: self.mac_share.as_ref().map(|mac| mac.value()),
+        }
+    }
+
+    /// Attaches detached shares of a point and its MAC to a network and value source, under the
+    /// party's share of the MAC key of the session that authenticated the point
+    pub fn attach(
+        share: PointShare,
+        key_share: MpcScalar<N, S>,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        let mac_share = share.mac_share.map(|mac| {
+            MpcRistrettoPoint::from_ristretto_point_with_visibility(
+                mac,
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            )
+        });
+
+        Self {
+            value: MpcRistrettoPoint::attach(share, network, beaver_source),
+            mac_share,
+            key_share,
+            visibility: share.visibility,
+        }
+    }
+
     // Create a random authenticated Ristretto point, assumed private
     pub fn random<R: RngCore + CryptoRng>(
         rng: &mut R,
@@ -204,10 +253,27 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
     pub fn share_secret(
         &self,
         party_id: u64,
+    ) -> Result<AuthenticatedRistretto<N, S>, MpcNetworkError> {
+        block_on(self.share_secret_async(party_id))
+    }
+
+    /// Awaits the network rather than blocking on it, see `share_secret`
+    ///
+    /// The async methods of values that share a network must be awaited in sequence rather
+    /// than polled concurrently; an operation started while another is pending on the same
+    /// network fails with `MpcNetworkError::NetworkBusy`
+    pub async fn share_secret_async(
+        &self,
+        party_id: u64,
     ) -> Result<AuthenticatedRistretto<N, S>, MpcNetworkError> {
         // Share the value and then create the mac
-        let my_share = self.value().share_secret(party_id)?;
-        let my_mac_share = &self.key_share() * &my_share;
+        let my_share = self.value().share_secret_async(party_id).await?;
+        let my_mac_share = MpcRistrettoPoint::batch_mul_async(
+            std::slice::from_ref(&self.key_share),
+            std::slice::from_ref(&my_share),
+        )
+        .await?
+        .remove(0);
 
         Ok(Self {
             value: my_share,
@@ -222,6 +288,17 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
         party_id: u64,
         secrets: &[AuthenticatedRistretto<N, S>],
     ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_share_secrets_async(party_id, secrets))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_share_secrets`
+    pub async fn batch_share_secrets_async(
+        party_id: u64,
+        secrets: &[AuthenticatedRistretto<N, S>],
+    ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(secrets.iter().map(|value| value.session_id()));
+
         assert!(
             !secrets.is_empty(),
             "Cannot batch share secrets of empty vector"
@@ -230,20 +307,21 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
         let key_share = secrets[0].key_share();
 
         // Batch secret share the underlying values
-        let my_shares = MpcRistrettoPoint::batch_share_secrets(
+        let my_shares = MpcRistrettoPoint::batch_share_secrets_async(
             party_id,
             &secrets
                 .iter()
                 .map(|secret| secret.value().clone())
                 .collect::<Vec<MpcRistrettoPoint<_, _>>>(),
-        )?;
+        )
+        .await?;
 
         // Compute the MACs for the newly shared values
-        #[allow(clippy::needless_collect)]
-        let my_mac_shares: Vec<MpcRistrettoPoint<N, S>> = my_shares
-            .iter()
-            .map(|share| &key_share.clone() * share)
-            .collect();
+        let my_mac_shares = MpcRistrettoPoint::batch_mul_async(
+            &vec![key_share.clone(); my_shares.len()],
+            &my_shares,
+        )
+        .await?;
 
         Ok(my_shares
             .into_iter()
@@ -260,8 +338,13 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
     /// From a shared value, both parties distribute their shares of the underlying value
     /// The parties locally sum all shares to reconstruct the value
     pub fn open(&self) -> Result<AuthenticatedRistretto<N, S>, MpcNetworkError> {
+        block_on(self.open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open`
+    pub async fn open_async(&self) -> Result<AuthenticatedRistretto<N, S>, MpcNetworkError> {
         Ok(Self {
-            value: self.value().open()?,
+            value: self.value().open_async().await?,
             visibility: Visibility::Public,
             mac_share: None, // Public values have no MAC
             key_share: self.key_share(),
@@ -272,17 +355,28 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
     pub fn batch_open(
         values: &[AuthenticatedRistretto<N, S>],
     ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open`
+    pub async fn batch_open_async(
+        values: &[AuthenticatedRistretto<N, S>],
+    ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(!values.is_empty(), "Cannot batch open an empty vector");
 
         let key_share = values[0].key_share();
 
         // Open the values
-        let opened_values = MpcRistrettoPoint::batch_open(
+        let opened_values = MpcRistrettoPoint::batch_open_async(
             &values
                 .iter()
                 .map(|shared_value| shared_value.value().clone())
                 .collect::<Vec<MpcRistrettoPoint<_, _>>>(),
-        )?;
+        )
+        .await?;
 
         // Reconstruct from opened shares
         Ok(opened_values
@@ -303,20 +397,32 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
     ///     2. Compute and commit to their share of \key_share * value - \mac_share
     ///     3. Open their commitments to the other party, and verify that the shares sum to zero
     pub fn open_and_authenticate(&self) -> Result<AuthenticatedRistretto<N, S>, MpcError> {
+        block_on(self.open_and_authenticate_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open_and_authenticate`
+    pub async fn open_and_authenticate_async(
+        &self,
+    ) -> Result<AuthenticatedRistretto<N, S>, MpcError> {
         // If the value is not shard, there is nothing to open or authenticate
         if !self.is_shared() {
             return Ok(self.clone());
         }
 
         // 1. Open the underlying value
-        let opened_value = self.value().open().map_err(MpcError::NetworkError)?;
+        let opened_value = self
+            .value()
+            .open_async()
+            .await
+            .map_err(MpcError::NetworkError)?;
 
         // 2. Commit to the value key_share * value - mac_share, then open the values and check commitments
         let mac_check_share = &self.key_share * &opened_value - self.mac().unwrap();
 
         // 3. Verify the authenticated mac check shares sum to zero
         if mac_check_share
-            .commit_and_open()?
+            .commit_and_open_async()
+            .await?
             .value()
             .ne(&RistrettoPoint::identity())
         {
@@ -335,6 +441,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
     pub fn batch_open_and_authenticate(
         values: &[AuthenticatedRistretto<N, S>],
     ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcError> {
+        block_on(Self::batch_open_and_authenticate_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open_and_authenticate`
+    pub async fn batch_open_and_authenticate_async(
+        values: &[AuthenticatedRistretto<N, S>],
+    ) -> Result<Vec<AuthenticatedRistretto<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(
             !values.is_empty(),
             "Cannot batch open and authenticate an empty vector"
@@ -343,12 +459,13 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
         let key_share = values[0].key_share();
 
         // 1. Open the underlying values
-        let opened_values = MpcRistrettoPoint::batch_open(
+        let opened_values = MpcRistrettoPoint::batch_open_async(
             &values
                 .iter()
                 .map(|shared_value| shared_value.value().clone())
                 .collect::<Vec<MpcRistrettoPoint<_, _>>>(),
         )
+        .await
         .map_err(MpcError::NetworkError)?;
 
         // 2. Commit to the value key_share * value - mac_share, then open the values and check commitments
@@ -369,7 +486,8 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedRistretto<
             .collect::<Vec<MpcRistrettoPoint<_, _>>>();
 
         // 3. Verify that the MACs pass the authentication check
-        MpcRistrettoPoint::batch_commit_and_open(&mac_check_shares)?
+        MpcRistrettoPoint::batch_commit_and_open_async(&mac_check_shares)
+            .await?
             .iter()
             .try_for_each(|commit_result| {
                 if commit_result.value().ne(&RistrettoPoint::identity()) {
@@ -428,6 +546,22 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clear for Authenticated
     }
 }
 
+/// Zeroizes the point, its MAC and the MAC key share; the MAC is dropped
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Zeroize for AuthenticatedRistretto<N, S> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+        self.mac_share.zeroize();
+        self.key_share.zeroize();
+    }
+}
+
+/// Each of the fields zeroizes itself when dropped
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> ZeroizeOnDrop
+    for AuthenticatedRistretto<N, S>
+{
+}
+
 /**
  * Mul and variants for borrowed, non-borrowed values
  */
@@ -437,6 +571,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a Authenticat
     type Output = AuthenticatedRistretto<N, S>;
 
     fn mul(self, rhs: &'a AuthenticatedScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         let value = self.value() * rhs.value();
         let mac = {
             // Public * public results in a public value, which has no MAC
@@ -520,6 +657,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a Authenticat
     type Output = AuthenticatedRistretto<N, S>;
 
     fn add(self, rhs: &'a AuthenticatedRistretto<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         // For a public value + a scalar value; always put the public value on the RHS
         if self.is_public() && rhs.is_shared() {
             return rhs + self;
@@ -679,6 +819,10 @@ pub struct AuthenticatedCompressedRistretto<N: MpcNetwork + Send, S: SharedValue
     key_share: MpcScalar<N, S>,
 }
 
+/// An `AuthenticatedCompressedRistretto` over a network chosen at runtime
+pub type DynAuthenticatedCompressedRistretto<S> =
+    AuthenticatedCompressedRistretto<DynMpcNetwork, S>;
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone
     for AuthenticatedCompressedRistretto<N, S>
 {
@@ -8,15 +8,22 @@ use std::{
 
 use clear_on_drop::clear::Clear;
 use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "bigint")]
+use num_bigint_dig::BigUint;
 use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize-on-drop")]
+use zeroize::ZeroizeOnDrop;
 
 use crate::{
     beaver::SharedValueSource,
+    block_on,
     error::{MpcError, MpcNetworkError},
     macros,
     mpc_scalar::MpcScalar,
-    network::MpcNetwork,
-    BeaverSource, SharedNetwork, Visibility, Visible,
+    network::{DynMpcNetwork, MpcNetwork},
+    share::ScalarShare,
+    BeaverSource, SessionId, SharedNetwork, Visibility, Visible,
 };
 
 /// An authenticated scalar, wrapper around an MPC-capable Scalar that supports methods
@@ -38,6 +45,9 @@ pub struct AuthenticatedScalar<N: MpcNetwork + Send, S: SharedValueSource<Scalar
     visibility: Visibility,
 }
 
+/// An `AuthenticatedScalar` over a network chosen at runtime
+pub type DynAuthenticatedScalar<S> = AuthenticatedScalar<DynMpcNetwork, S>;
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for AuthenticatedScalar<N, S> {
     fn clone(&self) -> Self {
         Self {
@@ -81,6 +91,12 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         self.value().network.clone()
     }
 
+    /// Returns the ID of the session the value was allocated in
+    #[inline]
+    pub fn session_id(&self) -> SessionId {
+        self.value.session_id()
+    }
+
     #[inline]
     pub(crate) fn beaver_source(&self) -> BeaverSource<S> {
         self.value().beaver_source.clone()
@@ -91,6 +107,22 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         self.value().value()
     }
 
+    /// Converts the value to a u64, see `MpcScalar::to_u64_checked`
+    pub fn to_u64_checked(&self) -> Result<u64, MpcError> {
+        self.value().to_u64_checked()
+    }
+
+    /// Converts the value to a u128, see `MpcScalar::to_u128`
+    pub fn to_u128(&self) -> Result<u128, MpcError> {
+        self.value().to_u128()
+    }
+
+    /// Converts the value to an arbitrary precision integer, see `MpcScalar::to_biguint`
+    #[cfg(feature = "bigint")]
+    pub fn to_biguint(&self) -> Result<BigUint, MpcError> {
+        self.value().to_biguint()
+    }
+
     #[inline]
     /// Recompute the MAC of the given value
     pub(crate) fn recompute_mac(&mut self) {
@@ -140,6 +172,41 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         }
     }
 
+    /// Detaches the party's shares of the value and its MAC from the network and value source,
+    /// e.g. to persist them or route them over another transport
+    pub fn detach(&self) -> ScalarShare {
+        ScalarShare {
+            value: self.to_scalar(),
+            visibility: self.visibility,
+            mac_share: self.mac_share.as_ref().map(|mac| mac.value()),
+        }
+    }
+
+    /// Attaches detached shares of a value and its MAC to a network and value source, under the
+    /// party's share of the MAC key of the session that authenticated the value
+    pub fn attach(
+        share: ScalarShare,
+        key_share: MpcScalar<N, S>,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        let mac_share = share.mac_share.map(|mac| {
+            MpcScalar::from_scalar_with_visibility(
+                mac,
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            )
+        });
+
+        Self {
+            value: MpcScalar::attach(share, network, beaver_source),
+            mac_share,
+            key_share,
+            visibility: share.visibility,
+        }
+    }
+
     macros::impl_authenticated!(MpcScalar<N, S>, zero);
     macros::impl_authenticated!(MpcScalar<N, S>, one);
     macros::impl_authenticated!(MpcScalar<N, S>, default);
@@ -203,10 +270,27 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
     pub fn share_secret(
         &self,
         party_id: u64,
+    ) -> Result<AuthenticatedScalar<N, S>, MpcNetworkError> {
+        block_on(self.share_secret_async(party_id))
+    }
+
+    /// Awaits the network rather than blocking on it, see `share_secret`
+    ///
+    /// The async methods of values that share a network must be awaited in sequence rather
+    /// than polled concurrently; an operation started while another is pending on the same
+    /// network fails with `MpcNetworkError::NetworkBusy`
+    pub async fn share_secret_async(
+        &self,
+        party_id: u64,
     ) -> Result<AuthenticatedScalar<N, S>, MpcNetworkError> {
         // Share the underlying value then construct a MAC share with the counterparty
-        let my_share = self.value.share_secret(party_id)?;
-        let my_mac_share = &self.key_share * &my_share;
+        let my_share = self.value.share_secret_async(party_id).await?;
+        let my_mac_share = MpcScalar::batch_mul_async(
+            std::slice::from_ref(&self.key_share),
+            std::slice::from_ref(&my_share),
+        )
+        .await?
+        .remove(0);
 
         Ok(Self {
             value: my_share,
@@ -221,26 +305,36 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         party_id: u64,
         secrets: &[AuthenticatedScalar<N, S>],
     ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_share_secrets_async(party_id, secrets))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_share_secrets`
+    pub async fn batch_share_secrets_async(
+        party_id: u64,
+        secrets: &[AuthenticatedScalar<N, S>],
+    ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(secrets.iter().map(|value| value.session_id()));
+
         if secrets.is_empty() {
             return Ok(Vec::new());
         }
 
         // Construct secret shares from the underlying values
         let key_share = secrets[0].key_share();
-        let my_shares = MpcScalar::batch_share_secrets(
+        let my_shares = MpcScalar::batch_share_secrets_async(
             party_id,
             &secrets
                 .iter()
                 .map(|secret| secret.value().clone())
                 .collect::<Vec<MpcScalar<_, _>>>(),
-        )?;
+        )
+        .await?;
 
         // Construct the MACs for the newly shared values
-        #[allow(clippy::needless_collect)]
-        let my_mac_shares: Vec<MpcScalar<N, S>> = my_shares
-            .iter()
-            .map(|share| &key_share.clone() * share)
-            .collect();
+        let my_mac_shares =
+            MpcScalar::batch_mul_async(&vec![key_share.clone(); my_shares.len()], &my_shares)
+                .await?;
 
         // Build these values into AuthenticatedScalars
         Ok(my_shares
@@ -258,12 +352,17 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
     /// From a shared value, both parties broadcast their shares and reconstruct the plaintext.
     /// The parties no longer hold a valid secret sharing of the result, they hold the result itself.
     pub fn open(&self) -> Result<AuthenticatedScalar<N, S>, MpcNetworkError> {
+        block_on(self.open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open`
+    pub async fn open_async(&self) -> Result<AuthenticatedScalar<N, S>, MpcNetworkError> {
         if self.is_public() {
             return Ok(self.clone());
         }
 
         Ok(Self {
-            value: self.value.open()?,
+            value: self.value.open_async().await?,
             visibility: Visibility::Public,
             key_share: self.key_share.clone(),
             mac_share: self.mac_share.clone(),
@@ -274,18 +373,29 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
     pub fn batch_open(
         values: &[AuthenticatedScalar<N, S>],
     ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open`
+    pub async fn batch_open_async(
+        values: &[AuthenticatedScalar<N, S>],
+    ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         if values.is_empty() {
             return Ok(Vec::new());
         }
         let key_share = values[0].key_share();
 
         // Open the values
-        let opened_values = MpcScalar::batch_open(
+        let opened_values = MpcScalar::batch_open_async(
             &values
                 .iter()
                 .map(|shared_value| shared_value.value().clone())
                 .collect::<Vec<MpcScalar<N, S>>>(),
-        )?;
+        )
+        .await?;
 
         // Reconstruct `AuthenticatedScalar`s
         Ok(opened_values
@@ -307,20 +417,30 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
     ///     3. The parties open these commitments and add them; if equal to 0 the
     ///        value is authenticated
     pub fn open_and_authenticate(&self) -> Result<AuthenticatedScalar<N, S>, MpcError> {
+        block_on(self.open_and_authenticate_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open_and_authenticate`
+    pub async fn open_and_authenticate_async(&self) -> Result<AuthenticatedScalar<N, S>, MpcError> {
         // If the value is not shared, there is nothing to open and authenticate
         if !self.is_shared() {
             return Ok(self.clone());
         }
 
         // 1. Open the underlying value
-        let opened_value = self.value().open().map_err(MpcError::NetworkError)?;
+        let opened_value = self
+            .value()
+            .open_async()
+            .await
+            .map_err(MpcError::NetworkError)?;
 
         // 2. Commit to the value key_share * value - mac_share, then open the values and check commitments
         let mac_check_share = &self.key_share * &opened_value - self.mac().unwrap();
 
         // 3. Verify the authenticated mac check shares sum to zero
         if mac_check_share
-            .commit_and_open()?
+            .commit_and_open_async()
+            .await?
             .value()
             .ne(&Scalar::zero())
         {
@@ -336,10 +456,85 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         })
     }
 
+    /// Open the value, withholding the plaintext until the MAC check has passed. This works in 4 steps:
+    ///     1. The parties mask the value with a shared random value r and compute the MAC of r
+    ///     2. The parties open the masked value and authenticate it against the combined MAC;
+    ///        the masked value reveals nothing about the underlying value
+    ///     3. Only once the masked value is authenticated, the parties open and authenticate r
+    ///     4. The parties unmask the opened value
+    /// On a failed check an error is returned before the plaintext is ever reconstructed
+    pub fn open_authenticated(&self) -> Result<AuthenticatedScalar<N, S>, MpcError> {
+        block_on(self.open_authenticated_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open_authenticated`
+    pub async fn open_authenticated_async(&self) -> Result<AuthenticatedScalar<N, S>, MpcError> {
+        // If the value is not shared, there is nothing to open and authenticate
+        if !self.is_shared() {
+            return Ok(self.clone());
+        }
+
+        // 1. Sample a shared mask and compute its MAC
+        let mask = MpcScalar::random_shared(self.network(), self.beaver_source());
+        let mask_mac = MpcScalar::batch_mul_async(
+            std::slice::from_ref(&self.key_share),
+            std::slice::from_ref(&mask),
+        )
+        .await
+        .map_err(MpcError::NetworkError)?
+        .remove(0);
+
+        // 2. Open the masked value and authenticate it against the MAC of the masked value
+        let opened_masked_value = (self.value() + &mask)
+            .open_async()
+            .await
+            .map_err(MpcError::NetworkError)?;
+        let masked_mac_check_share =
+            &self.key_share * &opened_masked_value - (self.mac().unwrap() + &mask_mac);
+        if masked_mac_check_share
+            .commit_and_open_async()
+            .await?
+            .value()
+            .ne(&Scalar::zero())
+        {
+            return Err(MpcError::AuthenticationError);
+        }
+
+        // 3. Open the mask and authenticate it
+        let opened_mask = mask.open_async().await.map_err(MpcError::NetworkError)?;
+        let mask_mac_check_share = &self.key_share * &opened_mask - mask_mac;
+        if mask_mac_check_share
+            .commit_and_open_async()
+            .await?
+            .value()
+            .ne(&Scalar::zero())
+        {
+            return Err(MpcError::AuthenticationError);
+        }
+
+        // 4. Both checks passed, unmask the value
+        Ok(Self {
+            value: opened_masked_value - opened_mask,
+            visibility: Visibility::Public,
+            key_share: self.key_share.clone(),
+            mac_share: None, // Public value has no MAC
+        })
+    }
+
     /// Open a batch of `AuthenticatedScalar`s and authenticate the result with the given MACs
     pub fn batch_open_and_authenticate(
         values: &[AuthenticatedScalar<N, S>],
     ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcError> {
+        block_on(Self::batch_open_and_authenticate_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open_and_authenticate`
+    pub async fn batch_open_and_authenticate_async(
+        values: &[AuthenticatedScalar<N, S>],
+    ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         if values.is_empty() {
             return Ok(Vec::new());
         }
@@ -347,12 +542,13 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         let key_share = values[0].key_share();
 
         // 1. Open the underlying values
-        let opened_values = MpcScalar::batch_open(
+        let opened_values = MpcScalar::batch_open_async(
             &values
                 .iter()
                 .map(|shared_value| shared_value.value().clone())
                 .collect::<Vec<MpcScalar<N, S>>>(),
         )
+        .await
         .map_err(MpcError::NetworkError)?;
 
         // 2. Commit to the value key_share * value - mac_share, then open the values and check commitments
@@ -374,7 +570,8 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
             .collect::<Vec<MpcScalar<N, S>>>();
 
         // 3. Verify that the MACs pass the authentication check
-        MpcScalar::batch_commit_and_open(&mac_check_shares)?
+        MpcScalar::batch_commit_and_open_async(&mac_check_shares)
+            .await?
             .iter()
             .try_for_each(|commit_result| {
                 if commit_result.value().ne(&Scalar::zero()) {
@@ -444,6 +641,22 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clear for &mut Authenti
     }
 }
 
+/// Zeroizes the value, its MAC and the MAC key share; the MAC is dropped
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Zeroize for AuthenticatedScalar<N, S> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+        self.mac_share.zeroize();
+        self.key_share.zeroize();
+    }
+}
+
+/// Each of the fields zeroizes itself when dropped
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> ZeroizeOnDrop
+    for AuthenticatedScalar<N, S>
+{
+}
+
 /**
  * Mul and variants for borrowed, non-borrowed, wrapped values
  */
@@ -453,11 +666,23 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a Authenticat
     type Output = AuthenticatedScalar<N, S>;
 
     fn mul(self, rhs: &'a AuthenticatedScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         // If public * shared, swap arguments so public is on the RHS
         if self.is_public() && rhs.is_shared() {
             return rhs * self;
         }
 
+        if self.is_shared() && rhs.is_shared() && self.has_authenticated_triplets() {
+            return block_on(AuthenticatedScalar::authenticated_beaver_mul(
+                std::slice::from_ref(self),
+                std::slice::from_ref(rhs),
+            ))
+            .expect("authenticated multiplication failed")
+            .remove(0);
+        }
+
         let value = self.value() * rhs.value();
         let mac = {
             // Public * public results in a public value, which has no MAC
@@ -503,17 +728,62 @@ macros::impl_arithmetic_assign!(AuthenticatedScalar<N, S>, MulAssign, mul_assign
 
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N, S> {
     /// Batch multiply; computes a resul [a_1 * b_1, ..., a_n * b_n]
+    ///
+    /// If the Beaver source supplies authenticated triplets, products of shared values consume
+    /// them and their openings are checked against their MACs; panics if a check fails
     pub fn batch_mul(
         a: &[AuthenticatedScalar<N, S>],
         b: &[AuthenticatedScalar<N, S>],
     ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_mul_async(a, b))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_mul`
+    pub async fn batch_mul_async(
+        a: &[AuthenticatedScalar<N, S>],
+        b: &[AuthenticatedScalar<N, S>],
+    ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            a.iter()
+                .map(|value| value.session_id())
+                .chain(b.iter().map(|value| value.session_id())),
+        );
+
         assert_eq!(a.len(), b.len(), "batch_mul requires equal length inputs");
+        if !a.is_empty() && a[0].has_authenticated_triplets() {
+            let (shared_a, shared_b): (Vec<_>, Vec<_>) = a
+                .iter()
+                .zip(b.iter())
+                .filter(|(a, b)| a.is_shared() && b.is_shared())
+                .map(|(a, b)| (a.clone(), b.clone()))
+                .unzip();
+            let mut shared_products =
+                match AuthenticatedScalar::authenticated_beaver_mul(&shared_a, &shared_b).await {
+                    Ok(products) => products.into_iter(),
+                    Err(MpcError::NetworkError(err)) => return Err(err),
+                    Err(err) => panic!("authenticated multiplication failed: {}", err),
+                };
+
+            return Ok(a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| {
+                    if a.is_shared() && b.is_shared() {
+                        shared_products.next().unwrap()
+                    } else {
+                        a * b
+                    }
+                })
+                .collect());
+        }
 
         // First multiply the underlying values
-        let values_batch_mul = MpcScalar::batch_mul(
+        let values_batch_mul = MpcScalar::batch_mul_async(
             &a.iter().map(|val| val.value().clone()).collect::<Vec<_>>(),
             &b.iter().map(|val| val.value().clone()).collect::<Vec<_>>(),
-        )?;
+        )
+        .await?;
 
         // Now compute the updated MACs:
         //      1. Find all pairs of a_i, b_i where neither value is public
@@ -531,7 +801,9 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
         }
 
         // Multiply the MAC keys with the values
-        let mut mac_key_mul_res = MpcScalar::batch_mul(&mac_mul_a, &mac_mul_b)?;
+        let mut mac_key_mul_res = MpcScalar::batch_mul_async(&mac_mul_a, &mac_mul_b)
+            .await?
+            .into_iter();
 
         // Loop over values and recombine either by direct multiplication or from the MAC batch mul
         let mut res = Vec::with_capacity(a.len());
@@ -545,7 +817,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
                     Some(a[i].mac().unwrap() * b[i].value())
                 } else {
                     // Pop from the pre-computed list of key_share * value results
-                    Some(mac_key_mul_res.remove(0))
+                    mac_key_mul_res.next()
                 }
             };
 
@@ -561,6 +833,101 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N,
     }
 }
 
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedScalar<N, S> {
+    /// Returns true if the Beaver source supplies authenticated triplets, which back the
+    /// multiplication of shared values in place of unauthenticated triplets
+    fn has_authenticated_triplets(&self) -> bool {
+        self.beaver_source()
+            .as_ref()
+            .borrow()
+            .mac_key_share()
+            .is_some()
+    }
+
+    /// Multiplies pairs of shared values with authenticated triplets
+    ///
+    /// For each pair x, y and triplet (a, b, c) the parties open d = x - a and e = y - b, and
+    /// check the openings against the MACs of x - a and y - b before using them; then
+    /// x * y = de + d[b] + e[a] + [c], and the MAC of the product follows by the same identity
+    /// over the MACs of the triplet
+    async fn authenticated_beaver_mul(
+        lhs: &[AuthenticatedScalar<N, S>],
+        rhs: &[AuthenticatedScalar<N, S>],
+    ) -> Result<Vec<AuthenticatedScalar<N, S>>, MpcError> {
+        if lhs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let network = lhs[0].network();
+        let beaver_source = lhs[0].beaver_source();
+        let key_share = lhs[0].key_share();
+        let triplets = (0..lhs.len())
+            .map(|_| {
+                beaver_source
+                    .as_ref()
+                    .borrow_mut()
+                    .next_authenticated_triplet()
+            })
+            .collect::<Result<Vec<_>, MpcError>>()?;
+
+        let shared = |value: Scalar| {
+            MpcScalar::from_scalar_with_visibility(
+                value,
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            )
+        };
+        let masked = |value: &AuthenticatedScalar<N, S>, mask: Scalar, mask_mac: Scalar| {
+            AuthenticatedScalar {
+                value: value.value() - shared(mask),
+                visibility: Visibility::Shared,
+                mac_share: Some(value.mac().unwrap() - shared(mask_mac)),
+                key_share: key_share.clone(),
+            }
+        };
+
+        // Open and authenticate d = x - a and e = y - b for every pair in one batch
+        let masked_values = lhs
+            .iter()
+            .zip(rhs.iter())
+            .zip(triplets.iter())
+            .flat_map(|((x, y), triplet)| {
+                [
+                    masked(x, triplet.a, triplet.a_mac),
+                    masked(y, triplet.b, triplet.b_mac),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let opened = AuthenticatedScalar::batch_open_and_authenticate_async(&masked_values).await?;
+
+        // The king adds the public term de to the value, and each party adds its share of the
+        // MAC of de
+        Ok(opened
+            .chunks(2)
+            .zip(triplets.iter())
+            .map(|(opened, triplet)| {
+                let (d, e) = (opened[0].to_scalar(), opened[1].to_scalar());
+                let value =
+                    shared(d * triplet.b + e * triplet.a + triplet.c).add_public_constant(d * e);
+                let mac = shared(
+                    triplet.c_mac
+                        + d * triplet.b_mac
+                        + e * triplet.a_mac
+                        + d * e * key_share.value(),
+                );
+
+                AuthenticatedScalar {
+                    value,
+                    visibility: Visibility::Shared,
+                    mac_share: Some(mac),
+                    key_share: key_share.clone(),
+                }
+            })
+            .collect())
+    }
+}
+
 /**
  * Add and variants for borrowed, non-borrowed, wrapped values
  */
@@ -571,6 +938,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a Authenticat
     type Output = AuthenticatedScalar<N, S>;
 
     fn add(self, rhs: &'a AuthenticatedScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         // For a public value + a scalar value; always put the public value on the RHS
         if self.is_public() && rhs.is_shared() {
             return rhs + self;
@@ -742,6 +1112,7 @@ mod authenticated_scalar_tests {
 
     use clear_on_drop::clear::Clear;
     use curve25519_dalek::scalar::Scalar;
+    use zeroize::Zeroize;
 
     use crate::{
         beaver::DummySharedScalarSource, mpc_scalar::MpcScalar,
@@ -770,4 +1141,25 @@ mod authenticated_scalar_tests {
         assert_eq!(value.mac_share, None);
         assert_eq!(value.key_share().to_scalar(), Scalar::zero());
     }
+
+    #[test]
+    fn test_zeroize() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let key_share = MpcScalar::from_public_u64(2, network.clone(), beaver_source.clone());
+
+        let mut value = AuthenticatedScalar::from_public_u64(
+            3,
+            key_share,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        value.mac_share = Some(MpcScalar::from_public_u64(4u64, network, beaver_source));
+
+        value.zeroize();
+
+        assert_eq!(value.to_scalar(), Scalar::zero());
+        assert_eq!(value.mac_share, None);
+        assert_eq!(value.key_share().to_scalar(), Scalar::zero());
+    }
 }
@@ -1,10 +1,106 @@
 //! Defines the Beaver value generation interface
 //! as well as a dummy beaver interface for testing
+mod counting;
+mod dabit;
+mod dealer;
+mod dry_run;
+mod fallback;
+mod file;
+mod inverse;
+mod mascot;
+mod offline;
+mod ot;
+#[cfg(feature = "paillier")]
+mod paillier;
+mod point;
+mod pool;
+mod prefetch;
+mod sacrifice;
+mod seeded;
 
+pub use counting::{CountingBeaverSource, PreprocessingCounts, DEFAULT_PHASE};
+pub use dabit::{DaBit, DaBitGenerator, DaBitSource, EdaBit};
+pub use dealer::{Dealer, DealerBeaverSource};
+pub use dry_run::{dry_run, DryRunBeaverSource, DryRunFabric, PreprocessingReport};
+pub use fallback::{ExhaustionPolicy, FallbackBeaverSource};
+#[cfg(test)]
+pub(crate) use file::DealtFiles;
+pub use file::{BeaverShares, FileBeaverSource};
+pub use inverse::generate_inverse_pairs;
+pub use mascot::MascotTripleGenerator;
+pub use offline::{OfflinePhase, PreprocessingGenerator, SealedPreprocessing};
+pub use ot::OtTripleGenerator;
+#[cfg(feature = "paillier")]
+pub use paillier::PaillierTripleGenerator;
+pub(crate) use point::to_point_triplet;
+pub use point::PointTripletSource;
+pub use pool::{SharedTriplePool, TripletRefillHook};
+pub use prefetch::PrefetchingBeaverSource;
+pub use sacrifice::verify_triples;
+pub use seeded::SeededMockBeaverSource;
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+#[cfg(test)]
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use curve25519_dalek::ristretto::RistrettoPoint;
 #[cfg(test)]
 use curve25519_dalek::scalar::Scalar;
 use itertools::Itertools;
 
+use crate::{error::MpcError, macros};
+
+/// A matrix triplet (A, B, C) with C = A * B, each matrix flattened in row-major order
+pub type MatrixTriplet<T> = (Vec<T>, Vec<T>, Vec<T>);
+
+/// A point triplet (a, bG, cG) with c = a * b for the Ristretto base point G, which backs the
+/// product of a shared scalar with a shared point without multiplying by the base point online
+pub type PointTriplet<T> = (T, RistrettoPoint, RistrettoPoint);
+
+/// A party's half of a permutation correlation for vectors of length n, which backs a
+/// reshuffle of a shared vector by the permuting party without revealing the permutation
+///
+/// The permuting party holds a random permutation pi and delta = pi(a) - b for random vectors a
+/// and b held by the other party, where pi(v)[i] = v[pi[i]]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PermutationCorrelation<T> {
+    /// The permuting party's half
+    Permuter {
+        /// The permutation pi, as the index of the input each output is taken from
+        permutation: Vec<usize>,
+        /// The vector pi(a) - b
+        delta: Vec<T>,
+    },
+    /// The other party's half
+    Masker {
+        /// The mask a applied to the party's shares before they are sent to the permuting party
+        a: Vec<T>,
+        /// The party's shares of the permuted vector
+        b: Vec<T>,
+    },
+}
+
+/// A party's shares of a Beaver triplet (a, b, c) with c = a * b, along with its shares of the
+/// MACs of a, b, and c under the source's MAC key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuthenticatedTriplet<T> {
+    /// The party's share of a
+    pub a: T,
+    /// The party's share of b
+    pub b: T,
+    /// The party's share of c
+    pub c: T,
+    /// The party's share of the MAC of a
+    pub a_mac: T,
+    /// The party's share of the MAC of b
+    pub b_mac: T,
+    /// The party's share of the MAC of c
+    pub c_mac: T,
+}
+
 /// SharedValueSource implements both the functionality for:
 ///     1. Single additively shared values [x] where party 1 holds
 ///        x_1 and party 2 holds x_2 such that x_1 + x_2 = x
@@ -19,6 +115,11 @@ pub trait SharedValueSource<T> {
             .map(|_| self.next_shared_bit())
             .collect_vec()
     }
+    /// Fetch the next shared batch of bits, or an error if the source cannot supply them; e.g.
+    /// a `PreprocessingExhausted` error once a finite source runs out
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<T>, MpcError> {
+        Ok(self.next_shared_bit_batch(num_values))
+    }
     /// Fetch the next shared single value
     fn next_shared_value(&mut self) -> T;
     /// Fetch a batch of shared single values
@@ -35,12 +136,295 @@ pub trait SharedValueSource<T> {
             .map(|_| self.next_shared_inverse_pair())
             .collect_vec()
     }
+    /// Fetch the next batch of multiplicative inverse pairs, or an error if the source cannot
+    /// supply them
+    fn try_next_inverse_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        Ok(self.next_shared_invers_pair_batch(num_pairs))
+    }
+    /// Fetch the next pair (a, a^2) of a random value and its square
+    fn next_square_pair(&mut self) -> (T, T);
+    /// Fetch the next batch of square pairs
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        (0..num_pairs)
+            .map(|_| self.next_square_pair())
+            .collect_vec()
+    }
+    /// Fetch the next batch of square pairs, or an error if the source cannot supply them
+    fn try_next_square_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        Ok(self.next_square_pair_batch(num_pairs))
+    }
     /// Fetch the next beaver triplet
     fn next_triplet(&mut self) -> (T, T, T);
     /// Fetch a batch of beaver triplets
     fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(T, T, T)> {
         (0..num_triplets).map(|_| self.next_triplet()).collect_vec()
     }
+    /// Fetch a batch of beaver triplets, or an error if the source cannot supply them
+    fn try_next_triplet_batch(&mut self, num_triplets: usize) -> Result<Vec<(T, T, T)>, MpcError> {
+        Ok(self.next_triplet_batch(num_triplets))
+    }
+    /// Fetch a matrix triplet (A, B, A * B) for the product of an n x m matrix with an m x k
+    /// matrix, or an error if the source does not supply matrix triplets
+    fn next_matrix_triplet(
+        &mut self,
+        _n: usize,
+        _m: usize,
+        _k: usize,
+    ) -> Result<MatrixTriplet<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not supply matrix triplets".to_string(),
+        ))
+    }
+    /// Fetch shares of the powers (r, r^2, ..., r^k) of a random value r, or an error if the
+    /// source does not supply exponentiation tuples
+    fn next_exp_tuple(&mut self, _k: usize) -> Result<Vec<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not supply exponentiation tuples".to_string(),
+        ))
+    }
+    /// Fetch the local party's half of a permutation correlation for vectors of length `n`, in
+    /// which `permuting_party` holds the permutation, or an error if the source does not supply
+    /// permutation correlations
+    fn next_permutation_correlation(
+        &mut self,
+        _n: usize,
+        _permuting_party: u64,
+    ) -> Result<PermutationCorrelation<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not supply permutation correlations".to_string(),
+        ))
+    }
+    /// Fetch a batch of point triplets, or `None` if the source does not supply them or holds
+    /// too few, in which case the caller derives them from Beaver triplets
+    fn next_point_triplet_batch(&mut self, _num_triplets: usize) -> Option<Vec<PointTriplet<T>>> {
+        None
+    }
+    /// Fetch the `num_triplets` triplets that follow the next `skip` without consuming them, or
+    /// `None` if the source cannot read ahead or holds too few; a source that records what it
+    /// consumes reads ahead so that a caller prefetching triplets records only those it uses
+    fn peek_triplet_batch(&mut self, _skip: usize, _num_triplets: usize) -> Option<Vec<(T, T, T)>> {
+        None
+    }
+    /// Consume the next `num_triplets` triplets without fetching them, e.g. once the triplets
+    /// read ahead with `peek_triplet_batch` are used
+    fn skip_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        self.try_next_triplet_batch(num_triplets).map(|_| ())
+    }
+    /// Fetch the next authenticated Beaver triplet, or an error if the source does not supply
+    /// authenticated triplets
+    fn next_authenticated_triplet(&mut self) -> Result<AuthenticatedTriplet<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not supply authenticated triplets".to_string(),
+        ))
+    }
+    /// The local party's share of the MAC key the source authenticates its triplets under, or
+    /// `None` if the source does not supply authenticated triplets
+    fn mac_key_share(&self) -> Option<T> {
+        None
+    }
+    /// The number of Beaver triplets left in the source, or `None` if the source is unbounded
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+    /// The number of values consumed from the source so far; persisting the position allows a
+    /// restarted computation to resume without reusing preprocessed values
+    fn position(&self) -> Result<u64, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not support checkpointing".to_string(),
+        ))
+    }
+    /// Resume consuming values from the given position, as returned by `position`
+    fn seek(&mut self, _pos: u64) -> Result<(), MpcError> {
+        Err(MpcError::PreprocessingError(
+            "source does not support checkpointing".to_string(),
+        ))
+    }
+}
+
+/// A callback invoked with the remaining triplet budget when a source runs low
+pub type LowPreprocessingCallback = Box<dyn FnMut(usize)>;
+
+/// Wraps a value source and notifies a registered callback when the source's remaining
+/// triplet budget crosses a threshold
+///
+/// The callback fires once when the budget drops to or below the threshold, and is re-armed
+/// once the budget rises above the threshold again; e.g. after the inner source is refilled.
+/// The callback is invoked while the source is mutably borrowed, so it must not borrow the
+/// source itself.
+pub struct PreprocessingMonitor<S> {
+    /// The underlying source of preprocessed values
+    inner: S,
+    /// The budget at or below which the callback is invoked
+    threshold: usize,
+    /// The callback to invoke when the budget is low
+    on_low_preprocessing: Option<LowPreprocessingCallback>,
+    /// Whether the callback has fired since the budget last crossed the threshold
+    fired: bool,
+}
+
+impl<S> PreprocessingMonitor<S> {
+    /// Wrap a value source with no callback registered
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            threshold: 0,
+            on_low_preprocessing: None,
+            fired: false,
+        }
+    }
+
+    /// Register a callback to be invoked when the remaining budget is at or below `threshold`,
+    /// replacing any previously registered callback
+    pub fn on_low_preprocessing(&mut self, threshold: usize, callback: LowPreprocessingCallback) {
+        self.threshold = threshold;
+        self.on_low_preprocessing = Some(callback);
+        self.fired = false;
+    }
+
+    /// Borrow the underlying source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Mutably borrow the underlying source, e.g. to refill it
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Invoke the callback if the budget has crossed the threshold since it last fired
+    fn check_budget<T>(&mut self)
+    where
+        S: SharedValueSource<T>,
+    {
+        let budget = match self.inner.remaining() {
+            Some(budget) => budget,
+            None => return,
+        };
+
+        if budget > self.threshold {
+            self.fired = false;
+        } else if !self.fired {
+            if let Some(callback) = self.on_low_preprocessing.as_mut() {
+                callback(budget);
+                self.fired = true;
+            }
+        }
+    }
+}
+
+impl<S: Debug> Debug for PreprocessingMonitor<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("PreprocessingMonitor")
+            .field("inner", &self.inner)
+            .field("threshold", &self.threshold)
+            .field("registered", &self.on_low_preprocessing.is_some())
+            .field("fired", &self.fired)
+            .finish()
+    }
+}
+
+impl<T, S: SharedValueSource<T>> SharedValueSource<T> for PreprocessingMonitor<S> {
+    fn next_shared_bit(&mut self) -> T {
+        self.inner.next_shared_bit()
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.inner.next_shared_bit_batch(num_values)
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<T>, MpcError> {
+        self.inner.try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> T {
+        self.inner.next_shared_value()
+    }
+
+    fn next_shared_value_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.inner.next_shared_value_batch(num_values)
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (T, T) {
+        self.inner.next_shared_inverse_pair()
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.inner.next_shared_invers_pair_batch(num_pairs)
+    }
+
+    fn try_next_inverse_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        self.inner.try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (T, T) {
+        self.inner.next_square_pair()
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.inner.next_square_pair_batch(num_pairs)
+    }
+
+    fn try_next_square_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        self.inner.try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (T, T, T) {
+        let triplet = self.inner.next_triplet();
+        self.check_budget();
+        triplet
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(T, T, T)> {
+        let triplets = self.inner.next_triplet_batch(num_triplets);
+        self.check_budget();
+        triplets
+    }
+
+    fn try_next_triplet_batch(&mut self, num_triplets: usize) -> Result<Vec<(T, T, T)>, MpcError> {
+        let triplets = self.inner.try_next_triplet_batch(num_triplets)?;
+        self.check_budget();
+        Ok(triplets)
+    }
+
+    macros::impl_forwarded_correlations!(T, |this| this.inner);
+
+    fn next_point_triplet_batch(&mut self, num_triplets: usize) -> Option<Vec<PointTriplet<T>>> {
+        let triplets = self.inner.next_point_triplet_batch(num_triplets);
+        self.check_budget();
+        triplets
+    }
+
+    fn peek_triplet_batch(&mut self, skip: usize, num_triplets: usize) -> Option<Vec<(T, T, T)>> {
+        self.inner.peek_triplet_batch(skip, num_triplets)
+    }
+
+    fn skip_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        self.inner.skip_triplets(num_triplets)?;
+        self.check_budget();
+        Ok(())
+    }
+
+    fn next_authenticated_triplet(&mut self) -> Result<AuthenticatedTriplet<T>, MpcError> {
+        let triplet = self.inner.next_authenticated_triplet()?;
+        self.check_budget();
+        Ok(triplet)
+    }
+
+    fn mac_key_share(&self) -> Option<T> {
+        self.inner.mac_key_share()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+
+    fn position(&self) -> Result<u64, MpcError> {
+        self.inner.position()
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        self.inner.seek(pos)
+    }
 }
 
 /// A dummy value source that outputs only ones
@@ -71,7 +455,194 @@ impl SharedValueSource<Scalar> for DummySharedScalarSource {
         (Scalar::one(), Scalar::one())
     }
 
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        (Scalar::one(), Scalar::one())
+    }
+
     fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
         (Scalar::one(), Scalar::one(), Scalar::one())
     }
 }
+
+/// A value source whose triplets count up from zero, drawn from an optional fixed capacity
+/// Used for testing
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct CountingSharedScalarSource {
+    /// The number of triplets left in the source, `None` if the source is unbounded
+    pub(crate) capacity: Option<usize>,
+    /// The number of triplets drawn, shared with the test across threads
+    pub(crate) num_drawn: Arc<AtomicUsize>,
+}
+
+#[cfg(test)]
+impl CountingSharedScalarSource {
+    /// Creates a source that holds `capacity` triplets
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the number of triplets drawn from the source
+    pub(crate) fn num_drawn(&self) -> usize {
+        self.num_drawn.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+impl SharedValueSource<Scalar> for CountingSharedScalarSource {
+    fn next_shared_bit(&mut self) -> Scalar {
+        Scalar::one()
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        Scalar::one()
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        (Scalar::one(), Scalar::one())
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        (Scalar::one(), Scalar::one())
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        if let Some(capacity) = self.capacity.as_mut() {
+            *capacity -= 1;
+        }
+
+        let index = Scalar::from(self.num_drawn.fetch_add(1, Ordering::SeqCst) as u64);
+        (index, index, index)
+    }
+
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        match self.capacity {
+            Some(capacity) if num_triplets > capacity => {
+                Err(MpcError::PreprocessingExhausted(format!(
+                    "{} triplets requested, {} remaining",
+                    num_triplets, capacity
+                )))
+            }
+            _ => Ok(self.next_triplet_batch(num_triplets)),
+        }
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        error::MpcError, fabric::AuthenticatedMpcFabric, mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork, Visibility,
+    };
+
+    use super::{CountingSharedScalarSource, PreprocessingMonitor, SharedValueSource};
+
+    #[test]
+    fn test_low_preprocessing_callback() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(PreprocessingMonitor::new(
+            CountingSharedScalarSource::with_capacity(4),
+        )));
+        let fabric =
+            AuthenticatedMpcFabric::new_with_network(0, network.clone(), beaver_source.clone());
+
+        let budgets = Rc::new(RefCell::new(Vec::new()));
+        let budgets_clone = budgets.clone();
+        fabric.on_low_preprocessing(
+            2,
+            Box::new(move |budget| budgets_clone.borrow_mut().push(budget)),
+        );
+
+        let shared_value = MpcScalar::from_u64_with_visibility(
+            2,
+            Visibility::Shared,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let multiply = || {
+            // Mock the peer's shares of the opened Beaver values
+            network
+                .borrow_mut()
+                .add_mock_scalars(vec![Scalar::zero(), Scalar::zero()]);
+            let _ = &shared_value * &shared_value;
+        };
+
+        // The budget is still above the threshold
+        multiply();
+        assert!(budgets.borrow().is_empty());
+
+        // The callback fires once when the budget crosses the threshold
+        multiply();
+        multiply();
+        assert_eq!(*budgets.borrow(), vec![2]);
+
+        // The callback re-arms after the source is refilled
+        beaver_source.borrow_mut().inner_mut().capacity = Some(4);
+        multiply();
+        multiply();
+        assert_eq!(*budgets.borrow(), vec![2, 2]);
+    }
+
+    #[test]
+    fn test_checkpoint_unsupported() {
+        let mut beaver_source =
+            PreprocessingMonitor::new(CountingSharedScalarSource::with_capacity(4));
+
+        assert!(matches!(
+            beaver_source.position(),
+            Err(MpcError::PreprocessingError(_))
+        ));
+        assert!(matches!(
+            beaver_source.seek(2),
+            Err(MpcError::PreprocessingError(_))
+        ));
+    }
+
+    #[test]
+    fn test_preprocessing_exhausted() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(PreprocessingMonitor::new(
+            CountingSharedScalarSource::with_capacity(1),
+        )));
+        let values = (0..2)
+            .map(|i| {
+                MpcScalar::from_scalar_with_visibility(
+                    Scalar::from(i as u64),
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // A batch larger than the source errors without consuming any triplets
+        assert!(matches!(
+            MpcScalar::try_batch_mul(&values, &values),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+
+        // Mock the peer's shares of the opened Beaver values
+        network
+            .borrow_mut()
+            .add_mock_scalars(vec![Scalar::zero(), Scalar::zero()]);
+        assert!(values[0].try_mul(&values[1]).is_ok());
+        assert!(matches!(
+            values[0].try_mul(&values[1]),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+    }
+}
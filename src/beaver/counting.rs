@@ -0,0 +1,423 @@
+//! Implements a value source wrapper that counts the values a computation draws, e.g. to size
+//! the offline phase that generates them
+
+use std::ops::AddAssign;
+
+use crate::error::MpcError;
+
+use super::{
+    AuthenticatedTriplet, MatrixTriplet, PermutationCorrelation, PointTriplet, SharedValueSource,
+};
+
+/// The phase values are attributed to until `CountingBeaverSource::set_phase` is called
+pub const DEFAULT_PHASE: &str = "default";
+
+/// The number of values of each kind drawn from a source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreprocessingCounts {
+    /// The number of Beaver triplets drawn
+    pub triplets: usize,
+    /// The number of shared bits drawn
+    pub bits: usize,
+    /// The number of inverse pairs drawn
+    pub inverse_pairs: usize,
+    /// The number of square pairs drawn
+    pub square_pairs: usize,
+    /// The number of matrix triplets drawn
+    pub matrix_triplets: usize,
+    /// The number of exponentiation tuples drawn
+    pub exp_tuples: usize,
+    /// The number of permutation correlations drawn
+    pub permutation_correlations: usize,
+    /// The number of point triplets drawn
+    pub point_triplets: usize,
+    /// The number of authenticated triplets drawn
+    pub authenticated_triplets: usize,
+    /// The number of shared values drawn
+    pub shared_values: usize,
+}
+
+impl AddAssign for PreprocessingCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.triplets += rhs.triplets;
+        self.bits += rhs.bits;
+        self.inverse_pairs += rhs.inverse_pairs;
+        self.square_pairs += rhs.square_pairs;
+        self.matrix_triplets += rhs.matrix_triplets;
+        self.exp_tuples += rhs.exp_tuples;
+        self.permutation_correlations += rhs.permutation_correlations;
+        self.point_triplets += rhs.point_triplets;
+        self.authenticated_triplets += rhs.authenticated_triplets;
+        self.shared_values += rhs.shared_values;
+    }
+}
+
+/// The counts of each phase of a computation, in the order the phases were first entered
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PhaseCounts(Vec<(String, PreprocessingCounts)>);
+
+impl PhaseCounts {
+    /// Returns the index of the named phase, adding the phase with zero counts if it is new
+    fn index_of(&mut self, phase: &str) -> usize {
+        match self.0.iter().position(|(name, _)| name == phase) {
+            Some(index) => index,
+            None => {
+                self.0
+                    .push((phase.to_string(), PreprocessingCounts::default()));
+                self.0.len() - 1
+            }
+        }
+    }
+
+    /// Returns the number of values drawn across all phases
+    pub(crate) fn totals(&self) -> PreprocessingCounts {
+        let mut totals = PreprocessingCounts::default();
+        for (_, counts) in self.0.iter() {
+            totals += *counts;
+        }
+
+        totals
+    }
+
+    /// Returns the number of values drawn in the named phase, or `None` if the phase was never
+    /// entered
+    pub(crate) fn phase_counts(&self, phase: &str) -> Option<PreprocessingCounts> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == phase)
+            .map(|(_, counts)| *counts)
+    }
+
+    /// Returns the counts of each phase, in the order the phases were first entered
+    pub(crate) fn phases(&self) -> impl Iterator<Item = (&str, PreprocessingCounts)> {
+        self.0.iter().map(|(name, counts)| (name.as_str(), *counts))
+    }
+}
+
+/// Wraps a value source and counts the values drawn from it, both in total and per phase of
+/// the computation
+///
+/// Values are attributed to the current phase, which the caller names with `set_phase` as the
+/// computation moves between protocols; e.g. "shuffle" and then "compare". Only values that
+/// were successfully drawn are counted
+#[derive(Debug)]
+pub struct CountingBeaverSource<S> {
+    /// The underlying source of preprocessed values
+    inner: S,
+    /// The index in `phases` of the current phase
+    current_phase: usize,
+    /// The counts of each phase
+    phases: PhaseCounts,
+}
+
+impl<S> CountingBeaverSource<S> {
+    /// Wrap a value source, attributing values to `DEFAULT_PHASE` until a phase is set
+    pub fn new(inner: S) -> Self {
+        let mut phases = PhaseCounts::default();
+        let current_phase = phases.index_of(DEFAULT_PHASE);
+
+        Self {
+            inner,
+            current_phase,
+            phases,
+        }
+    }
+
+    /// Attribute the values drawn from now on to the named phase; re-entering a phase adds to
+    /// its counts
+    pub fn set_phase(&mut self, phase: &str) {
+        self.current_phase = self.phases.index_of(phase);
+    }
+
+    /// Returns the name of the current phase
+    pub fn phase(&self) -> &str {
+        &self.phases.0[self.current_phase].0
+    }
+
+    /// Returns the number of values drawn across all phases
+    pub fn totals(&self) -> PreprocessingCounts {
+        self.phases.totals()
+    }
+
+    /// Returns the number of values drawn in the named phase, or `None` if the phase was never
+    /// entered
+    pub fn phase_counts(&self, phase: &str) -> Option<PreprocessingCounts> {
+        self.phases.phase_counts(phase)
+    }
+
+    /// Returns the counts of each phase, in the order the phases were first entered
+    pub fn phases(&self) -> impl Iterator<Item = (&str, PreprocessingCounts)> {
+        self.phases.phases()
+    }
+
+    /// Returns the counts of each phase, e.g. to report them once the computation is done
+    pub(crate) fn counts_by_phase(&self) -> &PhaseCounts {
+        &self.phases
+    }
+
+    /// Clears the counts of every phase, keeping the current phase
+    pub fn reset(&mut self) {
+        for (_, counts) in self.phases.0.iter_mut() {
+            *counts = PreprocessingCounts::default();
+        }
+    }
+
+    /// Borrow the underlying source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Mutably borrow the underlying source, e.g. to refill it
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Returns the counts of the current phase
+    fn current_counts(&mut self) -> &mut PreprocessingCounts {
+        &mut self.phases.0[self.current_phase].1
+    }
+}
+
+impl<T, S: SharedValueSource<T>> SharedValueSource<T> for CountingBeaverSource<S> {
+    fn next_shared_bit(&mut self) -> T {
+        self.current_counts().bits += 1;
+        self.inner.next_shared_bit()
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.current_counts().bits += num_values;
+        self.inner.next_shared_bit_batch(num_values)
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<T>, MpcError> {
+        let bits = self.inner.try_next_shared_bit_batch(num_values)?;
+        self.current_counts().bits += num_values;
+        Ok(bits)
+    }
+
+    fn next_shared_value(&mut self) -> T {
+        self.current_counts().shared_values += 1;
+        self.inner.next_shared_value()
+    }
+
+    fn next_shared_value_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.current_counts().shared_values += num_values;
+        self.inner.next_shared_value_batch(num_values)
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (T, T) {
+        self.current_counts().inverse_pairs += 1;
+        self.inner.next_shared_inverse_pair()
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.current_counts().inverse_pairs += num_pairs;
+        self.inner.next_shared_invers_pair_batch(num_pairs)
+    }
+
+    fn try_next_inverse_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        let pairs = self.inner.try_next_inverse_pair_batch(num_pairs)?;
+        self.current_counts().inverse_pairs += num_pairs;
+        Ok(pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (T, T) {
+        self.current_counts().square_pairs += 1;
+        self.inner.next_square_pair()
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.current_counts().square_pairs += num_pairs;
+        self.inner.next_square_pair_batch(num_pairs)
+    }
+
+    fn try_next_square_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        let pairs = self.inner.try_next_square_pair_batch(num_pairs)?;
+        self.current_counts().square_pairs += num_pairs;
+        Ok(pairs)
+    }
+
+    fn next_triplet(&mut self) -> (T, T, T) {
+        self.current_counts().triplets += 1;
+        self.inner.next_triplet()
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(T, T, T)> {
+        self.current_counts().triplets += num_triplets;
+        self.inner.next_triplet_batch(num_triplets)
+    }
+
+    fn try_next_triplet_batch(&mut self, num_triplets: usize) -> Result<Vec<(T, T, T)>, MpcError> {
+        let triplets = self.inner.try_next_triplet_batch(num_triplets)?;
+        self.current_counts().triplets += num_triplets;
+        Ok(triplets)
+    }
+
+    fn next_matrix_triplet(
+        &mut self,
+        n: usize,
+        m: usize,
+        k: usize,
+    ) -> Result<MatrixTriplet<T>, MpcError> {
+        let triplet = self.inner.next_matrix_triplet(n, m, k)?;
+        self.current_counts().matrix_triplets += 1;
+        Ok(triplet)
+    }
+
+    fn next_exp_tuple(&mut self, k: usize) -> Result<Vec<T>, MpcError> {
+        let powers = self.inner.next_exp_tuple(k)?;
+        self.current_counts().exp_tuples += 1;
+        Ok(powers)
+    }
+
+    fn next_permutation_correlation(
+        &mut self,
+        n: usize,
+        permuting_party: u64,
+    ) -> Result<PermutationCorrelation<T>, MpcError> {
+        let correlation = self
+            .inner
+            .next_permutation_correlation(n, permuting_party)?;
+        self.current_counts().permutation_correlations += 1;
+        Ok(correlation)
+    }
+
+    fn next_point_triplet_batch(&mut self, num_triplets: usize) -> Option<Vec<PointTriplet<T>>> {
+        let triplets = self.inner.next_point_triplet_batch(num_triplets)?;
+        self.current_counts().point_triplets += num_triplets;
+        Some(triplets)
+    }
+
+    fn peek_triplet_batch(&mut self, skip: usize, num_triplets: usize) -> Option<Vec<(T, T, T)>> {
+        self.inner.peek_triplet_batch(skip, num_triplets)
+    }
+
+    /// Triplets are counted once they are consumed, not when they are read ahead
+    fn skip_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        self.inner.skip_triplets(num_triplets)?;
+        self.current_counts().triplets += num_triplets;
+        Ok(())
+    }
+
+    fn next_authenticated_triplet(&mut self) -> Result<AuthenticatedTriplet<T>, MpcError> {
+        let triplet = self.inner.next_authenticated_triplet()?;
+        self.current_counts().authenticated_triplets += 1;
+        Ok(triplet)
+    }
+
+    fn mac_key_share(&self) -> Option<T> {
+        self.inner.mac_key_share()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+
+    fn position(&self) -> Result<u64, MpcError> {
+        self.inner.position()
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        self.inner.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::{DummySharedScalarSource, PointTripletSource, SharedValueSource},
+        mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork,
+        Visibility,
+    };
+
+    use super::{CountingBeaverSource, PreprocessingCounts, DEFAULT_PHASE};
+
+    #[test]
+    fn test_counting() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(CountingBeaverSource::new(
+            DummySharedScalarSource::new(),
+        )));
+
+        // A batch multiplication of shared values draws one triplet per product
+        let values = (0..3u64)
+            .map(|i| {
+                MpcScalar::from_u64_with_visibility(
+                    i,
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        network
+            .borrow_mut()
+            .add_mock_scalars(vec![Scalar::zero(); 6]);
+        MpcScalar::batch_mul(&values, &values).unwrap();
+
+        beaver_source.borrow_mut().set_phase("compare");
+        beaver_source.borrow_mut().next_shared_bit_batch(4);
+        beaver_source.borrow_mut().next_shared_value();
+        beaver_source.borrow_mut().set_phase(DEFAULT_PHASE);
+        beaver_source.borrow_mut().next_shared_inverse_pair();
+
+        let beaver_source = beaver_source.borrow();
+        assert_eq!(
+            beaver_source.phase_counts(DEFAULT_PHASE),
+            Some(PreprocessingCounts {
+                triplets: 3,
+                inverse_pairs: 1,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            beaver_source.phase_counts("compare"),
+            Some(PreprocessingCounts {
+                bits: 4,
+                shared_values: 1,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            beaver_source.totals(),
+            PreprocessingCounts {
+                triplets: 3,
+                bits: 4,
+                inverse_pairs: 1,
+                shared_values: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            beaver_source
+                .phases()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec![DEFAULT_PHASE, "compare"]
+        );
+    }
+
+    #[test]
+    fn test_counting_point_triplets() {
+        let mut point_source = PointTripletSource::new(DummySharedScalarSource::new());
+        point_source.precompute(2).unwrap();
+        let mut beaver_source = CountingBeaverSource::new(point_source);
+
+        // Point triplets are counted apart from the triplets they were precomputed from
+        assert!(beaver_source.next_point_triplet_batch(2).is_some());
+        beaver_source.next_triplet();
+        assert_eq!(
+            beaver_source.totals(),
+            PreprocessingCounts {
+                triplets: 1,
+                point_triplets: 2,
+                ..Default::default()
+            }
+        );
+    }
+}
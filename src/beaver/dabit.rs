@@ -0,0 +1,196 @@
+//! Implements the generation of daBits and edaBits; random bits shared both additively over the
+//! scalar field and by XOR, which allow values to be converted between the two domains
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::{izip, Itertools};
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    error::MpcError, mpc_scalar::bits::pow2, mpc_scalar::MpcScalar, network::MpcNetwork,
+    BeaverSource, SharedNetwork, Visibility,
+};
+
+use super::SharedValueSource;
+
+/// A party's shares of a random bit b, shared in both domains
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DaBit {
+    /// The party's additive share of b over the scalar field
+    pub arithmetic: Scalar,
+    /// The party's XOR share of b
+    pub binary: bool,
+}
+
+/// A party's shares of a random value r in [0, 2^k), shared additively over the scalar field,
+/// along with XOR shares of its k bits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdaBit {
+    /// The party's additive share of r over the scalar field
+    pub arithmetic: Scalar,
+    /// The party's XOR shares of the bits of r, in little endian order
+    pub binary: Vec<bool>,
+}
+
+/// A source of daBits and edaBits, the companion to `SharedValueSource` for protocols that
+/// convert between arithmetic and binary sharings
+pub trait DaBitSource {
+    /// Fetch the next daBit
+    fn next_dabit(&mut self) -> Result<DaBit, MpcError> {
+        Ok(self.next_dabit_batch(1)?.remove(0))
+    }
+    /// Fetch a batch of daBits
+    fn next_dabit_batch(&mut self, num_dabits: usize) -> Result<Vec<DaBit>, MpcError>;
+    /// Fetch the next edaBit of `num_bits` bits
+    fn next_edabit(&mut self, num_bits: usize) -> Result<EdaBit, MpcError> {
+        Ok(self.next_edabit_batch(1, num_bits)?.remove(0))
+    }
+    /// Fetch a batch of edaBits of `num_bits` bits each
+    ///
+    /// By default each edaBit is assembled from `num_bits` daBits, as r = \sum_i b_i * 2^i
+    fn next_edabit_batch(
+        &mut self,
+        num_edabits: usize,
+        num_bits: usize,
+    ) -> Result<Vec<EdaBit>, MpcError> {
+        if num_bits > 252 {
+            return Err(MpcError::PreprocessingError(format!(
+                "an edaBit of {} bits does not fit in the scalar field",
+                num_bits
+            )));
+        }
+
+        let dabits = self.next_dabit_batch(num_edabits * num_bits)?;
+        Ok((0..num_edabits)
+            .map(|i| {
+                let bits = &dabits[i * num_bits..(i + 1) * num_bits];
+                EdaBit {
+                    arithmetic: bits
+                        .iter()
+                        .enumerate()
+                        .map(|(j, bit)| bit.arithmetic * pow2(j))
+                        .sum(),
+                    binary: bits.iter().map(|bit| bit.binary).collect_vec(),
+                }
+            })
+            .collect_vec())
+    }
+}
+
+/// Generates daBits online from the local party's random bits and the network's Beaver triplets
+///
+/// Each party samples a random bit b_i, which is its XOR share of b = b_0 ^ b_1. The parties
+/// then compute the additive sharing b = b_0 + b_1 - 2 * b_0 * b_1, where each b_i is input as
+/// a trivial sharing by its owner. The protocol is secure against semi-honest parties only; a
+/// malicious party may input a value other than a bit.
+///
+/// Cost: one Beaver triplet per daBit and one round of communication per batch
+#[derive(Debug)]
+pub struct DaBitGenerator<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The network the daBits are generated over
+    network: SharedNetwork<N>,
+    /// The source of the Beaver triplets consumed by the generation
+    beaver_source: BeaverSource<S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> DaBitGenerator<N, S> {
+    /// Create a generator over the given network and Beaver source
+    pub fn new(network: SharedNetwork<N>, beaver_source: BeaverSource<S>) -> Self {
+        Self {
+            network,
+            beaver_source,
+        }
+    }
+
+    /// Allocates the trivial sharings of the given bits, input by the king if `king_input` is
+    /// set and by the peer otherwise
+    fn share_inputs(&self, bits: &[bool], king_input: bool) -> Vec<MpcScalar<N, S>> {
+        let is_owner = self.network.borrow().am_king() == king_input;
+        bits.iter()
+            .map(|bit| {
+                let share = if is_owner {
+                    Scalar::from(*bit as u64)
+                } else {
+                    Scalar::zero()
+                };
+
+                MpcScalar::from_scalar_with_visibility(
+                    share,
+                    Visibility::Shared,
+                    self.network.clone(),
+                    self.beaver_source.clone(),
+                )
+            })
+            .collect_vec()
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> DaBitSource for DaBitGenerator<N, S> {
+    fn next_dabit_batch(&mut self, num_dabits: usize) -> Result<Vec<DaBit>, MpcError> {
+        let mut rng = OsRng {};
+        let bits = (0..num_dabits)
+            .map(|_| rng.next_u32() & 1 == 1)
+            .collect_vec();
+
+        let king_bits = self.share_inputs(&bits, true /* king_input */);
+        let peer_bits = self.share_inputs(&bits, false /* king_input */);
+        let products =
+            MpcScalar::batch_mul(&king_bits, &peer_bits).map_err(MpcError::NetworkError)?;
+
+        Ok(izip!(bits, king_bits, peer_bits, products)
+            .map(|(bit, king_bit, peer_bit, product)| DaBit {
+                arithmetic: king_bit.value() + peer_bit.value()
+                    - Scalar::from(2u64) * product.value(),
+                binary: bit,
+            })
+            .collect_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{beaver::DealtFiles, mpc_scalar::bits::pow2, network::MpcNetwork};
+
+    use super::{DaBitGenerator, DaBitSource};
+
+    #[test]
+    fn test_dabits() {
+        let files = DealtFiles::new("dabits", 16 + 2 * 8, 0, 0, 0);
+        let mut results = files
+            .run_parties(|source, network| {
+                let network = Rc::new(RefCell::new(network));
+                let mut generator =
+                    DaBitGenerator::new(network.clone(), Rc::new(RefCell::new(source)));
+                let dabits = generator.next_dabit_batch(16).unwrap();
+                let edabits = generator.next_edabit_batch(2, 8).unwrap();
+
+                block_on(network.borrow_mut().close()).unwrap();
+                (dabits, edabits)
+            })
+            .into_iter();
+        let (dabits0, edabits0) = results.next().unwrap();
+        let (dabits1, edabits1) = results.next().unwrap();
+
+        // Both sharings of a daBit hold the same bit
+        for (dabit0, dabit1) in dabits0.iter().zip(dabits1.iter()) {
+            let bit = Scalar::from((dabit0.binary ^ dabit1.binary) as u64);
+            assert_eq!(dabit0.arithmetic + dabit1.arithmetic, bit);
+        }
+
+        // An edaBit's value is the sum of its bits
+        for (edabit0, edabit1) in edabits0.iter().zip(edabits1.iter()) {
+            let value = edabit0
+                .binary
+                .iter()
+                .zip(edabit1.binary.iter())
+                .enumerate()
+                .map(|(i, (bit0, bit1))| Scalar::from((bit0 ^ bit1) as u64) * pow2(i))
+                .sum::<Scalar>();
+            assert_eq!(edabit0.arithmetic + edabit1.arithmetic, value);
+        }
+    }
+}
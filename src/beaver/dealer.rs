@@ -0,0 +1,775 @@
+//! Implements a value source served by a trusted dealer, which samples the correlated randomness
+//! of the MPC and streams each party its shares
+
+use std::{collections::VecDeque, convert::TryInto, iter};
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    block_on,
+    error::{MpcError, MpcNetworkError},
+    network::MpcNetwork,
+};
+
+use super::{MatrixTriplet, PermutationCorrelation, SharedValueSource};
+
+/// The number of scalars in a request; the kind of the values requested and their number
+const REQUEST_SCALARS: usize = 2;
+/// The number of scalars that follow a request for a matrix triplet, whose number of values
+/// gives the rows n of A; the columns m of A and k of B
+const MATRIX_REQUEST_SCALARS: usize = 2;
+/// The number of scalars that follow a request for a permutation correlation, whose number of
+/// values gives its length; the permuting party
+const PERMUTATION_REQUEST_SCALARS: usize = 1;
+/// The largest number of values dealt in response to a single request
+const MAX_REQUEST_VALUES: u64 = 1 << 20;
+/// The number of values requested when a source runs dry, unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// The values a party requests from the dealer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RequestKind {
+    /// The party is done; the dealer closes its networks once both parties are done
+    Close = 0,
+    Triplets = 1,
+    Bits = 2,
+    InversePairs = 3,
+    SquarePairs = 4,
+    MatrixTriplet = 5,
+    ExpTuple = 6,
+    PermutationCorrelation = 7,
+}
+
+impl RequestKind {
+    /// Decodes the kind from the first scalar of a request
+    fn from_scalar(scalar: &Scalar) -> Result<Self, MpcNetworkError> {
+        match scalar_to_u64(scalar)? {
+            0 => Ok(Self::Close),
+            1 => Ok(Self::Triplets),
+            2 => Ok(Self::Bits),
+            3 => Ok(Self::InversePairs),
+            4 => Ok(Self::SquarePairs),
+            5 => Ok(Self::MatrixTriplet),
+            6 => Ok(Self::ExpTuple),
+            7 => Ok(Self::PermutationCorrelation),
+            _ => Err(MpcNetworkError::SerializationError),
+        }
+    }
+}
+
+/// Decodes a scalar that encodes a u64, as written by `Scalar::from`
+fn scalar_to_u64(scalar: &Scalar) -> Result<u64, MpcNetworkError> {
+    let bytes = scalar.as_bytes();
+    if bytes[8..].iter().any(|byte| *byte != 0) {
+        return Err(MpcNetworkError::SerializationError);
+    }
+
+    Ok(u64::from_le_bytes(bytes[..8].try_into().unwrap()))
+}
+
+/// A trusted dealer that samples Beaver triplets, shared bits, inverse pairs, square pairs,
+/// matrix triplets, exponentiation tuples, and permutation correlations, and streams each party
+/// its shares of them
+///
+/// The dealer holds a network to each party and serves the requests of their
+/// `DealerBeaverSource`s. The dealer learns every value it deals, so it is only suitable for
+/// semi-honest deployments in which the dealer does not collude with either party
+#[derive(Debug)]
+pub struct Dealer<N: MpcNetwork + Send> {
+    /// The network to party 0
+    party0_network: N,
+    /// The network to party 1
+    party1_network: N,
+}
+
+impl<N: MpcNetwork + Send> Dealer<N> {
+    pub fn new(party0_network: N, party1_network: N) -> Self {
+        Self {
+            party0_network,
+            party1_network,
+        }
+    }
+
+    /// Serves the parties' requests until both close their sources, then closes the networks
+    ///
+    /// The parties must request the same values in the same order; returns
+    /// `MpcNetworkError::DealerDesync` if their requests differ
+    pub async fn serve(&mut self) -> Result<(), MpcNetworkError> {
+        loop {
+            let request = self.party0_network.receive_scalars(REQUEST_SCALARS).await?;
+            let peer_request = self.party1_network.receive_scalars(REQUEST_SCALARS).await?;
+            if request != peer_request {
+                return Err(MpcNetworkError::DealerDesync);
+            }
+
+            let num_values = scalar_to_u64(&request[1])?;
+            if num_values > MAX_REQUEST_VALUES {
+                return Err(MpcNetworkError::SerializationError);
+            }
+
+            let (shares0, shares1) = match RequestKind::from_scalar(&request[0])? {
+                RequestKind::Close => {
+                    self.party0_network.close().await?;
+                    return self.party1_network.close().await;
+                }
+                RequestKind::Triplets => split_shares(&deal_triplets(num_values as usize)),
+                RequestKind::Bits => split_shares(&deal_bits(num_values as usize)),
+                RequestKind::InversePairs => split_shares(&deal_inverse_pairs(num_values as usize)),
+                RequestKind::SquarePairs => split_shares(&deal_square_pairs(num_values as usize)),
+                RequestKind::MatrixTriplet => {
+                    let shape = self
+                        .party0_network
+                        .receive_scalars(MATRIX_REQUEST_SCALARS)
+                        .await?;
+                    let peer_shape = self
+                        .party1_network
+                        .receive_scalars(MATRIX_REQUEST_SCALARS)
+                        .await?;
+                    if shape != peer_shape {
+                        return Err(MpcNetworkError::DealerDesync);
+                    }
+
+                    let (m, k) = (scalar_to_u64(&shape[0])?, scalar_to_u64(&shape[1])?);
+
+                    // The dimensions are bounded first so that the sizes cannot overflow
+                    if m > MAX_REQUEST_VALUES
+                        || k > MAX_REQUEST_VALUES
+                        || num_values * m + m * k + num_values * k > MAX_REQUEST_VALUES
+                    {
+                        return Err(MpcNetworkError::SerializationError);
+                    }
+
+                    split_shares(&deal_matrix_triplet(
+                        num_values as usize,
+                        m as usize,
+                        k as usize,
+                    ))
+                }
+                RequestKind::ExpTuple => split_shares(&deal_exp_tuple(num_values as usize)),
+                RequestKind::PermutationCorrelation => {
+                    let permuting_party = self
+                        .party0_network
+                        .receive_scalars(PERMUTATION_REQUEST_SCALARS)
+                        .await?;
+                    let peer_permuting_party = self
+                        .party1_network
+                        .receive_scalars(PERMUTATION_REQUEST_SCALARS)
+                        .await?;
+                    if permuting_party != peer_permuting_party {
+                        return Err(MpcNetworkError::DealerDesync);
+                    }
+
+                    let (permuter, masker) = deal_permutation_correlation(num_values as usize);
+                    match scalar_to_u64(&permuting_party[0])? {
+                        0 => (permuter, masker),
+                        1 => (masker, permuter),
+                        _ => return Err(MpcNetworkError::SerializationError),
+                    }
+                }
+            };
+
+            self.party0_network.send_scalars(&shares0).await?;
+            self.party1_network.send_scalars(&shares1).await?;
+        }
+    }
+}
+
+/// Splits values into additive shares; party 0 receives a random share and party 1 the remainder
+pub(super) fn split_shares(values: &[Scalar]) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut rng = OsRng {};
+    let shares0 = values
+        .iter()
+        .map(|_| Scalar::random(&mut rng))
+        .collect_vec();
+    let shares1 = values
+        .iter()
+        .zip(shares0.iter())
+        .map(|(value, share0)| value - share0)
+        .collect_vec();
+
+    (shares0, shares1)
+}
+
+/// Samples Beaver triplets (a, b, a * b), flattened
+pub(super) fn deal_triplets(num_triplets: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_triplets)
+        .flat_map(|_| {
+            let a = Scalar::random(&mut rng);
+            let b = Scalar::random(&mut rng);
+            [a, b, a * b]
+        })
+        .collect_vec()
+}
+
+/// Samples bits
+pub(super) fn deal_bits(num_bits: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_bits)
+        .map(|_| Scalar::from(rng.next_u32() as u64 & 1))
+        .collect_vec()
+}
+
+/// Samples pairs (r, r^-1), flattened
+pub(super) fn deal_inverse_pairs(num_pairs: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_pairs)
+        .flat_map(|_| {
+            let value = Scalar::random(&mut rng);
+            [value, value.invert()]
+        })
+        .collect_vec()
+}
+
+/// Samples pairs (a, a^2), flattened
+pub(super) fn deal_square_pairs(num_pairs: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_pairs)
+        .flat_map(|_| {
+            let value = Scalar::random(&mut rng);
+            [value, value * value]
+        })
+        .collect_vec()
+}
+
+/// Samples a matrix triplet (A, B, A * B) for the product of an n x m matrix with an m x k
+/// matrix, flattened in row-major order
+pub(super) fn deal_matrix_triplet(n: usize, m: usize, k: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    let a = (0..n * m).map(|_| Scalar::random(&mut rng)).collect_vec();
+    let b = (0..m * k).map(|_| Scalar::random(&mut rng)).collect_vec();
+    let c = (0..n * k)
+        .map(|index| {
+            let (row, col) = (index / k, index % k);
+            (0..m).map(|j| a[row * m + j] * b[j * k + col]).sum()
+        })
+        .collect_vec();
+
+    a.into_iter().chain(b).chain(c).collect_vec()
+}
+
+/// Samples an exponentiation tuple (r, r^2, ..., r^k)
+pub(super) fn deal_exp_tuple(k: usize) -> Vec<Scalar> {
+    let value = Scalar::random(&mut OsRng {});
+    let mut power = Scalar::one();
+    (0..k)
+        .map(|_| {
+            power *= value;
+            power
+        })
+        .collect_vec()
+}
+
+/// Samples a permutation correlation of length n; returns the permuting party's half, the
+/// permutation pi followed by pi(a) - b, and the other party's half, a followed by b, each
+/// prefixed with whether the half is the permuting party's
+///
+/// The halves are not additive shares, each party receives its half in the clear; the prefix
+/// tells a party its role, as its network to the dealer does not carry its ID in the MPC
+pub(super) fn deal_permutation_correlation(n: usize) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut rng = OsRng {};
+    let mut permutation = (0..n).collect_vec();
+    for i in (1..n).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        permutation.swap(i, j);
+    }
+
+    let a = (0..n).map(|_| Scalar::random(&mut rng)).collect_vec();
+    let b = (0..n).map(|_| Scalar::random(&mut rng)).collect_vec();
+    let delta = permutation
+        .iter()
+        .zip(b.iter())
+        .map(|(index, b)| a[*index] - b);
+    let permuter = iter::once(Scalar::one())
+        .chain(permutation.iter().map(|index| Scalar::from(*index as u64)))
+        .chain(delta)
+        .collect_vec();
+    let masker = iter::once(Scalar::zero()).chain(a).chain(b).collect_vec();
+
+    (permuter, masker)
+}
+
+/// A source of Beaver triplets, shared bits, inverse pairs, square pairs, matrix triplets,
+/// exponentiation tuples, and permutation correlations dealt by a trusted `Dealer`
+///
+/// Both parties must draw the same values in the same order, with the same batch size, so that
+/// their requests to the dealer match. Values are best requested ahead of the online phase
+/// with the `request_*` methods; a source that runs dry requests another batch on demand, and
+/// panics if the dealer cannot be reached
+#[derive(Debug)]
+pub struct DealerBeaverSource<N: MpcNetwork + Send> {
+    /// The network to the dealer
+    network: N,
+    /// The number of values requested when the source runs dry
+    batch_size: usize,
+    /// The dealt triplets not yet drawn from the source
+    triplets: VecDeque<(Scalar, Scalar, Scalar)>,
+    /// The dealt shared bits not yet drawn from the source
+    bits: VecDeque<Scalar>,
+    /// The dealt inverse pairs not yet drawn from the source
+    inverse_pairs: VecDeque<(Scalar, Scalar)>,
+    /// The dealt square pairs not yet drawn from the source
+    square_pairs: VecDeque<(Scalar, Scalar)>,
+    /// The dealt matrix triplets not yet drawn from the source, with the shape (n, m, k) of
+    /// each
+    matrix_triplets: VecDeque<((usize, usize, usize), MatrixTriplet<Scalar>)>,
+    /// The dealt exponentiation tuples not yet drawn from the source
+    exp_tuples: VecDeque<Vec<Scalar>>,
+    /// The dealt permutation correlations not yet drawn from the source, with the length and
+    /// permuting party of each
+    permutation_correlations: VecDeque<((usize, u64), PermutationCorrelation<Scalar>)>,
+}
+
+impl<N: MpcNetwork + Send> DealerBeaverSource<N> {
+    pub fn new(network: N) -> Self {
+        Self {
+            network,
+            batch_size: DEFAULT_BATCH_SIZE,
+            triplets: VecDeque::new(),
+            bits: VecDeque::new(),
+            inverse_pairs: VecDeque::new(),
+            square_pairs: VecDeque::new(),
+            matrix_triplets: VecDeque::new(),
+            exp_tuples: VecDeque::new(),
+            permutation_correlations: VecDeque::new(),
+        }
+    }
+
+    /// Sets the number of values requested when the source runs dry; both parties must use the
+    /// same batch size
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = usize::max(1, batch_size);
+        self
+    }
+
+    /// Requests Beaver triplets from the dealer, to be drawn from the source
+    pub async fn request_triplets(&mut self, num_triplets: usize) -> Result<(), MpcNetworkError> {
+        let shares = self.request(RequestKind::Triplets, num_triplets, 3).await?;
+        self.triplets.extend(
+            shares
+                .chunks(3)
+                .map(|shares| (shares[0], shares[1], shares[2])),
+        );
+        Ok(())
+    }
+
+    /// Requests shared bits from the dealer, to be drawn from the source
+    pub async fn request_bits(&mut self, num_bits: usize) -> Result<(), MpcNetworkError> {
+        let shares = self.request(RequestKind::Bits, num_bits, 1).await?;
+        self.bits.extend(shares);
+        Ok(())
+    }
+
+    /// Requests inverse pairs from the dealer, to be drawn from the source
+    pub async fn request_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcNetworkError> {
+        let shares = self
+            .request(RequestKind::InversePairs, num_pairs, 2)
+            .await?;
+        self.inverse_pairs
+            .extend(shares.chunks(2).map(|shares| (shares[0], shares[1])));
+        Ok(())
+    }
+
+    /// Requests square pairs from the dealer, to be drawn from the source
+    pub async fn request_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcNetworkError> {
+        let shares = self.request(RequestKind::SquarePairs, num_pairs, 2).await?;
+        self.square_pairs
+            .extend(shares.chunks(2).map(|shares| (shares[0], shares[1])));
+        Ok(())
+    }
+
+    /// Requests a matrix triplet for the product of an n x m matrix with an m x k matrix from
+    /// the dealer, to be drawn from the source
+    pub async fn request_matrix_triplet(
+        &mut self,
+        n: usize,
+        m: usize,
+        k: usize,
+    ) -> Result<(), MpcNetworkError> {
+        self.network
+            .send_scalars(&[
+                Scalar::from(RequestKind::MatrixTriplet as u64),
+                Scalar::from(n as u64),
+            ])
+            .await?;
+        self.network
+            .send_scalars(&[Scalar::from(m as u64), Scalar::from(k as u64)])
+            .await?;
+
+        let mut shares = self.network.receive_scalars(n * m + m * k + n * k).await?;
+        let c = shares.split_off(n * m + m * k);
+        let b = shares.split_off(n * m);
+        self.matrix_triplets.push_back(((n, m, k), (shares, b, c)));
+        Ok(())
+    }
+
+    /// Requests an exponentiation tuple (r, r^2, ..., r^k) from the dealer, to be drawn from the
+    /// source
+    pub async fn request_exp_tuple(&mut self, k: usize) -> Result<(), MpcNetworkError> {
+        let shares = self.request(RequestKind::ExpTuple, k, 1).await?;
+        self.exp_tuples.push_back(shares);
+        Ok(())
+    }
+
+    /// Requests a permutation correlation of length n, in which `permuting_party` holds the
+    /// permutation, from the dealer, to be drawn from the source
+    pub async fn request_permutation_correlation(
+        &mut self,
+        n: usize,
+        permuting_party: u64,
+    ) -> Result<(), MpcNetworkError> {
+        self.network
+            .send_scalars(&[
+                Scalar::from(RequestKind::PermutationCorrelation as u64),
+                Scalar::from(n as u64),
+            ])
+            .await?;
+        self.network
+            .send_scalars(&[Scalar::from(permuting_party)])
+            .await?;
+
+        let mut half = self.network.receive_scalars(2 * n + 1).await?;
+        let second = half.split_off(n + 1);
+        let correlation = if half.remove(0) == Scalar::one() {
+            let permutation = half
+                .iter()
+                .map(|index| match scalar_to_u64(index)? {
+                    index if index < n as u64 => Ok(index as usize),
+                    _ => Err(MpcNetworkError::SerializationError),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            PermutationCorrelation::Permuter {
+                permutation,
+                delta: second,
+            }
+        } else {
+            PermutationCorrelation::Masker { a: half, b: second }
+        };
+
+        self.permutation_correlations
+            .push_back(((n, permuting_party), correlation));
+        Ok(())
+    }
+
+    /// Tells the dealer the local party is done and closes the network to the dealer
+    pub async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.network
+            .send_scalars(&[Scalar::from(RequestKind::Close as u64), Scalar::zero()])
+            .await?;
+        self.network.close().await
+    }
+
+    /// Sends a request to the dealer and receives the local shares of the dealt values
+    async fn request(
+        &mut self,
+        kind: RequestKind,
+        num_values: usize,
+        scalars_per_value: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        self.network
+            .send_scalars(&[Scalar::from(kind as u64), Scalar::from(num_values as u64)])
+            .await?;
+        self.network
+            .receive_scalars(num_values * scalars_per_value)
+            .await
+    }
+}
+
+impl<N: MpcNetwork + Send> SharedValueSource<Scalar> for DealerBeaverSource<N> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.next_shared_bit_batch(1)[0]
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.try_next_shared_bit_batch(num_values)
+            .expect("failed to request shared bits")
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        if self.bits.len() < num_values {
+            let num_missing = usize::max(num_values - self.bits.len(), self.batch_size);
+            block_on(self.request_bits(num_missing)).map_err(MpcError::NetworkError)?;
+        }
+
+        Ok(self.bits.drain(..num_values).collect_vec())
+    }
+
+    /// Each party samples its share locally, the dealer is not needed
+    fn next_shared_value(&mut self) -> Scalar {
+        Scalar::random(&mut OsRng {})
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_shared_invers_pair_batch(1)[0]
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_inverse_pair_batch(num_pairs)
+            .expect("failed to request inverse pairs")
+    }
+
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        if self.inverse_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.inverse_pairs.len(), self.batch_size);
+            block_on(self.request_inverse_pairs(num_missing)).map_err(MpcError::NetworkError)?;
+        }
+
+        Ok(self.inverse_pairs.drain(..num_pairs).collect_vec())
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_square_pair_batch(1)[0]
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_square_pair_batch(num_pairs)
+            .expect("failed to request square pairs")
+    }
+
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        if self.square_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.square_pairs.len(), self.batch_size);
+            block_on(self.request_square_pairs(num_missing)).map_err(MpcError::NetworkError)?;
+        }
+
+        Ok(self.square_pairs.drain(..num_pairs).collect_vec())
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.next_triplet_batch(1)[0]
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        self.try_next_triplet_batch(num_triplets)
+            .expect("failed to request triplets")
+    }
+
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        if self.triplets.len() < num_triplets {
+            let num_missing = usize::max(num_triplets - self.triplets.len(), self.batch_size);
+            block_on(self.request_triplets(num_missing)).map_err(MpcError::NetworkError)?;
+        }
+
+        Ok(self.triplets.drain(..num_triplets).collect_vec())
+    }
+
+    /// Draws a matrix triplet of the shape requested ahead of time, or requests one on demand
+    fn next_matrix_triplet(
+        &mut self,
+        n: usize,
+        m: usize,
+        k: usize,
+    ) -> Result<MatrixTriplet<Scalar>, MpcError> {
+        let index = match self
+            .matrix_triplets
+            .iter()
+            .position(|(shape, _)| *shape == (n, m, k))
+        {
+            Some(index) => index,
+            None => {
+                block_on(self.request_matrix_triplet(n, m, k)).map_err(MpcError::NetworkError)?;
+                self.matrix_triplets.len() - 1
+            }
+        };
+
+        Ok(self.matrix_triplets.remove(index).unwrap().1)
+    }
+
+    /// Draws an exponentiation tuple of the length requested ahead of time, or requests one on
+    /// demand
+    fn next_exp_tuple(&mut self, k: usize) -> Result<Vec<Scalar>, MpcError> {
+        let index = match self.exp_tuples.iter().position(|powers| powers.len() == k) {
+            Some(index) => index,
+            None => {
+                block_on(self.request_exp_tuple(k)).map_err(MpcError::NetworkError)?;
+                self.exp_tuples.len() - 1
+            }
+        };
+
+        Ok(self.exp_tuples.remove(index).unwrap())
+    }
+
+    /// Draws a permutation correlation of the length and permuting party requested ahead of
+    /// time, or requests one on demand
+    fn next_permutation_correlation(
+        &mut self,
+        n: usize,
+        permuting_party: u64,
+    ) -> Result<PermutationCorrelation<Scalar>, MpcError> {
+        let index = match self
+            .permutation_correlations
+            .iter()
+            .position(|(shape, _)| *shape == (n, permuting_party))
+        {
+            Some(index) => index,
+            None => {
+                block_on(self.request_permutation_correlation(n, permuting_party))
+                    .map_err(MpcError::NetworkError)?;
+                self.permutation_correlations.len() - 1
+            }
+        };
+
+        Ok(self.permutation_correlations.remove(index).unwrap().1)
+    }
+
+    /// The number of triplets dealt and not yet drawn; the source requests more on demand once
+    /// they run out
+    fn remaining(&self) -> Option<usize> {
+        Some(self.triplets.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{
+        beaver::{MatrixTriplet, PermutationCorrelation, SharedValueSource},
+        error::MpcNetworkError,
+        network::local_network::LocalTwoPartyNet,
+    };
+
+    use super::{Dealer, DealerBeaverSource};
+
+    /// The triplets, shared bit, inverse pair, square pair, matrix triplet, exponentiation
+    /// tuple, and permutation correlation drawn by a party
+    type PartyValues = (
+        Vec<(Scalar, Scalar, Scalar)>,
+        Scalar,
+        (Scalar, Scalar),
+        (Scalar, Scalar),
+        MatrixTriplet<Scalar>,
+        Vec<Scalar>,
+        PermutationCorrelation<Scalar>,
+    );
+
+    /// Draws values from the party's source, requesting the triplets ahead of time and the rest
+    /// on demand
+    fn run_party(network: LocalTwoPartyNet, num_triplets: usize) -> PartyValues {
+        let mut source = DealerBeaverSource::new(network).with_batch_size(4);
+        block_on(source.request_triplets(num_triplets)).unwrap();
+        assert_eq!(source.remaining(), Some(num_triplets));
+
+        let triplets = source.next_triplet_batch(num_triplets);
+        let bit = source.next_shared_bit();
+        let inverse_pair = source.next_shared_inverse_pair();
+        let square_pair = source.next_square_pair();
+        let matrix_triplet = source.next_matrix_triplet(2, 3, 2).unwrap();
+        let exp_tuple = source.next_exp_tuple(3).unwrap();
+        let correlation = source
+            .next_permutation_correlation(4, 1 /* permuting_party */)
+            .unwrap();
+        block_on(source.close()).unwrap();
+
+        (
+            triplets,
+            bit,
+            inverse_pair,
+            square_pair,
+            matrix_triplet,
+            exp_tuple,
+            correlation,
+        )
+    }
+
+    #[test]
+    fn test_dealer() {
+        let (party0_network, dealer_network0) = LocalTwoPartyNet::new_pair();
+        let (party1_network, dealer_network1) = LocalTwoPartyNet::new_pair();
+        let dealer =
+            thread::spawn(move || block_on(Dealer::new(dealer_network0, dealer_network1).serve()));
+        let party0 = thread::spawn(move || run_party(party0_network, 3));
+        let party1 = thread::spawn(move || run_party(party1_network, 3));
+
+        let (triplets0, bit0, pair0, square0, matrix0, powers0, masker) = party0.join().unwrap();
+        let (triplets1, bit1, pair1, square1, matrix1, powers1, permuter) = party1.join().unwrap();
+        dealer.join().unwrap().unwrap();
+
+        for (triplet0, triplet1) in triplets0.iter().zip(triplets1.iter()) {
+            let a = triplet0.0 + triplet1.0;
+            let b = triplet0.1 + triplet1.1;
+            assert_eq!(a * b, triplet0.2 + triplet1.2);
+        }
+
+        let bit = bit0 + bit1;
+        assert!(bit == Scalar::zero() || bit == Scalar::one());
+        assert_eq!((pair0.0 + pair1.0) * (pair0.1 + pair1.1), Scalar::one());
+
+        let value = square0.0 + square1.0;
+        assert_eq!(value * value, square0.1 + square1.1);
+
+        // The 2 x 2 product of the 2 x 3 matrix A with the 3 x 2 matrix B
+        let open = |shares0: &[Scalar], shares1: &[Scalar]| {
+            shares0
+                .iter()
+                .zip(shares1.iter())
+                .map(|(share0, share1)| share0 + share1)
+                .collect::<Vec<_>>()
+        };
+        let a = open(&matrix0.0, &matrix1.0);
+        let b = open(&matrix0.1, &matrix1.1);
+        let c = open(&matrix0.2, &matrix1.2);
+        for row in 0..2 {
+            for col in 0..2 {
+                let entry = (0..3)
+                    .map(|j| a[row * 3 + j] * b[j * 2 + col])
+                    .sum::<Scalar>();
+                assert_eq!(c[row * 2 + col], entry);
+            }
+        }
+
+        let powers = open(&powers0, &powers1);
+        assert_eq!(powers[1], powers[0] * powers[0]);
+        assert_eq!(powers[2], powers[1] * powers[0]);
+
+        // Party 1 permutes, so it holds pi and pi(a) - b for the a and b of party 0
+        match (permuter, masker) {
+            (
+                PermutationCorrelation::Permuter { permutation, delta },
+                PermutationCorrelation::Masker { a, b },
+            ) => {
+                let mut sorted = permutation.clone();
+                sorted.sort_unstable();
+                assert_eq!(sorted, vec![0, 1, 2, 3]);
+                for i in 0..4 {
+                    assert_eq!(delta[i], a[permutation[i]] - b[i]);
+                }
+            }
+            _ => panic!("the parties hold the wrong halves of the correlation"),
+        }
+    }
+
+    #[test]
+    fn test_dealer_desync() {
+        let (party0_network, dealer_network0) = LocalTwoPartyNet::new_pair();
+        let (party1_network, dealer_network1) = LocalTwoPartyNet::new_pair();
+        let dealer =
+            thread::spawn(move || block_on(Dealer::new(dealer_network0, dealer_network1).serve()));
+
+        // The parties request different numbers of triplets
+        let party0 = thread::spawn(move || {
+            block_on(DealerBeaverSource::new(party0_network).request_triplets(1))
+        });
+        let party1 = thread::spawn(move || {
+            block_on(DealerBeaverSource::new(party1_network).request_triplets(2))
+        });
+
+        assert_eq!(dealer.join().unwrap(), Err(MpcNetworkError::DealerDesync));
+        assert!(party0.join().unwrap().is_err());
+        assert!(party1.join().unwrap().is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Implements a dry run of a computation that reports the preprocessing a real run consumes,
+//! e.g. to size the dealer requests or the offline phase ahead of the online phase
+
+use std::{cell::RefCell, rc::Rc, thread};
+
+use crate::{
+    block_on,
+    error::MpcError,
+    fabric::AuthenticatedMpcFabric,
+    network::{local_network::LocalTwoPartyNet, MpcNetwork},
+};
+
+use super::{
+    counting::PhaseCounts, CountingBeaverSource, PreprocessingCounts, SeededMockBeaverSource,
+};
+
+/// The seed both parties of a dry run derive their values from; the values of a dry run are
+/// never secret
+const DRY_RUN_SEED: u64 = 0;
+
+/// The value source of a dry run; correlated values derived from a public seed, counted per
+/// phase as they are drawn
+pub type DryRunBeaverSource = CountingBeaverSource<SeededMockBeaverSource>;
+
+/// The fabric a computation is dry run over
+pub type DryRunFabric = AuthenticatedMpcFabric<LocalTwoPartyNet, DryRunBeaverSource>;
+
+/// The preprocessing a computation consumes, per phase and in total, as reported by `dry_run`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreprocessingReport {
+    /// The counts of each phase, as counted by the dry run's value source
+    phases: PhaseCounts,
+}
+
+impl PreprocessingReport {
+    /// Returns the number of values consumed across all phases
+    pub fn totals(&self) -> PreprocessingCounts {
+        self.phases.totals()
+    }
+
+    /// Returns the number of values consumed in the named phase, or `None` if the computation
+    /// never entered the phase
+    pub fn phase_counts(&self, phase: &str) -> Option<PreprocessingCounts> {
+        self.phases.phase_counts(phase)
+    }
+
+    /// Returns the counts of each phase, in the order the phases were first entered
+    pub fn phases(&self) -> impl Iterator<Item = (&str, PreprocessingCounts)> {
+        self.phases.phases()
+    }
+}
+
+impl<S> From<&CountingBeaverSource<S>> for PreprocessingReport {
+    fn from(source: &CountingBeaverSource<S>) -> Self {
+        Self {
+            phases: source.counts_by_phase().clone(),
+        }
+    }
+}
+
+/// Runs the computation for both parties in process and reports the values it consumes
+///
+/// Each party runs the computation on its own thread over an in-memory network, with values
+/// derived from a public seed, so the run needs neither a peer nor real preprocessing; the
+/// computation names its phases through `borrow_beaver_source_mut().set_phase`. The report
+/// includes the shared value drawn for the fabric's MAC key. The counts are exact for
+/// computations whose control flow does not depend on the values of the inputs; otherwise they
+/// are those of the inputs the computation allocates in the dry run. Returns a
+/// `PreprocessingError` if the parties consume different values, and the first error of the
+/// computation otherwise
+pub fn dry_run<F, T>(computation: F) -> Result<PreprocessingReport, MpcError>
+where
+    F: Fn(&DryRunFabric) -> Result<T, MpcError> + Clone + Send + 'static,
+{
+    let (network0, network1) = LocalTwoPartyNet::new_pair();
+    let parties = [network0, network1]
+        .into_iter()
+        .enumerate()
+        .map(|(party_id, network)| {
+            let computation = computation.clone();
+            thread::spawn(move || {
+                let network = Rc::new(RefCell::new(network));
+                let beaver_source = Rc::new(RefCell::new(CountingBeaverSource::new(
+                    SeededMockBeaverSource::new(party_id as u64, DRY_RUN_SEED),
+                )));
+                let fabric = AuthenticatedMpcFabric::new_with_network(
+                    party_id as u64,
+                    network.clone(),
+                    beaver_source.clone(),
+                );
+
+                computation(&fabric)?;
+                block_on(network.borrow_mut().close()).map_err(MpcError::NetworkError)?;
+
+                let report = PreprocessingReport::from(&*beaver_source.borrow());
+                Ok(report)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut reports = parties.into_iter().map(|party| {
+        party.join().unwrap_or_else(|_| {
+            Err(MpcError::PreprocessingError(
+                "a party panicked during the dry run".to_string(),
+            ))
+        })
+    });
+    let report0 = reports.next().unwrap()?;
+    let report1 = reports.next().unwrap()?;
+    if report0 != report1 {
+        return Err(MpcError::PreprocessingError(
+            "the parties consumed different values during the dry run".to_string(),
+        ));
+    }
+
+    Ok(report0)
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::beaver::PreprocessingCounts;
+
+    use super::dry_run;
+
+    #[test]
+    fn test_dry_run() {
+        let report = dry_run(|fabric| {
+            let x =
+                fabric.allocate_private_scalar(0 /* owning_party */, Scalar::from(3u64))?;
+            let y =
+                fabric.allocate_private_scalar(1 /* owning_party */, Scalar::from(5u64))?;
+
+            fabric.borrow_beaver_source_mut().set_phase("mul");
+            let product = &x * &y;
+            product.open_and_authenticate()
+        })
+        .unwrap();
+
+        assert_eq!(
+            report.phase_counts("mul"),
+            Some(PreprocessingCounts {
+                triplets: 2,
+                ..Default::default()
+            })
+        );
+        assert_eq!(report.totals().shared_values, 1);
+    }
+}
@@ -0,0 +1,246 @@
+//! Implements a value source wrapper that falls back to generating values interactively with the
+//! peer once the wrapped source runs dry, rather than failing mid-protocol
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{block_on, error::MpcError, macros};
+
+use super::{PreprocessingCounts, PreprocessingGenerator, SharedValueSource};
+
+/// What a `FallbackBeaverSource` does when its primary source is exhausted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExhaustionPolicy {
+    /// Surface the primary source's `PreprocessingExhausted` error
+    #[default]
+    Fail,
+    /// Generate the values with the peer on demand, e.g. over oblivious transfer
+    GenerateOnline,
+}
+
+/// Wraps a primary value source, e.g. a `FileBeaverSource`, and a generator that produces
+/// values interactively with the peer, e.g. an `OtTripleGenerator`
+///
+/// Values are drawn from the primary source while it lasts. Once a draw finds it exhausted the
+/// policy decides whether the draw fails or is served by the generator, which is far slower
+/// but correct. A batch the primary source cannot serve in full is generated in full. Both
+/// parties' primary sources must hold the same numbers of values, so that both parties fall
+/// back on the same draw
+#[derive(Debug)]
+pub struct FallbackBeaverSource<S, G> {
+    /// The source drawn from while it lasts
+    primary: S,
+    /// The generator drawn from once the primary source is exhausted
+    fallback: G,
+    /// What to do when the primary source is exhausted
+    policy: ExhaustionPolicy,
+    /// The number of values of each kind generated online
+    generated_online: PreprocessingCounts,
+}
+
+impl<S, G> FallbackBeaverSource<S, G> {
+    /// Wrap the primary source, failing once it is exhausted until the policy is changed
+    pub fn new(primary: S, fallback: G) -> Self {
+        Self {
+            primary,
+            fallback,
+            policy: ExhaustionPolicy::default(),
+            generated_online: PreprocessingCounts::default(),
+        }
+    }
+
+    /// Sets the policy for when the primary source is exhausted
+    pub fn with_policy(mut self, policy: ExhaustionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Changes the policy, e.g. through the fabric's borrow of the source mid-protocol; both
+    /// parties must change it at the same point
+    pub fn set_policy(&mut self, policy: ExhaustionPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the policy for when the primary source is exhausted
+    pub fn policy(&self) -> ExhaustionPolicy {
+        self.policy
+    }
+
+    /// Returns the number of values of each kind generated online so far
+    pub fn generated_online(&self) -> PreprocessingCounts {
+        self.generated_online
+    }
+
+    /// Mutably borrow the primary source, e.g. to refill it
+    pub fn primary_mut(&mut self) -> &mut S {
+        &mut self.primary
+    }
+
+    /// Returns true if the draw from the primary source should be retried with the generator
+    fn falls_back<T>(&self, result: &Result<T, MpcError>) -> bool {
+        self.policy == ExhaustionPolicy::GenerateOnline
+            && matches!(result, Err(MpcError::PreprocessingExhausted(_)))
+    }
+}
+
+impl<S: SharedValueSource<Scalar>, G: PreprocessingGenerator> SharedValueSource<Scalar>
+    for FallbackBeaverSource<S, G>
+{
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.next_shared_bit_batch(1)[0]
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.try_next_shared_bit_batch(num_values)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        let bits = self.primary.try_next_shared_bit_batch(num_values);
+        if !self.falls_back(&bits) {
+            return bits;
+        }
+
+        block_on(self.fallback.generate_bits(num_values))?;
+        self.generated_online.bits += num_values;
+        self.fallback.try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        self.primary.next_shared_value()
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_shared_invers_pair_batch(1)[0]
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_inverse_pair_batch(num_pairs)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let pairs = self.primary.try_next_inverse_pair_batch(num_pairs);
+        if !self.falls_back(&pairs) {
+            return pairs;
+        }
+
+        block_on(self.fallback.generate_inverse_pairs(num_pairs))?;
+        self.generated_online.inverse_pairs += num_pairs;
+        self.fallback.try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_square_pair_batch(1)[0]
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_square_pair_batch(num_pairs)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let pairs = self.primary.try_next_square_pair_batch(num_pairs);
+        if !self.falls_back(&pairs) {
+            return pairs;
+        }
+
+        block_on(self.fallback.generate_square_pairs(num_pairs))?;
+        self.generated_online.square_pairs += num_pairs;
+        self.fallback.try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.next_triplet_batch(1)[0]
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        self.try_next_triplet_batch(num_triplets)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        let triplets = self.primary.try_next_triplet_batch(num_triplets);
+        if !self.falls_back(&triplets) {
+            return triplets;
+        }
+
+        block_on(self.fallback.generate_triplets(num_triplets))?;
+        self.generated_online.triplets += num_triplets;
+        self.fallback.try_next_triplet_batch(num_triplets)
+    }
+
+    macros::impl_forwarded_correlations!(Scalar, |this| this.primary);
+
+    /// The triplets left in the primary source, or `None` if the source generates triplets
+    /// once the primary source is exhausted
+    fn remaining(&self) -> Option<usize> {
+        match self.policy {
+            ExhaustionPolicy::Fail => self.primary.remaining(),
+            ExhaustionPolicy::GenerateOnline => None,
+        }
+    }
+
+    /// The position of the primary source; values generated online are never reused, so they
+    /// need no checkpoint
+    fn position(&self) -> Result<u64, MpcError> {
+        self.primary.position()
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        self.primary.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        beaver::{DealtFiles, OtTripleGenerator, SharedValueSource},
+        error::MpcError,
+    };
+
+    use super::{ExhaustionPolicy, FallbackBeaverSource};
+
+    #[test]
+    fn test_fallback() {
+        let files = DealtFiles::new("fallback", 1, 0, 0, 0);
+        let mut results = files
+            .run_parties(|source, network| {
+                let mut source = FallbackBeaverSource::new(source, OtTripleGenerator::new(network));
+
+                // A restart resumes the file from a checkpoint taken before the triplet is drawn
+                let position = source.position().unwrap();
+                let from_file = source.next_triplet();
+                source.seek(position).unwrap();
+                assert_eq!(source.next_triplet(), from_file);
+
+                // The file holds a single triplet, the second is generated with the peer
+                assert!(matches!(
+                    source.try_next_triplet_batch(1),
+                    Err(MpcError::PreprocessingExhausted(_))
+                ));
+                source.set_policy(ExhaustionPolicy::GenerateOnline);
+                let generated = source.next_triplet();
+                assert_eq!(source.generated_online().triplets, 1);
+
+                vec![from_file, generated]
+            })
+            .into_iter();
+        let triplets0 = results.next().unwrap();
+        let triplets1 = results.next().unwrap();
+
+        for (triplet0, triplet1) in triplets0.iter().zip(triplets1.iter()) {
+            let a = triplet0.0 + triplet1.0;
+            let b = triplet0.1 + triplet1.1;
+            assert_eq!(a * b, triplet0.2 + triplet1.2);
+        }
+    }
+}
@@ -0,0 +1,768 @@
+//! Implements a value source that reads values generated ahead of time from a file
+//!
+//! A file holds one party's shares, laid out as follows; all integers are little endian:
+//!
+//! | Bytes    | Contents                                                          |
+//! |----------|-------------------------------------------------------------------|
+//! | 4        | The magic `b"MPCB"`                                               |
+//! | 2        | The version of the layout, currently 2                            |
+//! | 2        | Reserved, zero                                                    |
+//! | 4 x 8    | The number of triplets, shared bits, inverse pairs, and square    |
+//! |          | pairs, as u64s                                                    |
+//! | 4 x 8    | The number of each consumed so far, as u64s                       |
+//! | 96 each  | The triplets, each the canonical encodings of a, b, and c         |
+//! | 32 each  | The shared bits                                                   |
+//! | 64 each  | The inverse pairs, each the canonical encodings of r and r^-1     |
+//! | 64 each  | The square pairs, each the canonical encodings of a and a^2       |
+//!
+//! The source records the values it consumes in the header before returning them, so a
+//! computation restarted on the same file never reuses a value
+
+#[cfg(test)]
+use std::path::PathBuf;
+use std::{
+    convert::TryInto,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use sha3::{Digest, Sha3_256};
+
+use crate::error::MpcError;
+#[cfg(test)]
+use crate::network::local_network::LocalTwoPartyNet;
+
+use super::{
+    dealer::{deal_bits, deal_inverse_pairs, deal_square_pairs, deal_triplets, split_shares},
+    SharedValueSource,
+};
+
+/// The bytes that open a file of values
+const FILE_MAGIC: [u8; 4] = *b"MPCB";
+/// The version of the layout of the file
+const FILE_VERSION: u16 = 2;
+/// The number of bytes in the header of the file
+const HEADER_BYTES: u64 = 72;
+/// The offset in the header of the consumed counts
+const CONSUMED_OFFSET: u64 = 40;
+/// The number of bytes in an encoded scalar
+const SCALAR_BYTES: u64 = 32;
+/// The version of the layout of shares written by `BeaverShares::save`
+#[cfg(feature = "serde")]
+const SAVED_SHARES_VERSION: u16 = 1;
+
+/// The sections of the file, in the order they are laid out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Triplets = 0,
+    Bits = 1,
+    InversePairs = 2,
+    SquarePairs = 3,
+}
+
+impl Section {
+    /// The number of scalars in each value of the section
+    fn scalars_per_value(&self) -> u64 {
+        match self {
+            Section::Triplets => 3,
+            Section::Bits => 1,
+            Section::InversePairs => 2,
+            Section::SquarePairs => 2,
+        }
+    }
+
+    /// The name of the values in the section, for error messages
+    fn name(&self) -> &'static str {
+        match self {
+            Section::Triplets => "triplets",
+            Section::Bits => "shared bits",
+            Section::InversePairs => "inverse pairs",
+            Section::SquarePairs => "square pairs",
+        }
+    }
+}
+
+/// Maps an I/O error to a preprocessing error
+fn io_error(err: std::io::Error) -> MpcError {
+    MpcError::PreprocessingError(format!("beaver file: {}", err))
+}
+
+/// One party's shares of a set of values, as written to a file by `FileBeaverSource::write`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BeaverShares {
+    /// The shares of Beaver triplets
+    pub triplets: Vec<(Scalar, Scalar, Scalar)>,
+    /// The shares of bits
+    pub bits: Vec<Scalar>,
+    /// The shares of pairs of multiplicative inverses
+    pub inverse_pairs: Vec<(Scalar, Scalar)>,
+    /// The shares of pairs of a value and its square
+    pub square_pairs: Vec<(Scalar, Scalar)>,
+}
+
+impl BeaverShares {
+    /// Samples values and splits them into the shares of party 0 and party 1, as a trusted
+    /// dealer would; whoever runs this learns every value, so it must not be either party
+    pub fn deal(
+        num_triplets: usize,
+        num_bits: usize,
+        num_pairs: usize,
+        num_square_pairs: usize,
+    ) -> (Self, Self) {
+        let (triplets0, triplets1) = split_shares(&deal_triplets(num_triplets));
+        let (bits0, bits1) = split_shares(&deal_bits(num_bits));
+        let (pairs0, pairs1) = split_shares(&deal_inverse_pairs(num_pairs));
+        let (squares0, squares1) = split_shares(&deal_square_pairs(num_square_pairs));
+
+        let to_pairs = |shares: Vec<Scalar>| {
+            shares
+                .chunks(2)
+                .map(|shares| (shares[0], shares[1]))
+                .collect_vec()
+        };
+        let shares = |triplets: Vec<Scalar>,
+                      bits: Vec<Scalar>,
+                      pairs: Vec<Scalar>,
+                      squares: Vec<Scalar>| Self {
+            triplets: triplets
+                .chunks(3)
+                .map(|shares| (shares[0], shares[1], shares[2]))
+                .collect_vec(),
+            bits,
+            inverse_pairs: to_pairs(pairs),
+            square_pairs: to_pairs(squares),
+        };
+
+        (
+            shares(triplets0, bits0, pairs0, squares0),
+            shares(triplets1, bits1, pairs1, squares1),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl BeaverShares {
+    /// Serializes the shares to the writer, e.g. to persist the output of an offline phase until
+    /// the online phase; the shares are preceded by the version of the layout and followed by a
+    /// checksum that `load` verifies
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), MpcError> {
+        bincode::serialize_into(&mut writer, &SAVED_SHARES_VERSION).map_err(serde_error)?;
+        bincode::serialize_into(&mut writer, &(self, self.checksum())).map_err(serde_error)
+    }
+
+    /// Deserializes shares written by `save`, checking the version of the layout and the
+    /// checksum of the shares
+    pub fn load<R: Read>(mut reader: R) -> Result<Self, MpcError> {
+        let version: u16 = bincode::deserialize_from(&mut reader).map_err(serde_error)?;
+        if version != SAVED_SHARES_VERSION {
+            return Err(MpcError::PreprocessingError(format!(
+                "saved shares: unsupported version {}",
+                version
+            )));
+        }
+
+        let (shares, checksum): (Self, [u8; 32]) =
+            bincode::deserialize_from(&mut reader).map_err(serde_error)?;
+        if checksum != shares.checksum() {
+            return Err(MpcError::PreprocessingError(
+                "saved shares: checksum mismatch, the shares are corrupted".to_string(),
+            ));
+        }
+
+        Ok(shares)
+    }
+
+    /// Returns the SHA3-256 digest of the number of values in each section followed by the
+    /// canonical encoding of every share
+    fn checksum(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        for count in [
+            self.triplets.len(),
+            self.bits.len(),
+            self.inverse_pairs.len(),
+            self.square_pairs.len(),
+        ] {
+            hasher.input((count as u64).to_le_bytes());
+        }
+
+        let scalars = self
+            .triplets
+            .iter()
+            .flat_map(|(a, b, c)| [a, b, c])
+            .chain(self.bits.iter())
+            .chain(
+                self.inverse_pairs
+                    .iter()
+                    .chain(self.square_pairs.iter())
+                    .flat_map(|(first, second)| [first, second]),
+            );
+        for scalar in scalars {
+            hasher.input(scalar.as_bytes());
+        }
+
+        let mut checksum = [0u8; 32];
+        checksum.copy_from_slice(&hasher.result());
+        checksum
+    }
+}
+
+/// Maps a serialization error to a preprocessing error
+#[cfg(feature = "serde")]
+fn serde_error(err: bincode::Error) -> MpcError {
+    MpcError::PreprocessingError(format!("saved shares: {}", err))
+}
+
+/// A source of Beaver triplets, shared bits, inverse pairs, and square pairs read from a file of
+/// values
+/// generated ahead of time, e.g. with `BeaverShares::deal`
+///
+/// The file holds a fixed number of each value; the fallible `try_next_*` methods return a
+/// `MpcError::PreprocessingExhausted` once the file is exhausted, whereas the infallible methods
+/// panic. Shared values are sampled locally and never exhaust the source
+#[derive(Debug)]
+pub struct FileBeaverSource {
+    /// The open file
+    file: File,
+    /// The number of values of each section in the file
+    counts: [u64; 4],
+    /// The number of values of each section consumed so far
+    consumed: [u64; 4],
+}
+
+impl FileBeaverSource {
+    /// Writes a party's shares to a new file at the given path, replacing any existing file
+    pub fn write<P: AsRef<Path>>(path: P, shares: &BeaverShares) -> Result<(), MpcError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&FILE_MAGIC);
+        bytes.extend_from_slice(&FILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 2]);
+        for count in [
+            shares.triplets.len(),
+            shares.bits.len(),
+            shares.inverse_pairs.len(),
+            shares.square_pairs.len(),
+        ] {
+            bytes.extend_from_slice(&(count as u64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0u8; 32]);
+
+        for (a, b, c) in shares.triplets.iter() {
+            bytes.extend_from_slice(a.as_bytes());
+            bytes.extend_from_slice(b.as_bytes());
+            bytes.extend_from_slice(c.as_bytes());
+        }
+        for bit in shares.bits.iter() {
+            bytes.extend_from_slice(bit.as_bytes());
+        }
+        for (value, inverse) in shares.inverse_pairs.iter() {
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.extend_from_slice(inverse.as_bytes());
+        }
+        for (value, square) in shares.square_pairs.iter() {
+            bytes.extend_from_slice(value.as_bytes());
+            bytes.extend_from_slice(square.as_bytes());
+        }
+
+        std::fs::write(path, bytes).map_err(io_error)
+    }
+
+    /// Opens a file written by `FileBeaverSource::write`, resuming after the values consumed
+    /// by any earlier source on the file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MpcError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(io_error)?;
+
+        let mut header = [0u8; HEADER_BYTES as usize];
+        file.read_exact(&mut header).map_err(io_error)?;
+        if header[..4] != FILE_MAGIC {
+            return Err(MpcError::PreprocessingError(
+                "beaver file: bad magic".to_string(),
+            ));
+        }
+
+        let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+        if version != FILE_VERSION {
+            return Err(MpcError::PreprocessingError(format!(
+                "beaver file: unsupported version {}",
+                version
+            )));
+        }
+
+        let read_u64 =
+            |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+        let counts = [read_u64(8), read_u64(16), read_u64(24), read_u64(32)];
+        let consumed = [read_u64(40), read_u64(48), read_u64(56), read_u64(64)];
+        let source = Self {
+            file,
+            counts,
+            consumed,
+        };
+
+        // Check the header against the length of the file before trusting any offset
+        let length = source.file.metadata().map_err(io_error)?.len();
+        if source.section_offset(Section::SquarePairs)
+            + counts[Section::SquarePairs as usize]
+                * Section::SquarePairs.scalars_per_value()
+                * SCALAR_BYTES
+            != length
+            || consumed
+                .iter()
+                .zip(counts.iter())
+                .any(|(consumed, count)| consumed > count)
+        {
+            return Err(MpcError::PreprocessingError(
+                "beaver file: header does not match the file".to_string(),
+            ));
+        }
+
+        Ok(source)
+    }
+
+    /// Returns the number of shared bits left in the file
+    pub fn remaining_bits(&self) -> u64 {
+        self.remaining_in(Section::Bits)
+    }
+
+    /// Returns the number of inverse pairs left in the file
+    pub fn remaining_inverse_pairs(&self) -> u64 {
+        self.remaining_in(Section::InversePairs)
+    }
+
+    /// Returns the number of square pairs left in the file
+    pub fn remaining_square_pairs(&self) -> u64 {
+        self.remaining_in(Section::SquarePairs)
+    }
+
+    /// Returns the number of values left in the section
+    fn remaining_in(&self, section: Section) -> u64 {
+        self.counts[section as usize] - self.consumed[section as usize]
+    }
+
+    /// Returns the offset in the file of the first value of the section
+    fn section_offset(&self, section: Section) -> u64 {
+        [
+            Section::Triplets,
+            Section::Bits,
+            Section::InversePairs,
+            Section::SquarePairs,
+        ]
+        .iter()
+        .take_while(|preceding| **preceding != section)
+        .map(|preceding| {
+            self.counts[*preceding as usize] * preceding.scalars_per_value() * SCALAR_BYTES
+        })
+        .sum::<u64>()
+            + HEADER_BYTES
+    }
+
+    /// Writes the consumed counts of every section to the header of the file
+    fn write_consumed(&mut self) -> Result<(), MpcError> {
+        let bytes = self
+            .consumed
+            .iter()
+            .flat_map(|consumed| consumed.to_le_bytes())
+            .collect_vec();
+        self.file
+            .seek(SeekFrom::Start(CONSUMED_OFFSET))
+            .map_err(io_error)?;
+        self.file.write_all(&bytes).map_err(io_error)
+    }
+
+    /// Consumes the next values of the section and returns their scalars, flattened
+    fn take(&mut self, section: Section, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        let first = self.consumed[section as usize];
+
+        // Record the values as consumed before reading them, so that they are never reused
+        self.consume(section, num_values)?;
+        self.read(section, first, num_values)
+    }
+
+    /// Records the next values of the section as consumed, or errors if it holds too few
+    fn consume(&mut self, section: Section, num_values: usize) -> Result<(), MpcError> {
+        let remaining = self.remaining_in(section);
+        if num_values as u64 > remaining {
+            return Err(MpcError::PreprocessingExhausted(format!(
+                "beaver file: {} {} requested, {} remaining",
+                num_values,
+                section.name(),
+                remaining
+            )));
+        }
+
+        self.consumed[section as usize] += num_values as u64;
+        self.write_consumed()
+    }
+
+    /// Reads the scalars of the values of the section starting at the given index, flattened
+    fn read(
+        &mut self,
+        section: Section,
+        index: u64,
+        num_values: usize,
+    ) -> Result<Vec<Scalar>, MpcError> {
+        let value_bytes = section.scalars_per_value() * SCALAR_BYTES;
+        let offset = self.section_offset(section) + index * value_bytes;
+
+        let mut bytes = vec![0u8; num_values * value_bytes as usize];
+        self.file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+        self.file.read_exact(&mut bytes).map_err(io_error)?;
+
+        bytes
+            .chunks(SCALAR_BYTES as usize)
+            .map(|chunk| {
+                Scalar::from_canonical_bytes(chunk.try_into().unwrap()).ok_or_else(|| {
+                    MpcError::PreprocessingError("beaver file: non-canonical scalar".to_string())
+                })
+            })
+            .collect()
+    }
+}
+
+impl SharedValueSource<Scalar> for FileBeaverSource {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.next_shared_bit_batch(1)[0]
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.try_next_shared_bit_batch(num_values).unwrap()
+    }
+
+    /// Reads the next shared bits, or errors if the file holds too few
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        self.take(Section::Bits, num_values)
+    }
+
+    /// Each party samples its share locally, the file is not needed
+    fn next_shared_value(&mut self) -> Scalar {
+        Scalar::random(&mut rand_core::OsRng {})
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_shared_invers_pair_batch(1)[0]
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_inverse_pair_batch(num_pairs).unwrap()
+    }
+
+    /// Reads the next inverse pairs, or errors if the file holds too few
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let shares = self.take(Section::InversePairs, num_pairs)?;
+        Ok(shares
+            .chunks(2)
+            .map(|shares| (shares[0], shares[1]))
+            .collect_vec())
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_square_pair_batch(1)[0]
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.try_next_square_pair_batch(num_pairs).unwrap()
+    }
+
+    /// Reads the next square pairs, or errors if the file holds too few
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let shares = self.take(Section::SquarePairs, num_pairs)?;
+        Ok(shares
+            .chunks(2)
+            .map(|shares| (shares[0], shares[1]))
+            .collect_vec())
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.next_triplet_batch(1)[0]
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        self.try_next_triplet_batch(num_triplets).unwrap()
+    }
+
+    /// Reads the next triplets, or errors if the file holds too few
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        let shares = self.take(Section::Triplets, num_triplets)?;
+        Ok(shares
+            .chunks(3)
+            .map(|shares| (shares[0], shares[1], shares[2]))
+            .collect_vec())
+    }
+
+    /// Reads the triplets without recording them as consumed
+    fn peek_triplet_batch(
+        &mut self,
+        skip: usize,
+        num_triplets: usize,
+    ) -> Option<Vec<(Scalar, Scalar, Scalar)>> {
+        if (skip + num_triplets) as u64 > self.remaining_in(Section::Triplets) {
+            return None;
+        }
+
+        let index = self.consumed[Section::Triplets as usize] + skip as u64;
+        let shares = self.read(Section::Triplets, index, num_triplets).ok()?;
+        Some(
+            shares
+                .chunks(3)
+                .map(|shares| (shares[0], shares[1], shares[2]))
+                .collect_vec(),
+        )
+    }
+
+    fn skip_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        self.consume(Section::Triplets, num_triplets)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.remaining_in(Section::Triplets) as usize)
+    }
+
+    /// Packs the consumed count of each section into one integer, in which the count of a
+    /// section is a digit whose base is one more than the number of values in the section;
+    /// errors if the file holds too many values for the packed counts to fit in a u64
+    fn position(&self) -> Result<u64, MpcError> {
+        self.consumed
+            .iter()
+            .zip(self.counts.iter())
+            .rev()
+            .try_fold(0u64, |position, (consumed, count)| {
+                position
+                    .checked_mul(count + 1)
+                    .and_then(|position| position.checked_add(*consumed))
+            })
+            .ok_or_else(|| {
+                MpcError::PreprocessingError(
+                    "beaver file: too many values to checkpoint the position".to_string(),
+                )
+            })
+    }
+
+    /// Restores the consumed counts packed by `position` and records them in the file; seeking
+    /// back makes the source return values it has returned before
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        let mut consumed = [0u64; 4];
+        let mut rest = pos;
+        for (consumed, count) in consumed.iter_mut().zip(self.counts.iter()) {
+            *consumed = rest % (count + 1);
+            rest /= count + 1;
+        }
+        if rest != 0 {
+            return Err(MpcError::PreprocessingError(format!(
+                "beaver file: position {} is past the end of the file",
+                pos
+            )));
+        }
+
+        self.consumed = consumed;
+        self.write_consumed()
+    }
+}
+
+/// A pair of temporary files of dealt shares, one for each party, removed when dropped; i.e.
+/// also when a test using them panics
+#[cfg(test)]
+pub(crate) struct DealtFiles {
+    /// The shares written to the files, indexed by party
+    shares: [BeaverShares; 2],
+    /// The paths of the files, indexed by party
+    paths: [PathBuf; 2],
+}
+
+#[cfg(test)]
+impl DealtFiles {
+    /// Deals shares of the given numbers of values and writes each party's to a temporary file;
+    /// the name distinguishes the files of tests running concurrently
+    pub(crate) fn new(
+        name: &str,
+        num_triplets: usize,
+        num_bits: usize,
+        num_pairs: usize,
+        num_square_pairs: usize,
+    ) -> Self {
+        let (shares0, shares1) =
+            BeaverShares::deal(num_triplets, num_bits, num_pairs, num_square_pairs);
+        let paths = [0, 1].map(|party_id| {
+            std::env::temp_dir().join(format!("mpc-{}-{}-{}", name, std::process::id(), party_id))
+        });
+        let files = Self {
+            shares: [shares0, shares1],
+            paths,
+        };
+        for (path, shares) in files.paths.iter().zip(files.shares.iter()) {
+            FileBeaverSource::write(path, shares).unwrap();
+        }
+
+        files
+    }
+
+    /// Returns the shares written to the given party's file
+    pub(crate) fn shares(&self, party_id: usize) -> &BeaverShares {
+        &self.shares[party_id]
+    }
+
+    /// Opens a source on the given party's file
+    pub(crate) fn open(&self, party_id: usize) -> FileBeaverSource {
+        FileBeaverSource::open(&self.paths[party_id]).unwrap()
+    }
+
+    /// Runs each party on its own thread with a source on its file and its end of a local
+    /// network, returning the parties' results in order of their IDs
+    pub(crate) fn run_parties<T: Send>(
+        &self,
+        party: impl Fn(FileBeaverSource, LocalTwoPartyNet) -> T + Sync,
+    ) -> Vec<T> {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        std::thread::scope(|scope| {
+            let party = &party;
+            let parties = [network0, network1]
+                .into_iter()
+                .enumerate()
+                .map(|(party_id, network)| {
+                    let source = self.open(party_id);
+                    scope.spawn(move || party(source, network))
+                })
+                .collect_vec();
+
+            parties
+                .into_iter()
+                .map(|party| party.join().unwrap())
+                .collect_vec()
+        })
+    }
+}
+
+#[cfg(test)]
+impl Drop for DealtFiles {
+    fn drop(&mut self) {
+        for path in self.paths.iter() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{beaver::SharedValueSource, error::MpcError};
+
+    #[cfg(feature = "serde")]
+    use super::BeaverShares;
+    use super::DealtFiles;
+
+    #[test]
+    fn test_file_source() {
+        let files = DealtFiles::new("beaver", 3, 2, 1, 1);
+        let shares0 = files.shares(0);
+
+        let mut source0 = files.open(0);
+        let mut source1 = files.open(1);
+        let (a0, b0, c0) = source0.next_triplet();
+        let (a1, b1, c1) = source1.next_triplet();
+        assert_eq!((a0 + a1) * (b0 + b1), c0 + c1);
+        let (r0, inverse0) = source0.next_shared_inverse_pair();
+        let (r1, inverse1) = source1.next_shared_inverse_pair();
+        assert_eq!((r0 + r1) * (inverse0 + inverse1), Scalar::one());
+        let (a0, square0) = source0.next_square_pair();
+        let (a1, square1) = source1.next_square_pair();
+        assert_eq!((a0 + a1) * (a0 + a1), square0 + square1);
+        assert_eq!(source0.next_shared_bit(), shares0.bits[0]);
+
+        // A reopened file resumes after the consumed values
+        drop(source0);
+        let mut source0 = files.open(0);
+        assert_eq!(source0.remaining(), Some(2));
+        assert_eq!(source0.remaining_bits(), 1);
+        assert_eq!(source0.remaining_inverse_pairs(), 0);
+        assert_eq!(source0.remaining_square_pairs(), 0);
+        assert_eq!(source0.next_triplet(), shares0.triplets[1]);
+
+        // Drawing past the end of a section errors
+        assert!(matches!(
+            source0.try_next_triplet_batch(2),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+        assert!(matches!(
+            source0.try_next_inverse_pair_batch(1),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+        assert_eq!(
+            source0.try_next_triplet_batch(1).unwrap(),
+            vec![shares0.triplets[2]]
+        );
+    }
+
+    #[test]
+    fn test_file_source_checkpoint() {
+        let files = DealtFiles::new("beaver-seek", 3, 2, 2, 1);
+        let shares = files.shares(0);
+
+        let mut source = files.open(0);
+        source.next_triplet();
+        source.next_shared_bit();
+        let checkpoint = source.position().unwrap();
+        source.next_triplet_batch(2);
+        source.next_shared_inverse_pair();
+        source.next_square_pair();
+
+        // A reopened file seeks back to the checkpoint and persists the restored counts
+        drop(source);
+        let mut source = files.open(0);
+        assert_eq!(source.remaining(), Some(0));
+        source.seek(checkpoint).unwrap();
+        assert_eq!(source.position().unwrap(), checkpoint);
+        drop(source);
+
+        let mut source = files.open(0);
+        assert_eq!(source.remaining(), Some(2));
+        assert_eq!(source.remaining_bits(), 1);
+        assert_eq!(source.remaining_inverse_pairs(), 2);
+        assert_eq!(source.remaining_square_pairs(), 1);
+        assert_eq!(source.next_triplet(), shares.triplets[1]);
+        assert_eq!(source.next_shared_bit(), shares.bits[1]);
+
+        // A position past the end of the file is rejected
+        assert!(source.seek(4 * 3 * 3 * 2).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load() {
+        let (shares, _) = BeaverShares::deal(3, 2, 1, 1);
+        let mut bytes = Vec::new();
+        shares.save(&mut bytes).unwrap();
+        assert_eq!(BeaverShares::load(&bytes[..]).unwrap(), shares);
+
+        // A corrupted share fails the checksum
+        let mut corrupted = bytes.clone();
+        let index = corrupted.len() - 64;
+        corrupted[index] ^= 1;
+        assert!(matches!(
+            BeaverShares::load(&corrupted[..]),
+            Err(MpcError::PreprocessingError(_))
+        ));
+
+        // Shares of another version of the layout are rejected
+        let mut other_version = bytes;
+        other_version[0] += 1;
+        assert!(BeaverShares::load(&other_version[..]).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! Implements the online generation of inverse pairs from Beaver triplets, for sources that do
+//! not supply inverse pairs of their own
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::{izip, Itertools};
+
+use crate::{error::MpcError, network::MpcNetwork};
+
+use super::{sacrifice::open_scalars, SharedValueSource};
+
+/// Generates shared pairs (r, r^-1) from the Beaver triplets of the source
+///
+/// The parties sample shared random values r and s, multiply them with a Beaver triplet, and
+/// open the product r * s; the share of r^-1 is then computed locally as s * (r * s)^-1. The
+/// opened product is uniformly random and reveals nothing about r. A product that opens to
+/// zero, which happens with negligible probability, is discarded and resampled.
+///
+/// Cost: one Beaver triplet per pair and two rounds of communication for the whole batch
+pub async fn generate_inverse_pairs<N: MpcNetwork + Send, S: SharedValueSource<Scalar>>(
+    network: &mut N,
+    beaver_source: &mut S,
+    num_pairs: usize,
+) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+    let mut pairs = Vec::with_capacity(num_pairs);
+    while pairs.len() < num_pairs {
+        let num_missing = num_pairs - pairs.len();
+        let r = beaver_source.next_shared_value_batch(num_missing);
+        let s = beaver_source.next_shared_value_batch(num_missing);
+        let triplets = beaver_source.try_next_triplet_batch(num_missing)?;
+
+        // Open d = r - a and e = s - b for every pair in one round
+        let mut masked = Vec::with_capacity(2 * num_missing);
+        for (r, s, (a, b, _)) in izip!(r.iter(), s.iter(), triplets.iter()) {
+            masked.push(r - a);
+            masked.push(s - b);
+        }
+        let opened = open_scalars(network, &masked).await?;
+
+        // Identity: [r * s] = de + d[b] + e[a] + [c], the king adds the public de
+        let products = izip!(triplets.iter(), opened.chunks(2))
+            .map(|((a, b, c), opened)| {
+                let (d, e) = (opened[0], opened[1]);
+                let product = c + d * b + e * a;
+                if network.am_king() {
+                    product + d * e
+                } else {
+                    product
+                }
+            })
+            .collect_vec();
+        let opened_products = open_scalars(network, &products).await?;
+
+        for (r, s, product) in izip!(r, s, opened_products) {
+            if product == Scalar::zero() {
+                continue;
+            }
+
+            pairs.push((r, s * product.invert()));
+        }
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::beaver::DealtFiles;
+
+    use super::generate_inverse_pairs;
+
+    #[test]
+    fn test_generate_inverse_pairs() {
+        let pairs =
+            DealtFiles::new("inverse-pairs", 8, 0, 0, 0).run_parties(|mut source, mut network| {
+                block_on(generate_inverse_pairs(&mut network, &mut source, 8)).unwrap()
+            });
+
+        assert_eq!(pairs[0].len(), 8);
+        for (pair0, pair1) in pairs[0].iter().zip(pairs[1].iter()) {
+            assert_eq!((pair0.0 + pair1.0) * (pair0.1 + pair1.1), Scalar::one());
+        }
+    }
+}
@@ -0,0 +1,543 @@
+//! Implements a value source that generates authenticated Beaver triplets with the peer, after
+//! MASCOT (https://eprint.iacr.org/2016/505.pdf)
+//!
+//! The triplets are generated by oblivious transfer as in `OtTripleGenerator`. A transfer in
+//! which the peer offers inconsistent messages fails selectively on the bits of a, so each a is
+//! combined from several candidates with the same b, weighted by the powers of a joint challenge
+//! sampled after the candidates are fixed, which hides any bits that leak.
+//!
+//! Every share is then authenticated by sharing its product with the MAC key in a correlated
+//! oblivious product evaluation (COPE): each party chooses once with the bits of its key share
+//! among seeds offered by the peer, and every MAC is expanded from those seeds, so that a party
+//! cannot use a different key share for different values. A peer that instead expands its
+//! values inconsistently is caught by opening a random combination of the values, masked with an
+//! extra authenticated value, and checking it against its MAC.
+//!
+//! Each triplet (a, b, c) is generated alongside a second triplet (a', b, c') with the same b,
+//! which is sacrificed to check the first: for a joint challenge r the parties open
+//! rho = r * a - a' and then tau = r * c - c' - rho * b, which is zero if both triplets are
+//! valid. Both openings are checked against their MACs before the triplets are used, so a peer
+//! that skews a product, a MAC, or an opening is caught, except with negligible probability.
+
+use std::{collections::VecDeque, iter};
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::{izip, Itertools};
+use rand_core::OsRng;
+use sha3::{Digest, Sha3_512};
+
+use crate::{
+    block_on,
+    {
+        error::MpcError,
+        mpc_scalar::bits::{pow2, scalar_bits_le},
+        network::MpcNetwork,
+    },
+};
+
+use super::{
+    ot::{random_scalars, SCALAR_BITS},
+    sacrifice::{commit_and_open_scalar, open_scalars, sample_challenge},
+    AuthenticatedTriplet, OtTripleGenerator, SharedValueSource,
+};
+
+/// The number of authenticated triplets generated when the source runs dry, unless configured
+/// otherwise
+const DEFAULT_BATCH_SIZE: usize = 8;
+/// The number of candidates combined into each a, enough to hide the bits that a selective
+/// failure leaks
+const NUM_CANDIDATES: usize = 3;
+/// Separates the expansion of the COPE seeds from any other use of the hash function
+const COPE_DOMAIN: &[u8] = b"mpc-ristretto cope";
+
+/// A source of authenticated Beaver triplets generated with the peer, which back the
+/// multiplication of `AuthenticatedScalar`s with preprocessing that is secure against a
+/// malicious peer
+///
+/// Each party samples its share of the MAC key when the generator is created, and a fabric over
+/// the source uses that share as its key share. The first batch of triplets also fixes the COPE
+/// seeds in 506 transfers, after which each triplet costs 3036 transfers and 3795 expansions of
+/// a seed. The unauthenticated values, e.g. shared bits, are generated by the wrapped
+/// `OtTripleGenerator` and are only semi-honest secure. As with `OtTripleGenerator` the
+/// generator owns a network to the peer that should not carry the online phase, and both
+/// parties must draw the same values in the same order, with the same batch size
+#[derive(Debug)]
+pub struct MascotTripleGenerator<N: MpcNetwork + Send> {
+    /// The generator of the unauthenticated values and of the products of shares
+    ot: OtTripleGenerator<N>,
+    /// The local party's share of the MAC key
+    key_share: Scalar,
+    /// The number of authenticated triplets generated when the source runs dry
+    batch_size: usize,
+    /// The generated authenticated triplets not yet drawn from the source
+    triplets: VecDeque<AuthenticatedTriplet<Scalar>>,
+    /// The seeds that MACs are expanded from, fixed with the first batch of triplets
+    cope_seeds: Option<CopeSeeds>,
+}
+
+/// The seeds of the correlated oblivious product evaluation with the peer
+#[derive(Debug)]
+struct CopeSeeds {
+    /// The pairs of seeds offered to the peer, one for each bit of its key share
+    offered: Vec<(Scalar, Scalar)>,
+    /// The seeds chosen with the bits of the local key share
+    chosen: Vec<Scalar>,
+    /// The number of values authenticated so far, which separates the expansions of the seeds
+    num_expanded: u64,
+}
+
+impl<N: MpcNetwork + Send> MascotTripleGenerator<N> {
+    pub fn new(network: N) -> Self {
+        Self {
+            ot: OtTripleGenerator::new(network),
+            key_share: Scalar::random(&mut OsRng {}),
+            batch_size: DEFAULT_BATCH_SIZE,
+            triplets: VecDeque::new(),
+            cope_seeds: None,
+        }
+    }
+
+    /// Sets the number of values generated when the source runs dry; both parties must use the
+    /// same batch size
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.ot = self.ot.with_batch_size(batch_size);
+        self.batch_size = usize::max(1, batch_size);
+        self
+    }
+
+    /// Generates authenticated Beaver triplets with the peer, to be drawn from the source
+    ///
+    /// Returns a `CheatingError` if a triplet fails the sacrifice check, and an
+    /// `AuthenticationError` if an opening fails its MAC check
+    pub async fn generate_authenticated_triplets(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<(), MpcError> {
+        if num_triplets == 0 {
+            return Ok(());
+        }
+
+        // Each party multiplies its own shares, and the cross terms are shared by transfers; the
+        // candidates of a and a' for each triplet share its b
+        let n = num_triplets;
+        let b = random_scalars(n);
+        let candidate_a = random_scalars(2 * NUM_CANDIDATES * n);
+        let candidate_b = b
+            .iter()
+            .flat_map(|b| iter::repeat_n(*b, 2 * NUM_CANDIDATES))
+            .collect_vec();
+        let cross_terms = self
+            .ot
+            .cross_products(&candidate_a, &candidate_b)
+            .await
+            .map_err(MpcError::NetworkError)?;
+        let candidate_c = izip!(&candidate_a, &candidate_b, &cross_terms)
+            .map(|(a, b, cross_term)| a * b + cross_term)
+            .collect_vec();
+
+        // Combine the candidates with the powers of a challenge sampled after they are fixed
+        let combine_challenge = sample_challenge(self.ot.network_mut()).await?;
+        let coefficients = iter::successors(Some(combine_challenge), |power| {
+            Some(power * combine_challenge)
+        })
+        .take(2 * NUM_CANDIDATES)
+        .collect_vec();
+        let combine = |candidates: &[Scalar], coefficients: &[Scalar]| {
+            candidates
+                .chunks(2 * NUM_CANDIDATES)
+                .map(|candidates| {
+                    izip!(&candidates[..NUM_CANDIDATES], coefficients)
+                        .map(|(candidate, coefficient)| candidate * coefficient)
+                        .sum::<Scalar>()
+                })
+                .collect_vec()
+        };
+        let (coefficients, sacrificed_coefficients) = coefficients.split_at(NUM_CANDIDATES);
+        let a = combine(&candidate_a, coefficients);
+        let c = combine(&candidate_c, coefficients);
+        let sacrificed_a = combine(&candidate_a[NUM_CANDIDATES..], sacrificed_coefficients);
+        let sacrificed_c = combine(&candidate_c[NUM_CANDIDATES..], sacrificed_coefficients);
+
+        // Authenticate the values along with a mask for the check of the MACs
+        let mask = random_scalars(1);
+        let values = [&a, &sacrificed_a, &b, &c, &sacrificed_c, &mask]
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect_vec();
+        let macs = self.authenticate(&values).await?;
+        self.check_authentication(&values, &macs).await?;
+        let macs = macs.chunks(n).collect_vec();
+        let (a_mac, sacrificed_a_mac, b_mac, c_mac, sacrificed_c_mac) =
+            (macs[0], macs[1], macs[2], macs[3], macs[4]);
+
+        // Open rho = r * a - a', then tau = r * c - c' - rho * b
+        let key_share = self.key_share;
+        let network = self.ot.network_mut();
+        let challenge = sample_challenge(network).await?;
+        let rho_shares = izip!(&a, &sacrificed_a)
+            .map(|(a, sacrificed_a)| challenge * a - sacrificed_a)
+            .collect_vec();
+        let rho_macs = izip!(a_mac, sacrificed_a_mac)
+            .map(|(a_mac, sacrificed_a_mac)| challenge * a_mac - sacrificed_a_mac)
+            .collect_vec();
+        let rho = open_scalars(network, &rho_shares).await?;
+
+        let tau_shares = izip!(&c, &sacrificed_c, &b, &rho)
+            .map(|(c, sacrificed_c, b, rho)| challenge * c - sacrificed_c - rho * b)
+            .collect_vec();
+        let tau_macs = izip!(c_mac, sacrificed_c_mac, b_mac, &rho)
+            .map(|(c_mac, sacrificed_c_mac, b_mac, rho)| {
+                challenge * c_mac - sacrificed_c_mac - rho * b_mac
+            })
+            .collect_vec();
+        let tau = open_scalars(network, &tau_shares).await?;
+
+        check_macs(
+            network,
+            key_share,
+            &[rho, tau.clone()].concat(),
+            &[rho_macs, tau_macs].concat(),
+        )
+        .await?;
+        if let Some(index) = tau.iter().position(|tau| *tau != Scalar::zero()) {
+            return Err(MpcError::CheatingError(format!(
+                "authenticated triplet {} failed the sacrifice check",
+                index
+            )));
+        }
+
+        self.triplets
+            .extend(
+                izip!(a, b, c, a_mac, b_mac, c_mac).map(|(a, b, c, a_mac, b_mac, c_mac)| {
+                    AuthenticatedTriplet {
+                        a,
+                        b,
+                        c,
+                        a_mac: *a_mac,
+                        b_mac: *b_mac,
+                        c_mac: *c_mac,
+                    }
+                }),
+            );
+        Ok(())
+    }
+
+    /// Shares the MACs of the shared values; each party multiplies its own shares with its key
+    /// share, and the cross terms with the peer's key share are shared by expanding the COPE
+    /// seeds
+    ///
+    /// For each bit i of the peer's key share, the local party expands its offered seeds to
+    /// t_0 and t_1 and sends u = t_0 - t_1 + x for its share x. The peer, which chose the seed
+    /// with its bit k_i, expands it to t_(k_i) and adds k_i * u = t_0 - t_(k_i) + k_i * x, so the
+    /// parties hold additive shares of 2^i * k_i * x once the terms are scaled by 2^i and the
+    /// local party subtracts 2^i * t_0
+    async fn authenticate(&mut self, values: &[Scalar]) -> Result<Vec<Scalar>, MpcError> {
+        let key_share = self.key_share;
+        let seeds = self.cope_seeds().await?;
+        let counters = (seeds.num_expanded..).take(values.len()).collect_vec();
+        seeds.num_expanded += values.len() as u64;
+
+        let mut shares = values.iter().map(|value| key_share * value).collect_vec();
+        let mut differences = Vec::with_capacity(values.len() * SCALAR_BITS);
+        for (share, value, counter) in izip!(shares.iter_mut(), values, &counters) {
+            for (i, (seed0, seed1)) in seeds.offered.iter().enumerate() {
+                let expanded0 = expand_seed(seed0, *counter);
+                differences.push(expanded0 - expand_seed(seed1, *counter) + value);
+                *share -= pow2(i) * expanded0;
+            }
+        }
+
+        let key_bits = scalar_bits_le(&key_share)
+            .take(SCALAR_BITS)
+            .map(u8::from)
+            .collect_vec();
+        let peer_differences = self
+            .ot
+            .network_mut()
+            .broadcast_scalars(&differences)
+            .await
+            .map_err(MpcError::NetworkError)?;
+        let chosen = &self.cope_seeds.as_ref().unwrap().chosen;
+        for (share, peer_differences, counter) in izip!(
+            shares.iter_mut(),
+            peer_differences.chunks(SCALAR_BITS),
+            &counters
+        ) {
+            for (i, (seed, difference, bit)) in
+                izip!(chosen, peer_differences, &key_bits).enumerate()
+            {
+                let term = expand_seed(seed, *counter) + Scalar::from(*bit) * difference;
+                *share += pow2(i) * term;
+            }
+        }
+
+        Ok(shares)
+    }
+
+    /// Fixes the COPE seeds with the peer on first use; each party offers random pairs of seeds
+    /// to the peer, and chooses among the peer's pairs with the bits of its key share
+    async fn cope_seeds(&mut self) -> Result<&mut CopeSeeds, MpcError> {
+        if self.cope_seeds.is_none() {
+            let offered = random_scalars(SCALAR_BITS)
+                .into_iter()
+                .zip(random_scalars(SCALAR_BITS))
+                .collect_vec();
+            let choices = scalar_bits_le(&self.key_share)
+                .take(SCALAR_BITS)
+                .map(u8::from)
+                .collect_vec();
+
+            // The king offers first and chooses second, the peer the opposite
+            let chosen = if self.ot.network_mut().am_king() {
+                self.ot
+                    .send_transfers(&offered)
+                    .await
+                    .map_err(MpcError::NetworkError)?;
+                self.ot.receive_transfers(&choices).await
+            } else {
+                let chosen = self
+                    .ot
+                    .receive_transfers(&choices)
+                    .await
+                    .map_err(MpcError::NetworkError)?;
+                self.ot.send_transfers(&offered).await.map(|_| chosen)
+            }
+            .map_err(MpcError::NetworkError)?;
+
+            self.cope_seeds = Some(CopeSeeds {
+                offered,
+                chosen,
+                num_expanded: 0,
+            });
+        }
+
+        Ok(self.cope_seeds.as_mut().unwrap())
+    }
+
+    /// Checks that the peer expanded its shares consistently, by opening a combination of the
+    /// authenticated values with the powers of a joint challenge and checking it against its MAC
+    ///
+    /// The last value is a random mask, authenticated along with the others, which hides the
+    /// combination of the rest
+    async fn check_authentication(
+        &mut self,
+        values: &[Scalar],
+        macs: &[Scalar],
+    ) -> Result<(), MpcError> {
+        let key_share = self.key_share;
+        let network = self.ot.network_mut();
+        let challenge = sample_challenge(network).await?;
+
+        let (mut value, mut mac) = (values[values.len() - 1], macs[macs.len() - 1]);
+        let mut coefficient = Scalar::one();
+        for (share, mac_share) in values.iter().zip(macs.iter()).take(values.len() - 1) {
+            coefficient *= challenge;
+            value += coefficient * share;
+            mac += coefficient * mac_share;
+        }
+
+        let opened = open_scalars(network, &[value]).await?;
+        check_macs(network, key_share, &opened, &[mac]).await
+    }
+}
+
+/// Expands a COPE seed into the value used for the given authenticated value
+fn expand_seed(seed: &Scalar, counter: u64) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.input(COPE_DOMAIN);
+    hasher.input(seed.as_bytes());
+    hasher.input(counter.to_le_bytes());
+    Scalar::from_hash(hasher)
+}
+
+/// Checks the opened values against the shares of their MACs
+///
+/// The checks are combined with the powers of a joint challenge, so that a single committed
+/// opening covers every value; a value that does not match its MAC passes with probability
+/// n / |F| for n values
+async fn check_macs<N: MpcNetwork + Send>(
+    network: &mut N,
+    key_share: Scalar,
+    opened: &[Scalar],
+    mac_shares: &[Scalar],
+) -> Result<(), MpcError> {
+    let challenge = sample_challenge(network).await?;
+
+    let mut coefficient = Scalar::one();
+    let (mut value, mut mac_share) = (Scalar::zero(), Scalar::zero());
+    for (opened, share) in opened.iter().zip(mac_shares.iter()) {
+        coefficient *= challenge;
+        value += coefficient * opened;
+        mac_share += coefficient * share;
+    }
+
+    if commit_and_open_scalar(network, key_share * value - mac_share).await? != Scalar::zero() {
+        return Err(MpcError::AuthenticationError);
+    }
+
+    Ok(())
+}
+
+impl<N: MpcNetwork + Send> SharedValueSource<Scalar> for MascotTripleGenerator<N> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.ot.next_shared_bit()
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.ot.next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        self.ot.next_shared_value()
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.ot.next_shared_inverse_pair()
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.ot.next_shared_invers_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.ot.next_square_pair()
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.ot.next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.ot.next_triplet()
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        self.ot.next_triplet_batch(num_triplets)
+    }
+
+    fn next_authenticated_triplet(&mut self) -> Result<AuthenticatedTriplet<Scalar>, MpcError> {
+        if self.triplets.is_empty() {
+            block_on(self.generate_authenticated_triplets(self.batch_size))?;
+        }
+
+        Ok(self.triplets.pop_front().unwrap())
+    }
+
+    fn mac_key_share(&self) -> Option<Scalar> {
+        Some(self.key_share)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.ot.remaining()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc, thread};
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{
+        beaver::SharedValueSource,
+        error::MpcError,
+        fabric::AuthenticatedMpcFabric,
+        network::{local_network::LocalTwoPartyNet, MpcNetwork},
+    };
+
+    use super::{random_scalars, AuthenticatedTriplet, MascotTripleGenerator};
+
+    /// Multiplies the parties' inputs over a fabric backed by the generator, returning the
+    /// opened product along with a triplet and key share drawn directly from the generator
+    fn run_party(
+        party_id: u64,
+        network: LocalTwoPartyNet,
+        ot_network: LocalTwoPartyNet,
+    ) -> (Scalar, AuthenticatedTriplet<Scalar>, Scalar) {
+        let network = Rc::new(RefCell::new(network));
+        let beaver_source = Rc::new(RefCell::new(
+            MascotTripleGenerator::new(ot_network).with_batch_size(2),
+        ));
+        let fabric =
+            AuthenticatedMpcFabric::new_with_network(party_id, network.clone(), beaver_source);
+
+        let x = fabric
+            .allocate_private_scalar(0 /* owning_party */, Scalar::from(3u64))
+            .unwrap();
+        let y = fabric
+            .allocate_private_scalar(1 /* owning_party */, Scalar::from(5u64))
+            .unwrap();
+        let product = (&x * &y).open_and_authenticate().unwrap().to_scalar();
+
+        let mut beaver_source = fabric.borrow_beaver_source_mut();
+        let triplet = beaver_source.next_authenticated_triplet().unwrap();
+        let key_share = beaver_source.mac_key_share().unwrap();
+        block_on(network.borrow_mut().close()).unwrap();
+        (product, triplet, key_share)
+    }
+
+    #[test]
+    fn test_mascot_triplets() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let (ot_network0, ot_network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_party(0, network0, ot_network0));
+        let party1 = thread::spawn(move || run_party(1, network1, ot_network1));
+        let (product0, triplet0, key_share0) = party0.join().unwrap();
+        let (product1, triplet1, key_share1) = party1.join().unwrap();
+
+        assert_eq!(product0, Scalar::from(15u64));
+        assert_eq!(product1, Scalar::from(15u64));
+
+        // The triplet is valid and each of its values matches its MAC
+        let key = key_share0 + key_share1;
+        let a = triplet0.a + triplet1.a;
+        let b = triplet0.b + triplet1.b;
+        let c = triplet0.c + triplet1.c;
+        assert_eq!(a * b, c);
+        assert_eq!(key * a, triplet0.a_mac + triplet1.a_mac);
+        assert_eq!(key * b, triplet0.b_mac + triplet1.b_mac);
+        assert_eq!(key * c, triplet0.c_mac + triplet1.c_mac);
+    }
+
+    /// Authenticates random values and checks them, skewing a MAC share of party 1 if `skew`
+    fn run_authentication(
+        network: LocalTwoPartyNet,
+        skew: bool,
+    ) -> (Vec<Scalar>, Vec<Scalar>, Scalar, Result<(), MpcError>) {
+        let mut generator = MascotTripleGenerator::new(network);
+        let values = random_scalars(4);
+        let mut macs = block_on(generator.authenticate(&values)).unwrap();
+        if skew && !generator.ot.network_mut().am_king() {
+            macs[1] += Scalar::one();
+        }
+
+        let res = block_on(generator.check_authentication(&values, &macs));
+        (values, macs, generator.key_share, res)
+    }
+
+    #[test]
+    fn test_check_authentication() {
+        for skew in [false, true] {
+            let (network0, network1) = LocalTwoPartyNet::new_pair();
+            let party0 = thread::spawn(move || run_authentication(network0, skew));
+            let party1 = thread::spawn(move || run_authentication(network1, skew));
+            let (values0, macs0, key_share0, res0) = party0.join().unwrap();
+            let (values1, macs1, key_share1, res1) = party1.join().unwrap();
+
+            if skew {
+                assert!(matches!(res0, Err(MpcError::AuthenticationError)));
+                assert!(matches!(res1, Err(MpcError::AuthenticationError)));
+                continue;
+            }
+
+            res0.unwrap();
+            res1.unwrap();
+            let key = key_share0 + key_share1;
+            for i in 0..values0.len() {
+                assert_eq!(key * (values0[i] + values1[i]), macs0[i] + macs1[i]);
+            }
+        }
+    }
+}
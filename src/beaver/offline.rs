@@ -0,0 +1,405 @@
+//! Implements an explicit offline phase, which generates the preprocessed values a computation
+//! declares it needs before the computation starts, and seals them for the online phase
+
+use async_trait::async_trait;
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{error::MpcError, network::MpcNetwork};
+
+#[cfg(feature = "paillier")]
+use super::PaillierTripleGenerator;
+use super::{
+    DealerBeaverSource, MatrixTriplet, OtTripleGenerator, PermutationCorrelation,
+    PreprocessingCounts, SharedValueSource,
+};
+
+/// A value source that generates its values with the peer ahead of time, e.g. over oblivious
+/// transfer or by requesting them from a dealer
+///
+/// Both parties must generate the same kinds of values, in the same numbers and order; so both
+/// parties' offline phases must have the same requirements
+#[async_trait]
+pub trait PreprocessingGenerator: SharedValueSource<Scalar> {
+    /// Generate Beaver triplets, to be drawn from the source
+    async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError>;
+    /// Generate shared bits, to be drawn from the source
+    async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcError>;
+    /// Generate inverse pairs, to be drawn from the source
+    async fn generate_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError>;
+    /// Generate square pairs, to be drawn from the source
+    async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError>;
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> PreprocessingGenerator for OtTripleGenerator<N> {
+    async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        OtTripleGenerator::generate_triplets(self, num_triplets)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcError> {
+        OtTripleGenerator::generate_bits(self, num_bits)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        OtTripleGenerator::generate_inverse_pairs(self, num_pairs)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        OtTripleGenerator::generate_square_pairs(self, num_pairs)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+}
+
+#[cfg(feature = "paillier")]
+#[async_trait]
+impl<N: MpcNetwork + Send> PreprocessingGenerator for PaillierTripleGenerator<N> {
+    async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        PaillierTripleGenerator::generate_triplets(self, num_triplets).await
+    }
+
+    async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcError> {
+        PaillierTripleGenerator::generate_bits(self, num_bits).await
+    }
+
+    async fn generate_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        PaillierTripleGenerator::generate_inverse_pairs(self, num_pairs).await
+    }
+
+    async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        PaillierTripleGenerator::generate_square_pairs(self, num_pairs).await
+    }
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> PreprocessingGenerator for DealerBeaverSource<N> {
+    async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        self.request_triplets(num_triplets)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcError> {
+        self.request_bits(num_bits)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        self.request_inverse_pairs(num_pairs)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        self.request_square_pairs(num_pairs)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+}
+
+/// The offline phase of a computation: the computation declares the values it needs up front,
+/// and the phase generates all of them before the online phase starts
+///
+/// The requirements may be measured by a dry run of the computation over a
+/// `CountingBeaverSource`. Shared values are sampled locally by every generator and are not
+/// generated ahead of time. Point triplets are generated as Beaver triplets, for a
+/// `PointTripletSource` to precompute, and matrix and authenticated triplets are not supported
+#[derive(Debug)]
+pub struct OfflinePhase<G> {
+    /// The generator of the values
+    generator: G,
+    /// The number of values of each kind to generate
+    requirements: PreprocessingCounts,
+}
+
+impl<G: PreprocessingGenerator> OfflinePhase<G> {
+    /// Create an offline phase over the given generator, requiring no values
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            requirements: PreprocessingCounts::default(),
+        }
+    }
+
+    /// Require the given number of values of each kind, e.g. as counted by a dry run
+    pub fn with_requirements(mut self, requirements: PreprocessingCounts) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    /// Require the given number of Beaver triplets
+    pub fn with_triplets(mut self, num_triplets: usize) -> Self {
+        self.requirements.triplets = num_triplets;
+        self
+    }
+
+    /// Require the given number of shared bits
+    pub fn with_bits(mut self, num_bits: usize) -> Self {
+        self.requirements.bits = num_bits;
+        self
+    }
+
+    /// Require the given number of inverse pairs
+    pub fn with_inverse_pairs(mut self, num_pairs: usize) -> Self {
+        self.requirements.inverse_pairs = num_pairs;
+        self
+    }
+
+    /// Require the given number of square pairs
+    pub fn with_square_pairs(mut self, num_pairs: usize) -> Self {
+        self.requirements.square_pairs = num_pairs;
+        self
+    }
+
+    /// Returns the number of values of each kind the phase generates
+    pub fn requirements(&self) -> PreprocessingCounts {
+        self.requirements
+    }
+
+    /// Generate every required value with the peer and seal them for the online phase
+    ///
+    /// The phase is a future, so it may run concurrently with the rest of the setup
+    pub async fn run(mut self) -> Result<SealedPreprocessing<G>, MpcError> {
+        let mut requirements = self.requirements;
+        if requirements.matrix_triplets > 0 {
+            return Err(MpcError::PreprocessingError(
+                "the offline phase does not generate matrix triplets".to_string(),
+            ));
+        }
+        if requirements.exp_tuples > 0 {
+            return Err(MpcError::PreprocessingError(
+                "the offline phase does not generate exponentiation tuples".to_string(),
+            ));
+        }
+        if requirements.permutation_correlations > 0 {
+            return Err(MpcError::PreprocessingError(
+                "the offline phase does not generate permutation correlations".to_string(),
+            ));
+        }
+        if requirements.authenticated_triplets > 0 {
+            return Err(MpcError::PreprocessingError(
+                "the offline phase does not generate authenticated triplets".to_string(),
+            ));
+        }
+
+        // Point triplets are precomputed from Beaver triplets
+        requirements.triplets += requirements.point_triplets;
+        requirements.point_triplets = 0;
+
+        // Both parties skip the same kinds, as they share the requirements
+        if requirements.triplets > 0 {
+            self.generator
+                .generate_triplets(requirements.triplets)
+                .await?;
+        }
+        if requirements.bits > 0 {
+            self.generator.generate_bits(requirements.bits).await?;
+        }
+        if requirements.inverse_pairs > 0 {
+            self.generator
+                .generate_inverse_pairs(requirements.inverse_pairs)
+                .await?;
+        }
+        if requirements.square_pairs > 0 {
+            self.generator
+                .generate_square_pairs(requirements.square_pairs)
+                .await?;
+        }
+
+        Ok(SealedPreprocessing {
+            inner: self.generator,
+            remaining: requirements,
+        })
+    }
+}
+
+/// The values generated by an offline phase, handed to the online phase
+///
+/// The source never generates values of its own; drawing more of a kind than the offline phase
+/// generated returns a `PreprocessingExhausted` error from the fallible methods and panics in the
+/// infallible ones, rather than falling back to generating values with the peer online
+#[derive(Debug)]
+pub struct SealedPreprocessing<S> {
+    /// The source holding the generated values
+    inner: S,
+    /// The number of values of each kind not yet drawn
+    remaining: PreprocessingCounts,
+}
+
+impl<S> SealedPreprocessing<S> {
+    /// Returns the number of values of each kind not yet drawn
+    pub fn remaining_counts(&self) -> PreprocessingCounts {
+        self.remaining
+    }
+}
+
+/// Deducts the values drawn from the remaining budget of their kind
+fn spend(remaining: &mut usize, num_values: usize, kind: &str) -> Result<(), MpcError> {
+    if num_values > *remaining {
+        return Err(MpcError::PreprocessingExhausted(format!(
+            "drew {} {} with {} left from the offline phase",
+            num_values, kind, remaining
+        )));
+    }
+
+    *remaining -= num_values;
+    Ok(())
+}
+
+impl<T, S: SharedValueSource<T>> SharedValueSource<T> for SealedPreprocessing<S> {
+    fn next_shared_bit(&mut self) -> T {
+        self.next_shared_bit_batch(1).remove(0)
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.try_next_shared_bit_batch(num_values)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<T>, MpcError> {
+        spend(&mut self.remaining.bits, num_values, "shared bits")?;
+        self.inner.try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> T {
+        self.inner.next_shared_value()
+    }
+
+    fn next_shared_value_batch(&mut self, num_values: usize) -> Vec<T> {
+        self.inner.next_shared_value_batch(num_values)
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (T, T) {
+        self.next_shared_invers_pair_batch(1).remove(0)
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.try_next_inverse_pair_batch(num_pairs)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_inverse_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        spend(
+            &mut self.remaining.inverse_pairs,
+            num_pairs,
+            "inverse pairs",
+        )?;
+        self.inner.try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (T, T) {
+        self.next_square_pair_batch(1).remove(0)
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(T, T)> {
+        self.try_next_square_pair_batch(num_pairs)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_square_pair_batch(&mut self, num_pairs: usize) -> Result<Vec<(T, T)>, MpcError> {
+        spend(&mut self.remaining.square_pairs, num_pairs, "square pairs")?;
+        self.inner.try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (T, T, T) {
+        self.next_triplet_batch(1).remove(0)
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(T, T, T)> {
+        self.try_next_triplet_batch(num_triplets)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_next_triplet_batch(&mut self, num_triplets: usize) -> Result<Vec<(T, T, T)>, MpcError> {
+        spend(&mut self.remaining.triplets, num_triplets, "triplets")?;
+        self.inner.try_next_triplet_batch(num_triplets)
+    }
+
+    fn next_matrix_triplet(
+        &mut self,
+        _n: usize,
+        _m: usize,
+        _k: usize,
+    ) -> Result<MatrixTriplet<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "the offline phase does not generate matrix triplets".to_string(),
+        ))
+    }
+
+    fn next_exp_tuple(&mut self, _k: usize) -> Result<Vec<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "the offline phase does not generate exponentiation tuples".to_string(),
+        ))
+    }
+
+    fn next_permutation_correlation(
+        &mut self,
+        _n: usize,
+        _permuting_party: u64,
+    ) -> Result<PermutationCorrelation<T>, MpcError> {
+        Err(MpcError::PreprocessingError(
+            "the offline phase does not generate permutation correlations".to_string(),
+        ))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.remaining.triplets)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{
+        beaver::{OtTripleGenerator, SharedValueSource},
+        error::MpcError,
+        network::local_network::LocalTwoPartyNet,
+    };
+
+    use super::OfflinePhase;
+
+    /// Runs the party's offline phase and draws every generated value
+    fn run_party(network: LocalTwoPartyNet) -> ((Scalar, Scalar, Scalar), Scalar) {
+        let offline = OfflinePhase::new(OtTripleGenerator::new(network))
+            .with_triplets(1)
+            .with_bits(1);
+        let mut preprocessing = block_on(offline.run()).unwrap();
+
+        let values = (
+            preprocessing.next_triplet(),
+            preprocessing.next_shared_bit(),
+        );
+        assert!(matches!(
+            preprocessing.try_next_triplet_batch(1),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+        values
+    }
+
+    #[test]
+    fn test_offline_phase() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_party(network0));
+        let party1 = thread::spawn(move || run_party(network1));
+        let (triplet0, bit0) = party0.join().unwrap();
+        let (triplet1, bit1) = party1.join().unwrap();
+
+        assert_eq!(
+            (triplet0.0 + triplet1.0) * (triplet0.1 + triplet1.1),
+            triplet0.2 + triplet1.2
+        );
+        let bit = bit0 + bit1;
+        assert!(bit == Scalar::zero() || bit == Scalar::one());
+    }
+}
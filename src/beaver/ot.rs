@@ -0,0 +1,471 @@
+//! Implements a value source that generates Beaver triplets with the peer by oblivious transfer
+//!
+//! Products of the parties' shares are computed with Gilboa's protocol: to share the product
+//! x * y of a value x held by one party and y held by the other, the holder of y offers the pair
+//! (r_i, r_i + 2^i * y) for each bit i of x in a 1-out-of-2 oblivious transfer, and the holder of
+//! x chooses with the bit. The sum of the chosen values is x * y + sum(r_i), so the parties hold
+//! additive shares of the product. The transfers use the "simplest OT" of Chou and Orlandi over
+//! the Ristretto group
+
+use std::collections::VecDeque;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_TABLE, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::Identity,
+};
+use itertools::{izip, Itertools};
+use rand_core::{OsRng, RngCore};
+use sha3::{Digest, Sha3_512};
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::{
+    block_on,
+    {error::MpcNetworkError, mpc_scalar::bits::pow2, network::MpcNetwork},
+};
+
+use super::SharedValueSource;
+
+/// The number of bits in a canonical scalar, and so the transfers used per product
+pub(super) const SCALAR_BITS: usize = 253;
+/// The number of values generated when the source runs dry, unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 16;
+/// Separates the keys of the transfers from any other use of the hash function
+const TRANSFER_KEY_DOMAIN: &[u8] = b"mpc-ristretto ot key";
+
+/// A source of Beaver triplets, shared bits, inverse pairs, and square pairs generated with the
+/// peer by oblivious transfer; i.e. the offline phase of the MPC
+///
+/// The generator owns a network to the peer that should not carry the online phase, e.g. a
+/// channel opened with `QuicTwoPartyNet::open_channel`. Both parties must draw the same values
+/// in the same order, with the same batch size. Values are best generated ahead of the online
+/// phase with the `generate_*` methods; a source that runs dry generates another batch on
+/// demand, and panics if the peer cannot be reached.
+///
+/// Each triplet costs 506 transfers of a few group operations each. The protocol is secure
+/// against a semi-honest peer; a malicious peer may skew the values undetected
+#[derive(Debug)]
+pub struct OtTripleGenerator<N: MpcNetwork + Send> {
+    /// The network to the peer
+    network: N,
+    /// The number of values generated when the source runs dry
+    batch_size: usize,
+    /// The generated triplets not yet drawn from the source
+    triplets: VecDeque<(Scalar, Scalar, Scalar)>,
+    /// The generated shared bits not yet drawn from the source
+    bits: VecDeque<Scalar>,
+    /// The generated inverse pairs not yet drawn from the source
+    inverse_pairs: VecDeque<(Scalar, Scalar)>,
+    /// The generated square pairs not yet drawn from the source
+    square_pairs: VecDeque<(Scalar, Scalar)>,
+}
+
+impl<N: MpcNetwork + Send> OtTripleGenerator<N> {
+    pub fn new(network: N) -> Self {
+        Self {
+            network,
+            batch_size: DEFAULT_BATCH_SIZE,
+            triplets: VecDeque::new(),
+            bits: VecDeque::new(),
+            inverse_pairs: VecDeque::new(),
+            square_pairs: VecDeque::new(),
+        }
+    }
+
+    /// Sets the number of values generated when the source runs dry; both parties must use the
+    /// same batch size
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = usize::max(1, batch_size);
+        self
+    }
+
+    /// Mutably borrow the network to the peer, e.g. to check values generated over it
+    pub(super) fn network_mut(&mut self) -> &mut N {
+        &mut self.network
+    }
+
+    /// Generates Beaver triplets with the peer, to be drawn from the source
+    pub async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcNetworkError> {
+        let triplets = self.random_triplets(num_triplets).await?;
+        self.triplets.extend(triplets);
+        Ok(())
+    }
+
+    /// Generates shared bits with the peer, to be drawn from the source
+    pub async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcNetworkError> {
+        let bits = self.random_bits(num_bits).await?;
+        self.bits.extend(bits);
+        Ok(())
+    }
+
+    /// Generates inverse pairs with the peer, to be drawn from the source
+    pub async fn generate_inverse_pairs(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<(), MpcNetworkError> {
+        let pairs = self.random_inverse_pairs(num_pairs).await?;
+        self.inverse_pairs.extend(pairs);
+        Ok(())
+    }
+
+    /// Generates square pairs with the peer, to be drawn from the source
+    pub async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcNetworkError> {
+        let pairs = self.random_square_pairs(num_pairs).await?;
+        self.square_pairs.extend(pairs);
+        Ok(())
+    }
+
+    /// Shares the products of random shared values a and b
+    ///
+    /// Each party multiplies its own shares, and the cross terms a_0 * b_1 + a_1 * b_0 are
+    /// shared by oblivious transfer
+    async fn random_triplets(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcNetworkError> {
+        let a = random_scalars(num_triplets);
+        let b = random_scalars(num_triplets);
+        let cross_terms = self.cross_products(&a, &b).await?;
+
+        Ok(izip!(a, b, cross_terms)
+            .map(|(a, b, cross_term)| (a, b, a * b + cross_term))
+            .collect_vec())
+    }
+
+    /// Shares random bits as the XOR of a bit sampled by each party, b_0 + b_1 - 2 * b_0 * b_1
+    async fn random_bits(&mut self, num_bits: usize) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let mut rng = OsRng {};
+        let bits = (0..num_bits)
+            .map(|_| Scalar::from(rng.next_u32() as u64 & 1))
+            .collect_vec();
+
+        // The king chooses with its bit, so each product takes a single transfer
+        let products = if self.network.am_king() {
+            self.multiply_as_receiver(&bits, 1 /* num_bits */).await?
+        } else {
+            self.multiply_as_sender(&bits, 1 /* num_bits */).await?
+        };
+
+        Ok(bits
+            .iter()
+            .zip(products.iter())
+            .map(|(bit, product)| bit - Scalar::from(2u64) * product)
+            .collect_vec())
+    }
+
+    /// Shares random values r and r^-1 by opening the product of r with a random mask s, then
+    /// sharing s * (r * s)^-1
+    async fn random_inverse_pairs(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcNetworkError> {
+        let triplets = self.random_triplets(num_pairs).await?;
+        let product_shares = triplets.iter().map(|(_, _, c)| *c).collect_vec();
+        let peer_shares = self.network.broadcast_scalars(&product_shares).await?;
+
+        Ok(izip!(triplets, peer_shares)
+            .map(|((r, s, c), peer_c)| (r, s * (c + peer_c).invert()))
+            .collect_vec())
+    }
+
+    /// Shares random values a and a^2; each party squares its own share, and the cross term
+    /// 2 * a_0 * a_1 is shared by a single oblivious multiplication, half the cost of a triplet
+    async fn random_square_pairs(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcNetworkError> {
+        let a = random_scalars(num_pairs);
+        let products = if self.network.am_king() {
+            self.multiply_as_receiver(&a, SCALAR_BITS).await?
+        } else {
+            self.multiply_as_sender(&a, SCALAR_BITS).await?
+        };
+
+        Ok(a.iter()
+            .zip(products.iter())
+            .map(|(a, product)| (*a, a * a + Scalar::from(2u64) * product))
+            .collect_vec())
+    }
+
+    /// Shares x_0 * y_1 + x_1 * y_0 for the local values x_i and y_i and the peer's values
+    pub(super) async fn cross_products(
+        &mut self,
+        x: &[Scalar],
+        y: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        // The king chooses with its x in the first round of transfers and offers its y in the
+        // second, the peer the opposite
+        let (first, second) = if self.network.am_king() {
+            let first = self.multiply_as_receiver(x, SCALAR_BITS).await?;
+            (first, self.multiply_as_sender(y, SCALAR_BITS).await?)
+        } else {
+            let first = self.multiply_as_sender(y, SCALAR_BITS).await?;
+            (first, self.multiply_as_receiver(x, SCALAR_BITS).await?)
+        };
+
+        Ok(first
+            .iter()
+            .zip(second.iter())
+            .map(|(first, second)| first + second)
+            .collect_vec())
+    }
+
+    /// Shares the products of the local values with the values the peer offers in
+    /// `multiply_as_sender`, choosing with the low `num_bits` bits of each local value
+    async fn multiply_as_receiver(
+        &mut self,
+        values: &[Scalar],
+        num_bits: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let choices = values
+            .iter()
+            .flat_map(|value| (0..num_bits).map(move |i| (value.as_bytes()[i / 8] >> (i % 8)) & 1))
+            .collect_vec();
+        let chosen = self.receive_transfers(&choices).await?;
+
+        Ok(chosen
+            .chunks(num_bits)
+            .map(|chosen| chosen.iter().sum())
+            .collect_vec())
+    }
+
+    /// Shares the products of the local values with the values the peer chooses with in
+    /// `multiply_as_receiver`, offering each value scaled by 2^i for each of the peer's bits
+    async fn multiply_as_sender(
+        &mut self,
+        values: &[Scalar],
+        num_bits: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let mut rng = OsRng {};
+        let mut shares = Vec::with_capacity(values.len());
+        let mut messages = Vec::with_capacity(values.len() * num_bits);
+        for value in values.iter() {
+            let mut share = Scalar::zero();
+            for i in 0..num_bits {
+                let mask = Scalar::random(&mut rng);
+                messages.push((mask, mask + pow2(i) * value));
+                share -= mask;
+            }
+
+            shares.push(share);
+        }
+
+        self.send_transfers(&messages).await?;
+        Ok(shares)
+    }
+
+    /// Offers each pair of messages to the peer in a 1-out-of-2 oblivious transfer
+    ///
+    /// The sender publishes A = aG, and the receiver replies with B = bG + cA for its choice c.
+    /// The sender encrypts the first message under aB and the second under a(B - A); the
+    /// receiver can derive only the key of its choice, bA
+    pub(super) async fn send_transfers(
+        &mut self,
+        messages: &[(Scalar, Scalar)],
+    ) -> Result<(), MpcNetworkError> {
+        let mut rng = OsRng {};
+        let secret = Scalar::random(&mut rng);
+        let public = &secret * &RISTRETTO_BASEPOINT_TABLE;
+        self.network.send_single_point(public).await?;
+
+        let secret_public = secret * public;
+        let choice_points = self.network.receive_points(messages.len()).await?;
+        let mut ciphertexts = Vec::with_capacity(2 * messages.len());
+        for (index, ((message0, message1), choice_point)) in
+            messages.iter().zip(choice_points.iter()).enumerate()
+        {
+            let shared_point0 = secret * choice_point;
+            let shared_point1 = shared_point0 - secret_public;
+            ciphertexts.push(message0 + transfer_key(index, &public, choice_point, &shared_point0));
+            ciphertexts.push(message1 + transfer_key(index, &public, choice_point, &shared_point1));
+        }
+
+        self.network.send_scalars(&ciphertexts).await
+    }
+
+    /// Receives the chosen message of each pair the peer offers in `send_transfers`
+    pub(super) async fn receive_transfers(
+        &mut self,
+        choices: &[u8],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let mut rng = OsRng {};
+        let public = self.network.receive_single_point().await?;
+
+        let secrets = (0..choices.len())
+            .map(|_| Scalar::random(&mut rng))
+            .collect_vec();
+        let choice_points = secrets
+            .iter()
+            .zip(choices.iter())
+            .map(|(secret, choice)| {
+                let offset = RistrettoPoint::conditional_select(
+                    &RistrettoPoint::identity(),
+                    &public,
+                    Choice::from(*choice),
+                );
+                secret * &RISTRETTO_BASEPOINT_TABLE + offset
+            })
+            .collect_vec();
+        self.network.send_points(&choice_points).await?;
+
+        let ciphertexts = self.network.receive_scalars(2 * choices.len()).await?;
+        let messages = izip!(secrets, choices, choice_points, ciphertexts.chunks(2))
+            .enumerate()
+            .map(|(index, (secret, choice, choice_point, ciphertexts))| {
+                // Select the chosen ciphertext without branching on the choice
+                let chosen =
+                    ciphertexts[0] + Scalar::from(*choice) * (ciphertexts[1] - ciphertexts[0]);
+                chosen - transfer_key(index, &public, &choice_point, &(secret * public))
+            })
+            .collect_vec();
+
+        Ok(messages)
+    }
+}
+
+/// Derives the key of a transfer from the Diffie-Hellman point shared by the parties, bound to
+/// the transcript of the transfer
+fn transfer_key(
+    index: usize,
+    public: &RistrettoPoint,
+    choice_point: &RistrettoPoint,
+    shared_point: &RistrettoPoint,
+) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.input(TRANSFER_KEY_DOMAIN);
+    hasher.input((index as u64).to_le_bytes());
+    hasher.input(public.compress().as_bytes());
+    hasher.input(choice_point.compress().as_bytes());
+    hasher.input(shared_point.compress().as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+/// Samples the local shares of random shared values
+pub(super) fn random_scalars(num_scalars: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_scalars)
+        .map(|_| Scalar::random(&mut rng))
+        .collect_vec()
+}
+
+impl<N: MpcNetwork + Send> SharedValueSource<Scalar> for OtTripleGenerator<N> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.next_shared_bit_batch(1)[0]
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        if self.bits.len() < num_values {
+            let num_missing = usize::max(num_values - self.bits.len(), self.batch_size);
+            block_on(self.generate_bits(num_missing)).expect("failed to generate shared bits");
+        }
+
+        self.bits.drain(..num_values).collect_vec()
+    }
+
+    /// Each party samples its share locally, no communication is needed
+    fn next_shared_value(&mut self) -> Scalar {
+        Scalar::random(&mut OsRng {})
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_shared_invers_pair_batch(1)[0]
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        if self.inverse_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.inverse_pairs.len(), self.batch_size);
+            block_on(self.generate_inverse_pairs(num_missing))
+                .expect("failed to generate inverse pairs");
+        }
+
+        self.inverse_pairs.drain(..num_pairs).collect_vec()
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_square_pair_batch(1)[0]
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        if self.square_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.square_pairs.len(), self.batch_size);
+            block_on(self.generate_square_pairs(num_missing))
+                .expect("failed to generate square pairs");
+        }
+
+        self.square_pairs.drain(..num_pairs).collect_vec()
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.next_triplet_batch(1)[0]
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        if self.triplets.len() < num_triplets {
+            let num_missing = usize::max(num_triplets - self.triplets.len(), self.batch_size);
+            block_on(self.generate_triplets(num_missing)).expect("failed to generate triplets");
+        }
+
+        self.triplets.drain(..num_triplets).collect_vec()
+    }
+
+    /// The number of triplets generated and not yet drawn; the source generates more on demand
+    /// once they run out
+    fn remaining(&self) -> Option<usize> {
+        Some(self.triplets.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{beaver::SharedValueSource, network::local_network::LocalTwoPartyNet};
+
+    use super::OtTripleGenerator;
+
+    /// The triplet, shared bit, inverse pair, and square pair drawn by a party
+    type PartyValues = (
+        (Scalar, Scalar, Scalar),
+        Scalar,
+        (Scalar, Scalar),
+        (Scalar, Scalar),
+    );
+
+    /// Draws one of each value from the party's generator, generating the triplet ahead of time
+    /// and the rest on demand
+    fn run_party(network: LocalTwoPartyNet) -> PartyValues {
+        let mut generator = OtTripleGenerator::new(network).with_batch_size(2);
+        block_on(generator.generate_triplets(1)).unwrap();
+        assert_eq!(generator.remaining(), Some(1));
+
+        let triplet = generator.next_triplet();
+        let bit = generator.next_shared_bit();
+        let inverse_pair = generator.next_shared_inverse_pair();
+        let square_pair = generator.next_square_pair();
+        (triplet, bit, inverse_pair, square_pair)
+    }
+
+    #[test]
+    fn test_ot_triplets() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_party(network0));
+        let party1 = thread::spawn(move || run_party(network1));
+        let (triplet0, bit0, pair0, square0) = party0.join().unwrap();
+        let (triplet1, bit1, pair1, square1) = party1.join().unwrap();
+
+        let a = triplet0.0 + triplet1.0;
+        let b = triplet0.1 + triplet1.1;
+        let c = triplet0.2 + triplet1.2;
+        assert_eq!(a * b, c);
+
+        let bit = bit0 + bit1;
+        assert!(bit == Scalar::zero() || bit == Scalar::one());
+
+        assert_eq!((pair0.0 + pair1.0) * (pair0.1 + pair1.1), Scalar::one());
+
+        let value = square0.0 + square1.0;
+        assert_eq!(value * value, square0.1 + square1.1);
+    }
+}
@@ -0,0 +1,569 @@
+//! Implements a value source that generates Beaver triplets with the peer using Paillier
+//! encryption, an alternative to the oblivious transfer of `OtTripleGenerator`
+//!
+//! To share the product x * y of a value x held by the king and y held by the peer, the king
+//! sends the encryption of x under its own key. The peer homomorphically computes an encryption
+//! of x * y + s for a random mask s, much larger than the product, and returns it; the king
+//! decrypts x * y + s as its share and the peer holds -s. The product is computed over the
+//! integers, so the modulus of the key must be large enough that x * y + s does not wrap around
+
+use std::collections::VecDeque;
+
+use curve25519_dalek::{constants::BASEPOINT_ORDER, scalar::Scalar};
+use itertools::{izip, Itertools};
+use num_bigint_dig::{BigUint, ModInverse, RandBigInt, RandPrime};
+use num_traits::One;
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    block_on,
+    error::MpcError,
+    mpc_scalar::{bits::STATISTICAL_SECURITY, scalar_to_biguint},
+    network::MpcNetwork,
+};
+
+use super::SharedValueSource;
+
+/// The number of bits in the mask of a product; the mask statistically hides products of two
+/// canonical scalars, which are less than 2^506
+const MASK_BITS: usize = 2 * 253 + STATISTICAL_SECURITY;
+/// The smallest modulus for which a masked product does not wrap around
+const MIN_KEY_BITS: usize = MASK_BITS + 2;
+/// The largest modulus the peer accepts, which bounds the size of the modulus the king announces
+const MAX_KEY_BITS: usize = 8192;
+/// The size of the modulus generated by the king, unless configured otherwise
+const DEFAULT_KEY_BITS: usize = 2048;
+/// The number of values generated when the source runs dry, unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 16;
+/// The number of bytes of an integer packed into each scalar sent over the network; the
+/// packed scalars are always canonical
+const LIMB_BYTES: usize = 31;
+
+/// A Paillier key with generator n + 1
+#[derive(Clone, Debug)]
+struct PaillierKey {
+    /// The modulus n = p * q
+    modulus: BigUint,
+    /// The modulus of the ciphertexts, n^2
+    modulus_squared: BigUint,
+    /// The secret phi(n) and its inverse modulo n, held only by the king
+    secret: Option<(BigUint, BigUint)>,
+}
+
+impl PaillierKey {
+    /// Generates a key with a modulus of `key_bits` bits
+    fn generate(key_bits: usize) -> Self {
+        let mut rng = rand::rngs::OsRng;
+        let (p, q) = loop {
+            let p: BigUint = rng.gen_prime(key_bits / 2);
+            let q: BigUint = rng.gen_prime(key_bits - key_bits / 2);
+            if p != q {
+                break (p, q);
+            }
+        };
+
+        let modulus = &p * &q;
+        let phi = (p - BigUint::one()) * (q - BigUint::one());
+        let phi_inverse = (&phi)
+            .mod_inverse(&modulus)
+            .and_then(|inverse| inverse.to_biguint())
+            .expect("phi(n) is invertible modulo n for primes of equal length");
+
+        let mut key = Self::from_modulus(modulus);
+        key.secret = Some((phi, phi_inverse));
+        key
+    }
+
+    /// Builds the public key of the given modulus
+    fn from_modulus(modulus: BigUint) -> Self {
+        Self {
+            modulus_squared: &modulus * &modulus,
+            modulus,
+            secret: None,
+        }
+    }
+
+    /// The number of limbs in a packed ciphertext
+    fn ciphertext_limbs(&self) -> usize {
+        num_limbs(&self.modulus_squared)
+    }
+
+    /// Encrypts the message as (1 + m * n) * r^n mod n^2
+    fn encrypt(&self, message: &BigUint) -> BigUint {
+        let randomness = rand::rngs::OsRng.gen_biguint_below(&self.modulus);
+        let blinding = randomness.modpow(&self.modulus, &self.modulus_squared);
+
+        (BigUint::one() + message * &self.modulus) * blinding % &self.modulus_squared
+    }
+
+    /// Decrypts the ciphertext as L(c^phi mod n^2) * phi^-1 mod n, for L(x) = (x - 1) / n
+    fn decrypt(&self, ciphertext: &BigUint) -> BigUint {
+        let (phi, phi_inverse) = self
+            .secret
+            .as_ref()
+            .expect("only the holder of the secret key may decrypt");
+        let power = ciphertext.modpow(phi, &self.modulus_squared);
+
+        (power - BigUint::one()) / &self.modulus * phi_inverse % &self.modulus
+    }
+}
+
+/// The number of limbs needed to pack integers up to the given bound
+fn num_limbs(bound: &BigUint) -> usize {
+    num_limbs_of_bits(bound.bits())
+}
+
+/// The number of limbs needed to pack integers of the given number of bits
+fn num_limbs_of_bits(num_bits: usize) -> usize {
+    num_bits.div_ceil(8 * LIMB_BYTES)
+}
+
+/// Packs an integer into `num_limbs` scalars, little endian
+fn pack(value: &BigUint, num_limbs: usize) -> Vec<Scalar> {
+    let mut bytes = value.to_bytes_le();
+    bytes.resize(num_limbs * LIMB_BYTES, 0);
+    bytes
+        .chunks(LIMB_BYTES)
+        .map(|limb| {
+            let mut limb_bytes = [0u8; 32];
+            limb_bytes[..LIMB_BYTES].copy_from_slice(limb);
+            Scalar::from_bits(limb_bytes)
+        })
+        .collect_vec()
+}
+
+/// Unpacks an integer packed by `pack`
+fn unpack(limbs: &[Scalar]) -> Result<BigUint, MpcError> {
+    let mut bytes = Vec::with_capacity(limbs.len() * LIMB_BYTES);
+    for limb in limbs.iter() {
+        if limb.as_bytes()[LIMB_BYTES] != 0 {
+            return Err(MpcError::PreprocessingError(
+                "peer sent a malformed packed integer".to_string(),
+            ));
+        }
+
+        bytes.extend_from_slice(&limb.as_bytes()[..LIMB_BYTES]);
+    }
+
+    Ok(BigUint::from_bytes_le(&bytes))
+}
+
+/// Reduces an integer modulo the order of the scalar field
+fn biguint_to_scalar(value: &BigUint) -> Scalar {
+    let mut bytes = (value % scalar_to_biguint(&BASEPOINT_ORDER)).to_bytes_le();
+    bytes.resize(32, 0);
+    Scalar::from_bytes_mod_order(bytes.try_into().unwrap())
+}
+
+/// A source of Beaver triplets, shared bits, inverse pairs, and square pairs generated with the
+/// peer using Paillier encryption; i.e. the offline phase of the MPC
+///
+/// The king generates a Paillier key when the first values are generated, and the peer encrypts
+/// under it for the rest of the session. Otherwise the generator is used as `OtTripleGenerator`:
+/// it owns a network to the peer that should not carry the online phase, both parties must draw
+/// the same values in the same order with the same batch size, and a source that runs dry
+/// generates another batch on demand, panicking if the peer cannot be reached.
+///
+/// Each triplet costs two encryptions and two decryptions by the king, and two homomorphic
+/// multiplications by the peer, in one round trip per batch. The protocol is secure against a
+/// semi-honest peer; a malicious peer may skew the values undetected
+#[derive(Debug)]
+pub struct PaillierTripleGenerator<N: MpcNetwork + Send> {
+    /// The network to the peer
+    network: N,
+    /// The number of values generated when the source runs dry
+    batch_size: usize,
+    /// The size of the modulus the king generates
+    key_bits: usize,
+    /// The king's key, once generated or received
+    key: Option<PaillierKey>,
+    /// The generated triplets not yet drawn from the source
+    triplets: VecDeque<(Scalar, Scalar, Scalar)>,
+    /// The generated shared bits not yet drawn from the source
+    bits: VecDeque<Scalar>,
+    /// The generated inverse pairs not yet drawn from the source
+    inverse_pairs: VecDeque<(Scalar, Scalar)>,
+    /// The generated square pairs not yet drawn from the source
+    square_pairs: VecDeque<(Scalar, Scalar)>,
+}
+
+impl<N: MpcNetwork + Send> PaillierTripleGenerator<N> {
+    pub fn new(network: N) -> Self {
+        Self {
+            network,
+            batch_size: DEFAULT_BATCH_SIZE,
+            key_bits: DEFAULT_KEY_BITS,
+            key: None,
+            triplets: VecDeque::new(),
+            bits: VecDeque::new(),
+            inverse_pairs: VecDeque::new(),
+            square_pairs: VecDeque::new(),
+        }
+    }
+
+    /// Sets the number of values generated when the source runs dry; both parties must use the
+    /// same batch size
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = usize::max(1, batch_size);
+        self
+    }
+
+    /// Sets the size of the modulus generated by the king, between 548 and 8192 bits; the peer
+    /// accepts any modulus in this range
+    pub fn with_key_bits(mut self, key_bits: usize) -> Self {
+        assert!(
+            (MIN_KEY_BITS..=MAX_KEY_BITS).contains(&key_bits),
+            "a Paillier modulus must be between {} and {} bits",
+            MIN_KEY_BITS,
+            MAX_KEY_BITS
+        );
+        self.key_bits = key_bits;
+        self
+    }
+
+    /// Generates Beaver triplets with the peer, to be drawn from the source
+    pub async fn generate_triplets(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        let triplets = self.random_triplets(num_triplets).await?;
+        self.triplets.extend(triplets);
+        Ok(())
+    }
+
+    /// Generates shared bits with the peer, to be drawn from the source
+    pub async fn generate_bits(&mut self, num_bits: usize) -> Result<(), MpcError> {
+        let bits = self.random_bits(num_bits).await?;
+        self.bits.extend(bits);
+        Ok(())
+    }
+
+    /// Generates inverse pairs with the peer, to be drawn from the source
+    pub async fn generate_inverse_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        let pairs = self.random_inverse_pairs(num_pairs).await?;
+        self.inverse_pairs.extend(pairs);
+        Ok(())
+    }
+
+    /// Generates square pairs with the peer, to be drawn from the source
+    pub async fn generate_square_pairs(&mut self, num_pairs: usize) -> Result<(), MpcError> {
+        let pairs = self.random_square_pairs(num_pairs).await?;
+        self.square_pairs.extend(pairs);
+        Ok(())
+    }
+
+    /// Shares the products of random shared values a and b
+    ///
+    /// Each party multiplies its own shares, and the cross terms a_0 * b_1 + a_1 * b_0 are
+    /// shared as two products of a value of the king with a value of the peer
+    async fn random_triplets(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        let a = random_scalars(num_triplets);
+        let b = random_scalars(num_triplets);
+
+        // The king inputs (a_0, b_0) and the peer (b_1, a_1)
+        let inputs = if self.network.am_king() {
+            a.iter().chain(b.iter()).cloned().collect_vec()
+        } else {
+            b.iter().chain(a.iter()).cloned().collect_vec()
+        };
+        let products = self.share_products(&inputs).await?;
+        let (first, second) = products.split_at(num_triplets);
+
+        Ok(izip!(a, b, first, second)
+            .map(|(a, b, first, second)| (a, b, a * b + first + second))
+            .collect_vec())
+    }
+
+    /// Shares random bits as the XOR of a bit sampled by each party, b_0 + b_1 - 2 * b_0 * b_1
+    async fn random_bits(&mut self, num_bits: usize) -> Result<Vec<Scalar>, MpcError> {
+        let mut rng = OsRng {};
+        let bits = (0..num_bits)
+            .map(|_| Scalar::from(rng.next_u32() as u64 & 1))
+            .collect_vec();
+        let products = self.share_products(&bits).await?;
+
+        Ok(bits
+            .iter()
+            .zip(products.iter())
+            .map(|(bit, product)| bit - Scalar::from(2u64) * product)
+            .collect_vec())
+    }
+
+    /// Shares random values r and r^-1 by opening the product of r with a random mask s, then
+    /// sharing s * (r * s)^-1
+    async fn random_inverse_pairs(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let triplets = self.random_triplets(num_pairs).await?;
+        let product_shares = triplets.iter().map(|(_, _, c)| *c).collect_vec();
+        let peer_shares = self
+            .network
+            .broadcast_scalars(&product_shares)
+            .await
+            .map_err(MpcError::NetworkError)?;
+
+        Ok(izip!(triplets, peer_shares)
+            .map(|((r, s, c), peer_c)| (r, s * (c + peer_c).invert()))
+            .collect_vec())
+    }
+
+    /// Shares random values a and a^2; each party squares its own share, and the cross term
+    /// 2 * a_0 * a_1 is shared as a single product, half the cost of a triplet
+    async fn random_square_pairs(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        let a = random_scalars(num_pairs);
+        let products = self.share_products(&a).await?;
+
+        Ok(a.iter()
+            .zip(products.iter())
+            .map(|(a, product)| (*a, a * a + Scalar::from(2u64) * product))
+            .collect_vec())
+    }
+
+    /// Returns the king's key, generating it or receiving it from the king on first use
+    async fn key(&mut self) -> Result<PaillierKey, MpcError> {
+        if let Some(key) = self.key.as_ref() {
+            return Ok(key.clone());
+        }
+
+        let key = if self.network.am_king() {
+            let key = PaillierKey::generate(self.key_bits);
+            let num_limbs = num_limbs(&key.modulus);
+            self.network
+                .send_single_scalar(Scalar::from(num_limbs as u64))
+                .await
+                .map_err(MpcError::NetworkError)?;
+            self.network
+                .send_scalars(&pack(&key.modulus, num_limbs))
+                .await
+                .map_err(MpcError::NetworkError)?;
+
+            key
+        } else {
+            let num_limbs = self
+                .network
+                .receive_single_scalar()
+                .await
+                .map_err(MpcError::NetworkError)?;
+            let num_limbs = u64::from_le_bytes(num_limbs.as_bytes()[..8].try_into().unwrap());
+            if num_limbs > num_limbs_of_bits(MAX_KEY_BITS) as u64 {
+                return Err(MpcError::PreprocessingError(format!(
+                    "peer announced a modulus of {} limbs",
+                    num_limbs
+                )));
+            }
+
+            let limbs = self
+                .network
+                .receive_scalars(num_limbs as usize)
+                .await
+                .map_err(MpcError::NetworkError)?;
+            let modulus = unpack(&limbs)?;
+            if !(MIN_KEY_BITS..=MAX_KEY_BITS).contains(&modulus.bits()) {
+                return Err(MpcError::PreprocessingError(format!(
+                    "peer's Paillier modulus of {} bits is not between {} and {} bits",
+                    modulus.bits(),
+                    MIN_KEY_BITS,
+                    MAX_KEY_BITS
+                )));
+            }
+
+            PaillierKey::from_modulus(modulus)
+        };
+
+        self.key = Some(key.clone());
+        Ok(key)
+    }
+
+    /// Shares the product of each local value with the peer's value at the same index
+    ///
+    /// The king sends its values encrypted, the peer returns encryptions of the masked products
+    async fn share_products(&mut self, values: &[Scalar]) -> Result<Vec<Scalar>, MpcError> {
+        let key = self.key().await?;
+        let num_limbs = key.ciphertext_limbs();
+
+        if self.network.am_king() {
+            let ciphertexts = values
+                .iter()
+                .flat_map(|value| pack(&key.encrypt(&scalar_to_biguint(value)), num_limbs))
+                .collect_vec();
+            self.network
+                .send_scalars(&ciphertexts)
+                .await
+                .map_err(MpcError::NetworkError)?;
+
+            let products = self
+                .network
+                .receive_scalars(values.len() * num_limbs)
+                .await
+                .map_err(MpcError::NetworkError)?;
+            products
+                .chunks(num_limbs)
+                .map(|limbs| Ok(biguint_to_scalar(&key.decrypt(&unpack(limbs)?))))
+                .collect()
+        } else {
+            let ciphertexts = self
+                .network
+                .receive_scalars(values.len() * num_limbs)
+                .await
+                .map_err(MpcError::NetworkError)?;
+
+            let mut rng = rand::rngs::OsRng;
+            let mut shares = Vec::with_capacity(values.len());
+            let mut products = Vec::with_capacity(values.len() * num_limbs);
+            for (value, limbs) in values.iter().zip(ciphertexts.chunks(num_limbs)) {
+                let mask = rng.gen_biguint(MASK_BITS);
+                let product = unpack(limbs)?
+                    .modpow(&scalar_to_biguint(value), &key.modulus_squared)
+                    * key.encrypt(&mask)
+                    % &key.modulus_squared;
+
+                products.extend(pack(&product, num_limbs));
+                shares.push(-biguint_to_scalar(&mask));
+            }
+
+            self.network
+                .send_scalars(&products)
+                .await
+                .map_err(MpcError::NetworkError)?;
+            Ok(shares)
+        }
+    }
+}
+
+/// Samples the local shares of random shared values
+fn random_scalars(num_scalars: usize) -> Vec<Scalar> {
+    let mut rng = OsRng {};
+    (0..num_scalars)
+        .map(|_| Scalar::random(&mut rng))
+        .collect_vec()
+}
+
+impl<N: MpcNetwork + Send> SharedValueSource<Scalar> for PaillierTripleGenerator<N> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.next_shared_bit_batch(1)[0]
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        if self.bits.len() < num_values {
+            let num_missing = usize::max(num_values - self.bits.len(), self.batch_size);
+            block_on(self.generate_bits(num_missing)).expect("failed to generate shared bits");
+        }
+
+        self.bits.drain(..num_values).collect_vec()
+    }
+
+    /// Each party samples its share locally, no communication is needed
+    fn next_shared_value(&mut self) -> Scalar {
+        Scalar::random(&mut OsRng {})
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_shared_invers_pair_batch(1)[0]
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        if self.inverse_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.inverse_pairs.len(), self.batch_size);
+            block_on(self.generate_inverse_pairs(num_missing))
+                .expect("failed to generate inverse pairs");
+        }
+
+        self.inverse_pairs.drain(..num_pairs).collect_vec()
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.next_square_pair_batch(1)[0]
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        if self.square_pairs.len() < num_pairs {
+            let num_missing = usize::max(num_pairs - self.square_pairs.len(), self.batch_size);
+            block_on(self.generate_square_pairs(num_missing))
+                .expect("failed to generate square pairs");
+        }
+
+        self.square_pairs.drain(..num_pairs).collect_vec()
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.next_triplet_batch(1)[0]
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        if self.triplets.len() < num_triplets {
+            let num_missing = usize::max(num_triplets - self.triplets.len(), self.batch_size);
+            block_on(self.generate_triplets(num_missing)).expect("failed to generate triplets");
+        }
+
+        self.triplets.drain(..num_triplets).collect_vec()
+    }
+
+    /// The number of triplets generated and not yet drawn; the source generates more on demand
+    /// once they run out
+    fn remaining(&self) -> Option<usize> {
+        Some(self.triplets.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{beaver::SharedValueSource, network::local_network::LocalTwoPartyNet};
+
+    use super::{PaillierTripleGenerator, MIN_KEY_BITS};
+
+    /// The triplet, shared bit, inverse pair, and square pair drawn by a party
+    type PartyValues = (
+        (Scalar, Scalar, Scalar),
+        Scalar,
+        (Scalar, Scalar),
+        (Scalar, Scalar),
+    );
+
+    /// Draws one of each value from the party's generator, generating the triplet ahead of time
+    /// and the rest on demand
+    fn run_party(network: LocalTwoPartyNet) -> PartyValues {
+        // A key of the minimum size keeps the test fast
+        let mut generator = PaillierTripleGenerator::new(network)
+            .with_batch_size(2)
+            .with_key_bits(MIN_KEY_BITS);
+        block_on(generator.generate_triplets(1)).unwrap();
+        assert_eq!(generator.remaining(), Some(1));
+
+        let triplet = generator.next_triplet();
+        let bit = generator.next_shared_bit();
+        let inverse_pair = generator.next_shared_inverse_pair();
+        let square_pair = generator.next_square_pair();
+        (triplet, bit, inverse_pair, square_pair)
+    }
+
+    #[test]
+    fn test_paillier_triplets() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_party(network0));
+        let party1 = thread::spawn(move || run_party(network1));
+        let (triplet0, bit0, pair0, square0) = party0.join().unwrap();
+        let (triplet1, bit1, pair1, square1) = party1.join().unwrap();
+
+        let a = triplet0.0 + triplet1.0;
+        let b = triplet0.1 + triplet1.1;
+        let c = triplet0.2 + triplet1.2;
+        assert_eq!(a * b, c);
+
+        let bit = bit0 + bit1;
+        assert!(bit == Scalar::zero() || bit == Scalar::one());
+
+        assert_eq!((pair0.0 + pair1.0) * (pair0.1 + pair1.1), Scalar::one());
+
+        let value = square0.0 + square1.0;
+        assert_eq!(value * value, square0.1 + square1.1);
+    }
+}
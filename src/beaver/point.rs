@@ -0,0 +1,226 @@
+//! Implements a value source wrapper that precomputes point triplets (a, bG, cG), so that the
+//! products of shared scalars and shared points skip the base point multiplications online
+
+use std::collections::VecDeque;
+
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_TABLE, scalar::Scalar};
+
+use crate::{error::MpcError, macros};
+
+use super::{PointTriplet, SharedValueSource};
+
+/// Derives a point triplet from a party's shares of a Beaver triplet; as the base point
+/// multiplication is linear, the shares of bG and cG are bG and cG for the shares b and c
+pub(crate) fn to_point_triplet((a, b, c): (Scalar, Scalar, Scalar)) -> PointTriplet<Scalar> {
+    (
+        a,
+        &RISTRETTO_BASEPOINT_TABLE * &b,
+        &RISTRETTO_BASEPOINT_TABLE * &c,
+    )
+}
+
+/// Wraps a value source and holds point triplets derived from its Beaver triplets ahead of the
+/// online phase
+///
+/// Point triplets are only computed by `precompute`, e.g. during the offline phase; a batch of
+/// products larger than the precomputed point triplets is served from the wrapped source's
+/// Beaver triplets instead, as if the wrapper were not there. Both parties must precompute the
+/// same numbers of point triplets at the same points of the protocol, so that they serve each
+/// batch the same way
+#[derive(Debug)]
+pub struct PointTripletSource<S> {
+    /// The source of every value other than point triplets
+    inner: S,
+    /// The precomputed point triplets
+    point_triplets: VecDeque<PointTriplet<Scalar>>,
+}
+
+impl<S: SharedValueSource<Scalar>> PointTripletSource<S> {
+    /// Wraps the source, holding no point triplets until they are precomputed
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            point_triplets: VecDeque::new(),
+        }
+    }
+
+    /// Draws `num_triplets` Beaver triplets from the wrapped source and derives point triplets
+    /// from them
+    pub fn precompute(&mut self, num_triplets: usize) -> Result<(), MpcError> {
+        let triplets = self.inner.try_next_triplet_batch(num_triplets)?;
+        self.point_triplets
+            .extend(triplets.into_iter().map(to_point_triplet));
+
+        Ok(())
+    }
+
+    /// Returns the number of precomputed point triplets
+    pub fn num_point_triplets(&self) -> usize {
+        self.point_triplets.len()
+    }
+
+    /// Borrow the wrapped source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Mutably borrow the wrapped source, e.g. to refill it
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+impl<S: SharedValueSource<Scalar>> SharedValueSource<Scalar> for PointTripletSource<S> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.inner.next_shared_bit()
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.inner.next_shared_bit_batch(num_values)
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        self.inner.try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        self.inner.next_shared_value()
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.inner.next_shared_inverse_pair()
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.inner.next_shared_invers_pair_batch(num_pairs)
+    }
+
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.inner.try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.inner.next_square_pair()
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.inner.next_square_pair_batch(num_pairs)
+    }
+
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.inner.try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.inner.next_triplet()
+    }
+
+    fn next_triplet_batch(&mut self, num_triplets: usize) -> Vec<(Scalar, Scalar, Scalar)> {
+        self.inner.next_triplet_batch(num_triplets)
+    }
+
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        self.inner.try_next_triplet_batch(num_triplets)
+    }
+
+    macros::impl_forwarded_correlations!(Scalar, |this| this.inner);
+
+    /// Serves the batch only if enough point triplets are precomputed, so that a batch is never
+    /// split between precomputed and derived point triplets
+    fn next_point_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Option<Vec<PointTriplet<Scalar>>> {
+        if self.point_triplets.len() < num_triplets {
+            return None;
+        }
+
+        Some(self.point_triplets.drain(..num_triplets).collect())
+    }
+
+    /// The Beaver triplets left in the wrapped source, not counting the point triplets
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+
+    /// The position of the wrapped source, which counts the triplets the precomputed point
+    /// triplets were derived from as consumed; an error while any point triplets are held, as
+    /// a restart from the position would lose them
+    fn position(&self) -> Result<u64, MpcError> {
+        if !self.point_triplets.is_empty() {
+            return Err(MpcError::PreprocessingError(
+                "precomputed point triplets cannot be checkpointed".to_string(),
+            ));
+        }
+
+        self.inner.position()
+    }
+
+    /// Seeks the wrapped source and discards the precomputed point triplets
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        self.inner.seek(pos)?;
+        self.point_triplets.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+    use futures::executor::block_on;
+
+    use crate::{beaver::DealtFiles, fabric::AuthenticatedMpcFabric, network::MpcNetwork};
+
+    use super::PointTripletSource;
+
+    #[test]
+    fn test_point_triplets() {
+        let files = DealtFiles::new("point", 4, 0, 0, 0);
+        let products = files.run_parties(|source, network| {
+            // Authenticating the point takes one point triplet and the product takes one for the
+            // value and one for the MAC; authenticating the scalar takes a Beaver triplet
+            let mut source = PointTripletSource::new(source);
+            source.precompute(3).unwrap();
+
+            let party_id = network.party_id();
+            let network = Rc::new(RefCell::new(network));
+            let fabric = AuthenticatedMpcFabric::new_with_network(
+                party_id,
+                network.clone(),
+                Rc::new(RefCell::new(source)),
+            );
+            let scalar = fabric
+                .allocate_private_scalar(0 /* owning_party */, Scalar::from(3u64))
+                .unwrap();
+            let point = fabric
+                .allocate_private_ristretto(
+                    1, /* owning_party */
+                    RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64),
+                )
+                .unwrap();
+            let product = (&scalar * &point)
+                .open_and_authenticate()
+                .unwrap()
+                .to_ristretto();
+
+            assert_eq!(fabric.borrow_beaver_source().num_point_triplets(), 0);
+            block_on(network.borrow_mut().close()).unwrap();
+            product
+        });
+
+        for product in products {
+            assert_eq!(product, RISTRETTO_BASEPOINT_POINT * Scalar::from(15u64));
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Implements a pool of Beaver triplets that many worker threads draw from concurrently, e.g.
+//! to evaluate independent parts of a circuit in parallel
+
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use concurrent_queue::ConcurrentQueue;
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{error::MpcError, macros};
+
+use super::SharedValueSource;
+
+/// A hook invoked with the number of triplets left in the pool to produce more triplets
+pub type TripletRefillHook =
+    Box<dyn FnMut(usize) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> + Send>;
+
+/// The state shared by every handle to a pool
+struct PoolState<S> {
+    /// The triplets in the pool
+    triplets: ConcurrentQueue<(Scalar, Scalar, Scalar)>,
+    /// The number of triplets at or below which the pool is refilled, and the hook that
+    /// refills it
+    refill: Mutex<Option<(usize, TripletRefillHook)>>,
+    /// The ID of the next batch to be reserved; held while a batch is drawn, so that batches are
+    /// drawn one at a time
+    next_batch: Mutex<u64>,
+    /// Signalled when a batch is reserved
+    batch_reserved: Condvar,
+    /// The source of every value other than triplets, shared with the pools reserved from this
+    /// one
+    inner: Arc<Mutex<S>>,
+}
+
+/// A pool of Beaver triplets shared between threads
+///
+/// Every clone of the pool is a handle to the same triplets, so each worker thread draws from
+/// its own clone; triplets are popped from a lock-free queue, and only the refill and the values
+/// other than triplets, which are drawn from the wrapped source, take a lock. When the pool
+/// drops to the refill threshold the drawing thread tops it up with the refill hook, and a
+/// thread that finds the pool empty waits for a refill.
+///
+/// Each party's pool hands triplets out in the order its threads draw them, and each batch drawn
+/// at once is contiguous; the order in which concurrent workers draw differs between the
+/// parties' pools, so the two shares of a multiplication would come from different triplets.
+/// Concurrent workers instead reserve their triplets with `reserve`, under batch IDs fixed by
+/// the protocol: the batches are handed out in the order of their IDs on both parties, however
+/// the threads are scheduled, and each worker draws from its own reservation
+pub struct SharedTriplePool<S> {
+    /// The state shared with the other handles to the pool
+    state: Arc<PoolState<S>>,
+}
+
+impl<S> Clone for SharedTriplePool<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S> SharedTriplePool<S> {
+    /// Create an empty pool, drawing the values other than triplets from `inner`
+    pub fn new(inner: S) -> Self {
+        Self {
+            state: Arc::new(PoolState {
+                triplets: ConcurrentQueue::unbounded(),
+                refill: Mutex::new(None),
+                next_batch: Mutex::new(0),
+                batch_reserved: Condvar::new(),
+                inner: Arc::new(Mutex::new(inner)),
+            }),
+        }
+    }
+
+    /// Reserves the next `num_triplets` triplets for the batch with the given ID, returning a
+    /// pool that holds only those triplets and draws the values other than triplets from the
+    /// same source as this pool
+    ///
+    /// Batch IDs count up from zero and each is reserved once; a reservation waits until the
+    /// batches with lower IDs are reserved, so each batch receives the same triplets on both
+    /// parties. Returns an error if the batch was already reserved, or if the pool is exhausted
+    /// and cannot be refilled, in which case the batch is skipped
+    pub fn reserve(&self, batch_id: u64, num_triplets: usize) -> Result<Self, MpcError> {
+        let mut next_batch = self.state.next_batch.lock().unwrap();
+        while *next_batch < batch_id {
+            next_batch = self.state.batch_reserved.wait(next_batch).unwrap();
+        }
+        if *next_batch > batch_id {
+            return Err(MpcError::PreprocessingError(format!(
+                "batch {} was already reserved",
+                batch_id
+            )));
+        }
+
+        let triplets = (0..num_triplets)
+            .map(|_| self.try_next_triplet())
+            .collect::<Result<Vec<_>, _>>();
+        *next_batch += 1;
+        self.state.batch_reserved.notify_all();
+
+        let reserved = Self {
+            state: Arc::new(PoolState {
+                triplets: ConcurrentQueue::unbounded(),
+                refill: Mutex::new(None),
+                next_batch: Mutex::new(0),
+                batch_reserved: Condvar::new(),
+                inner: self.state.inner.clone(),
+            }),
+        };
+        reserved.push_triplets(triplets?);
+        Ok(reserved)
+    }
+
+    /// Refill the pool with `hook` whenever it holds `threshold` or fewer triplets, replacing
+    /// any previously registered hook
+    pub fn with_refill(self, threshold: usize, hook: TripletRefillHook) -> Self {
+        *self.state.refill.lock().unwrap() = Some((threshold, hook));
+        self
+    }
+
+    /// Add triplets to the pool
+    pub fn push_triplets(&self, triplets: Vec<(Scalar, Scalar, Scalar)>) {
+        for triplet in triplets {
+            // The queue is unbounded and never closed
+            self.state.triplets.push(triplet).unwrap();
+        }
+    }
+
+    /// Returns the number of triplets in the pool
+    pub fn num_triplets(&self) -> usize {
+        self.state.triplets.len()
+    }
+
+    /// Draw a triplet, refilling the pool if it is empty or has dropped to the threshold
+    fn try_next_triplet(&self) -> Result<(Scalar, Scalar, Scalar), MpcError> {
+        loop {
+            if let Ok(triplet) = self.state.triplets.pop() {
+                // Top up without blocking; if another thread is refilling it will cover this
+                // draw, and a failed top up is retried by the next draw
+                if let Ok(mut refill) = self.state.refill.try_lock() {
+                    let _ = self.refill_below_threshold(&mut refill);
+                }
+                return Ok(triplet);
+            }
+
+            // The pool is empty, wait for the refill lock and check whether another thread
+            // refilled the pool in the meantime
+            let mut refill = self.state.refill.lock().unwrap();
+            if !self.state.triplets.is_empty() {
+                continue;
+            }
+
+            self.refill_below_threshold(&mut refill)?;
+            if self.state.triplets.is_empty() {
+                return Err(MpcError::PreprocessingExhausted(
+                    "the triplet pool is empty and could not be refilled".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Invoke the refill hook if the pool holds no more than the threshold
+    fn refill_below_threshold(
+        &self,
+        refill: &mut Option<(usize, TripletRefillHook)>,
+    ) -> Result<(), MpcError> {
+        if let Some((threshold, hook)) = refill.as_mut() {
+            let num_triplets = self.num_triplets();
+            if num_triplets <= *threshold {
+                self.push_triplets(hook(num_triplets)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: Debug> Debug for SharedTriplePool<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SharedTriplePool")
+            .field("inner", &self.state.inner)
+            .field("num_triplets", &self.num_triplets())
+            .finish()
+    }
+}
+
+impl<S: SharedValueSource<Scalar>> SharedValueSource<Scalar> for SharedTriplePool<S> {
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.state.inner.lock().unwrap().next_shared_bit()
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        self.state
+            .inner
+            .lock()
+            .unwrap()
+            .try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        self.state.inner.lock().unwrap().next_shared_value()
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.state.inner.lock().unwrap().next_shared_inverse_pair()
+    }
+
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.state
+            .inner
+            .lock()
+            .unwrap()
+            .try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.state.inner.lock().unwrap().next_square_pair()
+    }
+
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.state
+            .inner
+            .lock()
+            .unwrap()
+            .try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        let _next_batch = self.state.next_batch.lock().unwrap();
+        self.try_next_triplet()
+            .unwrap_or_else(|err| panic!("failed to draw a triplet from the pool: {}", err))
+    }
+
+    /// The batch is drawn at once, so it is not interleaved with the draws of other batches;
+    /// triplets drawn before an error are consumed
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        let _next_batch = self.state.next_batch.lock().unwrap();
+        (0..num_triplets).map(|_| self.try_next_triplet()).collect()
+    }
+
+    macros::impl_forwarded_correlations!(Scalar, |this| this.state.inner.lock().unwrap());
+
+    /// The triplets in the pool, or `None` if a refill hook is registered
+    fn remaining(&self) -> Option<usize> {
+        match *self.state.refill.lock().unwrap() {
+            Some(_) => None,
+            None => Some(self.num_triplets()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use curve25519_dalek::scalar::Scalar;
+    use itertools::Itertools;
+
+    use crate::{
+        beaver::{BeaverShares, DummySharedScalarSource, SharedValueSource},
+        error::MpcError,
+        mpc_scalar::MpcScalar,
+        network::local_network::LocalTwoPartyNet,
+    };
+
+    use super::SharedTriplePool;
+
+    /// The number of workers each party runs concurrently
+    const NUM_WORKERS: u64 = 4;
+
+    #[test]
+    fn test_triple_pool() {
+        // The hook produces triplets counting up from zero, so each triplet is drawn once
+        let num_produced = Arc::new(AtomicU64::new(0));
+        let hook_num_produced = num_produced.clone();
+        let pool = SharedTriplePool::new(DummySharedScalarSource::new()).with_refill(
+            8, /* threshold */
+            Box::new(move |_| {
+                Ok((0..16)
+                    .map(|_| {
+                        let index = Scalar::from(hook_num_produced.fetch_add(1, Ordering::SeqCst));
+                        (index, index, index)
+                    })
+                    .collect_vec())
+            }),
+        );
+
+        let workers = (0..4)
+            .map(|_| {
+                let mut pool = pool.clone();
+                thread::spawn(move || (0..100).map(|_| pool.next_triplet().0).collect_vec())
+            })
+            .collect_vec();
+        let mut drawn = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .map(|index| index.as_bytes().to_vec())
+            .collect_vec();
+        drawn.sort();
+        drawn.dedup();
+        assert_eq!(drawn.len(), 400);
+        assert_eq!(
+            pool.num_triplets() as u64 + 400,
+            num_produced.load(Ordering::SeqCst)
+        );
+
+        // Without a hook the pool is exhausted once its triplets are drawn
+        let mut pool = SharedTriplePool::new(DummySharedScalarSource::new());
+        pool.push_triplets(vec![(Scalar::one(), Scalar::one(), Scalar::one()); 2]);
+        assert_eq!(pool.remaining(), Some(2));
+        assert!(pool.try_next_triplet_batch(2).is_ok());
+        assert!(matches!(
+            pool.try_next_triplet_batch(1),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+    }
+
+    /// Runs the party's workers concurrently, each reserving the triplets of the batch with its
+    /// ID after a delay, and squaring then cubing its ID with the peer's worker of the same ID
+    fn run_workers(
+        pool: SharedTriplePool<DummySharedScalarSource>,
+        networks: Vec<LocalTwoPartyNet>,
+        delay: impl Fn(u64) -> Duration,
+    ) -> Vec<Scalar> {
+        let workers = networks
+            .into_iter()
+            .enumerate()
+            .map(|(worker_id, network)| {
+                let worker_id = worker_id as u64;
+                let pool = pool.clone();
+                let delay = delay(worker_id);
+                thread::spawn(move || {
+                    thread::sleep(delay);
+                    let reserved = pool.reserve(worker_id, 2 /* num_triplets */).unwrap();
+
+                    let network = Rc::new(RefCell::new(network));
+                    let beaver_source = Rc::new(RefCell::new(reserved));
+                    let value = MpcScalar::from_private_u64(worker_id + 2, network, beaver_source)
+                        .share_secret(0 /* party_id */)
+                        .unwrap();
+                    (&(&value * &value) * &value).open().unwrap().value()
+                })
+            })
+            .collect_vec();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_reserved_batches() {
+        let (shares0, shares1) = BeaverShares::deal(2 * NUM_WORKERS as usize, 0, 0, 0);
+        let pool0 = SharedTriplePool::new(DummySharedScalarSource::new());
+        pool0.push_triplets(shares0.triplets);
+        let pool1 = SharedTriplePool::new(DummySharedScalarSource::new());
+        pool1.push_triplets(shares1.triplets);
+
+        let (networks0, networks1): (Vec<_>, Vec<_>) = (0..NUM_WORKERS)
+            .map(|_| LocalTwoPartyNet::new_pair())
+            .unzip();
+
+        // The parties' workers arrive in opposite orders
+        let party0 = thread::spawn(move || {
+            run_workers(pool0, networks0, |worker_id| {
+                Duration::from_millis(10 * worker_id)
+            })
+        });
+        let party1 = thread::spawn(move || {
+            run_workers(pool1, networks1, |worker_id| {
+                Duration::from_millis(10 * (NUM_WORKERS - worker_id))
+            })
+        });
+
+        let expected = (0..NUM_WORKERS)
+            .map(|worker_id| Scalar::from((worker_id + 2).pow(3)))
+            .collect_vec();
+        assert_eq!(party0.join().unwrap(), expected);
+        assert_eq!(party1.join().unwrap(), expected);
+
+        // A batch is reserved once
+        let pool = SharedTriplePool::new(DummySharedScalarSource::new());
+        pool.reserve(0 /* batch_id */, 0 /* num_triplets */)
+            .unwrap();
+        assert!(matches!(
+            pool.reserve(0 /* batch_id */, 0 /* num_triplets */),
+            Err(MpcError::PreprocessingError(_))
+        ));
+    }
+}
@@ -0,0 +1,409 @@
+//! Implements a value source wrapper that draws triplets ahead of time on a background thread,
+//! so that multiplications in the online phase rarely wait on the underlying source
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    thread,
+};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{error::MpcError, macros};
+
+use super::{AuthenticatedTriplet, PointTriplet, SharedValueSource};
+
+/// A prefetched triplet
+#[derive(Clone, Copy, Debug)]
+enum Prefetched {
+    /// A triplet read ahead of the underlying source, consumed only once it is used
+    Peeked((Scalar, Scalar, Scalar)),
+    /// A triplet drawn from an underlying source that cannot read ahead
+    Drawn((Scalar, Scalar, Scalar)),
+}
+
+/// The queue of prefetched triplets, shared with the background thread
+#[derive(Debug, Default)]
+struct Queue {
+    /// The prefetched triplets, in the order of the underlying source
+    triplets: VecDeque<Prefetched>,
+    /// Whether the background thread is still prefetching
+    running: bool,
+}
+
+/// Marks the queue as stopped when the background thread exits, including by a panic of the
+/// underlying source
+struct StopGuard(Arc<(Mutex<Queue>, Condvar)>);
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        let (queue, changed) = &*self.0;
+        queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .running = false;
+        changed.notify_all();
+    }
+}
+
+/// Wraps a value source and keeps a bounded queue of its triplets filled from a background
+/// thread
+///
+/// Only triplets are prefetched; the other values are drawn from the underlying source on
+/// demand. If the underlying source can read ahead, e.g. a `FileBeaverSource`, the background
+/// thread reads triplets without consuming them and each is consumed only once it is drawn
+/// from the wrapper, so the source records exactly the triplets used. Otherwise the background
+/// thread draws triplets concurrently with, and in a different order to, the other draws of the
+/// local party, so the underlying source must not depend on the order of its draws matching the
+/// peer's; e.g. not a source that generates values with the peer. Once the underlying source
+/// is exhausted, triplets are drawn from it directly after the queue empties, so the fallible
+/// draws return its error; if it panics, drawing a triplet from the wrapper panics once the
+/// queue is empty
+#[derive(Debug)]
+pub struct PrefetchingBeaverSource<S> {
+    /// The underlying source, shared with the background thread
+    inner: Arc<Mutex<S>>,
+    /// The queue of prefetched triplets and the condition signalled when it changes
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+}
+
+impl<S: SharedValueSource<Scalar> + Send + 'static> PrefetchingBeaverSource<S> {
+    /// Wraps the source and starts prefetching up to `capacity` of its triplets
+    pub fn new(inner: S, capacity: usize) -> Self {
+        let inner = Arc::new(Mutex::new(inner));
+        let queue = Arc::new((
+            Mutex::new(Queue {
+                triplets: VecDeque::with_capacity(capacity),
+                running: true,
+            }),
+            Condvar::new(),
+        ));
+
+        let thread_inner = inner.clone();
+        let thread_queue = queue.clone();
+        thread::spawn(move || {
+            let _guard = StopGuard(thread_queue.clone());
+            let (queue, changed) = &*thread_queue;
+            let mut peeked_before = false;
+            loop {
+                // Only this thread adds to the queue, so waiting for room bounds it exactly
+                let running = changed
+                    .wait_while(queue.lock().unwrap(), |queue| {
+                        queue.running && queue.triplets.len() >= capacity
+                    })
+                    .unwrap()
+                    .running;
+                if !running {
+                    return;
+                }
+
+                // The queue is read under the lock of the underlying source, so that the
+                // triplets read ahead of it are not consumed concurrently
+                let mut inner = thread_inner.lock().unwrap();
+                let skip = queue.lock().unwrap().triplets.len();
+                let triplet = match inner.peek_triplet_batch(skip, 1) {
+                    Some(triplets) => {
+                        peeked_before = true;
+                        Prefetched::Peeked(triplets[0])
+                    }
+                    // The source is exhausted past the triplets already read ahead
+                    None if peeked_before => return,
+                    // An exhausted source surfaces its error once the queue is drawn down
+                    None => match inner.try_next_triplet_batch(1) {
+                        Ok(triplets) => Prefetched::Drawn(triplets[0]),
+                        Err(_) => return,
+                    },
+                };
+
+                queue.lock().unwrap().triplets.push_back(triplet);
+                changed.notify_all();
+            }
+        });
+
+        Self { inner, queue }
+    }
+
+    /// Returns the number of prefetched triplets waiting in the queue
+    pub fn num_queued(&self) -> usize {
+        self.queue.0.lock().unwrap().triplets.len()
+    }
+}
+
+impl<S: SharedValueSource<Scalar>> PrefetchingBeaverSource<S> {
+    /// Draws the next triplet from the queue, or from the underlying source once the
+    /// background thread has stopped and the queue is empty
+    fn try_next_triplet(&mut self) -> Result<(Scalar, Scalar, Scalar), MpcError> {
+        let (queue, changed) = &*self.queue;
+        loop {
+            // Lock the underlying source before the queue, as the background thread does
+            let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut locked = queue.lock().unwrap();
+            if let Some(triplet) = locked.triplets.pop_front() {
+                changed.notify_all();
+                return match triplet {
+                    Prefetched::Peeked(triplet) => inner.skip_triplets(1).map(|_| triplet),
+                    Prefetched::Drawn(triplet) => Ok(triplet),
+                };
+            }
+
+            if !locked.running {
+                assert!(
+                    !self.inner.is_poisoned(),
+                    "the prefetching thread stopped, the underlying source panicked"
+                );
+                drop(locked);
+                return Ok(inner.try_next_triplet_batch(1)?[0]);
+            }
+
+            // Release the underlying source for the background thread while waiting
+            drop(inner);
+            drop(
+                changed
+                    .wait_while(locked, |queue| queue.running && queue.triplets.is_empty())
+                    .unwrap(),
+            );
+        }
+    }
+}
+
+impl<S> Drop for PrefetchingBeaverSource<S> {
+    /// Stops the background thread; the triplets it read ahead are left in the underlying source
+    fn drop(&mut self) {
+        let (queue, changed) = &*self.queue;
+        if let Ok(mut queue) = queue.lock() {
+            queue.running = false;
+        }
+        changed.notify_all();
+    }
+}
+
+impl<S: SharedValueSource<Scalar> + Send + 'static> SharedValueSource<Scalar>
+    for PrefetchingBeaverSource<S>
+{
+    fn next_shared_bit(&mut self) -> Scalar {
+        self.inner.lock().unwrap().next_shared_bit()
+    }
+
+    fn next_shared_bit_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.inner.lock().unwrap().next_shared_bit_batch(num_values)
+    }
+
+    fn try_next_shared_bit_batch(&mut self, num_values: usize) -> Result<Vec<Scalar>, MpcError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .try_next_shared_bit_batch(num_values)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        self.inner.lock().unwrap().next_shared_value()
+    }
+
+    fn next_shared_value_batch(&mut self, num_values: usize) -> Vec<Scalar> {
+        self.inner
+            .lock()
+            .unwrap()
+            .next_shared_value_batch(num_values)
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        self.inner.lock().unwrap().next_shared_inverse_pair()
+    }
+
+    fn next_shared_invers_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .next_shared_invers_pair_batch(num_pairs)
+    }
+
+    fn try_next_inverse_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .try_next_inverse_pair_batch(num_pairs)
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        self.inner.lock().unwrap().next_square_pair()
+    }
+
+    fn next_square_pair_batch(&mut self, num_pairs: usize) -> Vec<(Scalar, Scalar)> {
+        self.inner.lock().unwrap().next_square_pair_batch(num_pairs)
+    }
+
+    fn try_next_square_pair_batch(
+        &mut self,
+        num_pairs: usize,
+    ) -> Result<Vec<(Scalar, Scalar)>, MpcError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .try_next_square_pair_batch(num_pairs)
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        self.try_next_triplet()
+            .unwrap_or_else(|err| panic!("failed to draw a triplet: {}", err))
+    }
+
+    /// Triplets drawn before an error are consumed
+    fn try_next_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+        (0..num_triplets).map(|_| self.try_next_triplet()).collect()
+    }
+
+    fn next_point_triplet_batch(
+        &mut self,
+        num_triplets: usize,
+    ) -> Option<Vec<PointTriplet<Scalar>>> {
+        self.inner
+            .lock()
+            .unwrap()
+            .next_point_triplet_batch(num_triplets)
+    }
+
+    fn next_authenticated_triplet(&mut self) -> Result<AuthenticatedTriplet<Scalar>, MpcError> {
+        self.inner.lock().unwrap().next_authenticated_triplet()
+    }
+
+    fn mac_key_share(&self) -> Option<Scalar> {
+        self.inner.lock().unwrap().mac_key_share()
+    }
+
+    /// The position of the underlying source; triplets read ahead are not yet consumed from
+    /// it, but a source that cannot read ahead has triplets drawn into the queue that the
+    /// position does not account for, so its position is an error while any are queued
+    fn position(&self) -> Result<u64, MpcError> {
+        let inner = self.inner.lock().unwrap();
+        let queue = self.queue.0.lock().unwrap();
+        if queue
+            .triplets
+            .iter()
+            .any(|triplet| matches!(triplet, Prefetched::Drawn(_)))
+        {
+            return Err(MpcError::PreprocessingError(
+                "triplets drawn ahead of the underlying source cannot be checkpointed".to_string(),
+            ));
+        }
+
+        inner.position()
+    }
+
+    /// Seeks the underlying source and discards the queue, which the background thread then
+    /// refills from the new position
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        let (queue, changed) = &*self.queue;
+        let mut inner = self.inner.lock().unwrap();
+        let mut locked = queue.lock().unwrap();
+        locked.triplets.clear();
+        inner.seek(pos)?;
+        changed.notify_all();
+        Ok(())
+    }
+
+    macros::impl_forwarded_correlations!(Scalar, |this| this.inner.lock().unwrap());
+
+    /// The triplets left in the underlying source, plus those drawn from it into the queue
+    fn remaining(&self) -> Option<usize> {
+        let remaining = self.inner.lock().unwrap().remaining()?;
+        let num_drawn = self
+            .queue
+            .0
+            .lock()
+            .unwrap()
+            .triplets
+            .iter()
+            .filter(|triplet| matches!(triplet, Prefetched::Drawn(_)))
+            .count();
+
+        Some(remaining + num_drawn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::{CountingSharedScalarSource, DealtFiles, SharedValueSource},
+        error::MpcError,
+    };
+
+    use super::PrefetchingBeaverSource;
+
+    #[test]
+    fn test_prefetching() {
+        let num_drawn = Arc::new(AtomicUsize::new(0));
+        let mut source = PrefetchingBeaverSource::new(
+            CountingSharedScalarSource {
+                num_drawn: num_drawn.clone(),
+                ..Default::default()
+            },
+            4, /* capacity */
+        );
+
+        // The queue fills to exactly its capacity, the thread then waits for room
+        while source.num_queued() < 4 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(source.num_queued(), 4);
+        assert_eq!(num_drawn.load(Ordering::SeqCst), 4);
+
+        // Triplets are drawn in the order of the underlying source
+        for i in 0..10u64 {
+            assert_eq!(source.next_triplet().0, Scalar::from(i));
+        }
+        assert_eq!(source.next_shared_bit(), Scalar::one());
+    }
+
+    #[test]
+    fn test_prefetching_file() {
+        let files = DealtFiles::new("prefetch", 6, 0, 0, 0);
+        let shares = files.shares(0);
+
+        // Triplets read ahead into the queue are not recorded as consumed
+        let mut source = PrefetchingBeaverSource::new(files.open(0), 4 /* capacity */);
+        while source.num_queued() < 4 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(source.remaining(), Some(6));
+        assert_eq!(source.next_triplet(), shares.triplets[0]);
+        assert_eq!(source.next_triplet(), shares.triplets[1]);
+        assert_eq!(source.remaining(), Some(4));
+
+        // A reopened file resumes after the triplets drawn from the wrapper
+        drop(source);
+        let mut source = PrefetchingBeaverSource::new(files.open(0), 4 /* capacity */);
+        let position = source.position().unwrap();
+        for triplet in shares.triplets[2..].iter() {
+            assert_eq!(source.next_triplet(), *triplet);
+        }
+        assert_eq!(source.remaining(), Some(0));
+
+        // An exhausted source surfaces its error rather than panicking
+        assert!(matches!(
+            source.try_next_triplet_batch(1),
+            Err(MpcError::PreprocessingExhausted(_))
+        ));
+
+        // Seeking back to a checkpoint replays the triplets after it
+        source.seek(position).unwrap();
+        assert_eq!(source.next_triplet(), shares.triplets[2]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Implements the sacrifice of Beaver triplets, which checks the triplets of an untrusted source
+//! before they are used
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::{izip, Itertools};
+
+use crate::{
+    commitment::PedersenCommitment,
+    error::MpcError,
+    network::{MpcNetwork, Payload},
+};
+
+/// Verifies the first half of the candidate triplets by sacrificing the second half
+///
+/// Each triplet (a, b, c) is paired with a triplet (f, g, h), and the parties open
+/// rho = r * a - f and sigma = b - g for a challenge r that they sample jointly after the
+/// triplets are fixed. If both triplets are valid then
+/// r * c - h - sigma * f - rho * g - sigma * rho = 0, and otherwise the check passes with
+/// probability 1/|F|; the opened values reveal nothing about a and b.
+///
+/// Returns the verified triplets, or `MpcError::CheatingError` if any check fails. The openings
+/// are not authenticated, so the check protects against a malicious source of triplets but not
+/// against a peer that deviates from the protocol
+pub async fn verify_triples<N: MpcNetwork + Send>(
+    network: &mut N,
+    candidates: &[(Scalar, Scalar, Scalar)],
+) -> Result<Vec<(Scalar, Scalar, Scalar)>, MpcError> {
+    if candidates.len() % 2 == 1 {
+        return Err(MpcError::PreprocessingError(
+            "triplets are sacrificed in pairs, an even number must be given".to_string(),
+        ));
+    }
+
+    let (verified, sacrificed) = candidates.split_at(candidates.len() / 2);
+    let challenge = sample_challenge(network).await?;
+
+    // Open rho and sigma for every pair in one round
+    let mut masked = Vec::with_capacity(2 * verified.len());
+    for ((a, b, _), (f, g, _)) in verified.iter().zip(sacrificed.iter()) {
+        masked.push(challenge * a - f);
+        masked.push(b - g);
+    }
+    let opened = open_scalars(network, &masked).await?;
+
+    let checks = izip!(verified, sacrificed, opened.chunks(2))
+        .map(|((_, _, c), (f, g, h), opened)| {
+            let (rho, sigma) = (opened[0], opened[1]);
+            let check = challenge * c - h - sigma * f - rho * g;
+
+            // The public term is added by the king alone
+            if network.am_king() {
+                check - sigma * rho
+            } else {
+                check
+            }
+        })
+        .collect_vec();
+
+    let opened_checks = open_scalars(network, &checks).await?;
+    if let Some(index) = opened_checks
+        .iter()
+        .position(|check| *check != Scalar::zero())
+    {
+        return Err(MpcError::CheatingError(format!(
+            "triplet {} failed the sacrifice check",
+            index
+        )));
+    }
+
+    Ok(verified.to_vec())
+}
+
+/// Samples a random challenge jointly with the peer; each party commits to its share of the
+/// challenge before either reveals it, so that neither chooses the challenge
+pub(super) async fn sample_challenge<N: MpcNetwork + Send>(
+    network: &mut N,
+) -> Result<Scalar, MpcError> {
+    commit_and_open_scalar(network, Scalar::random(&mut rand_core::OsRng {})).await
+}
+
+/// Opens an additively shared scalar, committing to the local share before either party reveals
+/// its share so that the peer cannot choose its share after seeing the local one
+pub(super) async fn commit_and_open_scalar<N: MpcNetwork + Send>(
+    network: &mut N,
+    share: Scalar,
+) -> Result<Scalar, MpcError> {
+    let commitment = PedersenCommitment::commit(share);
+
+    let (peer_commitment, peer_opening) = network
+        .exchange_commitments(
+            &Payload {
+                points: vec![commitment.get_commitment()],
+                ..Default::default()
+            },
+            &Payload {
+                scalars: vec![share, commitment.get_blinding()],
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(MpcError::NetworkError)?;
+    let (peer_commitment, peer_opening) = (peer_commitment.points[0], peer_opening.scalars);
+
+    if !PedersenCommitment::verify_from_values(peer_commitment, peer_opening[1], peer_opening[0]) {
+        return Err(MpcError::CheatingError(
+            "the peer opened a different share than it committed to".to_string(),
+        ));
+    }
+
+    Ok(share + peer_opening[0])
+}
+
+/// Opens additively shared scalars
+pub(super) async fn open_scalars<N: MpcNetwork + Send>(
+    network: &mut N,
+    shares: &[Scalar],
+) -> Result<Vec<Scalar>, MpcError> {
+    let peer_shares = network
+        .broadcast_scalars(shares)
+        .await
+        .map_err(MpcError::NetworkError)?;
+
+    Ok(shares
+        .iter()
+        .zip(peer_shares.iter())
+        .map(|(share, peer_share)| share + peer_share)
+        .collect_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::scalar::Scalar;
+    use futures::{executor::block_on, future::join};
+
+    use crate::{beaver::BeaverShares, error::MpcError, network::local_network::LocalTwoPartyNet};
+
+    use super::verify_triples;
+
+    #[test]
+    fn test_verify_triples() {
+        let (mut network0, mut network1) = LocalTwoPartyNet::new_pair();
+        let (shares0, mut shares1) = BeaverShares::deal(4, 0, 0, 0);
+
+        let (res0, res1) = block_on(join(
+            verify_triples(&mut network0, &shares0.triplets),
+            verify_triples(&mut network1, &shares1.triplets),
+        ));
+        assert_eq!(res0.unwrap(), shares0.triplets[..2]);
+        assert_eq!(res1.unwrap(), shares1.triplets[..2]);
+
+        // A triplet with c != a * b is caught
+        shares1.triplets[3].2 += Scalar::one();
+        let (res0, res1) = block_on(join(
+            verify_triples(&mut network0, &shares0.triplets),
+            verify_triples(&mut network1, &shares1.triplets),
+        ));
+        assert!(matches!(res0, Err(MpcError::CheatingError(_))));
+        assert!(matches!(res1, Err(MpcError::CheatingError(_))));
+    }
+}
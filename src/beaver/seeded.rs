@@ -0,0 +1,222 @@
+//! Implements a mock value source that derives correctly correlated values for both parties from
+//! a shared seed, for reproducible tests
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+use sha3::{Digest, Sha3_512};
+
+use crate::error::MpcError;
+
+use super::{MatrixTriplet, PermutationCorrelation, SharedValueSource};
+
+/// Separates the values of the source from any other use of the hash function
+const SEEDED_SOURCE_DOMAIN: &[u8] = b"mpc-ristretto seeded mock source";
+
+/// A source of Beaver triplets, shared bits, inverse pairs, square pairs, matrix triplets,
+/// exponentiation tuples, and permutation correlations derived from a seed shared by the parties
+///
+/// Each value is derived from the seed and a counter of the values drawn so far, and split into
+/// shares the same way by both parties; so two sources with the same seed, one for each party,
+/// produce valid shares as long as the parties draw the same values in the same order. Every
+/// value is known to anyone holding the seed, so the source is for tests only
+#[derive(Clone, Debug)]
+pub struct SeededMockBeaverSource {
+    /// The id of the local party, 0 or 1
+    party_id: u64,
+    /// The seed shared by the parties
+    seed: u64,
+    /// The number of scalars derived so far
+    counter: u64,
+}
+
+impl SeededMockBeaverSource {
+    /// Creates the source of the given party from the seed shared with the peer
+    pub fn new(party_id: u64, seed: u64) -> Self {
+        assert!(party_id == 0 || party_id == 1, "party id must be 0 or 1");
+        Self {
+            party_id,
+            seed,
+            counter: 0,
+        }
+    }
+
+    /// Derives the next scalar from the seed
+    fn sample(&mut self) -> Scalar {
+        let mut hasher = Sha3_512::new();
+        hasher.input(SEEDED_SOURCE_DOMAIN);
+        hasher.input(self.seed.to_le_bytes());
+        hasher.input(self.counter.to_le_bytes());
+        self.counter += 1;
+
+        Scalar::from_hash(hasher)
+    }
+
+    /// Derives the next index in the range [0, bound) from the seed
+    fn sample_index(&mut self, bound: usize) -> usize {
+        let bytes = self.sample().to_bytes();
+        (u64::from_le_bytes(bytes[..8].try_into().unwrap()) % bound as u64) as usize
+    }
+
+    /// Splits the value into shares with a mask derived from the seed, and returns the local
+    /// party's share; party 0 holds the mask and party 1 the remainder
+    fn share(&mut self, value: Scalar) -> Scalar {
+        let mask = self.sample();
+        if self.party_id == 0 {
+            mask
+        } else {
+            value - mask
+        }
+    }
+}
+
+impl SharedValueSource<Scalar> for SeededMockBeaverSource {
+    fn next_shared_bit(&mut self) -> Scalar {
+        let bit = Scalar::from((self.sample().as_bytes()[0] & 1) as u64);
+        self.share(bit)
+    }
+
+    fn next_shared_value(&mut self) -> Scalar {
+        let value = self.sample();
+        self.share(value)
+    }
+
+    fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+        let value = self.sample();
+        (self.share(value), self.share(value.invert()))
+    }
+
+    fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+        let value = self.sample();
+        (self.share(value), self.share(value * value))
+    }
+
+    fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+        let a = self.sample();
+        let b = self.sample();
+        (self.share(a), self.share(b), self.share(a * b))
+    }
+
+    fn next_matrix_triplet(
+        &mut self,
+        n: usize,
+        m: usize,
+        k: usize,
+    ) -> Result<MatrixTriplet<Scalar>, MpcError> {
+        let a = (0..n * m).map(|_| self.sample()).collect_vec();
+        let b = (0..m * k).map(|_| self.sample()).collect_vec();
+        let c = (0..n * k)
+            .map(|index| {
+                let (row, col) = (index / k, index % k);
+                (0..m).map(|j| a[row * m + j] * b[j * k + col]).sum()
+            })
+            .collect_vec();
+
+        Ok((
+            a.into_iter().map(|value| self.share(value)).collect_vec(),
+            b.into_iter().map(|value| self.share(value)).collect_vec(),
+            c.into_iter().map(|value| self.share(value)).collect_vec(),
+        ))
+    }
+
+    fn next_exp_tuple(&mut self, k: usize) -> Result<Vec<Scalar>, MpcError> {
+        let value = self.sample();
+        let mut power = Scalar::one();
+        Ok((0..k)
+            .map(|_| {
+                power *= value;
+                self.share(power)
+            })
+            .collect_vec())
+    }
+
+    fn next_permutation_correlation(
+        &mut self,
+        n: usize,
+        permuting_party: u64,
+    ) -> Result<PermutationCorrelation<Scalar>, MpcError> {
+        // Both parties derive every value, so that their counters stay in step
+        let mut permutation = (0..n).collect_vec();
+        for i in (1..n).rev() {
+            let j = self.sample_index(i + 1);
+            permutation.swap(i, j);
+        }
+        let a = (0..n).map(|_| self.sample()).collect_vec();
+        let b = (0..n).map(|_| self.sample()).collect_vec();
+
+        Ok(if self.party_id == permuting_party {
+            let delta = permutation
+                .iter()
+                .zip(b.iter())
+                .map(|(index, b)| a[*index] - b)
+                .collect_vec();
+            PermutationCorrelation::Permuter { permutation, delta }
+        } else {
+            PermutationCorrelation::Masker { a, b }
+        })
+    }
+
+    /// The number of scalars derived from the seed so far
+    fn position(&self) -> Result<u64, MpcError> {
+        Ok(self.counter)
+    }
+
+    fn seek(&mut self, pos: u64) -> Result<(), MpcError> {
+        self.counter = pos;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::beaver::{PermutationCorrelation, SharedValueSource};
+
+    use super::SeededMockBeaverSource;
+
+    #[test]
+    fn test_seeded_source() {
+        let mut source0 = SeededMockBeaverSource::new(0 /* party_id */, 42 /* seed */);
+        let mut source1 = SeededMockBeaverSource::new(1 /* party_id */, 42 /* seed */);
+
+        let (a0, b0, c0) = source0.next_triplet();
+        let (a1, b1, c1) = source1.next_triplet();
+        assert_eq!((a0 + a1) * (b0 + b1), c0 + c1);
+
+        let bit = source0.next_shared_bit() + source1.next_shared_bit();
+        assert!(bit == Scalar::zero() || bit == Scalar::one());
+
+        let (r0, inverse0) = source0.next_shared_inverse_pair();
+        let (r1, inverse1) = source1.next_shared_inverse_pair();
+        assert_eq!((r0 + r1) * (inverse0 + inverse1), Scalar::one());
+
+        // The permuting party's delta is pi(a) - b for the other party's masks
+        match (
+            source0.next_permutation_correlation(4, 0 /* permuting_party */),
+            source1.next_permutation_correlation(4, 0 /* permuting_party */),
+        ) {
+            (
+                Ok(PermutationCorrelation::Permuter { permutation, delta }),
+                Ok(PermutationCorrelation::Masker { a, b }),
+            ) => {
+                let mut sorted = permutation.clone();
+                sorted.sort_unstable();
+                assert_eq!(sorted, vec![0, 1, 2, 3]);
+                for (i, index) in permutation.iter().enumerate() {
+                    assert_eq!(delta[i], a[*index] - b[i]);
+                }
+            }
+            _ => panic!("expected the halves of a permutation correlation"),
+        }
+
+        // The same seed reproduces the same values, and another seed does not
+        let position = source0.position().unwrap();
+        let triplet = source0.next_triplet();
+        source0.seek(position).unwrap();
+        assert_eq!(source0.next_triplet(), triplet);
+        assert_ne!(
+            SeededMockBeaverSource::new(0 /* party_id */, 43 /* seed */).next_triplet(),
+            SeededMockBeaverSource::new(0 /* party_id */, 42 /* seed */).next_triplet()
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Implements a commit-then-reveal coin toss, in which the parties jointly sample public
+//! randomness that neither party can bias, e.g. as the challenges of verification subroutines
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::OsRng;
+use sha3::{Digest, Sha3_512};
+
+use crate::{
+    block_on,
+    commitment::RistrettoCommitment,
+    error::MpcError,
+    network::{MpcNetwork, Payload},
+    SharedNetwork,
+};
+
+/// Separates the values derived from a coin toss from any other use of the hash function
+const COIN_TOSS_DOMAIN: &[u8] = b"mpc-ristretto coin toss";
+
+/// Jointly samples a public random point that neither party can bias
+///
+/// Each party samples a random point and commits to it with a `RistrettoCommitment`; the
+/// commitments and openings are swapped by `MpcNetwork::exchange_commitments`, and the toss is
+/// the sum of both points. As neither party may change its point after seeing the peer's, the
+/// sum is uniformly random as long as one party samples honestly. The toss is recorded in the
+/// network's output digest.
+///
+/// Cost: three messages, see `MpcNetwork::exchange_commitments`
+fn toss_point<N: MpcNetwork + Send>(
+    network: &SharedNetwork<N>,
+) -> Result<RistrettoPoint, MpcError> {
+    let mut rng = OsRng {};
+    let commitment = RistrettoCommitment::commit(RistrettoPoint::random(&mut rng));
+
+    let (peer_commitment, peer_opening) =
+        block_on(network.as_ref().borrow_mut().exchange_commitments(
+            &Payload {
+                scalars: vec![commitment.get_commitment()],
+                ..Default::default()
+            },
+            &Payload {
+                scalars: vec![commitment.get_blinding()],
+                points: vec![commitment.get_value()],
+            },
+        ))
+        .map_err(MpcError::NetworkError)?;
+    let (peer_blinding, peer_point) = (peer_opening.scalars[0], peer_opening.points[0]);
+
+    if !RistrettoCommitment::verify_from_values(
+        peer_commitment.scalars[0],
+        peer_blinding,
+        peer_point,
+    ) {
+        return Err(MpcError::AuthenticationError);
+    }
+
+    let toss = commitment.get_value() + peer_point;
+    network
+        .as_ref()
+        .borrow_mut()
+        .record_opening(toss.compress().as_bytes());
+
+    Ok(toss)
+}
+
+/// Returns the hash of the toss for the given label and counter
+fn expand(toss: &RistrettoPoint, label: &[u8], counter: u64) -> Sha3_512 {
+    let mut hasher = Sha3_512::new();
+    hasher.input(COIN_TOSS_DOMAIN);
+    hasher.input(toss.compress().as_bytes());
+    hasher.input(label);
+    hasher.input(counter.to_le_bytes());
+    hasher
+}
+
+/// Jointly samples a public random scalar that neither party can bias
+///
+/// See `toss_scalars` for the protocol and its cost
+pub fn toss_scalar<N: MpcNetwork + Send>(network: &SharedNetwork<N>) -> Result<Scalar, MpcError> {
+    Ok(toss_scalars(network, 1)?.remove(0))
+}
+
+/// Jointly samples a batch of public random scalars that neither party can bias
+///
+/// The parties toss a single random point by commit-then-reveal, and derive each scalar from
+/// the hash of the point and the scalar's index. Returns an `AuthenticationError` if the peer
+/// opens its commitment to a different point.
+///
+/// Cost: three messages for the whole batch
+pub fn toss_scalars<N: MpcNetwork + Send>(
+    network: &SharedNetwork<N>,
+    num_scalars: usize,
+) -> Result<Vec<Scalar>, MpcError> {
+    let toss = toss_point(network)?;
+    Ok((0..num_scalars as u64)
+        .map(|i| Scalar::from_hash(expand(&toss, b"scalar", i)))
+        .collect())
+}
+
+/// Jointly samples a public random byte string of the given length that neither party can
+/// bias, e.g. to seed a public pseudorandom generator
+///
+/// The bytes are the hash of a tossed point expanded in counter mode; see `toss_scalars` for
+/// the protocol and its cost
+pub fn toss_bytes<N: MpcNetwork + Send>(
+    network: &SharedNetwork<N>,
+    len: usize,
+) -> Result<Vec<u8>, MpcError> {
+    let toss = toss_point(network)?;
+
+    let mut bytes = Vec::with_capacity(len);
+    let mut counter = 0;
+    while bytes.len() < len {
+        let block = expand(&toss, b"bytes", counter).result();
+        let take = (len - bytes.len()).min(block.len());
+        bytes.extend_from_slice(&block[..take]);
+        counter += 1;
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc, thread};
+
+    use futures::executor::block_on;
+
+    use crate::network::{local_network::LocalTwoPartyNet, MpcNetwork};
+
+    use super::{toss_bytes, toss_scalars};
+
+    #[test]
+    fn test_coin_toss() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let parties = [network0, network1]
+            .into_iter()
+            .map(|network| {
+                thread::spawn(move || {
+                    let network = Rc::new(RefCell::new(network));
+                    let scalars = toss_scalars(&network, 2).unwrap();
+                    let bytes = toss_bytes(&network, 100).unwrap();
+
+                    block_on(network.borrow_mut().close()).unwrap();
+                    (scalars, bytes)
+                })
+            })
+            .collect::<Vec<_>>();
+        let tosses = parties
+            .into_iter()
+            .map(|party| party.join().unwrap())
+            .collect::<Vec<_>>();
+
+        // Both parties agree on the tosses, and distinct scalars differ
+        assert_eq!(tosses[0], tosses[1]);
+        assert_ne!(tosses[0].0[0], tosses[0].0[1]);
+        assert_eq!(tosses[0].1.len(), 100);
+    }
+}
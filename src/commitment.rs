@@ -1,6 +1,7 @@
 //! Pedersen commitment implementation, borrowed from
 //! https://github.com/dalek-cryptography/bulletproofs/blob/main/src/generators.rs#L29
 
+use clear_on_drop::clear::Clear;
 use curve25519_dalek::{
     constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT},
     ristretto::RistrettoPoint,
@@ -9,6 +10,9 @@ use curve25519_dalek::{
 };
 use rand_core::{OsRng, RngCore};
 use sha3::{Digest, Sha3_512};
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize-on-drop")]
+use zeroize::ZeroizeOnDrop;
 
 /// Implementation of a Pedersen commitment scheme, modified from:
 /// https://github.com/dalek-cryptography/bulletproofs/blob/main/src/generators.rs#L29
@@ -168,6 +172,43 @@ impl RistrettoCommitment {
     }
 }
 
+impl Zeroize for PedersenCommitment {
+    fn zeroize(&mut self) {
+        self.commitment.clear();
+        self.blinding_factor.zeroize();
+        self.value.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl Drop for PedersenCommitment {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl ZeroizeOnDrop for PedersenCommitment {}
+
+/// Zeroizing a commitment leaves the identity in place of the committed point
+impl Zeroize for RistrettoCommitment {
+    fn zeroize(&mut self) {
+        self.commitment.zeroize();
+        self.blinding_factor.zeroize();
+        self.value.clear();
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl Drop for RistrettoCommitment {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl ZeroizeOnDrop for RistrettoCommitment {}
+
 #[cfg(test)]
 mod pedersen_tests {
     use curve25519_dalek::scalar::Scalar;
@@ -194,8 +235,9 @@ mod pedersen_tests {
 
 #[cfg(test)]
 mod hash_commit_tests {
-    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity};
     use rand_core::OsRng;
+    use zeroize::Zeroize;
 
     use super::RistrettoCommitment;
 
@@ -214,4 +256,14 @@ mod hash_commit_tests {
             bad_value
         ))
     }
+
+    #[test]
+    fn test_zeroize() {
+        let mut commitment = RistrettoCommitment::commit(RistrettoPoint::random(&mut OsRng {}));
+        commitment.zeroize();
+
+        assert_eq!(commitment.get_commitment(), Scalar::zero());
+        assert_eq!(commitment.get_blinding(), Scalar::zero());
+        assert_eq!(commitment.get_value(), RistrettoPoint::identity());
+    }
 }
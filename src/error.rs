@@ -3,12 +3,18 @@ use std::fmt::Display;
 
 use quinn::{ConnectError, ConnectionError};
 
+use crate::network::MessageHeader;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MpcError {
     NetworkError(MpcNetworkError),
     AuthenticationError,
     VisibilityError(String),
     ArithmeticError(String),
+    AssertionError(String),
+    PreprocessingError(String),
+    PreprocessingExhausted(String),
+    CheatingError(String),
 }
 
 impl Display for MpcError {
@@ -26,6 +32,31 @@ pub enum MpcNetworkError {
     NetworkUninitialized,
     BroadcastError(BroadcastError),
     SerializationError,
+    /// The parties could not agree on the stream position after reconnecting
+    ResyncError,
+    /// The peer did not present the pinned identity
+    AuthenticationError,
+    /// The peer did not send the expected values within the receive timeout
+    Timeout,
+    /// The peer stopped acknowledging keepalives and the connection timed out
+    PeerUnreachable,
+    /// The peer began the shutdown handshake while the local party awaited values
+    PeerClosed,
+    /// The peer speaks a different version of the wire protocol; holds the local and peer
+    /// versions
+    ProtocolVersionMismatch(u16, u16),
+    /// The parties ran different operations; holds the header of the message the local party
+    /// expected and the header of the message the peer sent
+    ProtocolDesync(MessageHeader, MessageHeader),
+    /// A datagram from the peer was lost or reordered; holds the sequence number of the datagram
+    /// the local party expected and that of the datagram it received
+    DatagramLost(u64, u64),
+    /// An operation was started on a shared network while another operation on it was pending
+    NetworkBusy,
+    /// The parties requested different values from the trusted dealer
+    DealerDesync,
+    /// A network option was configured with an invalid value
+    InvalidConfig(String),
 }
 
 impl Display for MpcNetworkError {
@@ -38,6 +69,7 @@ impl Display for MpcNetworkError {
 pub enum SetupError {
     ConnectError(ConnectError),
     ConnectionError(ConnectionError),
+    HandshakeError,
     KeygenError,
     NoIncomingConnection,
     ServerSetupError,
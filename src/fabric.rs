@@ -6,7 +6,7 @@
 //! references of the network layer or the beaver sources to allocate values.
 
 use std::{
-    cell::{Ref, RefCell, RefMut},
+    cell::{Cell, Ref, RefCell, RefMut},
     net::SocketAddr,
     rc::Rc,
 };
@@ -17,14 +17,17 @@ use curve25519_dalek::{
     scalar::Scalar,
 };
 use itertools::Itertools;
+use sha3::{Digest, Sha3_512};
 
 use crate::{
     authenticated_ristretto::{AuthenticatedCompressedRistretto, AuthenticatedRistretto},
     authenticated_scalar::AuthenticatedScalar,
-    beaver::SharedValueSource,
+    beaver::{
+        LowPreprocessingCallback, PreprocessingMonitor, SealedPreprocessing, SharedValueSource,
+    },
     error::MpcError,
     mpc_scalar::MpcScalar,
-    network::{MpcNetwork, QuicTwoPartyNet},
+    network::{DynMpcNetwork, MpcNetwork, QuicTwoPartyNet},
     BeaverSource, SharedNetwork, Visibility,
 };
 
@@ -39,8 +42,14 @@ pub struct AuthenticatedMpcFabric<N: MpcNetwork + Send, S: SharedValueSource<Sca
     /// The source from which the local party can draw results of the
     /// preprocessing functionality; i.e. Beaver triplets and shared scalars
     beaver_source: BeaverSource<S>,
+    /// A counter used to domain separate derived challenges, shared between clones of the fabric
+    /// and incremented identically by both parties
+    nonce: Rc<Cell<u64>>,
 }
 
+/// An `AuthenticatedMpcFabric` over a network chosen at runtime
+pub type DynAuthenticatedMpcFabric<S> = AuthenticatedMpcFabric<DynMpcNetwork, S>;
+
 impl<S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<QuicTwoPartyNet, S> {
     /// Create a new AuthenticatedMpcFabric with the defuault (QUIC two party) network
     pub fn new(
@@ -61,6 +70,20 @@ impl<S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<QuicTwoPartyNet, S> {
     }
 }
 
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>>
+    AuthenticatedMpcFabric<N, SealedPreprocessing<S>>
+{
+    /// Create a fabric for the online phase, drawing every preprocessed value from those
+    /// generated by an `OfflinePhase`
+    pub fn new_with_preprocessing(
+        party_id: u64,
+        network: SharedNetwork<N>,
+        preprocessing: SealedPreprocessing<S>,
+    ) -> Self {
+        Self::new_with_network(party_id, network, Rc::new(RefCell::new(preprocessing)))
+    }
+}
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<N, S> {
     /// Create a new AuthenticatedMpcFabric with a specific network implementation
     pub fn new_with_network(
@@ -68,8 +91,11 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<
         network: SharedNetwork<N>,
         beaver_source: BeaverSource<S>,
     ) -> Self {
-        // Create a shared key from the beaver source
-        let shared_value = beaver_source.as_ref().borrow_mut().next_shared_value();
+        // Create a shared key from the beaver source, using the key its triplets are
+        // authenticated under if it supplies authenticated triplets
+        let mac_key_share = beaver_source.as_ref().borrow().mac_key_share();
+        let shared_value = mac_key_share
+            .unwrap_or_else(|| beaver_source.as_ref().borrow_mut().next_shared_value());
         let key_share = MpcScalar::from_scalar_with_visibility(
             shared_value,
             crate::Visibility::Shared,
@@ -82,6 +108,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<
             key_share,
             network,
             beaver_source,
+            nonce: Rc::new(Cell::new(0)),
         }
     }
 
@@ -90,6 +117,43 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<
         self.party_id
     }
 
+    /// Returns the hash chain over every value opened in the session, or `None` if the
+    /// network was not configured to maintain one; the QUIC, UDS, WebSocket and Noise networks
+    /// maintain it when built with `with_output_digest`
+    ///
+    /// Both parties open the same values in the same order, so their digests match
+    pub fn output_digest(&self) -> Option<[u8; 32]> {
+        self.network.as_ref().borrow().output_digest()
+    }
+
+    /// Returns the session's next nonce and increments the counter
+    ///
+    /// The counter is local to each party; it stays in sync across parties so long as both
+    /// parties derive their challenges in the same order
+    pub fn next_nonce(&self) -> u64 {
+        let nonce = self.nonce.get();
+        self.nonce.set(nonce + 1);
+        nonce
+    }
+
+    /// Jointly sample a public random challenge, domain separated by the session nonce
+    pub fn coin_flip(&self) -> Result<AuthenticatedScalar<N, S>, MpcError> {
+        let coin = MpcScalar::coin_flip(self.network.clone(), self.beaver_source.clone())?;
+        Ok(self.derive_challenge(coin.value().as_bytes()))
+    }
+
+    /// Derive a public challenge from a public transcript, domain separated by the session nonce
+    ///
+    /// Deriving twice from the same transcript yields different challenges, as each derivation
+    /// consumes a nonce. This requires no communication
+    pub fn derive_challenge(&self, transcript: &[u8]) -> AuthenticatedScalar<N, S> {
+        let mut hasher = Sha3_512::new();
+        hasher.input(self.next_nonce().to_le_bytes());
+        hasher.input(transcript);
+
+        self.allocate_public_scalar(Scalar::from_hash(hasher))
+    }
+
     /// Borrow the beaver source from the fabric
     pub fn borrow_beaver_source(&self) -> Ref<S> {
         self.beaver_source.as_ref().borrow()
@@ -509,3 +573,41 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> AuthenticatedMpcFabric<
             .collect_vec()
     }
 }
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>>
+    AuthenticatedMpcFabric<N, PreprocessingMonitor<S>>
+{
+    /// Register a callback that is invoked from the multiplication path when the beaver
+    /// source's remaining triplet budget drops to or below `threshold`
+    ///
+    /// The callback receives the current budget, allowing the caller to start preprocessing
+    /// more values while the computation continues
+    pub fn on_low_preprocessing(&self, threshold: usize, callback: LowPreprocessingCallback) {
+        self.borrow_beaver_source_mut()
+            .on_low_preprocessing(threshold, callback);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+
+    use super::AuthenticatedMpcFabric;
+
+    #[test]
+    fn test_derive_challenge() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let fabric = AuthenticatedMpcFabric::new_with_network(0, network, beaver_source);
+
+        // Two derivations from the same transcript yield different challenges
+        let challenge1 = fabric.derive_challenge(b"transcript");
+        let challenge2 = fabric.derive_challenge(b"transcript");
+        assert_ne!(challenge1.value(), challenge2.value());
+
+        // Each derivation consumed a nonce
+        assert_eq!(fabric.next_nonce(), 2);
+    }
+}
@@ -1,34 +1,134 @@
-use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    future::Future,
+    pin::pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
 
 use beaver::SharedValueSource;
 use curve25519_dalek::scalar::Scalar;
+use error::MpcNetworkError;
+use futures::future::BoxFuture;
 
-use network::MpcNetwork;
+use network::{DynMpcNetwork, MpcNetwork};
 
 pub mod authenticated_ristretto;
 pub mod authenticated_scalar;
 pub mod beaver;
+pub mod coin_toss;
 pub mod commitment;
 pub mod error;
 pub mod fabric;
 mod macros;
+pub mod mpc_binary;
+pub mod mpc_bool;
+pub mod mpc_fixed_point;
+pub mod mpc_matrix;
 pub mod mpc_ristretto;
 pub mod mpc_scalar;
+pub mod mpc_u64;
 pub mod network;
+pub mod schnorr;
+pub mod share;
 
 /// SharedNetwork wraps a network implementation in a borrow-safe container
 /// while providing interior mutability
 #[allow(type_alias_bounds)]
 pub type SharedNetwork<N: MpcNetwork + Send> = Rc<RefCell<N>>;
+/// A shared network whose transport is chosen at runtime
+pub type DynSharedNetwork = SharedNetwork<DynMpcNetwork>;
 #[allow(type_alias_bounds)]
 pub type BeaverSource<S: SharedValueSource<Scalar>> = Rc<RefCell<S>>;
 
+/// Awaits an operation on a shared network, failing with `MpcNetworkError::NetworkBusy` rather
+/// than panicking if an operation on the network is already pending
+///
+/// The operation holds the network mutably until it completes, so the async methods of values
+/// that share a network must be awaited in sequence rather than polled concurrently. This is
+/// the only place in which the borrow of a shared network is held across an await
+#[allow(clippy::await_holding_refcell_ref)]
+pub(crate) async fn await_network<N, T>(
+    network: &SharedNetwork<N>,
+    op: impl for<'a> FnOnce(&'a mut N) -> BoxFuture<'a, Result<T, MpcNetworkError>>,
+) -> Result<T, MpcNetworkError>
+where
+    N: MpcNetwork + Send,
+{
+    let mut network = network
+        .try_borrow_mut()
+        .map_err(|_| MpcNetworkError::NetworkBusy)?;
+    op(&mut network).await
+}
+
+/// Wakes a thread blocked in `block_on`
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+/// Blocks the current thread on a future, backing the synchronous API with its async core
+///
+/// Unlike `futures::executor::block_on` this may be nested, as it is when a synchronous
+/// operator or a network-backed Beaver source is reached from within an async method
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            // A wakeup meant for an enclosing `block_on` may have been consumed by this one,
+            // so leave the thread unparked for the enclosing loop to poll again
+            thread::current().unpark();
+            return output;
+        }
+        thread::park();
+    }
+}
+
+/// Identifies the MPC session that a value was allocated in
+///
+/// A session is the shared network its values are allocated on; as each value retains its
+/// network, the address of the network's allocation is unique among the live sessions. The ID
+/// is derived from the network when asked for, so values carry nothing beyond their network
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SessionId(usize);
+
+impl SessionId {
+    /// Returns the ID of the session run over the given network
+    pub(crate) fn of<N: MpcNetwork + Send>(network: &SharedNetwork<N>) -> Self {
+        Self(Rc::as_ptr(network) as *const () as usize)
+    }
+}
+
+/// Asserts that the given session IDs, one for each operand of an operation, are all equal
+///
+/// Combining values from two sessions would silently run the operation over only one of the
+/// two networks
+#[cfg(feature = "session-check")]
+pub(crate) fn assert_same_session<I: IntoIterator<Item = SessionId>>(sessions: I) {
+    let mut sessions = sessions.into_iter();
+    if let Some(first) = sessions.next() {
+        assert!(
+            sessions.all(|session| session == first),
+            "cannot combine values allocated in different MPC sessions"
+        );
+    }
+}
+
 /// A wrapper trait that allows for implementing generic comparisons
 pub trait Visible {
     fn visibility(&self) -> Visibility;
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Visibility determines what information peers have for values allocated
 /// in the network.
 pub enum Visibility {
@@ -357,10 +357,45 @@ macro_rules! impl_wrapper_type {
     };
 }
 
+/// Used to implement the correlations of a value source that wraps another source, i.e. matrix
+/// triplets, exponentiation tuples, and permutation correlations, by drawing them from the
+/// wrapped source, given as an expression of the wrapping source `$this`
+macro_rules! impl_forwarded_correlations {
+    ($value_type:ty, |$this:ident| $inner:expr) => {
+        fn next_matrix_triplet(
+            &mut self,
+            n: usize,
+            m: usize,
+            k: usize,
+        ) -> Result<$crate::beaver::MatrixTriplet<$value_type>, $crate::error::MpcError> {
+            let $this = self;
+            $inner.next_matrix_triplet(n, m, k)
+        }
+
+        fn next_exp_tuple(
+            &mut self,
+            k: usize,
+        ) -> Result<Vec<$value_type>, $crate::error::MpcError> {
+            let $this = self;
+            $inner.next_exp_tuple(k)
+        }
+
+        fn next_permutation_correlation(
+            &mut self,
+            n: usize,
+            permuting_party: u64,
+        ) -> Result<$crate::beaver::PermutationCorrelation<$value_type>, $crate::error::MpcError> {
+            let $this = self;
+            $inner.next_permutation_correlation(n, permuting_party)
+        }
+    };
+}
+
 // Exports
 pub(crate) use impl_arithmetic_assign;
 pub(crate) use impl_authenticated;
 pub(crate) use impl_delegated;
 pub(crate) use impl_delegated_wrapper;
+pub(crate) use impl_forwarded_correlations;
 pub(crate) use impl_operator_variants;
 pub(crate) use impl_wrapper_type;
@@ -0,0 +1,372 @@
+//! Implements values shared by XOR over their bits, the binary counterpart of the additively
+//! shared `MpcScalar`, along with conversions between the two domains using daBits
+
+use std::ops::{BitXor, Not};
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::{izip, Itertools};
+
+use crate::{
+    beaver::{DaBitSource, SharedValueSource},
+    block_on,
+    error::MpcError,
+    mpc_scalar::{bits::pow2, MpcScalar},
+    network::MpcNetwork,
+    BeaverSource, SharedNetwork, Visibility,
+};
+
+/// The number of bits packed into each scalar sent over the network; 31 bytes always encode a
+/// canonical scalar
+const BITS_PER_SCALAR: usize = 31 * 8;
+
+/// Packs bits into scalars, `BITS_PER_SCALAR` bits to a scalar in little endian order
+fn pack_bits(bits: &[bool]) -> Vec<Scalar> {
+    bits.chunks(BITS_PER_SCALAR)
+        .map(|chunk| {
+            let mut bytes = [0u8; 32];
+            for (i, bit) in chunk.iter().enumerate() {
+                bytes[i / 8] |= (*bit as u8) << (i % 8);
+            }
+
+            Scalar::from_bits(bytes)
+        })
+        .collect()
+}
+
+/// Unpacks the first `num_bits` bits from scalars packed by `pack_bits`
+fn unpack_bits(scalars: &[Scalar], num_bits: usize) -> Vec<bool> {
+    scalars
+        .iter()
+        .flat_map(|scalar| {
+            let bytes = scalar.to_bytes();
+            (0..BITS_PER_SCALAR).map(move |i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+        })
+        .take(num_bits)
+        .collect()
+}
+
+/// Exchanges the local XOR shares of the bits with the peer and returns the opened bits
+fn open_bits<N: MpcNetwork + Send>(
+    network: &SharedNetwork<N>,
+    bits: &[bool],
+) -> Result<Vec<bool>, MpcError> {
+    let peer_scalars = block_on(
+        network
+            .as_ref()
+            .borrow_mut()
+            .broadcast_scalars(&pack_bits(bits)),
+    )
+    .map_err(MpcError::NetworkError)?;
+
+    Ok(bits
+        .iter()
+        .zip(unpack_bits(&peer_scalars, bits.len()))
+        .map(|(bit, peer_bit)| bit ^ peer_bit)
+        .collect())
+}
+
+/// A value shared by XOR over its little endian bits; each party holds a bit vector and the
+/// value's bits are the XOR of the parties' vectors
+///
+/// Bitwise operations with public operands and the rearrangement of bits are local, which
+/// makes the binary domain the natural home of shifts; `from_arithmetic` and `to_arithmetic`
+/// move values between the binary and arithmetic domains so that the gadgets of both may be
+/// mixed within one computation
+#[derive(Clone, Debug)]
+pub struct MpcBinary<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The local party's XOR shares of the bits, in little endian order
+    bits: Vec<bool>,
+    /// The underlying network that the MPC operates on
+    network: SharedNetwork<N>,
+    /// The source for shared values, kept to convert the value back to the arithmetic domain
+    beaver_source: BeaverSource<S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcBinary<N, S> {
+    /// Allocates a public bit vector, in little endian order, as a sharing in which the king
+    /// holds the bits and the peer holds zeros
+    pub fn from_public_bits(
+        bits: &[bool],
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        let am_king = network.as_ref().borrow().am_king();
+        Self {
+            bits: bits.iter().map(|bit| am_king && *bit).collect(),
+            network,
+            beaver_source,
+        }
+    }
+
+    /// Returns the local party's XOR shares of the bits, in little endian order
+    pub fn bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Returns the number of bits in the value
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns true if the value has no bits
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Opens the bits of the value, in little endian order
+    pub fn open(&self) -> Result<Vec<bool>, MpcError> {
+        open_bits(&self.network, &self.bits)
+    }
+
+    /// XORs the value with a public bit vector of the same length
+    pub fn xor_public(&self, bits: &[bool]) -> Self {
+        assert_eq!(
+            self.len(),
+            bits.len(),
+            "bit vectors must have the same length"
+        );
+        let am_king = self.network.as_ref().borrow().am_king();
+        self.with_bits(
+            self.bits
+                .iter()
+                .zip(bits.iter())
+                .map(|(share, bit)| share ^ (am_king && *bit))
+                .collect(),
+        )
+    }
+
+    /// Shifts the bits towards the more significant end, i.e. the bit at index i moves to index
+    /// i + by; bits shifted past the end are dropped and the vacated low bits are zero
+    ///
+    /// This is a local index reshuffle and requires no communication
+    pub fn shift_left(&self, by: usize) -> Self {
+        let shift = by.min(self.len());
+        self.with_bits(
+            std::iter::repeat_n(false, shift)
+                .chain(self.bits[..self.len() - shift].iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Shifts the bits towards the less significant end, i.e. the bit at index i + by moves to
+    /// index i; bits shifted past the start are dropped and the vacated high bits are zero
+    ///
+    /// This is a local index reshuffle and requires no communication
+    pub fn shift_right(&self, by: usize) -> Self {
+        let shift = by.min(self.len());
+        self.with_bits(
+            self.bits[shift..]
+                .iter()
+                .copied()
+                .chain(std::iter::repeat_n(false, shift))
+                .collect(),
+        )
+    }
+
+    /// Converts a value in the range [0, 2^num_bits) from the arithmetic to the binary domain
+    ///
+    /// See `batch_from_arithmetic` for the protocol and its cost
+    pub fn from_arithmetic<D: DaBitSource>(
+        value: &MpcScalar<N, S>,
+        num_bits: usize,
+        dabits: &mut D,
+    ) -> Result<Self, MpcError> {
+        Ok(Self::batch_from_arithmetic(std::slice::from_ref(value), num_bits, dabits)?.remove(0))
+    }
+
+    /// Converts a batch of values in the range [0, 2^num_bits) from the arithmetic to the
+    /// binary domain (A2B)
+    ///
+    /// The values are decomposed into additively shared bits as in `to_bits_le`, and each bit x
+    /// is masked with a daBit r as x ^ r = x + r - 2 * x * r. The masked bits are opened, and
+    /// each party's XOR share of x is its XOR share of r, flipped by the king if x ^ r = 1.
+    ///
+    /// Cost: the cost of `batch_to_bits_le`, plus one daBit and one Beaver triplet per bit and
+    /// one round of communication for the whole batch
+    pub fn batch_from_arithmetic<D: DaBitSource>(
+        values: &[MpcScalar<N, S>],
+        num_bits: usize,
+        dabits: &mut D,
+    ) -> Result<Vec<Self>, MpcError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let network = values[0].network();
+        let beaver_source = values[0].beaver_source();
+        if num_bits == 0 {
+            return Ok(values
+                .iter()
+                .map(|_| Self::from_public_bits(&[], network.clone(), beaver_source.clone()))
+                .collect());
+        }
+
+        let bits = MpcScalar::batch_to_bits_le(values, num_bits)?
+            .into_iter()
+            .flatten()
+            .collect_vec();
+        let dabits = dabits.next_dabit_batch(bits.len())?;
+        let masks = dabits
+            .iter()
+            .map(|dabit| {
+                MpcScalar::from_scalar_with_visibility(
+                    dabit.arithmetic,
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect_vec();
+
+        let products = MpcScalar::try_batch_mul(&bits, &masks)?;
+        let masked_bits = izip!(bits.iter(), masks.iter(), products.iter())
+            .map(|(bit, mask, product)| bit + mask - product * Scalar::from(2u64))
+            .collect_vec();
+        let opened = MpcScalar::batch_open(&masked_bits).map_err(MpcError::NetworkError)?;
+
+        let am_king = network.as_ref().borrow().am_king();
+        let shares = opened
+            .iter()
+            .zip(dabits.iter())
+            .map(|(masked_bit, dabit)| {
+                (am_king && masked_bit.value() == Scalar::one()) ^ dabit.binary
+            })
+            .collect_vec();
+
+        Ok(shares
+            .chunks(num_bits)
+            .map(|chunk| Self {
+                bits: chunk.to_vec(),
+                network: network.clone(),
+                beaver_source: beaver_source.clone(),
+            })
+            .collect())
+    }
+
+    /// Converts the value from the binary to the arithmetic domain
+    ///
+    /// See `batch_to_arithmetic` for the protocol and its cost
+    pub fn to_arithmetic<D: DaBitSource>(
+        &self,
+        dabits: &mut D,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_to_arithmetic(std::slice::from_ref(self), dabits)?.remove(0))
+    }
+
+    /// Converts a batch of values from the binary to the arithmetic domain (B2A)
+    ///
+    /// Each bit x is masked with a daBit r in the binary domain and x ^ r is opened; the
+    /// additive sharing of x is then r if x ^ r = 0 and 1 - r otherwise, and the value is
+    /// \sum_i x_i * 2^i. The values must fit in the scalar field, i.e. have at most 252 bits.
+    ///
+    /// Cost: one daBit per bit and one round of communication for the whole batch
+    pub fn batch_to_arithmetic<D: DaBitSource>(
+        values: &[Self],
+        dabits: &mut D,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if values.iter().any(|value| value.len() > 252) {
+            return Err(MpcError::ArithmeticError(
+                "a value of more than 252 bits does not fit in the scalar field".to_string(),
+            ));
+        }
+
+        let network = values[0].network.clone();
+        let beaver_source = values[0].beaver_source.clone();
+
+        let bits = values
+            .iter()
+            .flat_map(|value| value.bits.iter().copied())
+            .collect_vec();
+        let dabits = dabits.next_dabit_batch(bits.len())?;
+        let masked_bits = bits
+            .iter()
+            .zip(dabits.iter())
+            .map(|(bit, dabit)| bit ^ dabit.binary)
+            .collect_vec();
+        let opened = open_bits(&network, &masked_bits)?;
+
+        let mut arithmetic_bits = opened.iter().zip(dabits.iter()).map(|(masked_bit, dabit)| {
+            let mask = MpcScalar::from_scalar_with_visibility(
+                dabit.arithmetic,
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            );
+            if *masked_bit {
+                (-mask).add_public_constant(Scalar::one())
+            } else {
+                mask
+            }
+        });
+
+        Ok(values
+            .iter()
+            .map(|value| {
+                arithmetic_bits.by_ref().take(value.len()).enumerate().fold(
+                    MpcScalar::zero(network.clone(), beaver_source.clone()),
+                    |acc, (i, bit)| acc + bit * pow2(i),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns a value over the same network with the given shares
+    fn with_bits(&self, bits: Vec<bool>) -> Self {
+        Self {
+            bits,
+            network: self.network.clone(),
+            beaver_source: self.beaver_source.clone(),
+        }
+    }
+}
+
+/// XOR of two binary shared values is the XOR of their shares, and requires no communication
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> BitXor<&'a MpcBinary<N, S>>
+    for &'a MpcBinary<N, S>
+{
+    type Output = MpcBinary<N, S>;
+
+    fn bitxor(self, rhs: &'a MpcBinary<N, S>) -> Self::Output {
+        assert_eq!(
+            self.len(),
+            rhs.len(),
+            "bit vectors must have the same length"
+        );
+        self.with_bits(
+            self.bits
+                .iter()
+                .zip(rhs.bits.iter())
+                .map(|(lhs, rhs)| lhs ^ rhs)
+                .collect(),
+        )
+    }
+}
+
+/// Negating every bit flips the king's shares, and requires no communication
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Not for &MpcBinary<N, S> {
+    type Output = MpcBinary<N, S>;
+
+    fn not(self) -> Self::Output {
+        self.xor_public(&vec![true; self.len()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{pack_bits, unpack_bits, BITS_PER_SCALAR};
+
+    #[test]
+    fn test_pack_bits() {
+        let bits = (0..BITS_PER_SCALAR + 3)
+            .map(|i| i % 3 == 0)
+            .collect::<Vec<_>>();
+        let packed = pack_bits(&bits);
+        assert_eq!(packed.len(), 2);
+        assert!(packed.iter().all(|scalar| scalar.is_canonical()));
+        assert_eq!(unpack_bits(&packed, bits.len()), bits);
+    }
+}
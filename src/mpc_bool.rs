@@ -0,0 +1,234 @@
+//! Implements a shared boolean on top of a shared bit, so that predicates over shared values
+//! compose with the logical operators rather than hand-written arithmetic on 0/1 scalars
+
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::{MpcError, MpcNetworkError},
+    mpc_scalar::MpcScalar,
+    network::MpcNetwork,
+    BeaverSource, SharedNetwork,
+};
+
+/// A boolean held as a value of zero or one in the scalar field
+///
+/// NOT is local, while AND, XOR and OR of two shared booleans each consume one Beaver triplet
+/// and a round of communication. Like the operators of `MpcScalar`, the operators panic on a
+/// network error; the batch methods return it instead.
+#[derive(Debug)]
+pub struct MpcBool<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The underlying bit
+    value: MpcScalar<N, S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcBool<N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcBool<N, S> {
+    /// Allocate a public boolean
+    pub fn from_public_bool(
+        value: bool,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            value: MpcScalar::from_public_u64(value as u64, network, beaver_source),
+        }
+    }
+
+    /// Allocate a private boolean, to be shared with `share_secret`
+    pub fn from_private_bool(
+        value: bool,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            value: MpcScalar::from_private_u64(value as u64, network, beaver_source),
+        }
+    }
+
+    /// Wraps a value that is zero or one, e.g. the output of a comparison
+    ///
+    /// The value is not checked; a value other than zero or one makes the logical operators
+    /// meaningless
+    pub fn from_scalar(value: MpcScalar<N, S>) -> Self {
+        Self { value }
+    }
+
+    /// Returns the underlying bit
+    pub fn value(&self) -> &MpcScalar<N, S> {
+        &self.value
+    }
+
+    /// Returns the underlying bit, e.g. to use it in arithmetic
+    pub fn to_scalar(&self) -> MpcScalar<N, S> {
+        self.value.clone()
+    }
+
+    /// Secret shares a privately held boolean, see `MpcScalar::share_secret`
+    pub fn share_secret(&self, party_id: u64) -> Result<Self, MpcNetworkError> {
+        Ok(Self::from_scalar(self.value.share_secret(party_id)?))
+    }
+
+    /// Opens the boolean, returning an `ArithmeticError` if the underlying value is neither zero
+    /// nor one
+    pub fn open(&self) -> Result<bool, MpcError> {
+        let value = self.value.open().map_err(MpcError::NetworkError)?.value();
+        if value == Scalar::zero() {
+            Ok(false)
+        } else if value == Scalar::one() {
+            Ok(true)
+        } else {
+            Err(MpcError::ArithmeticError(
+                "opened boolean is neither zero nor one".to_string(),
+            ))
+        }
+    }
+
+    /// Returns `if_true` if the boolean is true and `if_false` otherwise, see
+    /// `MpcScalar::conditional_select`
+    pub fn select(
+        &self,
+        if_true: &MpcScalar<N, S>,
+        if_false: &MpcScalar<N, S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        MpcScalar::conditional_select(&self.value, if_true, if_false)
+    }
+
+    /// Computes a_i AND b_i for each pair, as a_i * b_i
+    ///
+    /// Cost: one Beaver triplet per pair of shared booleans and one round of communication for
+    /// the whole batch
+    pub fn batch_and(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Ok(Self::batch_products(a, b)?
+            .into_iter()
+            .map(Self::from_scalar)
+            .collect())
+    }
+
+    /// Computes a_i XOR b_i for each pair, as a_i + b_i - 2 * a_i * b_i
+    ///
+    /// Cost: one Beaver triplet per pair of shared booleans and one round of communication for
+    /// the whole batch
+    pub fn batch_xor(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Ok(Self::batch_products(a, b)?
+            .iter()
+            .zip(a.iter().zip(b.iter()))
+            .map(|(product, (a, b))| {
+                Self::from_scalar(&a.value + &b.value - product * Scalar::from(2u64))
+            })
+            .collect())
+    }
+
+    /// Computes a_i OR b_i for each pair, as a_i + b_i - a_i * b_i
+    ///
+    /// Cost: one Beaver triplet per pair of shared booleans and one round of communication for
+    /// the whole batch
+    pub fn batch_or(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Ok(Self::batch_products(a, b)?
+            .iter()
+            .zip(a.iter().zip(b.iter()))
+            .map(|(product, (a, b))| Self::from_scalar(&a.value + &b.value - product))
+            .collect())
+    }
+
+    /// Computes the products a_i * b_i of the underlying bits in one batch
+    fn batch_products(a: &[Self], b: &[Self]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if a.len() != b.len() {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot combine {} booleans with {} booleans",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        let a = a.iter().map(|bit| bit.to_scalar()).collect::<Vec<_>>();
+        let b = b.iter().map(|bit| bit.to_scalar()).collect::<Vec<_>>();
+        MpcScalar::try_batch_mul(&a, &b)
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> From<MpcBool<N, S>> for MpcScalar<N, S> {
+    fn from(value: MpcBool<N, S>) -> Self {
+        value.value
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> BitAnd<&'a MpcBool<N, S>>
+    for &'a MpcBool<N, S>
+{
+    type Output = MpcBool<N, S>;
+
+    fn bitand(self, rhs: &'a MpcBool<N, S>) -> Self::Output {
+        MpcBool::batch_and(std::slice::from_ref(self), std::slice::from_ref(rhs))
+            .unwrap()
+            .remove(0)
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> BitXor<&'a MpcBool<N, S>>
+    for &'a MpcBool<N, S>
+{
+    type Output = MpcBool<N, S>;
+
+    fn bitxor(self, rhs: &'a MpcBool<N, S>) -> Self::Output {
+        MpcBool::batch_xor(std::slice::from_ref(self), std::slice::from_ref(rhs))
+            .unwrap()
+            .remove(0)
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> BitOr<&'a MpcBool<N, S>>
+    for &'a MpcBool<N, S>
+{
+    type Output = MpcBool<N, S>;
+
+    fn bitor(self, rhs: &'a MpcBool<N, S>) -> Self::Output {
+        MpcBool::batch_or(std::slice::from_ref(self), std::slice::from_ref(rhs))
+            .unwrap()
+            .remove(0)
+    }
+}
+
+/// NOT is computed locally as 1 - b
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Not for &MpcBool<N, S> {
+    type Output = MpcBool<N, S>;
+
+    fn not(self) -> Self::Output {
+        MpcBool::from_scalar((-&self.value).add_public_constant(Scalar::one()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+
+    use super::MpcBool;
+
+    #[test]
+    fn test_public_logic() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public booleans are combined locally
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            let lhs = MpcBool::from_public_bool(a, network.clone(), beaver_source.clone());
+            let rhs = MpcBool::from_public_bool(b, network.clone(), beaver_source.clone());
+
+            assert_eq!((&lhs & &rhs).open().unwrap(), a & b);
+            assert_eq!((&lhs ^ &rhs).open().unwrap(), a ^ b);
+            assert_eq!((&lhs | &rhs).open().unwrap(), a | b);
+            assert_eq!((!&lhs).open().unwrap(), !a);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Implements a shared signed fixed-point number on top of the scalar field, along with
+//! approximations of the real functions that have no exact field counterpart
+
+pub mod math;
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::{MpcError, MpcNetworkError},
+    mpc_bool::MpcBool,
+    mpc_scalar::{bits::pow2, scalar_to_u128_checked, MpcScalar},
+    network::MpcNetwork,
+    BeaverSource, SharedNetwork,
+};
+
+/// The number of fractional bits in the representation of a fixed-point number
+pub const FRACTIONAL_BITS: usize = 32;
+/// The bit length of the magnitude of a representation; the representable numbers are those of
+/// magnitude less than 2^(REPR_BITS - FRACTIONAL_BITS)
+pub const REPR_BITS: usize = 63;
+
+/// Returns the representation round(value * 2^FRACTIONAL_BITS), with negative numbers
+/// represented by their additive inverse in the field
+fn f64_to_repr(value: f64) -> Scalar {
+    let scaled = (value * pow2_f64(FRACTIONAL_BITS)).round();
+    let magnitude = Scalar::from(scaled.abs() as u128);
+    if scaled < 0.0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Returns the number encoded by a representation, or an `ArithmeticError` if the
+/// representation is neither a small positive nor a small negative integer
+fn repr_to_f64(repr: &Scalar) -> Result<f64, MpcError> {
+    let (magnitude, sign) = match scalar_to_u128_checked(repr) {
+        Some(magnitude) => (magnitude, 1.0),
+        None => (
+            scalar_to_u128_checked(&-repr).ok_or_else(|| {
+                MpcError::ArithmeticError("fixed-point representation is out of range".to_string())
+            })?,
+            -1.0,
+        ),
+    };
+
+    Ok(sign * magnitude as f64 / pow2_f64(FRACTIONAL_BITS))
+}
+
+/// Returns 2^k as a float
+fn pow2_f64(k: usize) -> f64 {
+    2f64.powi(k as i32)
+}
+
+/// A signed fixed-point number x, held as the shared integer round(x * 2^FRACTIONAL_BITS)
+///
+/// Addition and subtraction are local. Multiplication multiplies the representations and
+/// rescales the product with `MpcScalar::batch_truncate_probabilistic`, after offsetting it
+/// into the non-negative range that truncation assumes; the result may thus be off by one in
+/// the last place. Numbers are assumed to have magnitude less than
+/// 2^(REPR_BITS - FRACTIONAL_BITS), which is not checked. Like the operators of `MpcScalar`,
+/// the operators panic on an error; the methods return it instead.
+#[derive(Debug)]
+pub struct MpcFixedPoint<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The representation of the number, scaled by 2^FRACTIONAL_BITS
+    repr: MpcScalar<N, S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcFixedPoint<N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            repr: self.repr.clone(),
+        }
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcFixedPoint<N, S> {
+    /// Allocate a public number, rounded to the nearest representable number
+    pub fn from_public_f64(
+        value: f64,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            repr: MpcScalar::from_public_scalar(f64_to_repr(value), network, beaver_source),
+        }
+    }
+
+    /// Allocate a private number, rounded to the nearest representable number, to be shared
+    /// with `share_secret`
+    pub fn from_private_f64(
+        value: f64,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            repr: MpcScalar::from_private_scalar(f64_to_repr(value), network, beaver_source),
+        }
+    }
+
+    /// Wraps a representation, i.e. a value scaled by 2^FRACTIONAL_BITS
+    ///
+    /// The representation is not checked; one of magnitude 2^REPR_BITS or more makes the
+    /// arithmetic meaningless
+    pub fn from_repr(repr: MpcScalar<N, S>) -> Self {
+        Self { repr }
+    }
+
+    /// Returns the representation of the number
+    pub fn repr(&self) -> &MpcScalar<N, S> {
+        &self.repr
+    }
+
+    /// Secret shares a privately held number, see `MpcScalar::share_secret`
+    pub fn share_secret(&self, party_id: u64) -> Result<Self, MpcNetworkError> {
+        Ok(Self::from_repr(self.repr.share_secret(party_id)?))
+    }
+
+    /// Opens the number, returning an `ArithmeticError` if its representation is out of range
+    pub fn open(&self) -> Result<f64, MpcError> {
+        repr_to_f64(&self.repr.open().map_err(MpcError::NetworkError)?.value())
+    }
+
+    /// Computes a_i * b_i for each pair
+    ///
+    /// Cost: one Beaver triplet per pair of shared numbers, `MAX_DECOMPOSITION_BITS +
+    /// STATISTICAL_SECURITY` shared bits per product, and two rounds of communication for the
+    /// whole batch
+    pub fn batch_mul(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        if a.len() != b.len() {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot multiply {} numbers with {} numbers",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        Self::batch_mul_reprs(
+            &a.iter().map(|a| a.repr.clone()).collect::<Vec<_>>(),
+            &b.iter().map(|b| b.repr.clone()).collect::<Vec<_>>(),
+            FRACTIONAL_BITS,
+        )
+    }
+
+    /// Computes self * rhs, see `batch_mul`
+    pub fn mul(&self, rhs: &Self) -> Result<Self, MpcError> {
+        Ok(Self::batch_mul(std::slice::from_ref(self), std::slice::from_ref(rhs))?.remove(0))
+    }
+
+    /// Computes a shared boolean that is true if self < rhs
+    ///
+    /// The representations are offset by 2^REPR_BITS into the range of `MpcScalar::less_than`
+    pub fn lt(&self, rhs: &Self) -> Result<MpcBool<N, S>, MpcError> {
+        self.repr
+            .add_public_constant(pow2(REPR_BITS))
+            .less_than(&rhs.repr.add_public_constant(pow2(REPR_BITS)))
+            .map(MpcBool::from_scalar)
+    }
+
+    /// Approximates the square root of a non-negative number by a public number of
+    /// Newton-Raphson iterations
+    ///
+    /// The number a is first normalized to m = a * 2^(-2c) in [1/2, 2), where c is derived from
+    /// the position of the most significant bit of the representation; the bit is located with
+    /// `to_bits_le` and `MpcScalar::prefix_prod`, which reveals nothing about it. The iteration
+    /// y <- y * (3 - m * y^2) / 2 then converges to 1 / sqrt(m) from y = 1 without any division,
+    /// and sqrt(a) = m * y * 2^c. As the normalized starting point is within a factor of sqrt(2)
+    /// of the root, the relative error of y after n iterations is at most 0.30, 0.12, 0.02,
+    /// 5 * 10^-4, 4 * 10^-7 and 3 * 10^-13 for n = 1, ..., 6: convergence is quadratic, and six
+    /// iterations reach the precision of the representation. Each multiplication adds a rounding
+    /// error of one in the last place, so with enough iterations the relative error of the
+    /// result is a few multiples of 2^-FRACTIONAL_BITS. The result for a negative number is
+    /// meaningless.
+    ///
+    /// Public numbers are computed locally; returns an `ArithmeticError` for a negative public
+    /// number and a `VisibilityError` for a private number.
+    ///
+    /// Cost: the cost of `to_bits_le` for REPR_BITS bits and of `prefix_prod` over them, plus
+    /// 3 * iterations + 2 multiplications in sequence, each as in `batch_mul`
+    pub fn sqrt(&self, iterations: usize) -> Result<Self, MpcError> {
+        if self.repr.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be rooted...".to_string(),
+            ));
+        }
+
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+
+        if self.repr.is_public() {
+            let value = repr_to_f64(&self.repr.value())?;
+            if value < 0.0 {
+                return Err(MpcError::ArithmeticError(
+                    "cannot take the square root of a negative number".to_string(),
+                ));
+            }
+
+            return Ok(Self::from_public_f64(value.sqrt(), network, beaver_source));
+        }
+
+        // For a most significant bit at j, a is in [2^e, 2^(e + 1)) for e = j - FRACTIONAL_BITS,
+        // and c = ceil(e / 2) scales it into [1/2, 2); the scale factors are selected by the
+        // one-hot vector of the bit's position, times 2^FRACTIONAL_BITS
+        let mut normalizer = MpcScalar::zero(network.clone(), beaver_source.clone());
+        let mut denormalizer = MpcScalar::zero(network.clone(), beaver_source.clone());
+        for (j, is_msb) in self.msb_one_hot()?.iter().enumerate() {
+            let c = (j as i64 - FRACTIONAL_BITS as i64 + 1).div_euclid(2);
+            normalizer += is_msb * pow2((FRACTIONAL_BITS as i64 - 2 * c) as usize);
+            denormalizer += is_msb * pow2((FRACTIONAL_BITS as i64 + c) as usize);
+        }
+
+        let normalized = Self::batch_mul_reprs(
+            std::slice::from_ref(&self.repr),
+            &[normalizer],
+            FRACTIONAL_BITS,
+        )?
+        .remove(0);
+
+        let three = Self::from_public_f64(3.0, network.clone(), beaver_source.clone());
+        let mut inverse_root = Self::from_public_f64(1.0, network, beaver_source);
+        for _ in 0..iterations {
+            let square = inverse_root.mul(&inverse_root)?;
+            let error = &three - &normalized.mul(&square)?;
+            inverse_root =
+                Self::batch_mul_reprs(&[inverse_root.repr], &[error.repr], FRACTIONAL_BITS + 1)?
+                    .remove(0);
+        }
+
+        let root = normalized.mul(&inverse_root)?;
+        Ok(Self::batch_mul_reprs(&[root.repr], &[denormalizer], FRACTIONAL_BITS)?.remove(0))
+    }
+
+    /// Returns the shared one-hot vector of the position of the most significant bit of a
+    /// non-negative representation, of length REPR_BITS; all zeros for a zero representation
+    ///
+    /// Cost: the cost of `to_bits_le` for REPR_BITS bits and of `prefix_prod` over them
+    fn msb_one_hot(&self) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        // zeros_from[REPR_BITS - 1 - j] is one if bits j and above are all zero, so the most
+        // significant bit is at j exactly when the bits above j are zero and bit j is not
+        let bits = self.repr.to_bits_le(REPR_BITS)?;
+        let zeros_from = MpcScalar::prefix_prod(
+            &bits
+                .iter()
+                .rev()
+                .map(|bit| (-bit).add_public_constant(Scalar::one()))
+                .collect::<Vec<_>>(),
+        )?;
+
+        Ok((0..REPR_BITS)
+            .map(|j| {
+                let zeros_above = if j + 1 == REPR_BITS {
+                    MpcScalar::one(self.repr.network(), self.repr.beaver_source())
+                } else {
+                    zeros_from[REPR_BITS - 2 - j].clone()
+                };
+                &zeros_above - &zeros_from[REPR_BITS - 1 - j]
+            })
+            .collect())
+    }
+
+    /// Multiplies the representations pairwise and divides the products by 2^shift
+    fn batch_mul_reprs(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+        shift: usize,
+    ) -> Result<Vec<Self>, MpcError> {
+        Self::batch_rescale(&MpcScalar::try_batch_mul(a, b)?, shift)
+    }
+
+    /// Divides each value by 2^shift, rounding as in `MpcScalar::batch_truncate_probabilistic`
+    ///
+    /// The values are assumed to have magnitude less than 2^(2 * REPR_BITS), e.g. as the
+    /// products of two representations, and are offset by as much so that they are
+    /// non-negative when truncated
+    fn batch_rescale(values: &[MpcScalar<N, S>], shift: usize) -> Result<Vec<Self>, MpcError> {
+        let offset = pow2(2 * REPR_BITS);
+        let offset_values = values
+            .iter()
+            .map(|value| value.add_public_constant(offset))
+            .collect::<Vec<_>>();
+
+        Ok(
+            MpcScalar::batch_truncate_probabilistic(&offset_values, shift)?
+                .iter()
+                .map(|truncated| {
+                    Self::from_repr(truncated.add_public_constant(-pow2(2 * REPR_BITS - shift)))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a MpcFixedPoint<N, S>>
+    for &'a MpcFixedPoint<N, S>
+{
+    type Output = MpcFixedPoint<N, S>;
+
+    fn add(self, rhs: &'a MpcFixedPoint<N, S>) -> Self::Output {
+        MpcFixedPoint::from_repr(&self.repr + &rhs.repr)
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Sub<&'a MpcFixedPoint<N, S>>
+    for &'a MpcFixedPoint<N, S>
+{
+    type Output = MpcFixedPoint<N, S>;
+
+    fn sub(self, rhs: &'a MpcFixedPoint<N, S>) -> Self::Output {
+        MpcFixedPoint::from_repr(&self.repr - &rhs.repr)
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Neg for &MpcFixedPoint<N, S> {
+    type Output = MpcFixedPoint<N, S>;
+
+    fn neg(self) -> Self::Output {
+        MpcFixedPoint::from_repr(-&self.repr)
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a MpcFixedPoint<N, S>>
+    for &'a MpcFixedPoint<N, S>
+{
+    type Output = MpcFixedPoint<N, S>;
+
+    /// Rescaled multiplication, see `MpcFixedPoint::batch_mul`
+    fn mul(self, rhs: &'a MpcFixedPoint<N, S>) -> Self::Output {
+        MpcFixedPoint::mul(self, rhs).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+
+    use super::MpcFixedPoint;
+
+    #[test]
+    fn test_public_arithmetic() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public numbers are combined locally, and negative numbers round trip
+        let a = MpcFixedPoint::from_public_f64(-1.5, network.clone(), beaver_source.clone());
+        let b = MpcFixedPoint::from_public_f64(2.25, network, beaver_source);
+
+        assert_eq!((&a + &b).open().unwrap(), 0.75);
+        assert_eq!((&a - &b).open().unwrap(), -3.75);
+        assert_eq!((-&a).open().unwrap(), 1.5);
+        assert_eq!((&a * &b).open().unwrap(), -3.375);
+        assert_eq!(b.sqrt(6 /* iterations */).unwrap().open().unwrap(), 1.5);
+        assert!(a.sqrt(6 /* iterations */).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Groups approximations of exp, ln and sigmoid over shared fixed-point numbers, built from a
+//! range reduction followed by a polynomial with public coefficients
+
+use std::f64::consts::{LN_2, LOG2_E};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::MpcError,
+    mpc_scalar::{bits::pow2, MpcScalar},
+    network::MpcNetwork,
+};
+
+use super::{f64_to_repr, repr_to_f64, MpcFixedPoint, FRACTIONAL_BITS};
+
+/// The degree of the Taylor polynomial of 2^f used for f in [0, 1); its truncation error is
+/// below 2^-34
+const EXP2_DEGREE: usize = 11;
+/// The bit length of the integer part of the reduced argument of `exp`
+const EXP_INTEGER_BITS: usize = 6;
+/// The number of terms of the series of atanh(u) used for u in [0, 1/3); its truncation error
+/// is below 2^-36
+const ATANH_TERMS: usize = 10;
+/// The number of Newton-Raphson iterations for the reciprocal of a value in [2, 3) in `ln`
+const LN_RECIPROCAL_ITERATIONS: usize = 4;
+/// The number of Newton-Raphson iterations for the reciprocal of a value in (1, 2] in `sigmoid`
+const SIGMOID_RECIPROCAL_ITERATIONS: usize = 5;
+/// The magnitude beyond which the sigmoid is within 2^-31 of zero or one
+const SIGMOID_CLAMP: f64 = 22.0;
+
+/// Returns the coefficients (ln 2)^i / i! of the Taylor polynomial of 2^f = e^(f * ln 2)
+fn exp2_coeffs() -> Vec<f64> {
+    (0..=EXP2_DEGREE)
+        .scan(1.0, |coeff, i| {
+            let res = *coeff;
+            *coeff *= LN_2 / (i + 1) as f64;
+            Some(res)
+        })
+        .collect()
+}
+
+/// Returns the coefficients of 2 * atanh(u) = \sum_i 2 * u^(2i + 1) / (2i + 1)
+fn atanh2_coeffs() -> Vec<f64> {
+    (0..2 * ATANH_TERMS)
+        .map(|i| if i % 2 == 1 { 2.0 / i as f64 } else { 0.0 })
+        .collect()
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcFixedPoint<N, S> {
+    /// Evaluates the polynomial \sum_i coeffs[i] * x^i with public coefficients at the number
+    ///
+    /// The powers x^2, ..., x^d are computed by a log-depth tree, each batch of multiplications
+    /// doubling the known powers, and the weighted sum of the powers is rescaled once. Each
+    /// power adds a rounding error of one in the last place, so the polynomial is best
+    /// evaluated where the powers are small, e.g. after a range reduction into [0, 1). Returns
+    /// a `VisibilityError` for a private number.
+    ///
+    /// Cost: d - 1 multiplications in ceil(log2(d)) batches, each as in `batch_mul`, and one
+    /// further truncation of the sum
+    pub fn evaluate_polynomial(&self, coeffs: &[f64]) -> Result<Self, MpcError> {
+        if self.repr.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be evaluated...".to_string(),
+            ));
+        }
+
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+        if coeffs.len() <= 1 {
+            return Ok(Self::from_public_f64(
+                coeffs.first().copied().unwrap_or(0.0),
+                network,
+                beaver_source,
+            ));
+        }
+
+        // The weighted sum is scaled by 2^(2 * FRACTIONAL_BITS)
+        let sum = self
+            .powers(coeffs.len() - 1)?
+            .iter()
+            .zip(coeffs[1..].iter())
+            .fold(
+                MpcScalar::from_public_scalar(
+                    f64_to_repr(coeffs[0]) * pow2(FRACTIONAL_BITS),
+                    network,
+                    beaver_source,
+                ),
+                |acc, (power, coeff)| acc + &power.repr * f64_to_repr(*coeff),
+            );
+
+        Ok(Self::batch_rescale(&[sum], FRACTIONAL_BITS)?.remove(0))
+    }
+
+    /// Approximates e^x for x in [-22, 21]; below the range the result underflows the precision
+    /// of the representation, and above it overflows its range
+    ///
+    /// The argument is reduced as e^x = 2^(z - FRACTIONAL_BITS) for z = x / ln 2 +
+    /// FRACTIONAL_BITS in [0, 2^EXP_INTEGER_BITS). z is decomposed into bits, of which the low
+    /// FRACTIONAL_BITS bits form its fractional part f and the rest its integer part k. 2^f is
+    /// evaluated by its Taylor polynomial of degree EXP2_DEGREE, and 2^(k - FRACTIONAL_BITS) is
+    /// exactly represented by the integer 2^k, the product of 2^(2^i) over the set bits i of k.
+    /// The relative error of the result is a few multiples of 2^-FRACTIONAL_BITS, and its
+    /// absolute error is at least one in the last place.
+    ///
+    /// Public numbers are computed locally; returns a `VisibilityError` for a private number.
+    ///
+    /// Cost: the cost of `to_bits_le` for FRACTIONAL_BITS + EXP_INTEGER_BITS bits, of
+    /// `evaluate_polynomial` of degree EXP2_DEGREE and of `prefix_prod` over EXP_INTEGER_BITS
+    /// values, plus two multiplications as in `batch_mul`
+    pub fn exp(&self) -> Result<Self, MpcError> {
+        if self.repr.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be exponentiated...".to_string(),
+            ));
+        }
+
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+        if self.repr.is_public() {
+            let value = repr_to_f64(&self.repr.value())?;
+            return Ok(Self::from_public_f64(value.exp(), network, beaver_source));
+        }
+
+        let log2_e = MpcScalar::from_public_scalar(
+            f64_to_repr(LOG2_E),
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let reduced =
+            Self::batch_mul_reprs(std::slice::from_ref(&self.repr), &[log2_e], FRACTIONAL_BITS)?
+                .remove(0)
+                .repr
+                .add_public_constant(f64_to_repr(FRACTIONAL_BITS as f64));
+        let bits = reduced.to_bits_le(FRACTIONAL_BITS + EXP_INTEGER_BITS)?;
+
+        let fraction = Self::from_repr(
+            bits[..FRACTIONAL_BITS]
+                .iter()
+                .enumerate()
+                .fold(MpcScalar::zero(network, beaver_source), |acc, (i, bit)| {
+                    acc + bit * pow2(i)
+                }),
+        );
+        let exp2_fraction = fraction.evaluate_polynomial(&exp2_coeffs())?;
+
+        // 2^k = \prod_i (1 + k_i * (2^(2^i) - 1)) over the bits k_i of k
+        let factors = bits[FRACTIONAL_BITS..]
+            .iter()
+            .enumerate()
+            .map(|(i, bit)| {
+                (bit * (pow2(1 << i) - Scalar::one())).add_public_constant(Scalar::one())
+            })
+            .collect::<Vec<_>>();
+        let exp2_integer = MpcScalar::prefix_prod(&factors)?.pop().unwrap();
+
+        Ok(
+            Self::batch_mul_reprs(&[exp2_fraction.repr], &[exp2_integer], FRACTIONAL_BITS)?
+                .remove(0),
+        )
+    }
+
+    /// Approximates the natural logarithm of a positive number
+    ///
+    /// The number is reduced as x = m * 2^e with m in [1, 2), where e is the position of the
+    /// most significant bit of the representation less FRACTIONAL_BITS, located as in `sqrt`;
+    /// e * ln 2 is then a public combination of the shared one-hot vector of the position. The
+    /// logarithm of m is 2 * atanh(u) for u = (m - 1) / (m + 1) in [0, 1/3), where 1 / (m + 1)
+    /// is approximated by LN_RECIPROCAL_ITERATIONS Newton-Raphson iterations from 0.4, which
+    /// square its relative error from at most 0.2 to below 2^-37, and the series of atanh(u) is
+    /// truncated after ATANH_TERMS terms. The absolute error of the result is a few multiples
+    /// of 2^-FRACTIONAL_BITS. The result for a number that is not positive is meaningless.
+    ///
+    /// Public numbers are computed locally; returns an `ArithmeticError` for a public number
+    /// that is not positive and a `VisibilityError` for a private number.
+    ///
+    /// Cost: the cost of `to_bits_le` for REPR_BITS bits and of `prefix_prod` over them, of
+    /// 2 * LN_RECIPROCAL_ITERATIONS + 2 multiplications in sequence, each as in `batch_mul`, and
+    /// of `evaluate_polynomial` of degree 2 * ATANH_TERMS - 1
+    pub fn ln(&self) -> Result<Self, MpcError> {
+        if self.repr.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be logarithmized...".to_string(),
+            ));
+        }
+
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+        if self.repr.is_public() {
+            let value = repr_to_f64(&self.repr.value())?;
+            if value <= 0.0 {
+                return Err(MpcError::ArithmeticError(
+                    "cannot take the logarithm of a number that is not positive".to_string(),
+                ));
+            }
+
+            return Ok(Self::from_public_f64(value.ln(), network, beaver_source));
+        }
+
+        // For a most significant bit at j, e = j - FRACTIONAL_BITS; the normalizer is 2^-e,
+        // times 2^FRACTIONAL_BITS
+        let mut normalizer = MpcScalar::zero(network.clone(), beaver_source.clone());
+        let mut exponent = MpcScalar::zero(network.clone(), beaver_source.clone());
+        for (j, is_msb) in self.msb_one_hot()?.iter().enumerate() {
+            normalizer += is_msb * pow2(2 * FRACTIONAL_BITS - j);
+            exponent += is_msb * f64_to_repr((j as f64 - FRACTIONAL_BITS as f64) * LN_2);
+        }
+
+        let normalized = Self::batch_mul_reprs(
+            std::slice::from_ref(&self.repr),
+            &[normalizer],
+            FRACTIONAL_BITS,
+        )?
+        .remove(0);
+
+        let one = Self::from_public_f64(1.0, network, beaver_source);
+        let reciprocal = (&normalized + &one).reciprocal(0.4, LN_RECIPROCAL_ITERATIONS)?;
+        let ratio = (&normalized - &one).mul(&reciprocal)?;
+
+        Ok(&ratio.evaluate_polynomial(&atanh2_coeffs())? + &Self::from_repr(exponent))
+    }
+
+    /// Approximates the logistic sigmoid 1 / (1 + e^-x)
+    ///
+    /// By the symmetry sigmoid(-x) = 1 - sigmoid(x), the sigmoid is computed for
+    /// a = min(|x|, SIGMOID_CLAMP) and flipped if x is negative, as determined with `lt`. The
+    /// reciprocal of 1 + e^-a in (1, 2] is approximated by SIGMOID_RECIPROCAL_ITERATIONS
+    /// Newton-Raphson iterations from 2/3, which square its relative error from at most 1/3 to
+    /// below 2^-50. The absolute error of the result is a few multiples of 2^-FRACTIONAL_BITS.
+    ///
+    /// Public numbers are computed locally; returns a `VisibilityError` for a private number.
+    ///
+    /// Cost: two comparisons, the cost of `exp`, 2 * SIGMOID_RECIPROCAL_ITERATIONS
+    /// multiplications in sequence, each as in `batch_mul`, and two Beaver triplets and rounds
+    /// of communication to apply the sign
+    pub fn sigmoid(&self) -> Result<Self, MpcError> {
+        if self.repr.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be passed through a sigmoid...".to_string(),
+            ));
+        }
+
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+        if self.repr.is_public() {
+            let value = repr_to_f64(&self.repr.value())?;
+            return Ok(Self::from_public_f64(
+                1.0 / (1.0 + (-value).exp()),
+                network,
+                beaver_source,
+            ));
+        }
+
+        let one = Self::from_public_f64(1.0, network.clone(), beaver_source.clone());
+        let zero = Self::from_public_f64(0.0, network.clone(), beaver_source.clone());
+        let negative = self.lt(&zero)?.to_scalar();
+
+        // |x| = x - 2 * s * x for the sign bit s; the product of a bit and a representation is
+        // itself a representation, and needs no rescaling
+        let abs = &self.repr - &(negative.try_mul(&self.repr)? * Scalar::from(2u64));
+        let clamped = Self::from_repr(abs.min(&MpcScalar::from_public_scalar(
+            f64_to_repr(SIGMOID_CLAMP),
+            network,
+            beaver_source,
+        ))?);
+
+        let denominator = &(-&clamped).exp()? + &one;
+        let positive = denominator.reciprocal(2.0 / 3.0, SIGMOID_RECIPROCAL_ITERATIONS)?;
+
+        // sigmoid(x) = r + s * (1 - 2r) for the sigmoid r of |x|
+        let flip = negative.try_mul(&(&one - &(&positive + &positive)).repr)?;
+        Ok(Self::from_repr(&positive.repr + &flip))
+    }
+
+    /// Returns the powers [x, x^2, ..., x^degree] of the number, doubling the known powers with
+    /// each batch of multiplications
+    fn powers(&self, degree: usize) -> Result<Vec<Self>, MpcError> {
+        let mut powers = vec![self.clone()];
+        while powers.len() < degree {
+            // x^(known + i) = x^known * x^i
+            let known = powers.len();
+            let count = (degree - known).min(known);
+            let next = Self::batch_mul(&vec![powers[known - 1].clone(); count], &powers[..count])?;
+            powers.extend(next);
+        }
+
+        Ok(powers)
+    }
+
+    /// Approximates 1 / self by Newton-Raphson iterations y <- y * (2 - self * y) from a public
+    /// initial guess
+    ///
+    /// The relative error 1 - self * y squares with each iteration, so the iteration converges
+    /// if the initial guess is within a relative error of one
+    fn reciprocal(&self, initial: f64, iterations: usize) -> Result<Self, MpcError> {
+        let network = self.repr.network();
+        let beaver_source = self.repr.beaver_source();
+
+        let two = Self::from_public_f64(2.0, network.clone(), beaver_source.clone());
+        let mut reciprocal = Self::from_public_f64(initial, network, beaver_source);
+        for _ in 0..iterations {
+            let error = &two - &self.mul(&reciprocal)?;
+            reciprocal = reciprocal.mul(&error)?;
+        }
+
+        Ok(reciprocal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        beaver::DummySharedScalarSource, mpc_fixed_point::MpcFixedPoint,
+        network::dummy_network::DummyMpcNetwork,
+    };
+
+    use super::{atanh2_coeffs, exp2_coeffs};
+
+    #[test]
+    fn test_approximations() {
+        // The polynomials approximate their functions on the reduced ranges
+        let evaluate =
+            |coeffs: &[f64], x: f64| coeffs.iter().rev().fold(0.0, |acc, coeff| acc * x + coeff);
+        for i in 0..=16 {
+            let f = i as f64 / 16.0;
+            assert!((evaluate(&exp2_coeffs(), f) - 2f64.powf(f)).abs() < 1e-10);
+
+            let u = f / 3.0;
+            assert!((evaluate(&atanh2_coeffs(), u) - 2.0 * u.atanh()).abs() < 1e-10);
+        }
+
+        // Public numbers are computed locally
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let x = MpcFixedPoint::from_public_f64(0.5, network, beaver_source);
+        assert_eq!(
+            x.evaluate_polynomial(&[1.0, 2.0, 4.0])
+                .unwrap()
+                .open()
+                .unwrap(),
+            3.0
+        );
+        assert!((x.exp().unwrap().open().unwrap() - 0.5f64.exp()).abs() < 1e-9);
+        assert!((x.ln().unwrap().open().unwrap() - 0.5f64.ln()).abs() < 1e-9);
+        assert!(
+            (x.sigmoid().unwrap().open().unwrap() - 1.0 / (1.0 + (-0.5f64).exp())).abs() < 1e-9
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Groups the definitions of a matrix of scalars allocated in an MPC network, and the
+//! multiplication of shared matrices with matrix triplets
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::{MpcError, MpcNetworkError},
+    mpc_scalar::MpcScalar,
+    network::MpcNetwork,
+    Visibility,
+};
+
+/// Represents a matrix of scalars allocated in an MPC network, stored in row-major order
+#[derive(Debug)]
+pub struct MpcMatrix<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The number of rows in the matrix
+    rows: usize,
+    /// The number of columns in the matrix
+    cols: usize,
+    /// The entries of the matrix in row-major order
+    entries: Vec<MpcScalar<N, S>>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcMatrix<N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcMatrix<N, S> {
+    /// Creates a matrix from its entries in row-major order
+    ///
+    /// Panics if the matrix is empty or the number of entries does not match its shape
+    pub fn new(rows: usize, cols: usize, entries: Vec<MpcScalar<N, S>>) -> Self {
+        assert!(
+            rows > 0 && cols > 0,
+            "a matrix must have at least one row and column"
+        );
+        assert_eq!(
+            entries.len(),
+            rows * cols,
+            "a {}x{} matrix must have {} entries",
+            rows,
+            cols,
+            rows * cols
+        );
+
+        Self {
+            rows,
+            cols,
+            entries,
+        }
+    }
+
+    /// Creates a matrix from its rows
+    ///
+    /// Panics if the matrix is empty or the rows are not all of the same length
+    pub fn from_rows(rows: Vec<Vec<MpcScalar<N, S>>>) -> Self {
+        let num_rows = rows.len();
+        let num_cols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == num_cols),
+            "every row of a matrix must have the same length"
+        );
+
+        Self::new(num_rows, num_cols, rows.into_iter().flatten().collect())
+    }
+
+    /// Returns the number of rows in the matrix
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns in the matrix
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the entries of the matrix in row-major order
+    pub fn entries(&self) -> &[MpcScalar<N, S>] {
+        &self.entries
+    }
+
+    /// Returns the entry at the given row and column
+    pub fn get(&self, row: usize, col: usize) -> &MpcScalar<N, S> {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        &self.entries[row * self.cols + col]
+    }
+
+    /// Consumes the matrix, returning its entries in row-major order
+    pub fn into_entries(self) -> Vec<MpcScalar<N, S>> {
+        self.entries
+    }
+
+    /// Returns the rows of the matrix
+    pub fn to_rows(&self) -> Vec<Vec<MpcScalar<N, S>>> {
+        self.entries
+            .chunks_exact(self.cols)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// Returns true if every entry of the matrix is public
+    pub(crate) fn is_public(&self) -> bool {
+        self.entries.iter().all(|entry| entry.is_public())
+    }
+
+    /// Opens every entry of the matrix in a single round of communication
+    pub fn open(&self) -> Result<MpcMatrix<N, S>, MpcNetworkError> {
+        Ok(Self::new(
+            self.rows,
+            self.cols,
+            MpcScalar::batch_open(&self.entries)?,
+        ))
+    }
+
+    /// Returns the transpose of the matrix, computed locally
+    pub fn transpose(&self) -> MpcMatrix<N, S> {
+        let entries = (0..self.rows * self.cols)
+            .map(|index| {
+                let (row, col) = (index / self.rows, index % self.rows);
+                self.get(col, row).clone()
+            })
+            .collect_vec();
+
+        Self::new(self.cols, self.rows, entries)
+    }
+
+    /// Adds the matrix to another entry-wise, computed locally
+    ///
+    /// Returns an `ArithmeticError` if the shapes of the matrices do not match
+    pub fn add(&self, rhs: &MpcMatrix<N, S>) -> Result<MpcMatrix<N, S>, MpcError> {
+        self.check_same_shape(rhs, "add")?;
+        let entries = self
+            .entries
+            .iter()
+            .zip(rhs.entries.iter())
+            .map(|(lhs, rhs)| lhs + rhs)
+            .collect_vec();
+
+        Ok(Self::new(self.rows, self.cols, entries))
+    }
+
+    /// Subtracts another matrix from the matrix entry-wise, computed locally
+    ///
+    /// Returns an `ArithmeticError` if the shapes of the matrices do not match
+    pub fn sub(&self, rhs: &MpcMatrix<N, S>) -> Result<MpcMatrix<N, S>, MpcError> {
+        self.check_same_shape(rhs, "subtract")?;
+        let entries = self
+            .entries
+            .iter()
+            .zip(rhs.entries.iter())
+            .map(|(lhs, rhs)| lhs - rhs)
+            .collect_vec();
+
+        Ok(Self::new(self.rows, self.cols, entries))
+    }
+
+    /// Multiplies every entry of the matrix by a (possibly shared) scalar
+    ///
+    /// Cost: one Beaver triplet per shared entry if the scalar is shared, and one round of
+    /// communication for the whole matrix
+    pub fn scale(&self, scalar: &MpcScalar<N, S>) -> Result<MpcMatrix<N, S>, MpcError> {
+        let scalars = vec![scalar.clone(); self.entries.len()];
+        let entries = MpcScalar::try_batch_mul(&self.entries, &scalars)?;
+
+        Ok(Self::new(self.rows, self.cols, entries))
+    }
+
+    /// Multiplies the matrix by another entry-wise
+    ///
+    /// Returns an `ArithmeticError` if the shapes of the matrices do not match.
+    ///
+    /// Cost: one Beaver triplet per product of shared entries, and one round of communication
+    /// for the whole matrix
+    pub fn hadamard(&self, rhs: &MpcMatrix<N, S>) -> Result<MpcMatrix<N, S>, MpcError> {
+        self.check_same_shape(rhs, "multiply entry-wise")?;
+        let entries = MpcScalar::try_batch_mul(&self.entries, &rhs.entries)?;
+
+        Ok(Self::new(self.rows, self.cols, entries))
+    }
+
+    /// Multiplies the matrix by another, consuming a single matrix triplet from the Beaver
+    /// source rather than a Beaver triplet per scalar product
+    ///
+    /// For shared matrices X and Y and a matrix triplet (A, B, C = A * B) the parties open
+    /// D = X - A and E = Y - B, after which X * Y = D * E + D * B + A * E + C is computed
+    /// locally. A product with a public matrix is computed locally. Returns an
+    /// `ArithmeticError` if the shapes of the matrices do not match, and a `PreprocessingError`
+    /// if the Beaver source does not supply matrix triplets.
+    ///
+    /// Cost: one matrix triplet and one round of communication opening n * m + m * k values,
+    /// for the product of an n x m matrix with an m x k matrix
+    pub fn mul(&self, rhs: &MpcMatrix<N, S>) -> Result<MpcMatrix<N, S>, MpcError> {
+        if self.cols != rhs.rows {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot multiply a {}x{} matrix by a {}x{} matrix",
+                self.rows, self.cols, rhs.rows, rhs.cols
+            )));
+        }
+
+        let (n, m, k) = (self.rows, self.cols, rhs.cols);
+        if self.is_public() || rhs.is_public() {
+            let entries = (0..n * k)
+                .map(|index| {
+                    let (row, col) = (index / k, index % k);
+                    (0..m).map(|j| self.get(row, j) * rhs.get(j, col)).sum()
+                })
+                .collect_vec();
+
+            return Ok(Self::new(n, k, entries));
+        }
+
+        let network = self.entries[0].network();
+        let beaver_source = self.entries[0].beaver_source();
+        let (a, b, c) = beaver_source
+            .as_ref()
+            .borrow_mut()
+            .next_matrix_triplet(n, m, k)?;
+        if a.len() != n * m || b.len() != m * k || c.len() != n * k {
+            return Err(MpcError::PreprocessingError(format!(
+                "the Beaver source returned a matrix triplet of the wrong shape for {}x{}x{}",
+                n, m, k
+            )));
+        }
+
+        // Open D = X - A and E = Y - B together
+        let masked = self
+            .entries
+            .iter()
+            .zip(a.iter())
+            .chain(rhs.entries.iter().zip(b.iter()))
+            .map(|(value, mask)| {
+                value
+                    - MpcScalar::from_scalar_with_visibility(
+                        *mask,
+                        Visibility::Shared,
+                        network.clone(),
+                        beaver_source.clone(),
+                    )
+            })
+            .collect_vec();
+        let opened = MpcScalar::batch_open(&masked)
+            .map_err(MpcError::NetworkError)?
+            .iter()
+            .map(|value| value.value())
+            .collect_vec();
+        let (d, e) = opened.split_at(n * m);
+
+        // Identity: [X * Y] = D * E + D * [B] + [A] * E + [C], the king adds the public D * E
+        let entries = (0..n * k)
+            .map(|index| {
+                let (row, col) = (index / k, index % k);
+                let (mut share, mut public) = (c[index], Scalar::zero());
+                for j in 0..m {
+                    let (d_entry, e_entry) = (d[row * m + j], e[j * k + col]);
+                    share += d_entry * b[j * k + col] + a[row * m + j] * e_entry;
+                    public += d_entry * e_entry;
+                }
+
+                MpcScalar::from_scalar_with_visibility(
+                    share,
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+                .add_public_constant(public)
+            })
+            .collect_vec();
+
+        Ok(Self::new(n, k, entries))
+    }
+
+    /// Returns an `ArithmeticError` naming the operation if the matrices differ in shape
+    fn check_same_shape(&self, rhs: &MpcMatrix<N, S>, operation: &str) -> Result<(), MpcError> {
+        if (self.rows, self.cols) != (rhs.rows, rhs.cols) {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot {} a {}x{} matrix and a {}x{} matrix",
+                operation, self.rows, self.cols, rhs.rows, rhs.cols
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> From<MpcMatrix<N, S>>
+    for Vec<MpcScalar<N, S>>
+{
+    fn from(matrix: MpcMatrix<N, S>) -> Self {
+        matrix.into_entries()
+    }
+}
@@ -1,5 +1,7 @@
 //! Groups the definitions and trait implementations for a Ristretto point within the MPC net
 
+pub mod multi_party;
+
 use std::{
     borrow::Borrow,
     ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
@@ -12,19 +14,23 @@ use curve25519_dalek::{
     scalar::Scalar,
     traits::{Identity, IsIdentity, MultiscalarMul},
 };
-use futures::executor::block_on;
 use itertools::izip;
 use rand_core::{CryptoRng, OsRng, RngCore};
 use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+#[cfg(feature = "zeroize-on-drop")]
+use zeroize::ZeroizeOnDrop;
 
 use crate::{
-    beaver::SharedValueSource,
+    beaver::{to_point_triplet, PointTriplet, SharedValueSource},
+    block_on,
     commitment::RistrettoCommitment,
     error::{MpcError, MpcNetworkError},
     macros,
     mpc_scalar::MpcScalar,
-    network::MpcNetwork,
-    BeaverSource, SharedNetwork, Visibility, Visible,
+    network::{DynMpcNetwork, MpcNetwork, Payload},
+    share::PointShare,
+    BeaverSource, SessionId, SharedNetwork, Visibility, Visible,
 };
 
 /// Represents a Ristretto point that has been allocated in the MPC network
@@ -40,6 +46,16 @@ pub struct MpcRistrettoPoint<N: MpcNetwork + Send, S: SharedValueSource<Scalar>>
     beaver_source: BeaverSource<S>,
 }
 
+/// An `MpcRistrettoPoint` over a network chosen at runtime
+pub type DynMpcRistrettoPoint<S> = MpcRistrettoPoint<DynMpcNetwork, S>;
+
+/// A party's shares of a point triplet (a, bG, cG) allocated in the network
+type PointTripletShares<N, S> = (
+    MpcScalar<N, S>,
+    MpcRistrettoPoint<N, S>,
+    MpcRistrettoPoint<N, S>,
+);
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcRistrettoPoint<N, S> {
     fn clone(&self) -> Self {
         Self {
@@ -51,7 +67,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcRistrettoP
     }
 }
 
-/**
+/*
  * Static and helper methods
  */
 
@@ -78,6 +94,18 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
     /// point. The local party gives R to the peer, and holds a - R for herself.
     /// This method is called by both parties, only one of which transmits
     pub fn share_secret(&self, party_id: u64) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
+        block_on(self.share_secret_async(party_id))
+    }
+
+    /// Awaits the network rather than blocking on it, see `share_secret`
+    ///
+    /// The async methods of values that share a network must be awaited in sequence rather
+    /// than polled concurrently; an operation started while another is pending on the same
+    /// network fails with `MpcNetworkError::NetworkBusy`
+    pub async fn share_secret_async(
+        &self,
+        party_id: u64,
+    ) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
         assert!(self.is_private(), "Only private values may be shared...");
         let my_party_id = self.network.as_ref().borrow().party_id();
 
@@ -87,12 +115,10 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
             let random_share = RistrettoPoint::random(&mut rng);
 
             // Broadcast the peer's share
-            block_on(
-                self.network
-                    .as_ref()
-                    .borrow_mut()
-                    .send_single_point(random_share),
-            )?;
+            crate::await_network(&self.network, |network| {
+                network.send_single_point(random_share)
+            })
+            .await?;
 
             // Local party takes a - R
             Ok(MpcRistrettoPoint {
@@ -103,7 +129,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
             })
         } else {
             // Receive a secret share from the peer
-            Self::receive_value(self.network.clone(), self.beaver_source.clone())
+            Self::receive_value_async(self.network.clone(), self.beaver_source.clone()).await
         }
     }
 
@@ -112,6 +138,17 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         party_id: u64,
         values: &[MpcRistrettoPoint<N, S>],
     ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_share_secrets_async(party_id, values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_share_secrets`
+    pub async fn batch_share_secrets_async(
+        party_id: u64,
+        values: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(!values.is_empty(), "Cannot batch share an empty vector");
         assert!(
             values.iter().all(|value| value.is_private()),
@@ -129,7 +166,11 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
                 .collect::<Vec<RistrettoPoint>>();
 
             // Broadcast the peer's share
-            block_on(network.as_ref().borrow_mut().send_points(&random_shares))?;
+            let shares_sent = random_shares.clone();
+            crate::await_network(&network, move |network| {
+                Box::pin(async move { network.send_points(&shares_sent).await })
+            })
+            .await?;
 
             // Local party takes the share a - R for each a
             Ok(values
@@ -143,11 +184,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
                 })
                 .collect())
         } else {
-            Ok(Self::batch_receive_values(
-                values.len(),
-                network,
-                beaver_source,
-            )?)
+            Self::batch_receive_values_async(values.len(), network, beaver_source).await
         }
     }
 
@@ -156,7 +193,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         network: SharedNetwork<N>,
         beaver_source: BeaverSource<S>,
     ) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
-        let value = block_on(network.as_ref().borrow_mut().receive_single_point())?;
+        block_on(Self::receive_value_async(network, beaver_source))
+    }
+
+    /// Awaits the network rather than blocking on it, see `receive_value`
+    pub async fn receive_value_async(
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
+        let value =
+            crate::await_network(&network, |network| network.receive_single_point()).await?;
 
         Ok(MpcRistrettoPoint {
             value,
@@ -172,7 +218,21 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         network: SharedNetwork<N>,
         beaver_source: BeaverSource<S>,
     ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
-        let values = block_on(network.as_ref().borrow_mut().receive_points(num_expected))?;
+        block_on(Self::batch_receive_values_async(
+            num_expected,
+            network,
+            beaver_source,
+        ))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_receive_values`
+    pub async fn batch_receive_values_async(
+        num_expected: usize,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        let values =
+            crate::await_network(&network, |network| network.receive_points(num_expected)).await?;
 
         Ok(values
             .into_iter()
@@ -189,30 +249,56 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
     /// The result is the sum of the shares of both parties and is a public value, so the result is no longer
     /// and additive secret sharing of some underlying Ristretto point
     pub fn open(&self) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
+        block_on(self.open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open`
+    pub async fn open_async(&self) -> Result<MpcRistrettoPoint<N, S>, MpcNetworkError> {
         assert!(!self.is_private(), "Private values may not be opened...");
         if self.is_public() {
             return Ok(self.clone());
         }
         // Send a Ristretto point and receive one in return
-        let received_point = block_on(
-            self.network
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_point(self.value()),
-        )?;
+        let my_share = self.value();
+        let received_point = crate::await_network(&self.network, |network| {
+            network.broadcast_single_point(my_share)
+        })
+        .await?;
 
-        Ok(MpcRistrettoPoint {
+        let opened_value = MpcRistrettoPoint {
             value: received_point + self.value(),
             visibility: Visibility::Public,
             network: self.network.clone(),
             beaver_source: self.beaver_source.clone(),
-        })
+        };
+        Self::record_openings(
+            std::slice::from_ref(self),
+            std::slice::from_ref(&opened_value),
+        );
+
+        Ok(opened_value)
+    }
+
+    /// Open the value and return the bytes of its compressed encoding, e.g. for absorbing
+    /// the opened point into a transcript
+    pub fn open_to_bytes(&self) -> Result<[u8; 32], MpcNetworkError> {
+        Ok(*self.open()?.compress().as_bytes())
     }
 
     /// Open a batch of `MpcRistrettoPoint`s
     pub fn batch_open(
         values: &[MpcRistrettoPoint<N, S>],
     ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open`
+    pub async fn batch_open_async(
+        values: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(!values.is_empty(), "Cannot open an empty vector of values");
         assert!(
             values.iter().all(|value| !value.is_private()),
@@ -223,16 +309,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         let beaver_source = values[0].beaver_source();
 
         // Both parties share their values
-        let received_points = block_on(
-            network.as_ref().borrow_mut().broadcast_points(
-                &values
-                    .iter()
-                    .map(|value| value.value())
-                    .collect::<Vec<RistrettoPoint>>(),
-            ),
-        )?;
+        let my_shares = values
+            .iter()
+            .map(|value| value.value())
+            .collect::<Vec<RistrettoPoint>>();
+        let received_points = crate::await_network(&network, move |network| {
+            Box::pin(async move { network.broadcast_points(&my_shares).await })
+        })
+        .await?;
 
-        Ok(values
+        let opened_values = values
             .iter()
             .zip(received_points.iter())
             .map(|(my_share, peer_share)| {
@@ -246,61 +332,99 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
                     beaver_source.clone(),
                 )
             })
-            .collect())
+            .collect::<Vec<_>>();
+        Self::record_openings(values, &opened_values);
+
+        Ok(opened_values)
+    }
+
+    /// Absorbs the compressed encodings of the newly opened values into the session's output
+    /// digest; values that were public before the opening are skipped
+    fn record_openings(
+        values: &[MpcRistrettoPoint<N, S>],
+        opened_values: &[MpcRistrettoPoint<N, S>],
+    ) {
+        for (value, opened_value) in values.iter().zip(opened_values.iter()) {
+            if value.is_shared() {
+                value
+                    .network
+                    .as_ref()
+                    .borrow_mut()
+                    .record_opening(opened_value.value.compress().as_bytes());
+            }
+        }
     }
 
     /// From a shared value:
     ///     1. Each party commits to their share of the underlying value
     ///     2. The parties exchange openings and verify the peer's opening
     pub fn commit_and_open(&self) -> Result<MpcRistrettoPoint<N, S>, MpcError> {
+        block_on(self.commit_and_open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `commit_and_open`
+    pub async fn commit_and_open_async(&self) -> Result<MpcRistrettoPoint<N, S>, MpcError> {
         assert!(!self.is_private(), "Private values may not be opened");
         if self.is_public() {
             return Ok(self.clone());
         }
 
+        // Exchange commitments to the values, then open them to their blinding and value
         let commitment = RistrettoCommitment::commit(self.value());
-        let peer_commitment = block_on(
-            self.network()
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_scalar(commitment.get_commitment()),
-        )
-        .map_err(MpcError::NetworkError)?;
-
-        // Open the commitment to the underlying value
-        let peer_blinding = block_on(
-            self.network
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_scalar(commitment.get_blinding()),
-        )
+        let my_commitment = Payload {
+            scalars: vec![commitment.get_commitment()],
+            ..Default::default()
+        };
+        let my_opening = Payload {
+            scalars: vec![commitment.get_blinding()],
+            points: vec![commitment.get_value()],
+        };
+        let (peer_commitment, peer_opening) = crate::await_network(&self.network, move |network| {
+            Box::pin(async move {
+                network
+                    .exchange_commitments(&my_commitment, &my_opening)
+                    .await
+            })
+        })
+        .await
         .map_err(MpcError::NetworkError)?;
 
-        let peer_value = block_on(
-            self.network
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_point(commitment.get_value()),
-        )
-        .map_err(MpcError::NetworkError)?;
+        let peer_commitment = peer_commitment.scalars[0];
+        let (peer_blinding, peer_value) = (peer_opening.scalars[0], peer_opening.points[0]);
 
         // Verify the commitment and return the opened value
         if !RistrettoCommitment::verify_from_values(peer_commitment, peer_blinding, peer_value) {
             return Err(MpcError::AuthenticationError);
         }
 
-        Ok(Self {
+        let opened_value = Self {
             value: self.value() + peer_value,
             visibility: Visibility::Public,
             network: self.network(),
             beaver_source: self.beaver_source(),
-        })
+        };
+        Self::record_openings(
+            std::slice::from_ref(self),
+            std::slice::from_ref(&opened_value),
+        );
+
+        Ok(opened_value)
     }
 
     /// Commit to and open a batch of secret shared values
     pub fn batch_commit_and_open(
         values: &[MpcRistrettoPoint<N, S>],
     ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcError> {
+        block_on(Self::batch_commit_and_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_commit_and_open`
+    pub async fn batch_commit_and_open_async(
+        values: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(
             !values.is_empty(),
             "Cannot batch commit and open an empty vector"
@@ -313,42 +437,38 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         let network = values[0].network();
         let beaver_source = values[0].beaver_source();
 
-        // Generate commitments to the points and share them with the peer
+        // Generate commitments to the points, then exchange them and open them to their
+        // blinding factors and points
         let commitments: Vec<RistrettoCommitment> = values
             .iter()
             .map(|value| RistrettoCommitment::commit(value.value()))
             .collect();
-        let peer_commitments = block_on(
-            network.as_ref().borrow_mut().broadcast_scalars(
-                &commitments
-                    .iter()
-                    .map(|comm| comm.get_commitment())
-                    .collect::<Vec<Scalar>>(),
-            ),
-        )
-        .map_err(MpcError::NetworkError)?;
-
-        // Peers open the blinding factors for the commitments
-        let peer_blinding = block_on(
-            network.as_ref().borrow_mut().broadcast_scalars(
-                &commitments
-                    .iter()
-                    .map(|comm| comm.get_blinding())
-                    .collect::<Vec<Scalar>>(),
-            ),
-        )
-        .map_err(MpcError::NetworkError)?;
+        let my_commitments = Payload {
+            scalars: commitments
+                .iter()
+                .map(|comm| comm.get_commitment())
+                .collect(),
+            ..Default::default()
+        };
+        let my_openings = Payload {
+            scalars: commitments.iter().map(|comm| comm.get_blinding()).collect(),
+            points: commitments.iter().map(|comm| comm.get_value()).collect(),
+        };
 
-        // Peers open the points they committed to
-        let peer_points = block_on(
-            network.as_ref().borrow_mut().broadcast_points(
-                &commitments
-                    .iter()
-                    .map(|comm| comm.get_value())
-                    .collect::<Vec<RistrettoPoint>>(),
-            ),
-        )
+        let (peer_commitments, peer_openings) = crate::await_network(&network, move |network| {
+            Box::pin(async move {
+                network
+                    .exchange_commitments(&my_commitments, &my_openings)
+                    .await
+            })
+        })
+        .await
         .map_err(MpcError::NetworkError)?;
+        let (peer_commitments, peer_blinding, peer_points) = (
+            peer_commitments.scalars,
+            peer_openings.scalars,
+            peer_openings.points,
+        );
 
         // Verify the commitments
         izip!(
@@ -365,7 +485,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         })?;
 
         // Lastly, add the peer's shares to the local share for the final opened result
-        Ok(values
+        let opened_values = values
             .iter()
             .zip(peer_points)
             .map(|(my_value, peer_value)| {
@@ -379,34 +499,83 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
                     beaver_source.clone(),
                 )
             })
-            .collect())
+            .collect::<Vec<_>>();
+        Self::record_openings(values, &opened_values);
+
+        Ok(opened_values)
     }
 
-    /// Fetch the next Beaver triplet from the source and cast them as MpcScalars
-    /// We leave them as scalars because some are directly used as scalars for Mul
-    fn next_beaver_triplet(&self) -> (MpcScalar<N, S>, MpcScalar<N, S>, MpcScalar<N, S>) {
-        let (a, b, c) = self.beaver_source.as_ref().borrow_mut().next_triplet();
+    /// Fetch a batch of point triplets (a, bG, cG) from the source, deriving them from Beaver
+    /// triplets if the source does not supply them
+    fn next_point_triplet_batch(&self, num_triplets: usize) -> Vec<PointTripletShares<N, S>> {
+        let triplet_batch = {
+            let mut beaver_source = self.beaver_source.as_ref().borrow_mut();
+            beaver_source
+                .next_point_triplet_batch(num_triplets)
+                .unwrap_or_else(|| {
+                    beaver_source
+                        .next_triplet_batch(num_triplets)
+                        .into_iter()
+                        .map(to_point_triplet)
+                        .collect()
+                })
+        };
 
-        (
-            MpcScalar::from_scalar_with_visibility(
-                a,
-                Visibility::Shared,
-                self.network.clone(),
-                self.beaver_source.clone(),
-            ),
-            MpcScalar::from_scalar_with_visibility(
-                b,
-                Visibility::Shared,
-                self.network.clone(),
-                self.beaver_source.clone(),
-            ),
-            MpcScalar::from_scalar_with_visibility(
-                c,
-                Visibility::Shared,
-                self.network.clone(),
-                self.beaver_source.clone(),
-            ),
-        )
+        self.allocate_point_triplets(triplet_batch)
+    }
+
+    /// Fetch a batch of point triplets as `next_point_triplet_batch`, returning an error if the
+    /// source cannot supply the batch
+    fn try_next_point_triplet_batch(
+        &self,
+        num_triplets: usize,
+    ) -> Result<Vec<PointTripletShares<N, S>>, MpcError> {
+        let triplet_batch = {
+            let mut beaver_source = self.beaver_source.as_ref().borrow_mut();
+            match beaver_source.next_point_triplet_batch(num_triplets) {
+                Some(triplet_batch) => triplet_batch,
+                None => beaver_source
+                    .try_next_triplet_batch(num_triplets)?
+                    .into_iter()
+                    .map(to_point_triplet)
+                    .collect(),
+            }
+        };
+
+        Ok(self.allocate_point_triplets(triplet_batch))
+    }
+
+    /// Cast point triplets drawn from the source as a shared MpcScalar and MpcRistrettoPoints
+    #[allow(non_snake_case)]
+    fn allocate_point_triplets(
+        &self,
+        triplet_batch: Vec<PointTriplet<Scalar>>,
+    ) -> Vec<PointTripletShares<N, S>> {
+        triplet_batch
+            .into_iter()
+            .map(|(a, bG, cG)| {
+                (
+                    MpcScalar::from_scalar_with_visibility(
+                        a,
+                        Visibility::Shared,
+                        self.network.clone(),
+                        self.beaver_source.clone(),
+                    ),
+                    MpcRistrettoPoint {
+                        value: bG,
+                        visibility: Visibility::Shared,
+                        network: self.network.clone(),
+                        beaver_source: self.beaver_source.clone(),
+                    },
+                    MpcRistrettoPoint {
+                        value: cG,
+                        visibility: Visibility::Shared,
+                        network: self.network.clone(),
+                        beaver_source: self.beaver_source.clone(),
+                    },
+                )
+            })
+            .collect()
     }
 }
 
@@ -427,6 +596,12 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         self.network.clone()
     }
 
+    /// Returns the ID of the session the value was allocated in
+    #[inline]
+    pub fn session_id(&self) -> SessionId {
+        SessionId::of(&self.network)
+    }
+
     #[inline]
     pub(crate) fn beaver_source(&self) -> BeaverSource<S> {
         self.beaver_source.clone()
@@ -543,6 +718,30 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         Self::from_ristretto_point_with_visibility(a, Visibility::Private, network, beaver_source)
     }
 
+    /// Create a trivial additive sharing of a public RistrettoPoint, in which the king holds
+    /// the point and the peer holds the identity
+    ///
+    /// Unlike `from_public_ristretto_point`, the result is tagged `Shared`, so it may be fed
+    /// directly into operations that expect shared inputs
+    pub fn shared_from_public(
+        point: RistrettoPoint,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        let share = if network.as_ref().borrow().am_king() {
+            point
+        } else {
+            RistrettoPoint::identity()
+        };
+
+        Self::from_ristretto_point_with_visibility(
+            share,
+            Visibility::Shared,
+            network,
+            beaver_source,
+        )
+    }
+
     /// Create a wrapper around an existing Ristretto point with visibility specified
     pub(crate) fn from_ristretto_point_with_visibility(
         a: RistrettoPoint,
@@ -558,6 +757,31 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
         }
     }
 
+    /// Detaches the party's share of the point from the network and value source, e.g. to
+    /// persist it or route it over another transport
+    pub fn detach(&self) -> PointShare {
+        PointShare {
+            value: self.value,
+            visibility: self.visibility,
+            mac_share: None,
+        }
+    }
+
+    /// Attaches a detached share to a network and value source; the share of a MAC, if any, is
+    /// dropped, see `AuthenticatedRistretto::attach` to keep it
+    pub fn attach(
+        share: PointShare,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self::from_ristretto_point_with_visibility(
+            share.value,
+            share.visibility,
+            network,
+            beaver_source,
+        )
+    }
+
     /// Create a random ristretto point
     pub fn random<R: RngCore + CryptoRng>(
         rng: &mut R,
@@ -619,7 +843,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S>
     }
 }
 
-/**
+/*
  * Generic Trait Implementations
  */
 
@@ -652,7 +876,24 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clear for MpcRistrettoP
     }
 }
 
-/**
+/// Zeroizing a point leaves the identity in its place
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Zeroize for MpcRistrettoPoint<N, S> {
+    fn zeroize(&mut self) {
+        self.value.clear();
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Drop for MpcRistrettoPoint<N, S> {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> ZeroizeOnDrop for MpcRistrettoPoint<N, S> {}
+
+/*
  * Mul and variants for borrowed, non-borrowed values
  */
 
@@ -672,35 +913,23 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a MpcScalar<N
     /// implementation makes use of.
     #[allow(non_snake_case)]
     fn mul(self, rhs: &'a MpcScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         if self.is_shared() && rhs.is_shared() {
-            let (a, b, c) = self.next_beaver_triplet();
+            let (a, bG, cG) = self.next_point_triplet_batch(1).remove(0);
 
             // Compute \alpha * \betaG for generator point G. As far as the interface is concerned:
             // self = \betaG, rhs = \alpha
             // Open the value d = [\alpha - a].open()
             let alpha_minus_a = (rhs - &a).open().unwrap();
             // Opem the value eG = [\betaG - bG].open(); where G is the Ristretto base point
-            let beta_minus_b = (self - MpcRistrettoPoint::<N, S>::base_point_mul(b.value()))
-                .open()
-                .unwrap();
+            let beta_minus_b = (self - &bG).open().unwrap();
 
             // Identity [a * bG] = deG + d[bG] + [a]eG + [c]G
             // To construct the secret share, only the king will add the deG term
             // All multiplications here are between a shared value and a public value or
             // two public values; so the recursion will not hit this case
-            let bG = MpcRistrettoPoint {
-                value: MpcRistrettoPoint::<N, S>::base_point_mul(b.value()),
-                visibility: Visibility::Shared,
-                network: self.network.clone(),
-                beaver_source: self.beaver_source.clone(),
-            };
-            let cG = MpcRistrettoPoint {
-                value: MpcRistrettoPoint::<N, S>::base_point_mul(c.value()),
-                visibility: Visibility::Shared,
-                network: self.network.clone(),
-                beaver_source: self.beaver_source.clone(),
-            };
-
             let mut res = &alpha_minus_a * bG + &a * &beta_minus_b + cG;
 
             if self.network.as_ref().borrow().am_king() {
@@ -757,6 +986,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a MpcRistrett
     type Output = MpcRistrettoPoint<N, S>;
 
     fn add(self, rhs: &'a MpcRistrettoPoint<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         // If public + shared, swap the arguments for simplicity
         if self.is_public() && rhs.is_shared() {
             return rhs + self;
@@ -797,6 +1029,34 @@ macros::impl_wrapper_type!(MpcRistrettoPoint<N, S>, RistrettoPoint, MpcRistretto
 macros::impl_arithmetic_assign!(MpcRistrettoPoint<N, S>, AddAssign, add_assign, +, MpcRistrettoPoint<N, S>);
 macros::impl_arithmetic_assign!(MpcRistrettoPoint<N, S>, AddAssign, add_assign, +, RistrettoPoint);
 
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S> {
+    /// Adds a public point, known to both parties, to the value
+    ///
+    /// For a public value both parties add the point. For a shared value only the king adds the
+    /// point to its share, so that the shares sum to the value plus the point; this is the
+    /// same addition performed implicitly by `shared + public`. For a private value, the party
+    /// holding the value adds the point
+    pub fn add_public_point(&self, point: RistrettoPoint) -> MpcRistrettoPoint<N, S> {
+        let add_locally = match self.visibility {
+            Visibility::Public | Visibility::Private => true,
+            Visibility::Shared => self.network.as_ref().borrow().am_king(),
+        };
+
+        let value = if add_locally {
+            self.value + point
+        } else {
+            self.value
+        };
+
+        Self {
+            value,
+            visibility: self.visibility,
+            network: self.network.clone(),
+            beaver_source: self.beaver_source.clone(),
+        }
+    }
+}
+
 /**
  * Sub and variants for borrowed, non-borrowed values
  */
@@ -826,7 +1086,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Neg for MpcRistrettoPoi
     }
 }
 
-impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Neg for &'a MpcRistrettoPoint<N, S> {
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Neg for &MpcRistrettoPoint<N, S> {
     type Output = MpcRistrettoPoint<N, S>;
 
     fn neg(self) -> Self::Output {
@@ -862,13 +1122,319 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MultiscalarMul
             (first_elem.network.clone(), first_elem.beaver_source.clone())
         };
 
-        scalars.into_iter().zip(peekable_points.into_iter()).fold(
+        scalars.into_iter().zip(peekable_points).fold(
             MpcRistrettoPoint::identity(network, beaver_source),
             |acc, pair| acc + pair.0.borrow() * pair.1.borrow(), // Pair is a 2-tuple of (c_i, P_i)
         )
     }
 }
 
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcRistrettoPoint<N, S> {
+    /// Returns the result [a_1 * P_1, ..., a_n * P_n] for (possibly shared) scalars a_i
+    /// and (possibly shared) points P_i
+    ///
+    /// The Beaver openings for all shared * shared products are batched, so this method
+    /// uses two rounds of communication (one for the scalars, one for the points)
+    /// regardless of the number of products
+    pub fn batch_mul(
+        scalars: &[MpcScalar<N, S>],
+        points: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_mul_async(scalars, points))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_mul`
+    pub async fn batch_mul_async(
+        scalars: &[MpcScalar<N, S>],
+        points: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            scalars
+                .iter()
+                .map(|value| value.session_id())
+                .chain(points.iter().map(|value| value.session_id())),
+        );
+
+        let num_beaver_muls = Self::num_beaver_muls(scalars, points);
+        let beaver_triplets = match points.first() {
+            Some(point) => point.next_point_triplet_batch(num_beaver_muls),
+            None => Vec::new(),
+        };
+
+        Self::batch_mul_with_triplets(scalars, points, beaver_triplets).await
+    }
+
+    /// Returns the result [a_1 * P_1, ..., a_n * P_n] as `batch_mul`, or a
+    /// `PreprocessingExhausted` error rather than panicking if the Beaver source cannot supply
+    /// the triplets
+    pub fn try_batch_mul(
+        scalars: &[MpcScalar<N, S>],
+        points: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcError> {
+        block_on(Self::try_batch_mul_async(scalars, points))
+    }
+
+    /// Awaits the network rather than blocking on it, see `try_batch_mul`
+    pub async fn try_batch_mul_async(
+        scalars: &[MpcScalar<N, S>],
+        points: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            scalars
+                .iter()
+                .map(|value| value.session_id())
+                .chain(points.iter().map(|value| value.session_id())),
+        );
+
+        let num_beaver_muls = Self::num_beaver_muls(scalars, points);
+        let beaver_triplets = match points.first() {
+            Some(point) => point.try_next_point_triplet_batch(num_beaver_muls)?,
+            None => Vec::new(),
+        };
+
+        Self::batch_mul_with_triplets(scalars, points, beaver_triplets)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    /// Asserts that the inputs to a batch multiplication are of equal length, and counts the
+    /// products that require a Beaver triplet
+    fn num_beaver_muls(scalars: &[MpcScalar<N, S>], points: &[MpcRistrettoPoint<N, S>]) -> usize {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "input arrays to batch_mul must be of equal length"
+        );
+
+        scalars
+            .iter()
+            .zip(points.iter())
+            .filter(|(scalar, point)| scalar.is_shared() && point.is_shared())
+            .count()
+    }
+
+    /// Multiplies the scalars and points with one of the given triplets for each product of two
+    /// shared values
+    #[allow(non_snake_case)]
+    async fn batch_mul_with_triplets(
+        scalars: &[MpcScalar<N, S>],
+        points: &[MpcRistrettoPoint<N, S>],
+        point_triplets: Vec<PointTripletShares<N, S>>,
+    ) -> Result<Vec<MpcRistrettoPoint<N, S>>, MpcNetworkError> {
+        if scalars.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Only products of two shared values require a Beaver triplet, the rest are computed locally
+        let beaver_mul_pairs = scalars
+            .iter()
+            .zip(points.iter())
+            .filter(|(scalar, point)| scalar.is_shared() && point.is_shared())
+            .collect::<Vec<_>>();
+        let num_beaver_muls = beaver_mul_pairs.len();
+
+        // Open the values d = [\alpha - a] and eG = [\betaG - bG] for each product
+        let (opened_scalar_subs, opened_point_subs) = if num_beaver_muls == 0 {
+            (Vec::new(), Vec::new())
+        } else {
+            let scalar_subs = beaver_mul_pairs
+                .iter()
+                .zip(point_triplets.iter())
+                .map(|((scalar, _), (a, _, _))| *scalar - a)
+                .collect::<Vec<_>>();
+            let point_subs = beaver_mul_pairs
+                .iter()
+                .zip(point_triplets.iter())
+                .map(|((_, point), (_, bG, _))| *point - bG)
+                .collect::<Vec<_>>();
+
+            (
+                MpcScalar::batch_open_async(&scalar_subs).await?,
+                MpcRistrettoPoint::batch_open_async(&point_subs).await?,
+            )
+        };
+
+        let am_king = points[0].network.as_ref().borrow().am_king();
+        let mut beaver_operands = opened_scalar_subs
+            .into_iter()
+            .zip(opened_point_subs)
+            .zip(point_triplets);
+        let mut res = Vec::with_capacity(scalars.len());
+        for (scalar, point) in scalars.iter().zip(points.iter()) {
+            if !(scalar.is_shared() && point.is_shared()) {
+                res.push(scalar * point);
+                continue;
+            }
+
+            let ((alpha_minus_a, beta_minus_b), (a, bG, cG)) = beaver_operands
+                .next()
+                .expect("one triplet is given per product of shared values");
+
+            // Identity [a * bG] = deG + d[bG] + [a]eG + [c]G, see the `Mul` implementation above
+            let mut product = &alpha_minus_a * bG + &a * &beta_minus_b + cG;
+            if am_king {
+                product += &alpha_minus_a * &beta_minus_b;
+            }
+
+            res.push(product);
+        }
+
+        Ok(res)
+    }
+
+    /// Computes the difference of two multiscalar multiplications:
+    ///     (a_1 * P_1 + ... + a_n * P_n) - (b_1 * Q_1 + ... + b_m * Q_m)
+    ///
+    /// This is the form of most verification equations, which are satisfied when the result
+    /// opens to the identity. The Beaver openings for both sides are batched into a single
+    /// pair of rounds, rather than the rounds used by two separate multiscalar multiplications
+    #[allow(non_snake_case)]
+    pub fn multiscalar_mul_difference(
+        a: &[MpcScalar<N, S>],
+        P: &[MpcRistrettoPoint<N, S>],
+        b: &[MpcScalar<N, S>],
+        Q: &[MpcRistrettoPoint<N, S>],
+    ) -> Result<MpcRistrettoPoint<N, S>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            a.iter()
+                .map(|value| value.session_id())
+                .chain(P.iter().map(|value| value.session_id()))
+                .chain(b.iter().map(|value| value.session_id()))
+                .chain(Q.iter().map(|value| value.session_id())),
+        );
+
+        assert_eq!(a.len(), P.len(), "a and P must be of equal length");
+        assert_eq!(b.len(), Q.len(), "b and Q must be of equal length");
+        assert!(
+            !P.is_empty() || !Q.is_empty(),
+            "multiscalar_mul_difference requires at least one term"
+        );
+
+        // Negate the subtracted side so that both sums are computed in one batch
+        let scalars = a
+            .iter()
+            .cloned()
+            .chain(b.iter().map(|scalar| scalar.neg()))
+            .collect::<Vec<_>>();
+        let points = P.iter().chain(Q.iter()).cloned().collect::<Vec<_>>();
+
+        let (network, beaver_source) = (points[0].network(), points[0].beaver_source());
+        Ok(MpcRistrettoPoint::batch_mul(&scalars, &points)
+            .map_err(MpcError::NetworkError)?
+            .iter()
+            .fold(
+                MpcRistrettoPoint::identity(network, beaver_source),
+                |acc, term| acc + term,
+            ))
+    }
+
+    /// Computes s_1 * PK_1 + ... + s_n * PK_n for (possibly shared) scalars s_i and public points PK_i
+    ///
+    /// Multiplying a shared scalar by a public point directly yields an additive share of the
+    /// product, so this method consumes no Beaver triplets and requires no communication
+    pub fn weighted_sum_public_points(
+        scalars: &[MpcScalar<N, S>],
+        points: &[RistrettoPoint],
+    ) -> Result<MpcRistrettoPoint<N, S>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(scalars.iter().map(|value| value.session_id()));
+
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "scalars and points must be of equal length"
+        );
+        assert!(
+            !scalars.is_empty(),
+            "weighted_sum_public_points requires at least one term"
+        );
+
+        if scalars.iter().any(|scalar| scalar.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private scalars may not be used in a weighted sum...".to_string(),
+            ));
+        }
+
+        let network = scalars[0].network();
+        let beaver_source = scalars[0].beaver_source();
+
+        // Split the sum into its shared and public terms, the public terms are added only by the king
+        let (shared_terms, public_terms): (Vec<_>, Vec<_>) = scalars
+            .iter()
+            .zip(points.iter())
+            .partition(|(scalar, _)| scalar.is_shared());
+
+        let shared_sum = MpcRistrettoPoint::from_ristretto_point_with_visibility(
+            RistrettoPoint::multiscalar_mul(
+                shared_terms.iter().map(|(scalar, _)| scalar.value()),
+                shared_terms.iter().map(|(_, point)| *point),
+            ),
+            Visibility::Shared,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let public_sum = MpcRistrettoPoint::from_public_ristretto_point(
+            RistrettoPoint::multiscalar_mul(
+                public_terms.iter().map(|(scalar, _)| scalar.value()),
+                public_terms.iter().map(|(_, point)| *point),
+            ),
+            network,
+            beaver_source,
+        );
+
+        if shared_terms.is_empty() {
+            Ok(public_sum)
+        } else {
+            Ok(shared_sum + public_sum)
+        }
+    }
+
+    /// Computes (s_1 * w_1 + ... + s_n * w_n) * G for (possibly shared) scalars s_i, public
+    /// weights w_i, and the Ristretto base point G
+    ///
+    /// The inner product is a local linear combination of the shares, so this method consumes no
+    /// Beaver triplets and requires no communication; only a single base point multiplication
+    pub fn inner_product_to_group(
+        scalars: &[MpcScalar<N, S>],
+        weights: &[Scalar],
+    ) -> Result<MpcRistrettoPoint<N, S>, MpcError> {
+        assert_eq!(
+            scalars.len(),
+            weights.len(),
+            "scalars and weights must be of equal length"
+        );
+        assert!(
+            !scalars.is_empty(),
+            "inner_product_to_group requires at least one term"
+        );
+
+        if scalars.iter().any(|scalar| scalar.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private scalars may not be used in an inner product...".to_string(),
+            ));
+        }
+
+        let network = scalars[0].network();
+        let beaver_source = scalars[0].beaver_source();
+
+        // Public terms are added to the shared sum only by the king
+        let inner_product = scalars.iter().zip(weights.iter()).fold(
+            MpcScalar::zero(network.clone(), beaver_source.clone()),
+            |acc, (scalar, weight)| acc + scalar * weight,
+        );
+
+        Ok(MpcRistrettoPoint::from_scalar_with_visibility(
+            inner_product.value(),
+            inner_product.visibility(),
+            network,
+            beaver_source,
+        ))
+    }
+}
+
 /// Represents a CompressedRistretto point allocated in the network
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -883,6 +1449,9 @@ pub struct MpcCompressedRistretto<N: MpcNetwork + Send, S: SharedValueSource<Sca
     beaver_source: BeaverSource<S>,
 }
 
+/// An `MpcCompressedRistretto` over a network chosen at runtime
+pub type DynMpcCompressedRistretto<S> = MpcCompressedRistretto<DynMpcNetwork, S>;
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcCompressedRistretto<N, S> {
     fn clone(&self) -> Self {
         Self {
@@ -1035,9 +1604,17 @@ mod mpc_ristretto_tests {
     use std::{cell::RefCell, rc::Rc};
 
     use clear_on_drop::clear::Clear;
-    use curve25519_dalek::{ristretto::RistrettoPoint, traits::Identity};
-
-    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+    use curve25519_dalek::{
+        constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+        traits::Identity,
+    };
+
+    use crate::{
+        beaver::{CountingSharedScalarSource, DummySharedScalarSource},
+        mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork,
+        Visibility,
+    };
 
     use super::MpcRistrettoPoint;
 
@@ -1052,4 +1629,103 @@ mod mpc_ristretto_tests {
 
         assert_eq!(value.value, RistrettoPoint::identity());
     }
+
+    #[test]
+    fn test_weighted_sum_public_points() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(CountingSharedScalarSource::default()));
+
+        // The dummy network is always the king, so the public term is added to the local share
+        let scalars = vec![
+            MpcScalar::from_scalar_with_visibility(
+                Scalar::from(2u64),
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            ),
+            MpcScalar::from_scalar_with_visibility(
+                Scalar::from(3u64),
+                Visibility::Shared,
+                network.clone(),
+                beaver_source.clone(),
+            ),
+            MpcScalar::from_public_u64(4, network, beaver_source.clone()),
+        ];
+        let points = vec![
+            RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64),
+            RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64),
+            RISTRETTO_BASEPOINT_POINT,
+        ];
+
+        let res = MpcRistrettoPoint::weighted_sum_public_points(&scalars, &points).unwrap();
+
+        assert_eq!(res.value(), RISTRETTO_BASEPOINT_POINT * Scalar::from(35u64));
+        assert_eq!(res.visibility, Visibility::Shared);
+        assert_eq!(beaver_source.borrow().num_drawn(), 0);
+    }
+
+    #[test]
+    fn test_open_to_bytes() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Mock the peer's share for both openings
+        let peer_share = RISTRETTO_BASEPOINT_POINT * Scalar::from(3u64);
+        network
+            .borrow_mut()
+            .add_mock_points(vec![peer_share, peer_share]);
+
+        let value = MpcRistrettoPoint::from_scalar_with_visibility(
+            Scalar::from(2u64),
+            Visibility::Shared,
+            network,
+            beaver_source,
+        );
+
+        let bytes = value.open_to_bytes().unwrap();
+        assert_eq!(&bytes, value.open().unwrap().compress().as_bytes());
+        assert_eq!(
+            bytes,
+            *(RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64))
+                .compress()
+                .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_shared_from_public() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // The dummy network is always the king, the peer holds the identity
+        network
+            .borrow_mut()
+            .add_mock_points(vec![RistrettoPoint::identity()]);
+
+        let point = RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64);
+        let shared = MpcRistrettoPoint::shared_from_public(point, network, beaver_source);
+        assert_eq!(shared.visibility, Visibility::Shared);
+        assert_eq!(shared.open().unwrap().value(), point);
+    }
+
+    #[test]
+    fn test_add_public_point() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // The dummy network is always the king, so the point is added to the local share
+        let shared_value = MpcRistrettoPoint::from_scalar_with_visibility(
+            Scalar::from(3u64),
+            Visibility::Shared,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let point = RISTRETTO_BASEPOINT_POINT * Scalar::from(4u64);
+        let res = shared_value.add_public_point(point);
+        let expected = &shared_value
+            + MpcRistrettoPoint::from_public_ristretto_point(point, network, beaver_source);
+
+        assert_eq!(res.visibility, Visibility::Shared);
+        assert_eq!(res.value(), expected.value());
+    }
 }
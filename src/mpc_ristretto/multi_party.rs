@@ -0,0 +1,61 @@
+//! Groups additive sharing of Ristretto points among any number of parties, over a
+//! `MultiPartyNetwork`
+//!
+//! A point P is shared as P_0 + ... + P_(n-1), each party holding one share. Shares add
+//! locally, and a public point is added by the king alone
+
+use curve25519_dalek::{ristretto::RistrettoPoint, traits::Identity};
+use itertools::Itertools;
+use rand_core::OsRng;
+
+use crate::{
+    error::MpcNetworkError,
+    network::{multi_party::MultiPartyNetwork, PartyId},
+};
+
+/// Shares the owning party's points among all parties; the owner sends each other party a
+/// random share of each point and keeps the difference
+///
+/// Only the owning party's points are read, every other party passes a slice of the same
+/// length, e.g. of identities. Returns the local party's shares
+pub async fn share_secret<N: MultiPartyNetwork + Send>(
+    network: &mut N,
+    owning_party: PartyId,
+    points: &[RistrettoPoint],
+) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+    if network.party_id() != owning_party {
+        return network
+            .receive_points_from(owning_party, points.len())
+            .await;
+    }
+
+    let mut rng = OsRng {};
+    let mut local_shares = points.to_vec();
+    for peer_id in (0..network.num_parties() as PartyId).filter(|id| *id != owning_party) {
+        let shares = (0..points.len())
+            .map(|_| RistrettoPoint::random(&mut rng))
+            .collect_vec();
+        network.send_points_to(peer_id, &shares).await?;
+        for (local_share, share) in local_shares.iter_mut().zip(shares.iter()) {
+            *local_share -= share;
+        }
+    }
+
+    Ok(local_shares)
+}
+
+/// Opens additively shared points to every party; each party broadcasts its shares and sums
+/// the shares of all parties
+pub async fn open<N: MultiPartyNetwork + Send>(
+    network: &mut N,
+    shares: &[RistrettoPoint],
+) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+    let all_shares = network.broadcast_points(shares).await?;
+    Ok((0..shares.len())
+        .map(|i| {
+            all_shares
+                .iter()
+                .fold(RistrettoPoint::identity(), |sum, shares| sum + shares[i])
+        })
+        .collect_vec())
+}
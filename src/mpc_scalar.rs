@@ -1,26 +1,42 @@
 //! Groups the definitions and trait implementations for a scalar value within an MPC network
 #![allow(unused_doc_comments)]
+pub mod arithmetic;
+pub mod assertions;
+pub mod bits;
+pub mod comparison;
+pub mod lazy;
+pub mod multi_party;
+pub mod oblivious;
+pub mod random;
+pub mod shuffle;
+pub mod truncation;
+
 use std::{
     borrow::Borrow,
     convert::TryInto,
     iter::{Product, Sum},
-    ops::{Add, AddAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign},
+    ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
 use clear_on_drop::clear::Clear;
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
-use futures::executor::block_on;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "bigint")]
+use num_bigint_dig::BigUint;
 use rand_core::{CryptoRng, OsRng, RngCore};
 use subtle::ConstantTimeEq;
 use zeroize::Zeroize;
+#[cfg(feature = "zeroize-on-drop")]
+use zeroize::ZeroizeOnDrop;
 
 use crate::{
     beaver::SharedValueSource,
+    block_on, coin_toss,
     commitment::PedersenCommitment,
     error::{MpcError, MpcNetworkError},
     macros::{self},
-    network::MpcNetwork,
-    BeaverSource, SharedNetwork, Visibility, Visible,
+    network::{DynMpcNetwork, MpcNetwork, Payload},
+    share::ScalarShare,
+    BeaverSource, SessionId, SharedNetwork, Visibility, Visible,
 };
 
 /// Represents a scalar value allocated in an MPC network
@@ -36,6 +52,9 @@ pub struct MpcScalar<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
     pub(crate) beaver_source: BeaverSource<S>,
 }
 
+/// An `MpcScalar` over a network chosen at runtime
+pub type DynMpcScalar<S> = MpcScalar<DynMpcNetwork, S>;
+
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcScalar<N, S> {
     fn clone(&self) -> Self {
         Self {
@@ -56,6 +75,32 @@ pub fn scalar_to_u64(a: &Scalar) -> u64 {
     u64::from_le_bytes(a.to_bytes()[..8].try_into().unwrap()) as u64
 }
 
+/// Converts a scalar to u64, or `None` if the scalar does not fit in 64 bits
+pub fn scalar_to_u64_checked(a: &Scalar) -> Option<u64> {
+    let bytes = a.to_bytes();
+    if bytes[8..].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    Some(scalar_to_u64(a))
+}
+
+/// Converts a scalar to u128, or `None` if the scalar does not fit in 128 bits
+pub fn scalar_to_u128_checked(a: &Scalar) -> Option<u128> {
+    let bytes = a.to_bytes();
+    if bytes[16..].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    Some(u128::from_le_bytes(bytes[..16].try_into().unwrap()))
+}
+
+/// Converts a scalar to its canonical representative as an arbitrary precision integer
+#[cfg(feature = "bigint")]
+pub fn scalar_to_biguint(a: &Scalar) -> BigUint {
+    BigUint::from_bytes_le(a.as_bytes())
+}
+
 /**
  * Wrapper type implementations
  */
@@ -88,11 +133,51 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         self.value()
     }
 
+    /// Converts the value to a u64, returning an `ArithmeticError` if it does not fit in 64 bits
+    ///
+    /// Returns a `VisibilityError` if the value is shared, as the local share is not the value;
+    /// shared values must be opened first
+    pub fn to_u64_checked(&self) -> Result<u64, MpcError> {
+        scalar_to_u64_checked(&self.plaintext()?)
+            .ok_or_else(|| MpcError::ArithmeticError("value does not fit in 64 bits".to_string()))
+    }
+
+    /// Converts the value to a u128, returning an `ArithmeticError` if it does not fit in 128
+    /// bits and a `VisibilityError` if the value is shared
+    pub fn to_u128(&self) -> Result<u128, MpcError> {
+        scalar_to_u128_checked(&self.plaintext()?)
+            .ok_or_else(|| MpcError::ArithmeticError("value does not fit in 128 bits".to_string()))
+    }
+
+    /// Converts the value to an arbitrary precision integer, returning a `VisibilityError` if
+    /// the value is shared
+    #[cfg(feature = "bigint")]
+    pub fn to_biguint(&self) -> Result<BigUint, MpcError> {
+        Ok(scalar_to_biguint(&self.plaintext()?))
+    }
+
+    /// Returns the value if the local party knows it, i.e. if it is public or private
+    fn plaintext(&self) -> Result<Scalar, MpcError> {
+        if self.is_shared() {
+            return Err(MpcError::VisibilityError(
+                "Shared values must be opened before they are converted...".to_string(),
+            ));
+        }
+
+        Ok(self.value)
+    }
+
     #[inline]
     pub(crate) fn network(&self) -> SharedNetwork<N> {
         self.network.clone()
     }
 
+    /// Returns the ID of the session the value was allocated in
+    #[inline]
+    pub fn session_id(&self) -> SessionId {
+        SessionId::of(&self.network)
+    }
+
     #[inline]
     pub(crate) fn beaver_source(&self) -> BeaverSource<S> {
         self.beaver_source.clone()
@@ -120,6 +205,44 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         Self::from_u64_with_visibility(a, Visibility::Private, network, beaver_source)
     }
 
+    /// Create a vector of public network scalars from a slice of u64s
+    pub fn from_public_u64_slice(
+        values: &[u64],
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Vec<Self> {
+        Self::from_u64_slice_with_visibility(values, Visibility::Public, network, beaver_source)
+    }
+
+    /// Create a vector of private network scalars from a slice of u64s
+    pub fn from_private_u64_slice(
+        values: &[u64],
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Vec<Self> {
+        Self::from_u64_slice_with_visibility(values, Visibility::Private, network, beaver_source)
+    }
+
+    /// Create a vector of scalars from a slice of u64s with the given visibility
+    pub(crate) fn from_u64_slice_with_visibility(
+        values: &[u64],
+        visibility: Visibility,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Vec<Self> {
+        values
+            .iter()
+            .map(|value| {
+                Self::from_u64_with_visibility(
+                    *value,
+                    visibility,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect()
+    }
+
     /// Create a scalar from a given u64 and visibility
     pub(crate) fn from_u64_with_visibility(
         a: u64,
@@ -168,6 +291,26 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         }
     }
 
+    /// Detaches the party's share of the value from the network and value source, e.g. to
+    /// persist it or route it over another transport
+    pub fn detach(&self) -> ScalarShare {
+        ScalarShare {
+            value: self.value,
+            visibility: self.visibility,
+            mac_share: None,
+        }
+    }
+
+    /// Attaches a detached share to a network and value source; the share of a MAC, if any, is
+    /// dropped, see `AuthenticatedScalar::attach` to keep it
+    pub fn attach(
+        share: ScalarShare,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self::from_scalar_with_visibility(share.value, share.visibility, network, beaver_source)
+    }
+
     /// Generate a random scalar
     /// Random will always be SharedWithOwner(self); two parties cannot reliably generate the same random value
     pub fn random<R: RngCore + CryptoRng>(
@@ -260,6 +403,18 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
     /// This method is called by both parties, only one of which transmits, the peer will simply
     /// await the sent share
     pub fn share_secret(&self, party_id: u64) -> Result<MpcScalar<N, S>, MpcNetworkError> {
+        block_on(self.share_secret_async(party_id))
+    }
+
+    /// Awaits the network rather than blocking on it, see `share_secret`
+    ///
+    /// The async methods of values that share a network must be awaited in sequence rather
+    /// than polled concurrently; an operation started while another is pending on the same
+    /// network fails with `MpcNetworkError::NetworkBusy`
+    pub async fn share_secret_async(
+        &self,
+        party_id: u64,
+    ) -> Result<MpcScalar<N, S>, MpcNetworkError> {
         let my_party_id = self.network.as_ref().borrow().party_id();
 
         if my_party_id == party_id {
@@ -269,12 +424,10 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
             let random_share = Scalar::random(&mut rng);
 
             // Broadcast the counterparty's share
-            block_on(
-                self.network
-                    .as_ref()
-                    .borrow_mut()
-                    .send_single_scalar(random_share),
-            )?;
+            crate::await_network(&self.network, |network| {
+                network.send_single_scalar(random_share)
+            })
+            .await?;
 
             // Do not subtract directly as the random scalar is not directly allocated in the network
             // subtracting directly ties it to the subtraction implementaiton in a fragile way
@@ -285,7 +438,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                 beaver_source: self.beaver_source.clone(),
             })
         } else {
-            Self::receive_value(self.network.clone(), self.beaver_source.clone())
+            Self::receive_value_async(self.network.clone(), self.beaver_source.clone()).await
         }
     }
 
@@ -294,6 +447,17 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         party_id: u64,
         secrets: &[MpcScalar<N, S>],
     ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_share_secrets_async(party_id, secrets))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_share_secrets`
+    pub async fn batch_share_secrets_async(
+        party_id: u64,
+        secrets: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(secrets.iter().map(|value| value.session_id()));
+
         assert!(
             secrets.iter().all(|secret| secret.is_private()),
             "Values to be shared must be in private state"
@@ -315,7 +479,11 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                 .collect();
 
             // Broadcast the random shares to the peer
-            block_on(network.as_ref().borrow_mut().send_scalars(&random_shares))?;
+            let shares_sent = random_shares.clone();
+            crate::await_network(&network, move |network| {
+                Box::pin(async move { network.send_scalars(&shares_sent).await })
+            })
+            .await?;
 
             Ok(secrets
                 .iter()
@@ -328,7 +496,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                 })
                 .collect())
         } else {
-            Self::batch_receive_values(secrets.len(), network, beaver_source)
+            Self::batch_receive_values_async(secrets.len(), network, beaver_source).await
         }
     }
 
@@ -337,7 +505,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         network: SharedNetwork<N>,
         beaver_source: BeaverSource<S>,
     ) -> Result<MpcScalar<N, S>, MpcNetworkError> {
-        let value = block_on(network.as_ref().borrow_mut().receive_single_scalar())?;
+        block_on(Self::receive_value_async(network, beaver_source))
+    }
+
+    /// Awaits the network rather than blocking on it, see `receive_value`
+    pub async fn receive_value_async(
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<MpcScalar<N, S>, MpcNetworkError> {
+        let value =
+            crate::await_network(&network, |network| network.receive_single_scalar()).await?;
 
         Ok(MpcScalar {
             value,
@@ -353,7 +530,21 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         network: SharedNetwork<N>,
         beaver_source: BeaverSource<S>,
     ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
-        let values = block_on(network.as_ref().borrow_mut().receive_scalars(num_expected))?;
+        block_on(Self::batch_receive_values_async(
+            num_expected,
+            network,
+            beaver_source,
+        ))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_receive_values`
+    pub async fn batch_receive_values_async(
+        num_expected: usize,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        let values =
+            crate::await_network(&network, |network| network.receive_scalars(num_expected)).await?;
 
         Ok(values
             .iter()
@@ -369,30 +560,83 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
     /// From a shared value, both parties open their shares and construct the plaintext value.
     /// Note that the parties no longer hold valid additive secret shares of the value, this is used
     /// at the end of a computation
+    ///
+    /// Opening a public value is a no-op. Private values must be shared before they are opened;
+    /// this method panics on a private value, see `try_open` for a fallible alternative
     pub fn open(&self) -> Result<MpcScalar<N, S>, MpcNetworkError> {
+        block_on(self.open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `open`
+    pub async fn open_async(&self) -> Result<MpcScalar<N, S>, MpcNetworkError> {
         assert!(!self.is_private(), "Private values may not be opened...");
         if self.is_public() {
             return Ok(self.clone());
         }
 
         // Send my scalar and expect one back
-        let received_scalar = block_on(
-            self.network
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_scalar(self.value),
-        )?;
+        let my_share = self.value;
+        let received_scalar = crate::await_network(&self.network, |network| {
+            network.broadcast_single_scalar(my_share)
+        })
+        .await?;
 
         // Reconstruct the plaintext from the peer's share
-        Ok(MpcScalar::from_public_scalar(
+        let opened_value = MpcScalar::from_public_scalar(
             self.value + received_scalar,
             self.network.clone(),
             self.beaver_source.clone(),
-        ))
+        );
+        Self::record_openings(
+            std::slice::from_ref(self),
+            std::slice::from_ref(&opened_value),
+        );
+
+        Ok(opened_value)
+    }
+
+    /// Opens a shared value, returning a `VisibilityError` rather than panicking if the value
+    /// is private
+    ///
+    /// Only public values (a no-op) and shared values (a real opening) may be opened; a private
+    /// value is held by one party alone and must be shared first
+    pub fn try_open(&self) -> Result<MpcScalar<N, S>, MpcError> {
+        match self.visibility {
+            Visibility::Private => Err(MpcError::VisibilityError(
+                "Private values may not be opened, share the value first...".to_string(),
+            )),
+            Visibility::Public | Visibility::Shared => self.open().map_err(MpcError::NetworkError),
+        }
+    }
+
+    /// Opens the value and applies a party-specific post-processing to the result;
+    /// party 0 applies `f0` and party 1 applies `f1` to the same opened value
+    pub fn open_then<T>(
+        &self,
+        f0: impl Fn(Scalar) -> T,
+        f1: impl Fn(Scalar) -> T,
+    ) -> Result<T, MpcNetworkError> {
+        let opened_value = self.open()?.value();
+
+        if self.network.as_ref().borrow().party_id() == 0 {
+            Ok(f0(opened_value))
+        } else {
+            Ok(f1(opened_value))
+        }
     }
 
     /// Open a batch of shared values
     pub fn batch_open(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_open`
+    pub async fn batch_open_async(
+        values: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(
             values.iter().all(|value| !value.is_private()),
             "Private values may not be opened..."
@@ -406,16 +650,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         let beaver_source = values[0].beaver_source();
 
         // Both parties share their values
-        let received_scalars = block_on(
-            network.as_ref().borrow_mut().broadcast_scalars(
-                &values
-                    .iter()
-                    .map(|value| value.value())
-                    .collect::<Vec<Scalar>>(),
-            ),
-        )?;
+        let my_shares = values
+            .iter()
+            .map(|value| value.value())
+            .collect::<Vec<Scalar>>();
+        let received_scalars = crate::await_network(&network, move |network| {
+            Box::pin(async move { network.broadcast_scalars(&my_shares).await })
+        })
+        .await?;
 
-        Ok(values
+        let opened_values = values
             .iter()
             .zip(received_scalars.iter())
             .map(|(my_share, peer_share)| {
@@ -429,7 +673,24 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                     beaver_source.clone(),
                 )
             })
-            .collect())
+            .collect::<Vec<_>>();
+        Self::record_openings(values, &opened_values);
+
+        Ok(opened_values)
+    }
+
+    /// Absorbs the newly opened values into the session's output digest; values that were
+    /// public before the opening are skipped
+    fn record_openings(values: &[MpcScalar<N, S>], opened_values: &[MpcScalar<N, S>]) {
+        for (value, opened_value) in values.iter().zip(opened_values.iter()) {
+            if value.is_shared() {
+                value
+                    .network
+                    .as_ref()
+                    .borrow_mut()
+                    .record_opening(opened_value.value.as_bytes());
+            }
+        }
     }
 
     /// From a shared value:
@@ -437,49 +698,95 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
     ///     2. Open those commitments to the underlying value
     ///     3. Verify that the peer's opening matches their commitment
     pub fn commit_and_open(&self) -> Result<MpcScalar<N, S>, MpcError> {
+        block_on(self.commit_and_open_async())
+    }
+
+    /// Awaits the network rather than blocking on it, see `commit_and_open`
+    pub async fn commit_and_open_async(&self) -> Result<MpcScalar<N, S>, MpcError> {
         assert!(!self.is_private(), "Private values may not be opened...");
         if self.is_public() {
             return Ok(self.clone());
         }
 
-        // Compute a Pedersen commitment to the value
+        // Compute a Pedersen commitment to the value, then exchange and open the commitments
         let commitment = PedersenCommitment::commit(self.to_scalar());
-        let peer_commitment = block_on(
-            self.network()
-                .as_ref()
-                .borrow_mut()
-                .broadcast_single_point(commitment.get_commitment()),
-        )
-        .map_err(MpcError::NetworkError)?;
-
-        // Open the commitment to the underlying value
-        let received_scalars = block_on(
-            self.network()
-                .as_ref()
-                .borrow_mut()
-                .broadcast_scalars(&[commitment.get_blinding(), commitment.get_value()]),
-        )
+        let my_commitment = Payload {
+            points: vec![commitment.get_commitment()],
+            ..Default::default()
+        };
+        let my_opening = Payload {
+            scalars: vec![commitment.get_blinding(), commitment.get_value()],
+            ..Default::default()
+        };
+        let (peer_commitment, peer_opening) = crate::await_network(&self.network, move |network| {
+            Box::pin(async move {
+                network
+                    .exchange_commitments(&my_commitment, &my_opening)
+                    .await
+            })
+        })
+        .await
         .map_err(MpcError::NetworkError)?;
 
-        let (peer_blinding, peer_value) = (received_scalars[0], received_scalars[1]);
+        let peer_commitment = peer_commitment.points[0];
+        let (peer_blinding, peer_value) = (peer_opening.scalars[0], peer_opening.scalars[1]);
 
         // Verify the commitment and return the opened value
         if !PedersenCommitment::verify_from_values(peer_commitment, peer_blinding, peer_value) {
             return Err(MpcError::AuthenticationError);
         }
 
-        Ok(Self {
+        let opened_value = Self {
             value: self.value() + peer_value,
             visibility: Visibility::Public,
             network: self.network(),
             beaver_source: self.beaver_source(),
-        })
+        };
+        Self::record_openings(
+            std::slice::from_ref(self),
+            std::slice::from_ref(&opened_value),
+        );
+
+        Ok(opened_value)
     }
 
     /// Commit to and open a batch of secret shared values
     pub fn batch_commit_and_open(
         values: &[MpcScalar<N, S>],
     ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        block_on(Self::batch_commit_and_open_async(values))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_commit_and_open`
+    pub async fn batch_commit_and_open_async(
+        values: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Self::batch_commit_and_open_indexed(values)
+            .await
+            .map_err(|(_, err)| err)
+    }
+
+    /// Commit to and open a batch of secret shared values, reporting the index of the first
+    /// value whose commitment fails to verify
+    ///
+    /// This reveals which of the peer's openings was inconsistent, and is intended only for
+    /// diagnostics in a trusted development setting. Network errors are not attributable to
+    /// a single value and are reported at index 0
+    #[cfg(feature = "debug")]
+    pub fn batch_commit_and_open_diagnostic(
+        values: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, (usize, MpcError)> {
+        block_on(Self::batch_commit_and_open_indexed(values))
+    }
+
+    /// Commit to and open a batch of secret shared values, on failure returns the index of the
+    /// first value whose commitment failed to verify
+    async fn batch_commit_and_open_indexed(
+        values: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, (usize, MpcError)> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(values.iter().map(|value| value.session_id()));
+
         assert!(
             values.iter().all(|value| !value.is_private()),
             "Private values may not be opened...",
@@ -492,56 +799,59 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         let network = values[0].network();
         let beaver_source = values[0].beaver_source();
 
-        // Generate commitments to the values and share them with the peer
+        // Generate commitments to the values, then exchange them and their openings; the
+        // opening of each commitment is its blinding factor and value
         let commitments: Vec<PedersenCommitment> = values
             .iter()
             .map(|value| PedersenCommitment::commit(value.to_scalar()))
             .collect();
-        let peer_commitments = block_on(
-            network.as_ref().borrow_mut().broadcast_points(
-                &commitments
-                    .iter()
-                    .map(|comm| comm.get_commitment())
-                    .collect::<Vec<RistrettoPoint>>(),
-            ),
-        )
-        .map_err(MpcError::NetworkError)?;
+        let my_commitments = Payload {
+            points: commitments
+                .iter()
+                .map(|comm| comm.get_commitment())
+                .collect(),
+            ..Default::default()
+        };
+        let my_openings = Payload {
+            scalars: commitments
+                .iter()
+                .flat_map(|comm| [comm.get_blinding(), comm.get_value()])
+                .collect(),
+            ..Default::default()
+        };
 
-        // Open both the underlying values and the blinding factos
-        let mut commitment_data: Vec<Scalar> = Vec::new();
-        commitments.iter().for_each(|comm| {
-            commitment_data.push(comm.get_blinding());
-            commitment_data.push(comm.get_value());
-        });
-
-        let received_values = block_on(
-            network
-                .as_ref()
-                .borrow_mut()
-                .broadcast_scalars(&commitment_data),
-        )
-        .map_err(MpcError::NetworkError)?;
+        let (peer_commitments, peer_openings) = crate::await_network(&network, move |network| {
+            Box::pin(async move {
+                network
+                    .exchange_commitments(&my_commitments, &my_openings)
+                    .await
+            })
+        })
+        .await
+        .map_err(|err| (0, MpcError::NetworkError(err)))?;
+        let (peer_commitments, received_values) = (peer_commitments.points, peer_openings.scalars);
 
         // Verify the peer's commitments
         let mut peer_values: Vec<Scalar> = Vec::new();
         received_values
             .chunks(2 /* chunk_size */) // Fetch each pair of blinding, value
             .zip(peer_commitments.into_iter())
-            .try_for_each(|(revealed_values, comm)| {
+            .enumerate()
+            .try_for_each(|(i, (revealed_values, comm))| {
                 // Destructure the received payload and append to the peer values vector
                 let (blinding, value) = (revealed_values[0], revealed_values[1]);
                 peer_values.push(value);
 
                 // Verify the Pedersen commitment, report an authentication error if opening fails
                 if !PedersenCommitment::verify_from_values(comm, blinding, value) {
-                    return Err(MpcError::AuthenticationError);
+                    return Err((i, MpcError::AuthenticationError));
                 }
 
                 Ok(())
             })?;
 
         // If the commitments open properly then add shares together to recover cleartext
-        Ok(values
+        let opened_values = values
             .iter()
             .zip(peer_values)
             .map(|(my_value, peer_value)| {
@@ -556,7 +866,22 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                     beaver_source: beaver_source.clone(),
                 }
             })
-            .collect())
+            .collect::<Vec<_>>();
+        Self::record_openings(values, &opened_values);
+
+        Ok(opened_values)
+    }
+
+    /// Jointly sample a public random scalar that neither party can bias
+    ///
+    /// The scalar is tossed by commit-then-reveal, so that neither party may choose its
+    /// contribution after seeing the peer's; see `coin_toss::toss_scalars`
+    pub fn coin_flip(
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        let coin = coin_toss::toss_scalar(&network)?;
+        Ok(MpcScalar::from_public_scalar(coin, network, beaver_source))
     }
 
     /// Retreives the next Beaver triplet from the Beaver source and allocates the values within the network
@@ -597,7 +922,31 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
             .borrow_mut()
             .next_triplet_batch(num_triplets);
 
-        // Allocate values as shared in the network
+        self.allocate_beaver_triplets(&triplet_batch)
+    }
+
+    /// Retrieves the next Beaver triplet batch as `next_beaver_triplet_batch`, returning an error
+    /// if the Beaver source cannot supply the batch
+    #[allow(clippy::type_complexity)]
+    fn try_next_beaver_triplet_batch(
+        &self,
+        num_triplets: usize,
+    ) -> Result<Vec<(MpcScalar<N, S>, MpcScalar<N, S>, MpcScalar<N, S>)>, MpcError> {
+        let triplet_batch = self
+            .beaver_source
+            .as_ref()
+            .borrow_mut()
+            .try_next_triplet_batch(num_triplets)?;
+
+        Ok(self.allocate_beaver_triplets(&triplet_batch))
+    }
+
+    /// Allocates Beaver triplets drawn from the Beaver source as shared values in the network
+    #[allow(clippy::type_complexity)]
+    fn allocate_beaver_triplets(
+        &self,
+        triplet_batch: &[(Scalar, Scalar, Scalar)],
+    ) -> Vec<(MpcScalar<N, S>, MpcScalar<N, S>, MpcScalar<N, S>)> {
         triplet_batch
             .iter()
             .map(|(a, b, c)| {
@@ -682,6 +1031,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a MpcScalar<N
     /// See https://securecomputation.org/docs/pragmaticmpc.pdf (Section 3.4) for the identities this
     /// implementation makes use of.
     fn mul(self, rhs: &'a MpcScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         if self.is_shared() && rhs.is_shared() {
             let (a, b, c) = self.next_beaver_triplet();
 
@@ -720,6 +1072,31 @@ macros::impl_wrapper_type!(MpcScalar<N, S>, Scalar, MpcScalar::from_public_scala
 macros::impl_arithmetic_assign!(MpcScalar<N, S>, MulAssign, mul_assign, *, MpcScalar<N, S>);
 macros::impl_arithmetic_assign!(MpcScalar<N, S>, MulAssign, mul_assign, *, Scalar);
 
+/**
+ * Div and variants for: borrowed, non-borrowed, and Scalar types
+ */
+
+/// Implementation of division by masking the divisor, see `MpcScalar::try_div`
+/// This implementation panics in the case of a network error or a zero divisor
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Div<&'a MpcScalar<N, S>>
+    for &'a MpcScalar<N, S>
+{
+    type Output = MpcScalar<N, S>;
+
+    fn div(self, rhs: &'a MpcScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
+        self.try_div(rhs)
+            .unwrap_or_else(|err| panic!("failed to divide: {}", err))
+    }
+}
+
+macros::impl_operator_variants!(MpcScalar<N, S>, Div, div, /, MpcScalar<N, S>);
+macros::impl_wrapper_type!(MpcScalar<N, S>, Scalar, MpcScalar::from_public_scalar, Div, div, /, authenticated=false);
+macros::impl_arithmetic_assign!(MpcScalar<N, S>, DivAssign, div_assign, /, MpcScalar<N, S>);
+macros::impl_arithmetic_assign!(MpcScalar<N, S>, DivAssign, div_assign, /, Scalar);
+
 /**
  * Batch multiply allowing for batches of communication
  */
@@ -733,12 +1110,139 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         a: &[MpcScalar<N, S>],
         b: &[MpcScalar<N, S>],
     ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        block_on(Self::batch_mul_async(a, b))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_mul`
+    pub async fn batch_mul_async(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            a.iter()
+                .map(|value| value.session_id())
+                .chain(b.iter().map(|value| value.session_id())),
+        );
+
+        let num_beaver_muls = Self::num_beaver_muls(a, b);
+        let beaver_triplets = match a.first() {
+            Some(value) => value.next_beaver_triplet_batch(num_beaver_muls),
+            None => Vec::new(),
+        };
+
+        Self::batch_mul_with_triplets(a, b, None, beaver_triplets).await
+    }
+
+    /// Returns the result [a_1 * b_1, ..., a_n * b_n], or a `PreprocessingExhausted` error
+    /// rather than panicking if the Beaver source cannot supply the triplets
+    pub fn try_batch_mul(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        block_on(Self::try_batch_mul_async(a, b))
+    }
+
+    /// Awaits the network rather than blocking on it, see `try_batch_mul`
+    pub async fn try_batch_mul_async(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            a.iter()
+                .map(|value| value.session_id())
+                .chain(b.iter().map(|value| value.session_id())),
+        );
+
+        let num_beaver_muls = Self::num_beaver_muls(a, b);
+        let beaver_triplets = match a.first() {
+            Some(value) => value.try_next_beaver_triplet_batch(num_beaver_muls)?,
+            None => Vec::new(),
+        };
+
+        Self::batch_mul_with_triplets(a, b, None, beaver_triplets)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    /// Returns the result [a_1 * b_1 + c_1, ..., a_n * b_n + c_n], or a
+    /// `PreprocessingExhausted` error if the Beaver source cannot supply the triplets
+    ///
+    /// The addends are folded into the products as they are recombined from the Beaver
+    /// openings, rather than added to an intermediate vector of products.
+    ///
+    /// Cost: one Beaver triplet per product of two non-public values, drawn in one batch, and
+    /// a single round of communication for the whole batch
+    pub fn batch_mul_add(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+        c: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        block_on(Self::batch_mul_add_async(a, b, c))
+    }
+
+    /// Awaits the network rather than blocking on it, see `batch_mul_add`
+    pub async fn batch_mul_add_async(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+        c: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session(
+            a.iter()
+                .map(|value| value.session_id())
+                .chain(b.iter().map(|value| value.session_id()))
+                .chain(c.iter().map(|value| value.session_id())),
+        );
+
+        assert_eq!(
+            a.len(),
+            c.len(),
+            "input arrays to batch_mul_add must be of equal length"
+        );
+
+        let num_beaver_muls = Self::num_beaver_muls(a, b);
+        let beaver_triplets = match a.first() {
+            Some(value) => value.try_next_beaver_triplet_batch(num_beaver_muls)?,
+            None => Vec::new(),
+        };
+
+        Self::batch_mul_with_triplets(a, b, Some(c), beaver_triplets)
+            .await
+            .map_err(MpcError::NetworkError)
+    }
+
+    /// Multiplies two values, or returns a `PreprocessingExhausted` error rather than panicking
+    /// if the Beaver source cannot supply a triplet
+    pub fn try_mul(&self, rhs: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::try_batch_mul(std::slice::from_ref(self), std::slice::from_ref(rhs))?.remove(0))
+    }
+
+    /// Asserts that the inputs to a batch multiplication are of equal length, and counts the
+    /// products that require a Beaver triplet
+    fn num_beaver_muls(a: &[MpcScalar<N, S>], b: &[MpcScalar<N, S>]) -> usize {
         assert_eq!(
             a.len(),
             b.len(),
             "input arrays to batch_mul must be of equal length"
         );
 
+        a.iter()
+            .zip(b.iter())
+            .filter(|(a_val, b_val)| !a_val.is_public() && !b_val.is_public())
+            .count()
+    }
+
+    /// Multiplies the values with one of the given triplets for each product of two non-public
+    /// values, adding the corresponding addend to each product if any are given
+    #[allow(clippy::type_complexity)]
+    async fn batch_mul_with_triplets(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+        addends: Option<&[MpcScalar<N, S>]>,
+        mut beaver_triplets: Vec<(MpcScalar<N, S>, MpcScalar<N, S>, MpcScalar<N, S>)>,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
         if a.is_empty() {
             return Ok(Vec::new());
         }
@@ -754,10 +1258,7 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                 beaver_mul_pairs.push((&a[i], &b[i]))
             }
         }
-
-        // For each of the multiplications that requires a beaver-style mul; sample a multiplication triplet
         let num_beaver_muls = beaver_mul_pairs.len();
-        let mut beaver_triplets = a[0].next_beaver_triplet_batch(num_beaver_muls);
 
         // Tile a payload buffer with the beaver openings then share
         let mut beaver_subs = Vec::with_capacity(2 * n);
@@ -773,11 +1274,11 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
         let mut opened_beaver_subs = if num_beaver_muls == 0 {
             Vec::new()
         } else {
-            MpcScalar::batch_open(&beaver_subs)?
+            MpcScalar::batch_open_async(&beaver_subs).await?
         };
         for i in 0..n {
-            if a[i].is_public() || b[i].is_public() {
-                res.push(&a[i] * &b[i])
+            let product = if a[i].is_public() || b[i].is_public() {
+                &a[i] * &b[i]
             } else {
                 // Fetch the next opening of a beaver sub
                 let (lhs_minus_a, rhs_minus_b) =
@@ -789,13 +1290,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
                 // Identity: [a * b] = de + d[b] + e[a] + [c]
                 // All multiplications here are between a public and shared value or
                 // two public values, so the recursion will not hit this case
-                let result = &lhs_minus_a * &beaver_b
+                &lhs_minus_a * &beaver_b
                     + &rhs_minus_b * &beaver_a
                     + lhs_minus_a * rhs_minus_b
-                    + &beaver_c;
+                    + &beaver_c
+            };
 
-                res.push(result);
-            }
+            res.push(match addends {
+                Some(addends) => product + &addends[i],
+                None => product,
+            });
         }
 
         Ok(res)
@@ -811,6 +1315,9 @@ impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a MpcScalar<N
     type Output = MpcScalar<N, S>;
 
     fn add(self, rhs: &'a MpcScalar<N, S>) -> Self::Output {
+        #[cfg(feature = "session-check")]
+        crate::assert_same_session([self.session_id(), rhs.session_id()]);
+
         // If public + shared swap the arguments for simplicity
         if self.is_public() && rhs.is_shared() {
             return rhs + self;
@@ -852,6 +1359,34 @@ macros::impl_wrapper_type!(MpcScalar<N, S>, Scalar, MpcScalar::from_public_scala
 macros::impl_arithmetic_assign!(MpcScalar<N, S>, AddAssign, add_assign, +, MpcScalar<N, S>);
 macros::impl_arithmetic_assign!(MpcScalar<N, S>, AddAssign, add_assign, +, Scalar);
 
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Adds a public constant, known to both parties, to the value
+    ///
+    /// For a public value both parties add the constant. For a shared value only the king adds the
+    /// constant to its share, so that the shares sum to the value plus the constant; this is the
+    /// same addition performed implicitly by `shared + public`. For a private value, the party
+    /// holding the value adds the constant
+    pub fn add_public_constant(&self, c: Scalar) -> MpcScalar<N, S> {
+        let add_locally = match self.visibility {
+            Visibility::Public | Visibility::Private => true,
+            Visibility::Shared => self.network.as_ref().borrow().am_king(),
+        };
+
+        let value = if add_locally {
+            self.value + c
+        } else {
+            self.value
+        };
+
+        Self {
+            value,
+            visibility: self.visibility,
+            network: self.network.clone(),
+            beaver_source: self.beaver_source.clone(),
+        }
+    }
+}
+
 /**
  * Sub and variants for: borrowed, non-borrowed, and scalar types
  */
@@ -943,6 +1478,70 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
     ) -> Result<MpcScalar<N, S>, MpcNetworkError> {
         Ok(MpcScalar::batch_mul(scalars, coeffs)?.iter().sum())
     }
+
+    /// Computes the inner product of two vectors of values
+    ///
+    /// Every product of two non-public entries draws a Beaver triplet from a single batch, and
+    /// the masked entries of both vectors are opened together; the shares of the products are
+    /// then summed directly, with the king adding the public cross terms once for the whole
+    /// vector. Returns an `ArithmeticError` if the vectors are empty or of different lengths,
+    /// and a `VisibilityError` if any entry is private.
+    ///
+    /// Cost: one Beaver triplet per product of non-public entries, and exactly one round of
+    /// communication
+    pub fn dot(a: &[MpcScalar<N, S>], b: &[MpcScalar<N, S>]) -> Result<MpcScalar<N, S>, MpcError> {
+        if a.is_empty() || a.len() != b.len() {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot take the inner product of vectors of lengths {} and {}",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        if a.iter().chain(b.iter()).any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be multiplied...".to_string(),
+            ));
+        }
+
+        let (beaver_pairs, local_pairs): (Vec<_>, Vec<_>) = a
+            .iter()
+            .zip(b.iter())
+            .partition(|(a_val, b_val)| !a_val.is_public() && !b_val.is_public());
+
+        let mut res = local_pairs.iter().fold(
+            MpcScalar::zero(a[0].network(), a[0].beaver_source()),
+            |acc, (a_val, b_val)| acc + *a_val * *b_val,
+        );
+        if beaver_pairs.is_empty() {
+            return Ok(res);
+        }
+
+        let beaver_triplets = a[0].try_next_beaver_triplet_batch(beaver_pairs.len())?;
+        let beaver_subs = beaver_pairs
+            .iter()
+            .zip(beaver_triplets.iter())
+            .flat_map(|((a_val, b_val), (beaver_a, beaver_b, _))| {
+                [*a_val - beaver_a, *b_val - beaver_b]
+            })
+            .collect::<Vec<_>>();
+        let opened_beaver_subs =
+            MpcScalar::batch_open(&beaver_subs).map_err(MpcError::NetworkError)?;
+
+        // Identity: [<x, y>] = sum_i d_i e_i + d_i[b_i] + e_i[a_i] + [c_i], where the king adds
+        // the public sum of d_i e_i
+        let mut public_sum = Scalar::zero();
+        for (opened, (beaver_a, beaver_b, beaver_c)) in opened_beaver_subs
+            .chunks_exact(2)
+            .zip(beaver_triplets.iter())
+        {
+            let (d, e) = (opened[0].value(), opened[1].value());
+            public_sum += d * e;
+            res = res + beaver_b * d + beaver_a * e + beaver_c;
+        }
+
+        Ok(res.add_public_constant(public_sum))
+    }
 }
 
 impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Zeroize for MpcScalar<N, S> {
@@ -951,6 +1550,16 @@ impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Zeroize for MpcScalar<N
     }
 }
 
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Drop for MpcScalar<N, S> {
+    fn drop(&mut self) {
+        self.zeroize()
+    }
+}
+
+#[cfg(feature = "zeroize-on-drop")]
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> ZeroizeOnDrop for MpcScalar<N, S> {}
+
 #[cfg(test)]
 mod test {
     use std::{cell::RefCell, rc::Rc};
@@ -959,9 +1568,52 @@ mod test {
     use curve25519_dalek::scalar::Scalar;
     use rand_core::OsRng;
 
-    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+    use crate::{
+        beaver::DummySharedScalarSource, error::MpcError, network::dummy_network::DummyMpcNetwork,
+    };
+
+    use super::{MpcScalar, Visibility, Visible};
+
+    #[test]
+    fn test_checked_conversions() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        let small = MpcScalar::from_public_u64(u64::MAX, network.clone(), beaver_source.clone());
+        assert_eq!(small.to_u64_checked().unwrap(), u64::MAX);
+        assert_eq!(small.to_u128().unwrap(), u64::MAX as u128);
+
+        // 2^64 no longer fits in a u64, rather than truncating to zero
+        let wide = &small + Scalar::one();
+        assert!(matches!(
+            wide.to_u64_checked(),
+            Err(MpcError::ArithmeticError(_))
+        ));
+        assert_eq!(wide.to_u128().unwrap(), 1u128 << 64);
+        #[cfg(feature = "bigint")]
+        assert_eq!(
+            wide.to_biguint().unwrap(),
+            num_bigint_dig::BigUint::from_bytes_le(&(1u128 << 64).to_le_bytes())
+        );
+        assert!(MpcScalar::from_public_scalar(
+            -Scalar::one(),
+            network.clone(),
+            beaver_source.clone()
+        )
+        .to_u128()
+        .is_err());
 
-    use super::{MpcScalar, Visibility};
+        let shared = MpcScalar::from_scalar_with_visibility(
+            Scalar::one(),
+            Visibility::Shared,
+            network,
+            beaver_source,
+        );
+        assert!(matches!(
+            shared.to_u64_checked(),
+            Err(MpcError::VisibilityError(_))
+        ));
+    }
 
     #[test]
     fn test_zero() {
@@ -975,6 +1627,37 @@ mod test {
         assert_eq!(zero, expected);
     }
 
+    #[test]
+    fn test_from_u64_slice() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let values = [1u64, 2u64, 3u64];
+
+        let expected_public = values
+            .iter()
+            .map(|value| MpcScalar::from_public_u64(*value, network.clone(), beaver_source.clone()))
+            .collect::<Vec<_>>();
+        let expected_private = values
+            .iter()
+            .map(|value| {
+                MpcScalar::from_private_u64(*value, network.clone(), beaver_source.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let public =
+            MpcScalar::from_public_u64_slice(&values, network.clone(), beaver_source.clone());
+        let private = MpcScalar::from_private_u64_slice(&values, network, beaver_source);
+
+        assert_eq!(public, expected_public);
+        assert!(public
+            .iter()
+            .all(|value| value.visibility() == Visibility::Public));
+        assert_eq!(private, expected_private);
+        assert!(private
+            .iter()
+            .all(|value| value.visibility() == Visibility::Private));
+    }
+
     #[test]
     fn test_open() {
         let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
@@ -1185,4 +1868,140 @@ mod test {
         (&mut value).clear();
         assert_eq!(value.value(), Scalar::zero());
     }
+
+    #[test]
+    #[cfg(feature = "session-check")]
+    #[should_panic(expected = "different MPC sessions")]
+    fn test_cross_session_add() {
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let network1 = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let network2 = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+
+        let value1 = MpcScalar::from_public_u64(1, network1, beaver_source.clone());
+        let value2 = MpcScalar::from_public_u64(2, network2, beaver_source);
+
+        let _ = value1 + value2;
+    }
+
+    #[test]
+    #[cfg(feature = "session-check")]
+    #[should_panic(expected = "different MPC sessions")]
+    fn test_cross_session_batch_mul() {
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let network1 = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let network2 = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+
+        // The sessions only differ in the second pair of operands
+        let a = MpcScalar::from_public_u64_slice(&[1, 2], network1.clone(), beaver_source.clone());
+        let b = vec![
+            MpcScalar::from_public_u64(3, network1, beaver_source.clone()),
+            MpcScalar::from_public_u64(4, network2, beaver_source),
+        ];
+
+        let _ = MpcScalar::batch_mul(&a, &b);
+    }
+
+    #[test]
+    #[cfg(feature = "debug")]
+    fn test_batch_commit_and_open_diagnostic() {
+        use crate::commitment::PedersenCommitment;
+
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Mock the peer's commitments and openings, tampering with the opening at index 1
+        let peer_commitments = (0..3u64)
+            .map(|value| PedersenCommitment::commit(Scalar::from(value)))
+            .collect::<Vec<_>>();
+        network.borrow_mut().add_mock_points(
+            peer_commitments
+                .iter()
+                .map(|comm| comm.get_commitment())
+                .collect(),
+        );
+        network.borrow_mut().add_mock_scalars(
+            peer_commitments
+                .iter()
+                .enumerate()
+                .flat_map(|(i, comm)| {
+                    let value = if i == 1 {
+                        comm.get_value() + Scalar::one()
+                    } else {
+                        comm.get_value()
+                    };
+                    vec![comm.get_blinding(), value]
+                })
+                .collect(),
+        );
+
+        let values = (0..3u64)
+            .map(|value| {
+                MpcScalar::from_u64_with_visibility(
+                    value,
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        match MpcScalar::batch_commit_and_open_diagnostic(&values) {
+            Err((1, MpcError::AuthenticationError)) => {}
+            res => panic!("expected an authentication error at index 1, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_try_open() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public values open to themselves without communication
+        let public_value = MpcScalar::from_public_u64(3, network.clone(), beaver_source.clone());
+        assert_eq!(public_value.try_open().unwrap(), public_value);
+
+        // Shared values are reconstructed from the peer's share
+        network
+            .borrow_mut()
+            .add_mock_scalars(vec![Scalar::from(4u64)]);
+        let shared_value = MpcScalar::from_u64_with_visibility(
+            3,
+            Visibility::Shared,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        assert_eq!(shared_value.try_open().unwrap().value(), Scalar::from(7u64));
+
+        // Private values must be shared first
+        let private_value = MpcScalar::from_private_u64(3, network, beaver_source);
+        assert!(matches!(
+            private_value.try_open(),
+            Err(MpcError::VisibilityError(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_public_constant() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // The dummy network is always the king, so the constant is added to the local share
+        let shared_value = MpcScalar::from_u64_with_visibility(
+            3,
+            Visibility::Shared,
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let res = shared_value.add_public_constant(Scalar::from(4u64));
+        let expected =
+            &shared_value + MpcScalar::from_public_u64(4, network.clone(), beaver_source.clone());
+        assert_eq!(res.visibility, Visibility::Shared);
+        assert_eq!(res.value(), expected.value());
+
+        // Public values are updated by both parties
+        let public_value = MpcScalar::from_public_u64(3, network, beaver_source);
+        let res = public_value.add_public_constant(Scalar::from(4u64));
+        assert_eq!(res.visibility, Visibility::Public);
+        assert_eq!(res.value(), Scalar::from(7u64));
+    }
 }
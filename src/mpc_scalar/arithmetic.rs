@@ -0,0 +1,579 @@
+//! Groups arithmetic gadgets over shared scalars beyond the field operations
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::{MpcError, MpcNetworkError},
+    mpc_scalar::{bits::scalar_bits_le, MpcScalar},
+    network::MpcNetwork,
+    Visibility,
+};
+
+/// The bit length of shared exponents; shared exponents passed to `pow` are assumed to lie in
+/// the range [0, 2^SECRET_EXPONENT_BITS)
+pub const SECRET_EXPONENT_BITS: usize = 64;
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Computes the multiplicative inverse of a shared value without revealing the value, by
+    /// dividing one by the value
+    ///
+    /// See `try_div` for the protocol and its cost
+    pub fn invert(&self) -> Result<MpcScalar<N, S>, MpcError> {
+        MpcScalar::one(self.network(), self.beaver_source()).try_div(self)
+    }
+
+    /// Divides the value by a (possibly shared) divisor without revealing either
+    ///
+    /// A shared divisor y is masked with a shared random value r, sampled locally with
+    /// `random_shared` at no cost in communication or preprocessing: the value x and the
+    /// divisor are multiplied by r in one batch, the masked divisor y * r is opened, and the
+    /// quotient is computed locally as [x * r] * (y * r)^-1. A public divisor divides locally. Returns an
+    /// `ArithmeticError` if the divisor is zero, or opens to zero when masked, which reveals
+    /// that the divisor is zero (or, with negligible probability, that the mask is zero), and a
+    /// `VisibilityError` if either value is private.
+    ///
+    /// Cost: one Beaver triplet per shared operand and two rounds of communication; a round
+    /// fewer than inverting the divisor and then multiplying
+    pub fn try_div(&self, rhs: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        if self.is_private() || rhs.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be divided...".to_string(),
+            ));
+        }
+
+        if rhs.is_public() {
+            if rhs.value() == Scalar::zero() {
+                return Err(MpcError::ArithmeticError(
+                    "cannot divide by zero".to_string(),
+                ));
+            }
+
+            return Ok(self * rhs.value().invert());
+        }
+
+        let mask = MpcScalar::random_shared(self.network(), self.beaver_source());
+        let mut masked =
+            MpcScalar::try_batch_mul(&[self.clone(), rhs.clone()], &[mask.clone(), mask])?;
+        let masked_divisor = masked.pop().unwrap().try_open()?;
+
+        if masked_divisor.value() == Scalar::zero() {
+            return Err(MpcError::ArithmeticError(
+                "cannot divide by a shared value that opens to zero when masked".to_string(),
+            ));
+        }
+
+        Ok(masked.pop().unwrap() * masked_divisor.value().invert())
+    }
+
+    /// Inverts each value without revealing the values
+    ///
+    /// Each shared value x_i is masked with its own shared random value r_i; the masked values
+    /// x_i * r_i are computed in one batch and opened together, after which every opened value
+    /// and public value is inverted with a single field inversion by Montgomery's trick, and
+    /// [x_i^-1] = (x_i * r_i)^-1 * [r_i]. Returns an `ArithmeticError` if any value is zero, or
+    /// opens to zero when masked, and a `VisibilityError` if any value is private.
+    ///
+    /// Cost: one Beaver triplet per shared value and two rounds of communication for the whole
+    /// batch; the rounds of a single `invert`
+    pub fn batch_invert(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.iter().any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be inverted...".to_string(),
+            ));
+        }
+
+        let shared_values = values
+            .iter()
+            .filter(|value| value.is_shared())
+            .cloned()
+            .collect::<Vec<_>>();
+        let (masks, opened) = if shared_values.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            let masks = MpcScalar::batch_random_shared(
+                shared_values.len(),
+                values[0].network(),
+                values[0].beaver_source(),
+            );
+            let masked = MpcScalar::try_batch_mul(&shared_values, &masks)?;
+            let opened = MpcScalar::batch_open(&masked).map_err(MpcError::NetworkError)?;
+
+            (masks, opened)
+        };
+
+        // Invert the opened masked values and the public values together, in the order of the
+        // values
+        let mut opened = opened.into_iter();
+        let mut inverses = values
+            .iter()
+            .map(|value| {
+                if value.is_shared() {
+                    opened.next().unwrap().value()
+                } else {
+                    value.value()
+                }
+            })
+            .collect::<Vec<_>>();
+        if inverses.iter().any(|value| *value == Scalar::zero()) {
+            return Err(MpcError::ArithmeticError(
+                "cannot invert a value that is zero, or opens to zero when masked".to_string(),
+            ));
+        }
+        Scalar::batch_invert(&mut inverses);
+
+        let mut masks = masks.into_iter();
+        Ok(values
+            .iter()
+            .zip(inverses)
+            .map(|(value, inverse)| {
+                if value.is_shared() {
+                    masks.next().unwrap() * inverse
+                } else {
+                    MpcScalar::from_public_scalar(inverse, value.network(), value.beaver_source())
+                }
+            })
+            .collect())
+    }
+
+    /// Squares the value, drawing a square pair from the Beaver source rather than a triplet
+    ///
+    /// See `batch_square` for the protocol and its cost
+    pub fn square(&self) -> Result<MpcScalar<N, S>, MpcNetworkError> {
+        Ok(Self::batch_square(std::slice::from_ref(self))?.remove(0))
+    }
+
+    /// Squares each value; a shared value x is squared with a square pair (a, a^2) rather than
+    /// a Beaver triplet
+    ///
+    /// The parties open d = x - a, after which x^2 = d^2 + 2 * d * a + a^2 is computed locally.
+    /// Values that are not shared are squared locally.
+    ///
+    /// Cost: one square pair and one opened value per shared value, in one round of
+    /// communication for the whole batch; half the openings of a Beaver multiplication
+    pub fn batch_square(
+        values: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcNetworkError> {
+        let num_shared = values.iter().filter(|value| value.is_shared()).count();
+        if num_shared == 0 {
+            return Ok(values.iter().map(|value| value * value).collect());
+        }
+
+        let square_pairs = values[0]
+            .beaver_source()
+            .as_ref()
+            .borrow_mut()
+            .next_square_pair_batch(num_shared)
+            .into_iter()
+            .map(|(a, a_squared)| {
+                let share = |value| {
+                    MpcScalar::from_scalar_with_visibility(
+                        value,
+                        Visibility::Shared,
+                        values[0].network(),
+                        values[0].beaver_source(),
+                    )
+                };
+                (share(a), share(a_squared))
+            })
+            .collect::<Vec<_>>();
+
+        let masked = values
+            .iter()
+            .filter(|value| value.is_shared())
+            .zip(square_pairs.iter())
+            .map(|(value, (a, _))| value - a)
+            .collect::<Vec<_>>();
+        let mut opened = MpcScalar::batch_open(&masked)?.into_iter();
+        let mut square_pairs = square_pairs.into_iter();
+
+        Ok(values
+            .iter()
+            .map(|value| {
+                if !value.is_shared() {
+                    return value * value;
+                }
+
+                // Identity: [x^2] = d^2 + 2d[a] + [a^2], the king adds the public d^2
+                let d = opened.next().unwrap().value();
+                let (a, a_squared) = square_pairs.next().unwrap();
+                (a * (d + d) + a_squared).add_public_constant(d * d)
+            })
+            .collect())
+    }
+
+    /// Raises the value to a public or shared power
+    ///
+    /// A public exponent is applied by square-and-multiply over all of its bits, scanned from
+    /// the least significant; each round squares the running power of the base and, if the bit
+    /// is set, multiplies it into the result in the same batch. A shared exponent is assumed to
+    /// lie in [0, 2^SECRET_EXPONENT_BITS) and is not revealed: it is decomposed into shared bits
+    /// e_i, the powers x^(2^i) of the base are computed by repeated squaring, and
+    /// x^e = \prod_i (1 + e_i * (x^(2^i) - 1)) is computed by selecting each factor with one
+    /// multiplication and multiplying the factors in a tree. A public value raised to a public
+    /// power is computed locally; returns a `VisibilityError` if either value is private.
+    ///
+    /// To raise a shared value to a small public power in one round, evaluate the monomial x^k
+    /// with `evaluate_polynomial`, which draws an exponentiation tuple instead of triplets.
+    ///
+    /// Cost: for a public exponent e, floor(log2(e)) + popcount(e) - 1 Beaver triplets and at
+    /// most floor(log2(e)) + 1 rounds of communication. For a shared exponent, the cost of
+    /// `to_bits_le` for SECRET_EXPONENT_BITS bits, SECRET_EXPONENT_BITS - 1 square pairs,
+    /// 2 * SECRET_EXPONENT_BITS - 1 Beaver triplets, and
+    /// SECRET_EXPONENT_BITS + log2(SECRET_EXPONENT_BITS) further rounds of communication
+    pub fn pow(&self, exp: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        if self.is_private() || exp.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be raised to a power...".to_string(),
+            ));
+        }
+
+        if exp.is_public() {
+            self.pow_public(&exp.value())
+        } else {
+            self.pow_shared(exp)
+        }
+    }
+
+    /// Raises a public or shared value to a public power by square-and-multiply, see `pow`
+    fn pow_public(&self, exp: &Scalar) -> Result<MpcScalar<N, S>, MpcError> {
+        let mut exp_bits = scalar_bits_le(exp).collect::<Vec<_>>();
+        while exp_bits.last() == Some(&false) {
+            exp_bits.pop();
+        }
+
+        if self.is_public() {
+            let value = exp_bits.iter().rev().fold(Scalar::one(), |acc, bit| {
+                let acc = acc * acc;
+                if *bit {
+                    acc * self.value()
+                } else {
+                    acc
+                }
+            });
+            return Ok(MpcScalar::from_public_scalar(
+                value,
+                self.network(),
+                self.beaver_source(),
+            ));
+        }
+
+        let mut result: Option<MpcScalar<N, S>> = None;
+        let mut base = self.clone();
+        for (i, bit) in exp_bits.iter().enumerate() {
+            let square_base = i + 1 < exp_bits.len();
+            let mut lhs = Vec::with_capacity(2);
+            let mut rhs = Vec::with_capacity(2);
+            if square_base {
+                lhs.push(base.clone());
+                rhs.push(base.clone());
+            }
+            if *bit {
+                match result.as_ref() {
+                    Some(result) => {
+                        lhs.push(result.clone());
+                        rhs.push(base.clone());
+                    }
+                    None => result = Some(base.clone()),
+                }
+            }
+
+            if !lhs.is_empty() {
+                let mut products = MpcScalar::try_batch_mul(&lhs, &rhs)?.into_iter();
+                if square_base {
+                    base = products.next().unwrap();
+                }
+                if let Some(product) = products.next() {
+                    result = Some(product);
+                }
+            }
+        }
+
+        Ok(result.unwrap_or_else(|| MpcScalar::one(self.network(), self.beaver_source())))
+    }
+
+    /// Raises a public or shared value to a shared power without revealing either, see `pow`
+    fn pow_shared(&self, exp: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        let exp_bits = exp.to_bits_le(SECRET_EXPONENT_BITS)?;
+
+        // The powers x^(2^i), squared locally if the base is public
+        let mut powers = Vec::with_capacity(SECRET_EXPONENT_BITS);
+        powers.push(self.clone());
+        for _ in 1..SECRET_EXPONENT_BITS {
+            let last = powers.last().unwrap();
+            let square = if last.is_public() {
+                last * last
+            } else {
+                last.square().map_err(MpcError::NetworkError)?
+            };
+            powers.push(square);
+        }
+
+        // Select x^(2^i) if the ith bit is set and one otherwise, as 1 + e_i * (x^(2^i) - 1)
+        let one = MpcScalar::one(self.network(), self.beaver_source());
+        let powers_minus_one = powers.iter().map(|power| power - &one).collect::<Vec<_>>();
+        let mut factors = MpcScalar::try_batch_mul(&exp_bits, &powers_minus_one)?
+            .into_iter()
+            .map(|selected| selected + &one)
+            .collect::<Vec<_>>();
+
+        // Multiply the factors in a tree, one round per level
+        while factors.len() > 1 {
+            let num_pairs = factors.len() / 2;
+            let mut products = MpcScalar::try_batch_mul(
+                &factors[..num_pairs],
+                &factors[num_pairs..2 * num_pairs],
+            )?;
+            if factors.len() % 2 == 1 {
+                products.push(factors.pop().unwrap());
+            }
+
+            factors = products;
+        }
+
+        Ok(factors.remove(0))
+    }
+
+    /// Evaluates the polynomial with public coefficients, lowest degree first, at the value
+    ///
+    /// A shared value x is evaluated with an exponentiation tuple (r, r^2, ..., r^k) for the
+    /// degree k: the parties open d = x - r, shift the polynomial locally to q(y) = p(d + y),
+    /// and compute p(x) = q(r) as a linear combination of the shared powers of r. Public values
+    /// are evaluated locally; returns a `VisibilityError` for a private value.
+    ///
+    /// Cost: one exponentiation tuple of length k and one opened value, in one round of
+    /// communication regardless of the degree; the local work is quadratic in the degree
+    pub fn evaluate_polynomial(&self, coeffs: &[Scalar]) -> Result<MpcScalar<N, S>, MpcError> {
+        if self.is_private() {
+            return Err(MpcError::VisibilityError(
+                "Polynomials may not be evaluated at private values...".to_string(),
+            ));
+        }
+
+        let degree = coeffs.len().saturating_sub(1);
+        if self.is_public() || degree == 0 {
+            let value = coeffs
+                .iter()
+                .rev()
+                .fold(Scalar::zero(), |acc, coeff| acc * self.value() + coeff);
+            return Ok(MpcScalar::from_public_scalar(
+                value,
+                self.network(),
+                self.beaver_source(),
+            ));
+        }
+
+        let powers = self
+            .beaver_source()
+            .as_ref()
+            .borrow_mut()
+            .next_exp_tuple(degree)?
+            .into_iter()
+            .map(|power| {
+                MpcScalar::from_scalar_with_visibility(
+                    power,
+                    Visibility::Shared,
+                    self.network(),
+                    self.beaver_source(),
+                )
+            })
+            .collect::<Vec<_>>();
+        if powers.len() != degree {
+            return Err(MpcError::PreprocessingError(format!(
+                "expected an exponentiation tuple of length {}, got {}",
+                degree,
+                powers.len()
+            )));
+        }
+
+        let d = (self - &powers[0])
+            .open()
+            .map_err(MpcError::NetworkError)?
+            .value();
+
+        // Taylor shift by repeated synthetic division; afterwards shifted[j] is the coefficient
+        // of y^j in p(d + y)
+        let mut shifted = coeffs.to_vec();
+        for i in 0..degree {
+            for j in (i..degree).rev() {
+                let carry = d * shifted[j + 1];
+                shifted[j] += carry;
+            }
+        }
+
+        // Identity: [p(x)] = q(0) + sum_j q_j[r^j], the king adds the public q(0)
+        Ok(powers
+            .iter()
+            .zip(shifted.iter().skip(1))
+            .map(|(power, coeff)| power * coeff)
+            .sum::<MpcScalar<N, S>>()
+            .add_public_constant(shifted[0]))
+    }
+
+    /// Evaluates the polynomial with (possibly shared) coefficients, lowest degree first, at a
+    /// (possibly shared) point
+    ///
+    /// The powers x, x^2, ..., x^k of a shared point are derived from an exponentiation tuple
+    /// (r, r^2, ..., r^k) for the degree k: the parties open d = x - r and expand
+    /// [x^j] = \sum_i C(j, i) d^(j - i) [r^i] locally. The polynomial is then the inner product
+    /// of the coefficients with the powers, taken with `dot`. Returns a `VisibilityError` if
+    /// the point or any coefficient is private.
+    ///
+    /// Cost: one exponentiation tuple of length k for a shared point, one Beaver triplet per
+    /// shared coefficient of degree at least one, and two rounds of communication regardless
+    /// of the degree
+    pub fn eval_poly(
+        coeffs: &[MpcScalar<N, S>],
+        x: &MpcScalar<N, S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        if x.is_private() || coeffs.iter().any(|coeff| coeff.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be evaluated...".to_string(),
+            ));
+        }
+
+        if coeffs.len() <= 1 {
+            return Ok(coeffs
+                .first()
+                .cloned()
+                .unwrap_or_else(|| MpcScalar::zero(x.network(), x.beaver_source())));
+        }
+
+        let powers = x.powers(coeffs.len() - 1)?;
+        Ok(&coeffs[0] + MpcScalar::dot(&coeffs[1..], &powers)?)
+    }
+
+    /// Computes the powers x, x^2, ..., x^k of the value from an exponentiation tuple, see
+    /// `eval_poly`; a public value is raised locally
+    fn powers(&self, k: usize) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if self.is_public() {
+            let mut power = Scalar::one();
+            return Ok((0..k)
+                .map(|_| {
+                    power *= self.value();
+                    MpcScalar::from_public_scalar(power, self.network(), self.beaver_source())
+                })
+                .collect());
+        }
+
+        let tuple = self
+            .beaver_source()
+            .as_ref()
+            .borrow_mut()
+            .next_exp_tuple(k)?;
+        if tuple.len() != k {
+            return Err(MpcError::PreprocessingError(format!(
+                "expected an exponentiation tuple of length {}, got {}",
+                k,
+                tuple.len()
+            )));
+        }
+
+        // The shared powers r^0, r^1, ..., r^k, where r^0 = 1 is public
+        let r_powers = std::iter::once(MpcScalar::one(self.network(), self.beaver_source()))
+            .chain(tuple.into_iter().map(|power| {
+                MpcScalar::from_scalar_with_visibility(
+                    power,
+                    Visibility::Shared,
+                    self.network(),
+                    self.beaver_source(),
+                )
+            }))
+            .collect::<Vec<_>>();
+
+        let d = (self - &r_powers[1])
+            .open()
+            .map_err(MpcError::NetworkError)?
+            .value();
+
+        // Identity: [x^j] = \sum_i C(j, i) d^(j - i) [r^i], with the binomial coefficients taken
+        // row by row from Pascal's triangle
+        let d_powers = std::iter::successors(Some(Scalar::one()), |power| Some(power * d))
+            .take(k + 1)
+            .collect::<Vec<_>>();
+        let mut binomials = vec![Scalar::one()];
+        let mut res = Vec::with_capacity(k);
+        for j in 1..=k {
+            let mut next = vec![Scalar::one(); j + 1];
+            for i in 1..j {
+                next[i] = binomials[i - 1] + binomials[i];
+            }
+            binomials = next;
+
+            res.push(
+                (0..=j)
+                    .map(|i| &r_powers[i] * (binomials[i] * d_powers[j - i]))
+                    .sum(),
+            );
+        }
+
+        Ok(res)
+    }
+
+    /// Computes the prefix sums [x_1, x_1 + x_2, ..., x_1 + ... + x_n] of the values locally
+    pub fn prefix_sum(values: &[MpcScalar<N, S>]) -> Vec<MpcScalar<N, S>> {
+        let mut res: Vec<MpcScalar<N, S>> = Vec::with_capacity(values.len());
+        for value in values.iter() {
+            let sum = match res.last() {
+                Some(prev) => prev + value,
+                None => value.clone(),
+            };
+            res.push(sum);
+        }
+
+        res
+    }
+
+    /// Computes the prefix products [x_1, x_1 * x_2, ..., x_1 * ... * x_n] of the values
+    ///
+    /// The prefixes are computed by a log-depth tree: in round j every prefix at index
+    /// i >= 2^j is multiplied by the prefix at index i - 2^j, in one batch. Unlike the masked
+    /// ratio technique the values may be zero. Returns a `VisibilityError` if any value is
+    /// private.
+    ///
+    /// Cost: at most n * ceil(log2(n)) Beaver triplets, and ceil(log2(n)) rounds of
+    /// communication
+    pub fn prefix_prod(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.iter().any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be multiplied...".to_string(),
+            ));
+        }
+
+        let mut res = values.to_vec();
+        let mut step = 1;
+        while step < res.len() {
+            let products = MpcScalar::try_batch_mul(&res[step..], &res[..res.len() - step])?;
+            for (prefix, product) in res[step..].iter_mut().zip(products) {
+                *prefix = product;
+            }
+
+            step *= 2;
+        }
+
+        Ok(res)
+    }
+
+    /// Divides each value by the sum of all the values, so that the result sums to one
+    ///
+    /// The sum is computed locally and inverted once with `invert`, after which every value is
+    /// multiplied by the inverse in one batch. Returns an `ArithmeticError` if the values sum
+    /// to zero.
+    ///
+    /// Cost: the cost of `invert`, plus one Beaver triplet per value and one further round of
+    /// communication
+    pub fn normalize(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sum_inverse = values
+            .iter()
+            .skip(1)
+            .fold(values[0].clone(), |acc, value| acc + value)
+            .invert()?;
+
+        MpcScalar::batch_mul(values, &vec![sum_inverse; values.len()])
+            .map_err(MpcError::NetworkError)
+    }
+}
@@ -0,0 +1,109 @@
+//! Groups gadgets that assert properties of shared values without revealing them
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+};
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Asserts that `b` is a permutation of `a` without revealing the permutation
+    ///
+    /// The parties jointly sample a random challenge x and check that
+    ///     \prod_i (x - a_i) == \prod_i (x - b_i)
+    /// which holds for a non-permutation with probability at most n / |F|. The difference
+    /// of the products is multiplied by a shared random mask before it is opened, so a
+    /// failing check reveals nothing beyond the failure itself.
+    ///
+    /// Cost: 2n - 1 Beaver triplets and ceil(log_2(n)) + 5 rounds of communication; two to
+    /// sample the challenge, the multiplication tree, one to mask the difference and two to
+    /// commit to and open the masked difference
+    pub fn assert_permutation(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<(), MpcError> {
+        if a.len() != b.len() {
+            return Err(MpcError::AssertionError(format!(
+                "permutation inputs must be of equal length, got {} and {}",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        if a.is_empty() {
+            return Ok(());
+        }
+
+        let network = a[0].network();
+        let beaver_source = a[0].beaver_source();
+        let challenge = MpcScalar::coin_flip(network.clone(), beaver_source.clone())?;
+
+        // Compute both products in a single multiplication tree
+        let mut a_terms = a.iter().map(|a_val| &challenge - a_val).collect::<Vec<_>>();
+        let mut b_terms = b.iter().map(|b_val| &challenge - b_val).collect::<Vec<_>>();
+        while a_terms.len() > 1 {
+            let (a_next, b_next) = Self::batch_pairwise_product(&a_terms, &b_terms)?;
+            a_terms = a_next;
+            b_terms = b_next;
+        }
+
+        // Mask the difference so that a failing check does not leak the difference of the products
+        let mask = MpcScalar::random_shared(network, beaver_source.clone());
+        let masked_difference = MpcScalar::batch_mul(&[&a_terms[0] - &b_terms[0]], &[mask])
+            .map_err(MpcError::NetworkError)?
+            .remove(0)
+            .commit_and_open()?;
+
+        if masked_difference.value() != Scalar::zero() {
+            return Err(MpcError::AssertionError(
+                "inputs are not a permutation of one another".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Asserts that the value lies in the range [0, bound) without revealing the value
+    ///
+    /// The value is compared against the bound with `less_than`, and only the comparison bit is
+    /// opened. The check is sound for values in the range [0, 2^COMPARISON_BITS); see
+    /// `batch_less_than` for the cost of the comparison
+    pub fn assert_in_range(&self, bound: u64) -> Result<(), MpcError> {
+        let bound_value = MpcScalar::from_public_u64(bound, self.network(), self.beaver_source());
+        let in_range = self.less_than(&bound_value)?.commit_and_open()?;
+
+        if in_range.value() != Scalar::one() {
+            return Err(MpcError::AssertionError(format!(
+                "value is not in the range [0, {})",
+                bound
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Multiplies adjacent pairs in both vectors in a single round, an odd element out is
+    /// carried over to the result unchanged
+    #[allow(clippy::type_complexity)]
+    fn batch_pairwise_product(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<(Vec<MpcScalar<N, S>>, Vec<MpcScalar<N, S>>), MpcError> {
+        let num_pairs = a.len() / 2;
+        let (lhs, rhs): (Vec<_>, Vec<_>) = a
+            .chunks_exact(2)
+            .chain(b.chunks_exact(2))
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .unzip();
+
+        let mut products = MpcScalar::batch_mul(&lhs, &rhs).map_err(MpcError::NetworkError)?;
+        let mut b_products = products.split_off(num_pairs);
+
+        if a.len() % 2 == 1 {
+            products.push(a[a.len() - 1].clone());
+            b_products.push(b[b.len() - 1].clone());
+        }
+
+        Ok((products, b_products))
+    }
+}
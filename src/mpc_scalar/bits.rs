@@ -0,0 +1,281 @@
+//! Groups gadgets for decomposing shared scalars into shared bits
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+    Visibility,
+};
+
+/// The statistical security parameter used when masking a value before opening it;
+/// the opened value leaks at most 2^-STATISTICAL_SECURITY bits about the underlying value
+pub const STATISTICAL_SECURITY: usize = 40;
+/// The maximum bit length that may be decomposed; the masked value must not
+/// wrap around the scalar field modulus (~2^252)
+pub const MAX_DECOMPOSITION_BITS: usize = 250 - STATISTICAL_SECURITY;
+
+/// Returns the scalar 2^k
+pub(crate) fn pow2(k: usize) -> Scalar {
+    assert!(k < 253, "2^{} is larger than the scalar field", k);
+    let mut bytes = [0u8; 32];
+    bytes[k / 8] |= 1 << (k % 8);
+
+    Scalar::from_bits(bytes)
+}
+
+/// Returns the little endian bits of the scalar, starting at the least significant bit
+pub(crate) fn scalar_bits_le(a: &Scalar) -> impl Iterator<Item = bool> + '_ {
+    a.as_bytes()
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Decomposes a value into its `num_bits` least significant bits, returned in little endian order
+    ///
+    /// The value is assumed to be in the range [0, 2^num_bits); the decomposition of a value outside
+    /// of this range is not well defined. See `batch_to_bits_le` for the cost of this method.
+    pub fn to_bits_le(&self, num_bits: usize) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Ok(Self::batch_to_bits_le(std::slice::from_ref(self), num_bits)?.remove(0))
+    }
+
+    /// Decomposes a batch of values into their `num_bits` least significant bits, each in little
+    /// endian order
+    ///
+    /// The parties mask each value with a random value r = \sum_i r_i * 2^i built from
+    /// `num_bits + STATISTICAL_SECURITY` shared bits, open c = x + r, and compute x = c - r
+    /// as a ripple-borrow subtraction of the shared bits of r from the public bits of c.
+    ///
+    /// Cost: `num_bits + STATISTICAL_SECURITY` shared bits per value, `num_bits - 1` Beaver
+    /// triplets per value, and `num_bits` rounds of communication for the whole batch
+    pub fn batch_to_bits_le(
+        values: &[MpcScalar<N, S>],
+        num_bits: usize,
+    ) -> Result<Vec<Vec<MpcScalar<N, S>>>, MpcError> {
+        Self::batch_low_bits_le(values, num_bits, num_bits)
+    }
+
+    /// Splits a value into its low `k` bits and its remaining high bits, such that
+    /// value = low + high * 2^k
+    ///
+    /// The value is assumed to be in the range [0, 2^MAX_DECOMPOSITION_BITS). The low bits are
+    /// extracted with a bit decomposition masked over the full input range, after which the
+    /// high bits are computed locally as (value - low) * 2^-k.
+    ///
+    /// Cost: `MAX_DECOMPOSITION_BITS + STATISTICAL_SECURITY` shared bits, `k - 1` Beaver triplets,
+    /// and `k` rounds of communication
+    #[allow(clippy::type_complexity)]
+    pub fn split_at_bit(&self, k: usize) -> Result<(MpcScalar<N, S>, MpcScalar<N, S>), MpcError> {
+        let low_bits =
+            Self::batch_low_bits_le(std::slice::from_ref(self), k, MAX_DECOMPOSITION_BITS)?
+                .remove(0);
+
+        let low = low_bits.iter().enumerate().fold(
+            MpcScalar::zero(self.network(), self.beaver_source()),
+            |acc, (i, bit)| acc + bit * pow2(i),
+        );
+        let high = (self - &low) * pow2(k).invert();
+
+        Ok((low, high))
+    }
+
+    /// Rotates a little endian bit vector by a public amount towards the more significant bits,
+    /// i.e. the bit at index i moves to index (i + by) mod n
+    ///
+    /// This is a local index reshuffle and requires no communication
+    pub fn rotate_bits(bits: &[MpcScalar<N, S>], by: usize) -> Vec<MpcScalar<N, S>> {
+        let mut res = bits.to_vec();
+        if !res.is_empty() {
+            res.rotate_right(by % bits.len());
+        }
+
+        res
+    }
+
+    /// Shifts a little endian bit vector by a public amount towards the more significant bits,
+    /// i.e. the bit at index i moves to index i + by; bits shifted past the end are dropped and
+    /// the vacated low bits are filled with zeros
+    ///
+    /// This is a local index reshuffle and requires no communication
+    pub fn shift_bits(bits: &[MpcScalar<N, S>], by: usize) -> Vec<MpcScalar<N, S>> {
+        if bits.is_empty() {
+            return Vec::new();
+        }
+
+        let shift = by.min(bits.len());
+        let zero = MpcScalar::zero(bits[0].network(), bits[0].beaver_source());
+
+        std::iter::repeat_n(zero, shift)
+            .chain(bits[..bits.len() - shift].iter().cloned())
+            .collect()
+    }
+
+    /// Extracts the `num_bits` least significant bits of each value, where each value is assumed
+    /// to be in the range [0, 2^value_bits)
+    ///
+    /// The mask is sampled over `value_bits + STATISTICAL_SECURITY` bits so that the opened value
+    /// statistically hides the full input, not only the extracted bits
+    pub(crate) fn batch_low_bits_le(
+        values: &[MpcScalar<N, S>],
+        num_bits: usize,
+        value_bits: usize,
+    ) -> Result<Vec<Vec<MpcScalar<N, S>>>, MpcError> {
+        if value_bits > MAX_DECOMPOSITION_BITS || num_bits > value_bits {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot decompose {} bits of a {} bit value, at most {} bits are supported",
+                num_bits, value_bits, MAX_DECOMPOSITION_BITS
+            )));
+        }
+
+        if values.iter().any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be decomposed...".to_string(),
+            ));
+        }
+
+        if values.is_empty() || num_bits == 0 {
+            return Ok(vec![Vec::new(); values.len()]);
+        }
+
+        let network = values[0].network();
+        let beaver_source = values[0].beaver_source();
+
+        // Public values are decomposed locally
+        if values.iter().all(|value| value.is_public()) {
+            return Ok(values
+                .iter()
+                .map(|value| {
+                    scalar_bits_le(&value.value())
+                        .take(num_bits)
+                        .map(|bit| {
+                            MpcScalar::from_public_u64(
+                                bit as u64,
+                                network.clone(),
+                                beaver_source.clone(),
+                            )
+                        })
+                        .collect()
+                })
+                .collect());
+        }
+
+        // Sample the random masks, only the low `num_bits` bits of each mask are needed after opening
+        let mask_bits = values
+            .iter()
+            .map(|_| {
+                beaver_source
+                    .as_ref()
+                    .borrow_mut()
+                    .next_shared_bit_batch(value_bits + STATISTICAL_SECURITY)
+                    .into_iter()
+                    .map(|bit| {
+                        MpcScalar::from_scalar_with_visibility(
+                            bit,
+                            Visibility::Shared,
+                            network.clone(),
+                            beaver_source.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let masked_values = values
+            .iter()
+            .zip(mask_bits.iter())
+            .map(|(value, bits)| {
+                bits.iter()
+                    .enumerate()
+                    .fold(value.clone(), |acc, (i, bit)| acc + bit * pow2(i))
+            })
+            .collect::<Vec<_>>();
+        let opened_masked_values =
+            MpcScalar::batch_open(&masked_values).map_err(MpcError::NetworkError)?;
+        let opened_bits = opened_masked_values
+            .iter()
+            .map(|value| scalar_bits_le(&value.value()).take(num_bits).collect())
+            .collect::<Vec<Vec<bool>>>();
+
+        // Ripple-borrow subtraction of the mask from the opened value, for the ith bit:
+        //  c_i = 0: x_i = r_i + b_i - 2 * r_i * b_i and b_{i+1} = r_i + b_i - r_i * b_i
+        //  c_i = 1: x_i = 1 - r_i - b_i + 2 * r_i * b_i and b_{i+1} = r_i * b_i
+        // The first bit has no incoming borrow so it is computed locally
+        let one = MpcScalar::one(network.clone(), beaver_source.clone());
+        let mut res = Vec::with_capacity(values.len());
+        let mut borrows = Vec::with_capacity(values.len());
+        for (mask, opened) in mask_bits.iter().zip(opened_bits.iter()) {
+            if opened[0] {
+                res.push(vec![&one - &mask[0]]);
+                borrows.push(MpcScalar::zero(network.clone(), beaver_source.clone()));
+            } else {
+                res.push(vec![mask[0].clone()]);
+                borrows.push(mask[0].clone());
+            }
+        }
+
+        for i in 1..num_bits {
+            // One round of communication for the ith bit of every value in the batch
+            let mask_bits_i = mask_bits
+                .iter()
+                .map(|bits| bits[i].clone())
+                .collect::<Vec<_>>();
+            let products =
+                MpcScalar::batch_mul(&mask_bits_i, &borrows).map_err(MpcError::NetworkError)?;
+
+            for (j, product) in products.iter().enumerate() {
+                let bit_sum = &mask_bits_i[j] + &borrows[j];
+                if opened_bits[j][i] {
+                    res[j].push(&one - &bit_sum + product * Scalar::from(2u64));
+                    borrows[j] = product.clone();
+                } else {
+                    res[j].push(&bit_sum - product * Scalar::from(2u64));
+                    borrows[j] = bit_sum - product;
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::DummySharedScalarSource, mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork,
+    };
+
+    use super::pow2;
+
+    #[test]
+    fn test_pow2() {
+        assert_eq!(pow2(0), Scalar::one());
+        assert_eq!(pow2(10), Scalar::from(1024u64));
+        assert_eq!(pow2(64), Scalar::from(u64::MAX) + Scalar::one());
+    }
+
+    #[test]
+    fn test_public_to_bits_le() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public values are decomposed without communication
+        let value = MpcScalar::from_public_u64(13, network, beaver_source);
+        let bits = value
+            .to_bits_le(6)
+            .unwrap()
+            .iter()
+            .map(|bit| bit.value())
+            .collect::<Vec<_>>();
+
+        let expected = vec![1u64, 0, 1, 1, 0, 0]
+            .into_iter()
+            .map(Scalar::from)
+            .collect::<Vec<_>>();
+        assert_eq!(bits, expected);
+    }
+}
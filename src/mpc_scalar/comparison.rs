@@ -0,0 +1,514 @@
+//! Groups comparison and selection gadgets over shared scalars
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+};
+
+use super::bits::pow2;
+
+/// The bit length of values that may be compared; inputs to the comparison gadgets
+/// are assumed to lie in the range [0, 2^COMPARISON_BITS)
+pub const COMPARISON_BITS: usize = 64;
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Computes a shared bit that is one if self < other and zero otherwise
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn less_than(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(
+            Self::batch_less_than(std::slice::from_ref(self), std::slice::from_ref(other))?
+                .remove(0),
+        )
+    }
+
+    /// Computes a shared bit that is one if self < other; equivalent to `less_than`
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn lt(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        self.less_than(other)
+    }
+
+    /// Computes a shared bit that is one if self <= other, as the negation of other < self
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn le(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Scalar::one() - other.less_than(self)?)
+    }
+
+    /// Computes a shared bit that is one if self > other, as other < self
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn gt(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        other.less_than(self)
+    }
+
+    /// Computes a shared bit that is one if self >= other, as the negation of self < other
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn ge(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Scalar::one() - self.less_than(other)?)
+    }
+
+    /// Computes the shared bits [a_1 < b_1, ..., a_n < b_n]
+    ///
+    /// The difference a_i - b_i + 2^COMPARISON_BITS is decomposed into COMPARISON_BITS + 1 bits;
+    /// the most significant of which is set exactly when a_i >= b_i. See `batch_to_bits_le` for
+    /// the cost of the decomposition
+    pub fn batch_less_than(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "input arrays to batch_less_than must be of equal length"
+        );
+
+        let shifted_differences = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a_val, b_val)| a_val - b_val + pow2(COMPARISON_BITS))
+            .collect::<Vec<_>>();
+
+        Ok(
+            MpcScalar::batch_to_bits_le(&shifted_differences, COMPARISON_BITS + 1)?
+                .into_iter()
+                .map(|bits| Scalar::one() - &bits[COMPARISON_BITS])
+                .collect(),
+        )
+    }
+
+    /// Computes a shared bit that is one if self == other and zero otherwise, without opening
+    /// either value
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS)
+    pub fn eq_secret(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(
+            Self::batch_eq_secret(std::slice::from_ref(self), std::slice::from_ref(other))?
+                .remove(0),
+        )
+    }
+
+    /// Computes the shared bits [a_1 == b_1, ..., a_n == b_n]
+    ///
+    /// The values are equal exactly when neither is less than the other, so each bit is
+    /// computed as 1 - (a_i < b_i) - (b_i < a_i), with both comparisons of every pair evaluated
+    /// in one batch; the cost of two comparisons per pair in the rounds of a single comparison
+    pub fn batch_eq_secret(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "input arrays to batch_eq_secret must be of equal length"
+        );
+
+        let lhs = a.iter().chain(b.iter()).cloned().collect::<Vec<_>>();
+        let rhs = b.iter().chain(a.iter()).cloned().collect::<Vec<_>>();
+        let less_than = Self::batch_less_than(&lhs, &rhs)?;
+        let (a_less_than, b_less_than) = less_than.split_at(a.len());
+
+        Ok(a_less_than
+            .iter()
+            .zip(b_less_than.iter())
+            .map(|(a_less, b_less)| Scalar::one() - a_less - b_less)
+            .collect())
+    }
+
+    /// Returns a if the condition bit is one, and b if the condition bit is zero
+    pub fn conditional_select(
+        condition: &MpcScalar<N, S>,
+        a: &MpcScalar<N, S>,
+        b: &MpcScalar<N, S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_conditional_select(
+            std::slice::from_ref(condition),
+            std::slice::from_ref(a),
+            std::slice::from_ref(b),
+        )?
+        .remove(0))
+    }
+
+    /// Selects between a_i and b_i for each of the condition bits in one round of communication
+    ///
+    /// Computes b_i + condition_i * (a_i - b_i), consuming one Beaver triplet for each selection
+    /// in which both the condition and the difference are shared
+    pub fn batch_conditional_select(
+        conditions: &[MpcScalar<N, S>],
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        assert!(
+            conditions.len() == a.len() && a.len() == b.len(),
+            "input arrays to batch_conditional_select must be of equal length"
+        );
+
+        let differences = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a_val, b_val)| a_val - b_val)
+            .collect::<Vec<_>>();
+        let selected_differences =
+            MpcScalar::batch_mul(conditions, &differences).map_err(MpcError::NetworkError)?;
+
+        Ok(selected_differences
+            .iter()
+            .zip(b.iter())
+            .map(|(difference, b_val)| difference + b_val)
+            .collect())
+    }
+
+    /// Opens the payload only if at least `k` of the values satisfy their predicate
+    ///
+    /// `predicate_bits[i]` is a shared bit indicating whether `values[i]` satisfies the condition.
+    /// The predicate bits are summed into a shared count, which is compared against the public
+    /// threshold `k`; the comparison bit is then opened and gates the opening of the payload.
+    ///
+    /// Leakage: beyond the payload itself when it is revealed, both parties learn whether the
+    /// count met the threshold. The count itself is not revealed.
+    pub fn reveal_if_count_at_least(
+        values: &[MpcScalar<N, S>],
+        predicate_bits: &[MpcScalar<N, S>],
+        k: u64,
+        payload: &MpcScalar<N, S>,
+    ) -> Result<Option<MpcScalar<N, S>>, MpcError> {
+        assert_eq!(
+            values.len(),
+            predicate_bits.len(),
+            "each value must have exactly one predicate bit"
+        );
+
+        let network = payload.network();
+        let beaver_source = payload.beaver_source();
+        let count = predicate_bits.iter().fold(
+            MpcScalar::zero(network.clone(), beaver_source.clone()),
+            |acc, bit| acc + bit,
+        );
+        let threshold = MpcScalar::from_public_u64(k, network, beaver_source);
+
+        // count >= k exactly when !(count < k)
+        let below_threshold = count.less_than(&threshold)?.commit_and_open()?;
+        if below_threshold.value() == Scalar::one() {
+            return Ok(None);
+        }
+
+        Ok(Some(payload.commit_and_open()?))
+    }
+
+    /// Merges two sorted sequences into a single sorted sequence without revealing the order
+    /// of any of the elements
+    ///
+    /// The second sequence is reversed and appended to the first to form a bitonic sequence, which
+    /// is sorted by log_2(n) layers of compare-and-swap gadgets; all the gadgets in a layer are
+    /// evaluated in one batch. Both sequences must be sorted in ascending order, and their
+    /// values are assumed to lie in the range [0, 2^COMPARISON_BITS).
+    ///
+    /// The combined length must be a power of two. Shorter inputs may be padded by appending
+    /// public values of 2^COMPARISON_BITS - 1 to the end of either sequence; the padding sorts to
+    /// the end of the merged sequence, from which it may be truncated
+    pub fn bitonic_merge(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        let n = a.len() + b.len();
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        assert!(
+            n.is_power_of_two(),
+            "the combined length of the inputs to bitonic_merge must be a power of two"
+        );
+
+        let mut values = a.iter().chain(b.iter().rev()).cloned().collect::<Vec<_>>();
+        let mut stride = n / 2;
+        while stride > 0 {
+            // Compare each element in the lower half of a block with its partner in the upper half
+            let (lower_indices, upper_indices): (Vec<_>, Vec<_>) = (0..n)
+                .filter(|i| i % (2 * stride) < stride)
+                .map(|i| (i, i + stride))
+                .unzip();
+            let lower = lower_indices
+                .iter()
+                .map(|i| values[*i].clone())
+                .collect::<Vec<_>>();
+            let upper = upper_indices
+                .iter()
+                .map(|i| values[*i].clone())
+                .collect::<Vec<_>>();
+
+            let (mins, maxs) = Self::batch_compare_and_swap(&lower, &upper)?;
+            for ((i, j), (min, max)) in lower_indices
+                .into_iter()
+                .zip(upper_indices)
+                .zip(mins.into_iter().zip(maxs))
+            {
+                values[i] = min;
+                values[j] = max;
+            }
+
+            stride /= 2;
+        }
+
+        Ok(values)
+    }
+
+    /// Sorts the values in ascending order without revealing the order of any of the elements
+    ///
+    /// Implements a bitonic sorting network: log_2(n) * (log_2(n) + 1) / 2 layers of n / 2
+    /// compare-and-swap gadgets each, where all the gadgets in a layer are evaluated in one
+    /// batch. Values are assumed to lie in the range [0, 2^COMPARISON_BITS); an input whose
+    /// length is not a power of two is padded with public values of 2^COMPARISON_BITS - 1, which
+    /// sort to the end and are truncated from the result.
+    ///
+    /// Cost: one batched comparison and one batched selection per layer, consuming the
+    /// comparison of n / 2 pairs and n / 2 Beaver triplets per layer for the padded length n
+    pub fn sort(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.len() <= 1 {
+            return Ok(values.to_vec());
+        }
+
+        let n = values.len().next_power_of_two();
+        let padding = MpcScalar::from_public_scalar(
+            pow2(COMPARISON_BITS) - Scalar::one(),
+            values[0].network(),
+            values[0].beaver_source(),
+        );
+        let mut sorted = values.to_vec();
+        sorted.resize(n, padding);
+
+        let mut block = 2;
+        while block <= n {
+            let mut stride = block / 2;
+            while stride > 0 {
+                // Pair each element with its partner a stride away; the minimum of a pair goes to
+                // the lower index in blocks sorted ascending and to the higher index otherwise
+                let (min_indices, max_indices): (Vec<_>, Vec<_>) = (0..n)
+                    .filter(|i| i & stride == 0)
+                    .map(|i| {
+                        if i & block == 0 {
+                            (i, i + stride)
+                        } else {
+                            (i + stride, i)
+                        }
+                    })
+                    .unzip();
+                let lhs = min_indices
+                    .iter()
+                    .map(|i| sorted[*i].clone())
+                    .collect::<Vec<_>>();
+                let rhs = max_indices
+                    .iter()
+                    .map(|i| sorted[*i].clone())
+                    .collect::<Vec<_>>();
+
+                let (mins, maxs) = Self::batch_compare_and_swap(&lhs, &rhs)?;
+                for ((i, j), (min, max)) in min_indices
+                    .into_iter()
+                    .zip(max_indices)
+                    .zip(mins.into_iter().zip(maxs))
+                {
+                    sorted[i] = min;
+                    sorted[j] = max;
+                }
+
+                stride /= 2;
+            }
+
+            block *= 2;
+        }
+
+        sorted.truncate(values.len());
+        Ok(sorted)
+    }
+
+    /// Computes the minimum of the two values without revealing which of them is smaller
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS). See `batch_min` for
+    /// the cost of this method
+    pub fn min(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_min(std::slice::from_ref(self), std::slice::from_ref(other))?.remove(0))
+    }
+
+    /// Computes the maximum of the two values without revealing which of them is larger
+    ///
+    /// Both values are assumed to lie in the range [0, 2^COMPARISON_BITS). See `batch_min` for
+    /// the cost of this method
+    pub fn max(&self, other: &MpcScalar<N, S>) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_max(std::slice::from_ref(self), std::slice::from_ref(other))?.remove(0))
+    }
+
+    /// Computes the element-wise minimum of each pair (a_i, b_i)
+    ///
+    /// The comparison bit a_i < b_i selects between the pair with `batch_conditional_select`,
+    /// so that the result is shared and neither party learns which operand was selected.
+    ///
+    /// Cost: one batched comparison, and one Beaver triplet per pair and one further round of
+    /// communication for the selection
+    pub fn batch_min(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Ok(Self::batch_compare_and_swap(a, b)?.0)
+    }
+
+    /// Computes the element-wise maximum of each pair (a_i, b_i); the maximum is computed
+    /// locally from the minimum as a_i + b_i - min_i, at the cost of `batch_min`
+    pub fn batch_max(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Ok(Self::batch_compare_and_swap(a, b)?.1)
+    }
+
+    /// Computes the element-wise minimum and maximum of each pair (a_i, b_i)
+    ///
+    /// Cost: one batched comparison, and one Beaver triplet per pair for the selection; the
+    /// maximum is computed locally as a_i + b_i - min_i
+    #[allow(clippy::type_complexity)]
+    fn batch_compare_and_swap(
+        a: &[MpcScalar<N, S>],
+        b: &[MpcScalar<N, S>],
+    ) -> Result<(Vec<MpcScalar<N, S>>, Vec<MpcScalar<N, S>>), MpcError> {
+        let a_smaller = Self::batch_less_than(a, b)?;
+        let mins = Self::batch_conditional_select(&a_smaller, a, b)?;
+        let maxs = a
+            .iter()
+            .zip(b.iter())
+            .zip(mins.iter())
+            .map(|((a_val, b_val), min)| a_val + b_val - min)
+            .collect();
+
+        Ok((mins, maxs))
+    }
+
+    /// Returns a shared one-hot vector indicating the position of the minimum value
+    ///
+    /// Ties are broken towards the lowest index. Values are assumed to lie in the range
+    /// [0, 2^COMPARISON_BITS)
+    pub fn argmin(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Self::tournament(values, true /* minimize */)
+    }
+
+    /// Returns a shared one-hot vector indicating the position of the maximum value
+    ///
+    /// Ties are broken towards the lowest index. Values are assumed to lie in the range
+    /// [0, 2^COMPARISON_BITS)
+    pub fn argmax(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Self::tournament(values, false /* minimize */)
+    }
+
+    /// Returns the shared index of the minimum value, ties broken towards the lowest index
+    ///
+    /// The index is computed locally from the one-hot vector of `argmin` as \sum_i i * e_i, at
+    /// the cost of `argmin`
+    pub fn argmin_index(values: &[MpcScalar<N, S>]) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::one_hot_to_index(&Self::argmin(values)?))
+    }
+
+    /// Returns the shared index of the maximum value, ties broken towards the lowest index
+    ///
+    /// The index is computed locally from the one-hot vector of `argmax` as \sum_i i * e_i, at
+    /// the cost of `argmax`
+    pub fn argmax_index(values: &[MpcScalar<N, S>]) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::one_hot_to_index(&Self::argmax(values)?))
+    }
+
+    /// Converts a non-empty shared one-hot vector into the shared index of its set entry
+    fn one_hot_to_index(one_hot: &[MpcScalar<N, S>]) -> MpcScalar<N, S> {
+        one_hot.iter().enumerate().fold(
+            MpcScalar::zero(one_hot[0].network(), one_hot[0].beaver_source()),
+            |acc, (i, entry)| acc + entry * Scalar::from(i as u64),
+        )
+    }
+
+    /// Runs a tournament over the values, tracking a one-hot vector of each winner's position
+    ///
+    /// Each level of the tournament halves the number of candidates with one batched comparison
+    /// and one batched selection, so the whole tournament takes log_2(n) levels
+    fn tournament(
+        values: &[MpcScalar<N, S>],
+        minimize: bool,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        assert!(!values.is_empty(), "Cannot select from an empty vector");
+
+        let network = values[0].network();
+        let beaver_source = values[0].beaver_source();
+        let n = values.len();
+
+        // Each candidate is a value and a one-hot vector of its position in the input
+        let mut candidates = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let one_hot = (0..n)
+                    .map(|j| {
+                        MpcScalar::from_public_u64(
+                            (i == j) as u64,
+                            network.clone(),
+                            beaver_source.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (value.clone(), one_hot)
+            })
+            .collect::<Vec<_>>();
+
+        while candidates.len() > 1 {
+            // Pair adjacent candidates, an odd candidate out advances to the next level
+            let bye = if candidates.len() % 2 == 1 {
+                candidates.pop()
+            } else {
+                None
+            };
+
+            // The condition bit is set when the right hand candidate wins, ties go to the left
+            let (lhs, rhs): (Vec<_>, Vec<_>) = candidates
+                .chunks(2)
+                .map(|pair| (pair[0].0.clone(), pair[1].0.clone()))
+                .unzip();
+            let right_wins = if minimize {
+                MpcScalar::batch_less_than(&rhs, &lhs)?
+            } else {
+                MpcScalar::batch_less_than(&lhs, &rhs)?
+            };
+
+            // Select the winning value and one-hot vector for each pair in a single batch
+            let mut conditions = Vec::new();
+            let mut right_selections = Vec::new();
+            let mut left_selections = Vec::new();
+            for (pair, condition) in candidates.chunks(2).zip(right_wins.iter()) {
+                for (right, left) in std::iter::once(&pair[1].0)
+                    .chain(pair[1].1.iter())
+                    .zip(std::iter::once(&pair[0].0).chain(pair[0].1.iter()))
+                {
+                    conditions.push(condition.clone());
+                    right_selections.push(right.clone());
+                    left_selections.push(left.clone());
+                }
+            }
+
+            let mut selected = MpcScalar::batch_conditional_select(
+                &conditions,
+                &right_selections,
+                &left_selections,
+            )?
+            .into_iter();
+
+            let mut next_candidates = (0..right_wins.len())
+                .map(|_| {
+                    let value = selected.next().unwrap();
+                    let one_hot = selected.by_ref().take(n).collect::<Vec<_>>();
+                    (value, one_hot)
+                })
+                .collect::<Vec<_>>();
+            next_candidates.extend(bye);
+
+            candidates = next_candidates;
+        }
+
+        Ok(candidates.remove(0).1)
+    }
+}
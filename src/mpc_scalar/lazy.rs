@@ -0,0 +1,326 @@
+//! Implements a deferred-execution mode for arithmetic on shared scalars, in which operations
+//! build a DAG that is evaluated layer by layer, coalescing the openings of each layer into a
+//! single round of communication
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+    BeaverSource, SharedNetwork,
+};
+
+/// A handle to a value recorded in a `LazyGraph`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LazyScalar(usize);
+
+/// An operation recorded in a `LazyGraph`, referring to its operands by their node indices
+#[derive(Debug)]
+enum Operation<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    Input(MpcScalar<N, S>),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    AddConstant(usize, Scalar),
+    MulConstant(usize, Scalar),
+    Mul(usize, usize),
+    Open(usize),
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Operation<N, S> {
+    /// Returns the node indices of the operands
+    fn operands(&self) -> Vec<usize> {
+        match self {
+            Operation::Input(_) => Vec::new(),
+            Operation::Add(lhs, rhs) | Operation::Sub(lhs, rhs) | Operation::Mul(lhs, rhs) => {
+                vec![*lhs, *rhs]
+            }
+            Operation::Neg(operand)
+            | Operation::AddConstant(operand, _)
+            | Operation::MulConstant(operand, _)
+            | Operation::Open(operand) => vec![*operand],
+        }
+    }
+}
+
+/// A node of a `LazyGraph`
+#[derive(Debug)]
+struct Node<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The operation that computes the node's value
+    operation: Operation<N, S>,
+    /// Whether the node's value is public
+    public: bool,
+    /// Whether computing the value requires an opening, i.e. it is the product of two
+    /// non-public values or the opening of a non-public value
+    interactive: bool,
+    /// The number of interactive operations on the longest path to the node, i.e. the round
+    /// of communication in which the value is computed
+    depth: usize,
+}
+
+/// A DAG of deferred operations on shared scalars
+///
+/// Operations on the graph's handles are only recorded; `evaluate` then computes the requested
+/// values layer by layer. A layer holds the products of non-public values and the openings
+/// whose operands are available after the previous layer, and its Beaver openings and openings
+/// are sent as one batch, so that the evaluation takes as many rounds of communication as the
+/// longest chain of dependent multiplications and openings rather than one round per
+/// operation. Local operations are computed as soon as their operands are available.
+#[derive(Debug)]
+pub struct LazyGraph<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The recorded nodes; the operands of each node precede it
+    nodes: Vec<Node<N, S>>,
+    /// The underlying network that the MPC operates on
+    network: SharedNetwork<N>,
+    /// The source for Beaver triplets
+    beaver_source: BeaverSource<S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> LazyGraph<N, S> {
+    /// Creates an empty graph over the given network
+    pub fn new(network: SharedNetwork<N>, beaver_source: BeaverSource<S>) -> Self {
+        Self {
+            nodes: Vec::new(),
+            network,
+            beaver_source,
+        }
+    }
+
+    /// Records an input value; private inputs must be shared before they are evaluated
+    pub fn input(&mut self, value: &MpcScalar<N, S>) -> LazyScalar {
+        self.push(Operation::Input(value.clone()))
+    }
+
+    /// Records a public constant
+    pub fn constant(&mut self, value: Scalar) -> LazyScalar {
+        self.push(Operation::Input(MpcScalar::from_public_scalar(
+            value,
+            self.network.clone(),
+            self.beaver_source.clone(),
+        )))
+    }
+
+    /// Records lhs + rhs
+    pub fn add(&mut self, lhs: LazyScalar, rhs: LazyScalar) -> LazyScalar {
+        self.push(Operation::Add(lhs.0, rhs.0))
+    }
+
+    /// Records lhs - rhs
+    pub fn sub(&mut self, lhs: LazyScalar, rhs: LazyScalar) -> LazyScalar {
+        self.push(Operation::Sub(lhs.0, rhs.0))
+    }
+
+    /// Records -value
+    pub fn neg(&mut self, value: LazyScalar) -> LazyScalar {
+        self.push(Operation::Neg(value.0))
+    }
+
+    /// Records value + c for a public constant c
+    pub fn add_constant(&mut self, value: LazyScalar, c: Scalar) -> LazyScalar {
+        self.push(Operation::AddConstant(value.0, c))
+    }
+
+    /// Records value * c for a public constant c
+    pub fn mul_constant(&mut self, value: LazyScalar, c: Scalar) -> LazyScalar {
+        self.push(Operation::MulConstant(value.0, c))
+    }
+
+    /// Records lhs * rhs; a product of two non-public values is computed in the layer after
+    /// its operands
+    pub fn mul(&mut self, lhs: LazyScalar, rhs: LazyScalar) -> LazyScalar {
+        self.push(Operation::Mul(lhs.0, rhs.0))
+    }
+
+    /// Records the opening of a value; the opening of a non-public value is computed in the
+    /// layer after its operand, and the opened value is public
+    pub fn open(&mut self, value: LazyScalar) -> LazyScalar {
+        self.push(Operation::Open(value.0))
+    }
+
+    /// Returns the number of rounds of communication that evaluating the outputs takes
+    pub fn num_rounds(&self, outputs: &[LazyScalar]) -> usize {
+        outputs
+            .iter()
+            .map(|output| self.nodes[output.0].depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Evaluates the outputs, computing only the nodes that they depend on
+    ///
+    /// Returns a `VisibilityError` if an output depends on a private input.
+    ///
+    /// Cost: one Beaver triplet per product of two non-public values, and `num_rounds` rounds
+    /// of communication
+    pub fn evaluate(&self, outputs: &[LazyScalar]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        let mut needed = vec![false; self.nodes.len()];
+        for output in outputs.iter() {
+            needed[output.0] = true;
+        }
+        for i in (0..self.nodes.len()).rev() {
+            if needed[i] {
+                for operand in self.nodes[i].operation.operands() {
+                    needed[operand] = true;
+                }
+            }
+        }
+
+        let mut values: Vec<Option<MpcScalar<N, S>>> = vec![None; self.nodes.len()];
+        for depth in 0..=self.num_rounds(outputs) {
+            let (interactive, local): (Vec<usize>, Vec<usize>) = (0..self.nodes.len())
+                .filter(|i| needed[*i] && self.nodes[*i].depth == depth)
+                .partition(|i| self.nodes[*i].interactive);
+
+            if !interactive.is_empty() {
+                self.evaluate_interactive(&interactive, &mut values)?;
+            }
+
+            // Local nodes of the layer may depend on each other, but only on earlier nodes
+            for i in local {
+                values[i] = Some(self.evaluate_local(i, &values)?);
+            }
+        }
+
+        Ok(outputs
+            .iter()
+            .map(|output| values[output.0].clone().unwrap())
+            .collect())
+    }
+
+    /// Records a node, deriving its visibility and depth from its operands
+    fn push(&mut self, operation: Operation<N, S>) -> LazyScalar {
+        let operands = operation.operands();
+        let public = match &operation {
+            Operation::Input(value) => value.is_public(),
+            Operation::Open(_) => true,
+            _ => operands.iter().all(|operand| self.nodes[*operand].public),
+        };
+        let interactive = match &operation {
+            Operation::Mul(lhs, rhs) => !self.nodes[*lhs].public && !self.nodes[*rhs].public,
+            Operation::Open(operand) => !self.nodes[*operand].public,
+            _ => false,
+        };
+        let depth = operands
+            .iter()
+            .map(|operand| self.nodes[*operand].depth)
+            .max()
+            .unwrap_or(0)
+            + interactive as usize;
+
+        self.nodes.push(Node {
+            operation,
+            public,
+            interactive,
+            depth,
+        });
+        LazyScalar(self.nodes.len() - 1)
+    }
+
+    /// Computes a node that requires no communication from the values of its operands
+    fn evaluate_local(
+        &self,
+        i: usize,
+        values: &[Option<MpcScalar<N, S>>],
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        let value = |operand: &usize| values[*operand].as_ref().unwrap();
+        Ok(match &self.nodes[i].operation {
+            Operation::Input(input) => {
+                if input.is_private() {
+                    return Err(MpcError::VisibilityError(
+                        "Private values may not be evaluated...".to_string(),
+                    ));
+                }
+
+                input.clone()
+            }
+            Operation::Add(lhs, rhs) => value(lhs) + value(rhs),
+            Operation::Sub(lhs, rhs) => value(lhs) - value(rhs),
+            Operation::Neg(operand) => -value(operand),
+            Operation::AddConstant(operand, c) => value(operand).add_public_constant(*c),
+            Operation::MulConstant(operand, c) => value(operand) * c,
+            // At least one operand is public
+            Operation::Mul(lhs, rhs) => value(lhs) * value(rhs),
+            // The operand is already public
+            Operation::Open(operand) => value(operand).clone(),
+        })
+    }
+
+    /// Computes the products and openings of a layer, opening the Beaver subtractions of the
+    /// products alongside the values to open in a single batch
+    fn evaluate_interactive(
+        &self,
+        layer: &[usize],
+        values: &mut [Option<MpcScalar<N, S>>],
+    ) -> Result<(), MpcError> {
+        let (products, openings): (Vec<usize>, Vec<usize>) = layer
+            .iter()
+            .partition(|i| matches!(self.nodes[**i].operation, Operation::Mul(..)));
+
+        let beaver_triplets = MpcScalar::zero(self.network.clone(), self.beaver_source.clone())
+            .try_next_beaver_triplet_batch(products.len())?;
+
+        let mut to_open = Vec::with_capacity(2 * products.len() + openings.len());
+        for (i, (beaver_a, beaver_b, _)) in products.iter().zip(beaver_triplets.iter()) {
+            if let Operation::Mul(lhs, rhs) = &self.nodes[*i].operation {
+                to_open.push(values[*lhs].as_ref().unwrap() - beaver_a);
+                to_open.push(values[*rhs].as_ref().unwrap() - beaver_b);
+            }
+        }
+        for i in openings.iter() {
+            if let Operation::Open(operand) = &self.nodes[*i].operation {
+                to_open.push(values[*operand].clone().unwrap());
+            }
+        }
+
+        let opened = MpcScalar::batch_open(&to_open).map_err(MpcError::NetworkError)?;
+
+        // Identity: [x * y] = de + d[b] + e[a] + [c] for d = x - a and e = y - b
+        for (k, (i, (beaver_a, beaver_b, beaver_c))) in
+            products.iter().zip(beaver_triplets.iter()).enumerate()
+        {
+            let (d, e) = (&opened[2 * k], &opened[2 * k + 1]);
+            values[*i] = Some(d * beaver_b + e * beaver_a + d * e + beaver_c);
+        }
+        for (k, i) in openings.iter().enumerate() {
+            values[*i] = Some(opened[2 * products.len() + k].clone());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::DummySharedScalarSource, mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork,
+    };
+
+    use super::LazyGraph;
+
+    #[test]
+    fn test_public_graph() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+        let mut graph = LazyGraph::new(network.clone(), beaver_source.clone());
+
+        // (3 * 4 + 5) * 2 - (-3) over public values takes no rounds of communication
+        let x = graph.input(&MpcScalar::from_public_u64(3, network, beaver_source));
+        let y = graph.constant(Scalar::from(4u64));
+        let product = graph.mul(x, y);
+        let sum = graph.add_constant(product, Scalar::from(5u64));
+        let doubled = graph.mul_constant(sum, Scalar::from(2u64));
+        let negated = graph.neg(x);
+        let res = graph.sub(doubled, negated);
+        let opened = graph.open(res);
+
+        assert_eq!(graph.num_rounds(&[opened]), 0);
+        assert_eq!(
+            graph.evaluate(&[opened]).unwrap()[0].value(),
+            Scalar::from(37u64)
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Groups additive sharing of scalars among any number of parties, over a
+//! `MultiPartyNetwork`
+//!
+//! A value x is shared as x_0 + ... + x_(n-1), each party holding one share. Shares add
+//! locally, and a public value is added by the king alone
+
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+use rand_core::OsRng;
+
+use crate::{
+    error::MpcNetworkError,
+    network::{multi_party::MultiPartyNetwork, PartyId},
+};
+
+/// Shares the owning party's values among all parties; the owner sends each other party a
+/// random share of each value and keeps the difference
+///
+/// Only the owning party's values are read, every other party passes a slice of the same
+/// length, e.g. of zeros. Returns the local party's shares
+pub async fn share_secret<N: MultiPartyNetwork + Send>(
+    network: &mut N,
+    owning_party: PartyId,
+    values: &[Scalar],
+) -> Result<Vec<Scalar>, MpcNetworkError> {
+    if network.party_id() != owning_party {
+        return network
+            .receive_scalars_from(owning_party, values.len())
+            .await;
+    }
+
+    let mut rng = OsRng {};
+    let mut local_shares = values.to_vec();
+    for peer_id in (0..network.num_parties() as PartyId).filter(|id| *id != owning_party) {
+        let shares = (0..values.len())
+            .map(|_| Scalar::random(&mut rng))
+            .collect_vec();
+        network.send_scalars_to(peer_id, &shares).await?;
+        for (local_share, share) in local_shares.iter_mut().zip(shares.iter()) {
+            *local_share -= share;
+        }
+    }
+
+    Ok(local_shares)
+}
+
+/// Opens additively shared values to every party; each party broadcasts its shares and sums
+/// the shares of all parties
+pub async fn open<N: MultiPartyNetwork + Send>(
+    network: &mut N,
+    shares: &[Scalar],
+) -> Result<Vec<Scalar>, MpcNetworkError> {
+    let all_shares = network.broadcast_scalars(shares).await?;
+    Ok((0..shares.len())
+        .map(|i| all_shares.iter().map(|shares| shares[i]).sum())
+        .collect_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::network::{local_network::LocalTwoPartyNet, multi_party::MultiPartyNetwork};
+
+    use super::{open, share_secret};
+
+    #[test]
+    fn test_share_and_open() {
+        let handles = LocalTwoPartyNet::new_mesh(4)
+            .into_iter()
+            .map(|mut network| {
+                thread::spawn(move || {
+                    // Each party shares its ID, and the parties open the sum of the IDs
+                    let party_id = network.party_id();
+                    let mut sum = vec![Scalar::zero(); 2];
+                    for owner in 0..network.num_parties() as u64 {
+                        let values = [Scalar::from(party_id), Scalar::from(2 * party_id)];
+                        let shares = block_on(share_secret(&mut network, owner, &values)).unwrap();
+                        sum[0] += shares[0];
+                        sum[1] += shares[1];
+                    }
+
+                    let opened = block_on(open(&mut network, &sum)).unwrap();
+                    block_on(network.close()).unwrap();
+                    (sum, opened)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let results = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        for (_, opened) in results.iter() {
+            assert_eq!(*opened, vec![Scalar::from(6u64), Scalar::from(12u64)]);
+        }
+
+        // No party holds the sum on its own
+        assert!(results
+            .iter()
+            .all(|(shares, _)| shares[0] != Scalar::from(6u64)));
+    }
+}
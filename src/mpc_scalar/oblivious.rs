@@ -0,0 +1,94 @@
+//! Groups gadgets for accessing a table at a shared index without revealing the index
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+};
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Converts a shared index into a shared one-hot vector of length `len`, in which only the
+    /// entry at the index is one
+    ///
+    /// The index is assumed to lie in the range [0, len); the result is not well defined for an
+    /// index outside of this range. The index is decomposed into its bits, which are expanded into
+    /// the one-hot vector one bit at a time.
+    ///
+    /// Cost: the decomposition of ceil(log_2(len)) bits, up to 2 * len Beaver triplets, and
+    /// ceil(log_2(len)) further rounds of communication
+    pub fn to_one_hot(&self, len: usize) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+
+        let num_bits = (usize::BITS - (len - 1).leading_zeros()) as usize;
+        let bits = self.to_bits_le(num_bits)?;
+
+        // After expanding j bits, entry i of the vector is one exactly when the low j bits of
+        // the index equal i
+        let mut one_hot = vec![MpcScalar::one(self.network(), self.beaver_source())];
+        for bit in bits.iter() {
+            let products = MpcScalar::batch_mul(&one_hot, &vec![bit.clone(); one_hot.len()])
+                .map_err(MpcError::NetworkError)?;
+
+            one_hot = one_hot
+                .iter()
+                .zip(products.iter())
+                .map(|(entry, product)| entry - product)
+                .chain(products.iter().cloned())
+                .collect();
+        }
+
+        one_hot.truncate(len);
+        Ok(one_hot)
+    }
+
+    /// Reads the entry of the table at a shared index without revealing the index
+    ///
+    /// The index is first asserted to lie in the range [0, table.len()), revealing only whether
+    /// the assertion holds. The index is then converted to a one-hot vector, and the entry is
+    /// read as the inner product of the one-hot vector with the table in one round of
+    /// communication
+    pub fn oblivious_read(
+        table: &[MpcScalar<N, S>],
+        index: &MpcScalar<N, S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        index.assert_in_range(table.len() as u64)?;
+        let one_hot = index.to_one_hot(table.len())?;
+
+        Ok(MpcScalar::batch_mul(&one_hot, table)
+            .map_err(MpcError::NetworkError)?
+            .into_iter()
+            .fold(
+                MpcScalar::zero(index.network(), index.beaver_source()),
+                |acc, term| acc + term,
+            ))
+    }
+
+    /// Writes the value to the table at a shared index without revealing the index
+    ///
+    /// The index is asserted to lie in the range [0, table.len()) and converted to a one-hot
+    /// vector as in `oblivious_read`. Every entry is then updated as
+    ///     table[i] = table[i] + one_hot[i] * (value - table[i])
+    /// in one batched round of communication, so that only the entry at the index changes
+    #[allow(clippy::ptr_arg)]
+    pub fn oblivious_write(
+        table: &mut Vec<MpcScalar<N, S>>,
+        index: &MpcScalar<N, S>,
+        value: &MpcScalar<N, S>,
+    ) -> Result<(), MpcError> {
+        index.assert_in_range(table.len() as u64)?;
+        let one_hot = index.to_one_hot(table.len())?;
+
+        let differences = table.iter().map(|entry| value - entry).collect::<Vec<_>>();
+        let updates =
+            MpcScalar::batch_mul(&one_hot, &differences).map_err(MpcError::NetworkError)?;
+
+        table
+            .iter_mut()
+            .zip(updates.iter())
+            .for_each(|(entry, update)| *entry += update);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+//! Groups protocols for jointly sampling shared random values online, rather than drawing them
+//! from the Beaver source
+
+use curve25519_dalek::scalar::Scalar;
+use rand_core::OsRng;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+    BeaverSource, SharedNetwork, Visibility,
+};
+
+/// The exponent (l - 5) / 8 used to take square roots in the scalar field, little endian
+///
+/// The scalar field modulus l is 5 mod 8, so square roots are computed with Atkin's algorithm
+const SQRT_EXPONENT: [u8; 32] = [
+    125, 186, 158, 75, 99, 76, 2, 203, 154, 243, 94, 212, 59, 223, 155, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 2,
+];
+
+/// Raises the base to a little endian exponent by square-and-multiply
+fn pow(base: &Scalar, exponent: &[u8; 32]) -> Scalar {
+    exponent
+        .iter()
+        .rev()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .fold(Scalar::one(), |acc, bit| {
+            let square = acc * acc;
+            if bit {
+                square * base
+            } else {
+                square
+            }
+        })
+}
+
+/// Returns a square root of the value, or `None` if the value is not a square
+fn sqrt(value: &Scalar) -> Option<Scalar> {
+    let double = value + value;
+    let b = pow(&double, &SQRT_EXPONENT);
+    let i = double * b * b;
+    let root = value * b * (i - Scalar::one());
+
+    if root * root == *value {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Jointly samples a shared value that is uniformly random and unknown to either party
+    ///
+    /// See `batch_random_shared` for the protocol and its cost
+    pub fn random_shared(network: SharedNetwork<N>, beaver_source: BeaverSource<S>) -> Self {
+        Self::batch_random_shared(1, network, beaver_source).remove(0)
+    }
+
+    /// Jointly samples a batch of shared values that are uniformly random and unknown to either
+    /// party, without drawing them from the Beaver source
+    ///
+    /// Each party samples its share of each value locally; the value is the sum of the shares,
+    /// which is uniformly random as long as one party samples honestly, and which neither party
+    /// learns as it never sees the peer's share.
+    ///
+    /// Cost: no preprocessing and no communication
+    pub fn batch_random_shared(
+        num_values: usize,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Vec<Self> {
+        let mut rng = OsRng {};
+        (0..num_values)
+            .map(|_| {
+                MpcScalar::from_scalar_with_visibility(
+                    Scalar::random(&mut rng),
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Jointly samples a shared bit that is uniformly random as long as one party is honest
+    ///
+    /// See `batch_random_shared_bits` for the protocol and its cost
+    pub fn random_shared_bit(
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_random_shared_bits(1, network, beaver_source)?.remove(0))
+    }
+
+    /// Jointly samples a batch of shared bits without drawing them from the Beaver source
+    ///
+    /// Each party samples a random share of a value r locally, and the parties open r^2. Given a
+    /// public square root s of r^2, r / s = +-1 with the sign hidden by r, so the bit is computed
+    /// locally as (r / s + 1) / 2. A value whose square opens to zero is discarded and resampled.
+    ///
+    /// Cost: one Beaver triplet per bit and two rounds of communication for the whole batch
+    pub fn batch_random_shared_bits(
+        num_bits: usize,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        let half = Scalar::from(2u64).invert();
+
+        let mut bits = Vec::with_capacity(num_bits);
+        while bits.len() < num_bits {
+            let values = Self::batch_random_shared(
+                num_bits - bits.len(),
+                network.clone(),
+                beaver_source.clone(),
+            );
+            let squares = MpcScalar::batch_mul(&values, &values)
+                .and_then(|squares| MpcScalar::batch_open(&squares))
+                .map_err(MpcError::NetworkError)?;
+
+            for (value, square) in values.iter().zip(squares.iter()) {
+                if square.value() == Scalar::zero() {
+                    continue;
+                }
+
+                let root = sqrt(&square.value()).ok_or_else(|| {
+                    MpcError::ArithmeticError(
+                        "an opened square has no square root, the peer's shares are inconsistent"
+                            .to_string(),
+                    )
+                })?;
+                bits.push((value * (root.invert() * half)).add_public_constant(half));
+            }
+        }
+
+        Ok(bits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{
+        beaver::{DealtFiles, DummySharedScalarSource},
+        mpc_scalar::MpcScalar,
+        network::{dummy_network::DummyMpcNetwork, MpcNetwork},
+    };
+
+    use super::sqrt;
+
+    #[test]
+    fn test_sqrt() {
+        let value = Scalar::from(1234u64);
+        let root = sqrt(&(value * value)).unwrap();
+        assert!(root == value || root == -value);
+
+        // 2 is not a square modulo l
+        assert!(sqrt(&Scalar::from(2u64)).is_none());
+    }
+
+    #[test]
+    fn test_random_shared() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // The shares are sampled locally, so distinct draws differ except with negligible
+        // probability
+        let values = MpcScalar::batch_random_shared(2, network, beaver_source);
+        assert!(values.iter().all(|value| value.is_shared()));
+        assert_ne!(values[0].value(), values[1].value());
+    }
+
+    #[test]
+    fn test_random_shared_bits() {
+        let opened = DealtFiles::new("random-bits", 32, 0, 0, 0).run_parties(|source, network| {
+            let network = Rc::new(RefCell::new(network));
+            let beaver_source = Rc::new(RefCell::new(source));
+            let bits =
+                MpcScalar::batch_random_shared_bits(32, network.clone(), beaver_source).unwrap();
+            let opened = MpcScalar::batch_open(&bits).unwrap();
+
+            block_on(network.borrow_mut().close()).unwrap();
+            opened.iter().map(|bit| bit.value()).collect::<Vec<_>>()
+        });
+
+        // The bits are bits, and are not all equal except with probability 2^-31
+        assert_eq!(opened[0], opened[1]);
+        assert!(opened[0]
+            .iter()
+            .all(|bit| *bit == Scalar::zero() || *bit == Scalar::one()));
+        assert!(opened[0].contains(&Scalar::zero()) && opened[0].contains(&Scalar::one()));
+    }
+}
@@ -0,0 +1,146 @@
+//! Groups protocols for obliviously permuting shared vectors, e.g. to unlink the positions of
+//! values before they are opened or compared
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::{PermutationCorrelation, SharedValueSource},
+    block_on,
+    error::MpcError,
+    mpc_scalar::MpcScalar,
+    network::MpcNetwork,
+    BeaverSource, SharedNetwork, Visibility,
+};
+
+/// Returns pi(values), where pi(v)[i] = v[pi[i]]
+fn permute<T: Copy>(values: &[T], permutation: &[usize]) -> Vec<T> {
+    permutation.iter().map(|index| values[*index]).collect()
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Permutes the shared vector by a random permutation that neither party learns
+    ///
+    /// Each party in turn applies a private permutation from a permutation correlation and
+    /// reshares the result: the other party masks its shares with a and sends them over, and the
+    /// permuting party adds them to its own shares, permutes the sum, and adds delta = pi(a) - b,
+    /// so that the parties hold pi(x) - b and b. The permuting party only sees the masked vector
+    /// x - a, and the other party's new shares b are independent of the old ones, so the output
+    /// is permuted by the composition of both permutations. Public values are shared before they
+    /// are permuted.
+    ///
+    /// The protocol is secure against semi-honest parties only; a malicious party may permute
+    /// or shift its shares arbitrarily without being detected.
+    ///
+    /// The correlations are drawn from the value source, e.g. a `DealerBeaverSource`; sources
+    /// that do not supply them fail with a `PreprocessingError`.
+    ///
+    /// Cost: two permutation correlations of the vector's length and two rounds of communication
+    pub fn shuffle(values: &[MpcScalar<N, S>]) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if values.iter().any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be shuffled...".to_string(),
+            ));
+        }
+
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let network = values[0].network();
+        let beaver_source = values[0].beaver_source();
+
+        // The king holds the whole of each public value as its share
+        let am_king = network.as_ref().borrow().am_king();
+        let mut shares = values
+            .iter()
+            .map(|value| {
+                if value.is_public() && !am_king {
+                    Scalar::zero()
+                } else {
+                    value.value()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for permuting_party in [0u64, 1u64] {
+            shares = Self::reshuffle_shares(&shares, permuting_party, &network, &beaver_source)?;
+        }
+
+        Ok(shares
+            .into_iter()
+            .map(|share| {
+                MpcScalar::from_scalar_with_visibility(
+                    share,
+                    Visibility::Shared,
+                    network.clone(),
+                    beaver_source.clone(),
+                )
+            })
+            .collect())
+    }
+
+    /// Permutes the shares by the private permutation of `permuting_party`, returning the local
+    /// party's shares of the permuted vector
+    fn reshuffle_shares(
+        shares: &[Scalar],
+        permuting_party: u64,
+        network: &SharedNetwork<N>,
+        beaver_source: &BeaverSource<S>,
+    ) -> Result<Vec<Scalar>, MpcError> {
+        let correlation = beaver_source
+            .as_ref()
+            .borrow_mut()
+            .next_permutation_correlation(shares.len(), permuting_party)?;
+
+        match correlation {
+            PermutationCorrelation::Permuter { permutation, delta } => {
+                if permutation.len() != shares.len() || delta.len() != shares.len() {
+                    return Err(MpcError::PreprocessingError(
+                        "permutation correlation has the wrong length".to_string(),
+                    ));
+                }
+
+                let masked = block_on(network.as_ref().borrow_mut().receive_scalars(shares.len()))
+                    .map_err(MpcError::NetworkError)?;
+                let sums = shares
+                    .iter()
+                    .zip(masked.iter())
+                    .map(|(share, masked)| share + masked)
+                    .collect::<Vec<_>>();
+
+                Ok(permute(&sums, &permutation)
+                    .into_iter()
+                    .zip(delta.iter())
+                    .map(|(sum, delta)| sum + delta)
+                    .collect())
+            }
+            PermutationCorrelation::Masker { a, b } => {
+                if a.len() != shares.len() || b.len() != shares.len() {
+                    return Err(MpcError::PreprocessingError(
+                        "permutation correlation has the wrong length".to_string(),
+                    ));
+                }
+
+                let masked = shares
+                    .iter()
+                    .zip(a.iter())
+                    .map(|(share, a)| share - a)
+                    .collect::<Vec<_>>();
+                block_on(network.as_ref().borrow_mut().send_scalars(&masked))
+                    .map_err(MpcError::NetworkError)?;
+
+                Ok(b)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::permute;
+
+    #[test]
+    fn test_permute() {
+        assert_eq!(permute(&[10, 20, 30], &[2, 0, 1]), vec![30, 10, 20]);
+    }
+}
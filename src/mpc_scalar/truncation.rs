@@ -0,0 +1,238 @@
+//! Groups gadgets for truncating shared scalars, i.e. dividing them by a public power of two
+//! and rounding the result to an integer, as needed to rescale fixed-point values
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+    Visibility,
+};
+
+use super::bits::{pow2, scalar_bits_le, MAX_DECOMPOSITION_BITS, STATISTICAL_SECURITY};
+
+/// Returns floor(a / 2^k) for the canonical integer representative of a
+fn shift_right(a: &Scalar, k: usize) -> Scalar {
+    let mut bytes = [0u8; 32];
+    for (i, bit) in scalar_bits_le(a).skip(k).enumerate() {
+        bytes[i / 8] |= (bit as u8) << (i % 8);
+    }
+
+    Scalar::from_bits(bytes)
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcScalar<N, S> {
+    /// Computes floor(self / 2^k) exactly
+    ///
+    /// The value is assumed to be in the range [0, 2^MAX_DECOMPOSITION_BITS); signed values
+    /// may be offset into this range before truncating. See `batch_truncate` for the cost of
+    /// this method.
+    pub fn truncate(&self, k: usize) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_truncate(std::slice::from_ref(self), k)?.remove(0))
+    }
+
+    /// Computes floor(value / 2^k) exactly for each value in the batch
+    ///
+    /// The low `k` bits of each value are extracted as in `split_at_bit` and subtracted from the
+    /// value, after which the remainder is divisible by 2^k and is scaled down locally.
+    ///
+    /// Cost: `MAX_DECOMPOSITION_BITS + STATISTICAL_SECURITY` shared bits per value, `k - 1`
+    /// Beaver triplets per value, and `k` rounds of communication for the whole batch
+    pub fn batch_truncate(
+        values: &[MpcScalar<N, S>],
+        k: usize,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        let low_bits = Self::batch_low_bits_le(values, k, MAX_DECOMPOSITION_BITS)?;
+        let scale = pow2(k).invert();
+
+        Ok(values
+            .iter()
+            .zip(low_bits.iter())
+            .map(|(value, bits)| {
+                let low = bits.iter().enumerate().fold(
+                    MpcScalar::zero(value.network(), value.beaver_source()),
+                    |acc, (i, bit)| acc + bit * pow2(i),
+                );
+                (value - &low) * scale
+            })
+            .collect())
+    }
+
+    /// Computes floor(self / 2^k) + e for an error bit e in a single round of communication
+    ///
+    /// The value is assumed to be in the range [0, 2^MAX_DECOMPOSITION_BITS). See
+    /// `batch_truncate_probabilistic` for the error bound and the cost of this method.
+    pub fn truncate_probabilistic(&self, k: usize) -> Result<MpcScalar<N, S>, MpcError> {
+        Ok(Self::batch_truncate_probabilistic(std::slice::from_ref(self), k)?.remove(0))
+    }
+
+    /// Computes floor(value / 2^k) + e for each value in the batch, where the error e is one with
+    /// probability (value mod 2^k) / 2^k and zero otherwise
+    ///
+    /// The result is thus off by at most one in the last place, and its expectation is exactly
+    /// value / 2^k. The parties mask each value with a random value r = \sum_i r_i * 2^i built
+    /// from shared bits, open c = x + r, and compute floor(c / 2^k) - floor(r / 2^k) from the
+    /// public bits of c and the shared high bits of r; the error is the carry out of the low
+    /// `k` bits of x + r. Public values, including those in a batch with shared values, are
+    /// truncated exactly and without communication.
+    ///
+    /// Cost: `MAX_DECOMPOSITION_BITS + STATISTICAL_SECURITY` shared bits per shared value and a
+    /// single round of communication for the whole batch
+    pub fn batch_truncate_probabilistic(
+        values: &[MpcScalar<N, S>],
+        k: usize,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        if k > MAX_DECOMPOSITION_BITS {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot truncate {} bits, at most {} bits are supported",
+                k, MAX_DECOMPOSITION_BITS
+            )));
+        }
+
+        if values.iter().any(|value| value.is_private()) {
+            return Err(MpcError::VisibilityError(
+                "Private values may not be truncated...".to_string(),
+            ));
+        }
+
+        if values.is_empty() || k == 0 {
+            return Ok(values.to_vec());
+        }
+
+        let network = values[0].network();
+        let beaver_source = values[0].beaver_source();
+
+        // Only the shared values are masked and opened
+        let shared_values = values
+            .iter()
+            .filter(|value| value.is_shared())
+            .collect::<Vec<_>>();
+        let mut mask_bits = Vec::with_capacity(shared_values.len());
+        for _ in shared_values.iter() {
+            let bits = beaver_source
+                .as_ref()
+                .borrow_mut()
+                .try_next_shared_bit_batch(MAX_DECOMPOSITION_BITS + STATISTICAL_SECURITY)?;
+            mask_bits.push(
+                bits.into_iter()
+                    .map(|bit| {
+                        MpcScalar::from_scalar_with_visibility(
+                            bit,
+                            Visibility::Shared,
+                            network.clone(),
+                            beaver_source.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let masked_values = shared_values
+            .iter()
+            .zip(mask_bits.iter())
+            .map(|(value, bits)| {
+                bits.iter()
+                    .enumerate()
+                    .fold((*value).clone(), |acc, (i, bit)| acc + bit * pow2(i))
+            })
+            .collect::<Vec<_>>();
+        let opened_masked_values = if masked_values.is_empty() {
+            Vec::new()
+        } else {
+            MpcScalar::batch_open(&masked_values).map_err(MpcError::NetworkError)?
+        };
+
+        let mut truncated_shared =
+            opened_masked_values
+                .iter()
+                .zip(mask_bits.iter())
+                .map(|(opened, bits)| {
+                    let mask_high = bits[k..].iter().enumerate().fold(
+                        MpcScalar::zero(network.clone(), beaver_source.clone()),
+                        |acc, (i, bit)| acc + bit * pow2(i),
+                    );
+                    MpcScalar::from_public_scalar(
+                        shift_right(&opened.value(), k),
+                        network.clone(),
+                        beaver_source.clone(),
+                    ) - mask_high
+                });
+
+        // Public values are truncated exactly and locally, in their place in the batch
+        Ok(values
+            .iter()
+            .map(|value| {
+                if value.is_shared() {
+                    truncated_shared.next().unwrap()
+                } else {
+                    MpcScalar::from_public_scalar(
+                        shift_right(&value.value(), k),
+                        network.clone(),
+                        beaver_source.clone(),
+                    )
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::DummySharedScalarSource, mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork, Visibility,
+    };
+
+    use super::shift_right;
+
+    #[test]
+    fn test_shift_right() {
+        assert_eq!(shift_right(&Scalar::from(1024u64), 10), Scalar::one());
+        assert_eq!(shift_right(&Scalar::from(1023u64), 10), Scalar::zero());
+        assert_eq!(
+            shift_right(&(Scalar::from(u64::MAX) + Scalar::one()), 63),
+            Scalar::from(2u64)
+        );
+    }
+
+    #[test]
+    fn test_public_truncate() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public values are truncated exactly and without communication by both variants
+        let value = MpcScalar::from_public_u64(1000, network, beaver_source);
+        assert_eq!(value.truncate(3).unwrap().value(), Scalar::from(125u64));
+        assert_eq!(
+            value.truncate_probabilistic(4).unwrap().value(),
+            Scalar::from(62u64)
+        );
+    }
+
+    #[test]
+    fn test_mixed_truncate_probabilistic() {
+        // The peer's share of the single opened value; opening the public value as well would
+        // exhaust the mock network
+        let mut mock_network = DummyMpcNetwork::new();
+        mock_network.add_mock_scalars(vec![Scalar::zero()]);
+        let network = Rc::new(RefCell::new(mock_network));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        let public = MpcScalar::from_public_u64(1000, network.clone(), beaver_source.clone());
+        let shared = MpcScalar::from_scalar_with_visibility(
+            Scalar::from(1000u64),
+            Visibility::Shared,
+            network,
+            beaver_source,
+        );
+        let truncated = MpcScalar::batch_truncate_probabilistic(&[public, shared], 4).unwrap();
+
+        // The public value is truncated exactly, with no error bit
+        assert!(truncated[0].is_public());
+        assert_eq!(truncated[0].value(), Scalar::from(62u64));
+        assert!(truncated[1].is_shared());
+    }
+}
@@ -0,0 +1,315 @@
+//! Implements a shared 64-bit unsigned integer with wrapping arithmetic, emulating the ring of
+//! integers modulo 2^64 on top of the scalar field
+
+use std::ops::{Add, Mul, Sub};
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{
+    beaver::SharedValueSource,
+    error::{MpcError, MpcNetworkError},
+    mpc_bool::MpcBool,
+    mpc_scalar::{bits::pow2, MpcScalar},
+    network::MpcNetwork,
+    BeaverSource, SharedNetwork,
+};
+
+/// The bit length of the integers
+const U64_BITS: usize = 64;
+
+/// A 64-bit unsigned integer held as a value in the range [0, 2^64) of the scalar field
+///
+/// The field has no wraparound at 2^64, so each wrapping operation computes its result over the
+/// field, where it cannot overflow, and reduces it modulo 2^64 by extracting its low 64 bits as
+/// in `MpcScalar::to_bits_le`. A reduction costs 63 Beaver triplets and 64 rounds of
+/// communication for a whole batch; operations on public integers are computed locally. Like the
+/// operators of `MpcScalar`, the operators panic on an error; the methods return it instead.
+#[derive(Debug)]
+pub struct MpcU64<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> {
+    /// The underlying value, in the range [0, 2^64)
+    value: MpcScalar<N, S>,
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Clone for MpcU64<N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> MpcU64<N, S> {
+    /// Allocate a public integer
+    pub fn from_public_u64(
+        value: u64,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            value: MpcScalar::from_public_u64(value, network, beaver_source),
+        }
+    }
+
+    /// Allocate a private integer, to be shared with `share_secret`
+    pub fn from_private_u64(
+        value: u64,
+        network: SharedNetwork<N>,
+        beaver_source: BeaverSource<S>,
+    ) -> Self {
+        Self {
+            value: MpcScalar::from_private_u64(value, network, beaver_source),
+        }
+    }
+
+    /// Wraps a value in the range [0, 2^64)
+    ///
+    /// The value is not checked; a value outside of the range makes the wrapping operations
+    /// meaningless
+    pub fn from_scalar(value: MpcScalar<N, S>) -> Self {
+        Self { value }
+    }
+
+    /// Returns the underlying value
+    pub fn value(&self) -> &MpcScalar<N, S> {
+        &self.value
+    }
+
+    /// Returns the underlying value, e.g. to use it in field arithmetic
+    pub fn to_scalar(&self) -> MpcScalar<N, S> {
+        self.value.clone()
+    }
+
+    /// Secret shares a privately held integer, see `MpcScalar::share_secret`
+    pub fn share_secret(&self, party_id: u64) -> Result<Self, MpcNetworkError> {
+        Ok(Self::from_scalar(self.value.share_secret(party_id)?))
+    }
+
+    /// Opens the integer, returning an `ArithmeticError` if the underlying value does not fit
+    /// in 64 bits
+    pub fn open(&self) -> Result<u64, MpcError> {
+        self.value
+            .open()
+            .map_err(MpcError::NetworkError)?
+            .to_u64_checked()
+    }
+
+    /// Computes self + rhs modulo 2^64
+    pub fn wrapping_add(&self, rhs: &Self) -> Result<Self, MpcError> {
+        Ok(
+            Self::batch_wrapping_add(std::slice::from_ref(self), std::slice::from_ref(rhs))?
+                .remove(0),
+        )
+    }
+
+    /// Computes self - rhs modulo 2^64
+    pub fn wrapping_sub(&self, rhs: &Self) -> Result<Self, MpcError> {
+        Ok(
+            Self::batch_wrapping_sub(std::slice::from_ref(self), std::slice::from_ref(rhs))?
+                .remove(0),
+        )
+    }
+
+    /// Computes self * rhs modulo 2^64
+    pub fn wrapping_mul(&self, rhs: &Self) -> Result<Self, MpcError> {
+        Ok(
+            Self::batch_wrapping_mul(std::slice::from_ref(self), std::slice::from_ref(rhs))?
+                .remove(0),
+        )
+    }
+
+    /// Computes -self modulo 2^64
+    pub fn wrapping_neg(&self) -> Result<Self, MpcError> {
+        Self::from_public_u64(0, self.value.network(), self.value.beaver_source())
+            .wrapping_sub(self)
+    }
+
+    /// Computes a_i + b_i modulo 2^64 for each pair; the sums are less than 2^65
+    pub fn batch_wrapping_add(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Self::check_same_length(a, b)?;
+        let sums = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| &a.value + &b.value)
+            .collect::<Vec<_>>();
+
+        Self::batch_reduce(&sums, U64_BITS + 1)
+    }
+
+    /// Computes a_i - b_i modulo 2^64 for each pair; the differences are offset by 2^64 so that
+    /// they lie in [1, 2^65)
+    pub fn batch_wrapping_sub(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Self::check_same_length(a, b)?;
+        let differences = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| &a.value - &b.value + pow2(U64_BITS))
+            .collect::<Vec<_>>();
+
+        Self::batch_reduce(&differences, U64_BITS + 1)
+    }
+
+    /// Computes a_i * b_i modulo 2^64 for each pair; the products are less than 2^128
+    ///
+    /// Cost: one Beaver triplet per pair of shared integers and one round of communication on
+    /// top of the reduction
+    pub fn batch_wrapping_mul(a: &[Self], b: &[Self]) -> Result<Vec<Self>, MpcError> {
+        Self::check_same_length(a, b)?;
+        let products = MpcScalar::try_batch_mul(
+            &a.iter().map(|a| a.to_scalar()).collect::<Vec<_>>(),
+            &b.iter().map(|b| b.to_scalar()).collect::<Vec<_>>(),
+        )?;
+
+        Self::batch_reduce(&products, 2 * U64_BITS)
+    }
+
+    /// Computes self << k modulo 2^64; shifting by 64 or more bits gives zero
+    pub fn wrapping_shl(&self, k: usize) -> Result<Self, MpcError> {
+        if k >= U64_BITS {
+            return Ok(Self::from_public_u64(
+                0,
+                self.value.network(),
+                self.value.beaver_source(),
+            ));
+        }
+
+        Ok(Self::batch_reduce(&[&self.value * pow2(k)], U64_BITS + k)?.remove(0))
+    }
+
+    /// Computes self >> k; shifting by 64 or more bits gives zero
+    ///
+    /// The low k bits are extracted and subtracted from the value, after which the remainder is
+    /// divisible by 2^k and is scaled down locally
+    pub fn shr(&self, k: usize) -> Result<Self, MpcError> {
+        if k >= U64_BITS {
+            return Ok(Self::from_public_u64(
+                0,
+                self.value.network(),
+                self.value.beaver_source(),
+            ));
+        }
+
+        let low =
+            Self::batch_reduce_bits(std::slice::from_ref(&self.value), k, U64_BITS)?.remove(0);
+        Ok(Self::from_scalar((&self.value - &low) * pow2(k).invert()))
+    }
+
+    /// Computes a shared boolean that is true if self < rhs, see `MpcScalar::less_than`
+    pub fn lt(&self, rhs: &Self) -> Result<MpcBool<N, S>, MpcError> {
+        self.value.less_than(&rhs.value).map(MpcBool::from_scalar)
+    }
+
+    /// Computes a shared boolean that is true if self == rhs, see `MpcScalar::eq_secret`
+    pub fn eq_secret(&self, rhs: &Self) -> Result<MpcBool<N, S>, MpcError> {
+        self.value.eq_secret(&rhs.value).map(MpcBool::from_scalar)
+    }
+
+    /// Returns an `ArithmeticError` if the batches have different lengths
+    fn check_same_length(a: &[Self], b: &[Self]) -> Result<(), MpcError> {
+        if a.len() != b.len() {
+            return Err(MpcError::ArithmeticError(format!(
+                "cannot combine {} integers with {} integers",
+                a.len(),
+                b.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reduces each value in the range [0, 2^value_bits) modulo 2^64
+    fn batch_reduce(values: &[MpcScalar<N, S>], value_bits: usize) -> Result<Vec<Self>, MpcError> {
+        Ok(Self::batch_reduce_bits(values, U64_BITS, value_bits)?
+            .into_iter()
+            .map(Self::from_scalar)
+            .collect())
+    }
+
+    /// Reduces each value in the range [0, 2^value_bits) modulo 2^num_bits
+    fn batch_reduce_bits(
+        values: &[MpcScalar<N, S>],
+        num_bits: usize,
+        value_bits: usize,
+    ) -> Result<Vec<MpcScalar<N, S>>, MpcError> {
+        Ok(MpcScalar::batch_low_bits_le(values, num_bits, value_bits)?
+            .iter()
+            .zip(values.iter())
+            .map(|(bits, value)| {
+                bits.iter().enumerate().fold(
+                    MpcScalar::zero(value.network(), value.beaver_source()),
+                    |acc, (i, bit)| acc + bit * pow2(i),
+                )
+            })
+            .collect())
+    }
+}
+
+impl<N: MpcNetwork + Send, S: SharedValueSource<Scalar>> From<MpcU64<N, S>> for MpcScalar<N, S> {
+    fn from(value: MpcU64<N, S>) -> Self {
+        value.value
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Add<&'a MpcU64<N, S>>
+    for &'a MpcU64<N, S>
+{
+    type Output = MpcU64<N, S>;
+
+    /// Wrapping addition, see `MpcU64::wrapping_add`
+    fn add(self, rhs: &'a MpcU64<N, S>) -> Self::Output {
+        self.wrapping_add(rhs).unwrap()
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Sub<&'a MpcU64<N, S>>
+    for &'a MpcU64<N, S>
+{
+    type Output = MpcU64<N, S>;
+
+    /// Wrapping subtraction, see `MpcU64::wrapping_sub`
+    fn sub(self, rhs: &'a MpcU64<N, S>) -> Self::Output {
+        self.wrapping_sub(rhs).unwrap()
+    }
+}
+
+impl<'a, N: MpcNetwork + Send, S: SharedValueSource<Scalar>> Mul<&'a MpcU64<N, S>>
+    for &'a MpcU64<N, S>
+{
+    type Output = MpcU64<N, S>;
+
+    /// Wrapping multiplication, see `MpcU64::wrapping_mul`
+    fn mul(self, rhs: &'a MpcU64<N, S>) -> Self::Output {
+        self.wrapping_mul(rhs).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{beaver::DummySharedScalarSource, network::dummy_network::DummyMpcNetwork};
+
+    use super::MpcU64;
+
+    #[test]
+    fn test_public_wrapping() {
+        let network = Rc::new(RefCell::new(DummyMpcNetwork::new()));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // Public integers wrap locally, as the native integers do
+        let a = MpcU64::from_public_u64(u64::MAX - 1, network.clone(), beaver_source.clone());
+        let b = MpcU64::from_public_u64(5, network, beaver_source);
+
+        assert_eq!((&a + &b).open().unwrap(), (u64::MAX - 1).wrapping_add(5));
+        assert_eq!((&b - &a).open().unwrap(), 5u64.wrapping_sub(u64::MAX - 1));
+        assert_eq!((&a * &b).open().unwrap(), (u64::MAX - 1).wrapping_mul(5));
+        assert_eq!(
+            b.wrapping_neg().unwrap().open().unwrap(),
+            5u64.wrapping_neg()
+        );
+        assert_eq!(
+            a.wrapping_shl(4).unwrap().open().unwrap(),
+            (u64::MAX - 1) << 4
+        );
+        assert_eq!(a.shr(60).unwrap().open().unwrap(), (u64::MAX - 1) >> 60);
+    }
+}
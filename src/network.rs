@@ -1,8 +1,28 @@
 //! The `network` module defines abstractions of the transport used to
 //! communicate during the course of an MPC
+pub mod buffered_network;
 mod cert_verifier;
+#[cfg(feature = "compression")]
+mod compression;
 mod config;
 pub mod dummy_network;
+mod identity;
+pub mod instrumented_network;
+pub mod local_network;
+pub mod multi_party;
+#[cfg(feature = "noise")]
+pub mod noise_network;
+mod rate_limiter;
+mod session_cache;
+pub mod simulated_network;
+#[cfg(unix)]
+pub mod uds_network;
+pub mod websocket_network;
+
+pub use config::{KeepAliveConfig, PeerAuthConfig};
+pub use identity::{IdentityKeypair, PeerIdentity, Signature};
+pub use rate_limiter::{RateLimit, RateLimitStats};
+pub use session_cache::SessionCache;
 
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
@@ -10,15 +30,73 @@ use curve25519_dalek::{
     ristretto::{CompressedRistretto, RistrettoPoint},
     scalar::Scalar,
 };
-use quinn::{Endpoint, RecvStream, SendStream};
-use std::{convert::TryInto, net::SocketAddr};
+use quinn::{
+    Connection, ConnectionError, Endpoint, ReadError, ReadExactError, RecvStream,
+    SendDatagramError, SendStream, VarInt, WriteError,
+};
+use sha3::{Digest, Sha3_256};
+use std::{
+    collections::VecDeque, convert::TryInto, future::Future, net::SocketAddr, time::Duration,
+};
+use tokio::net::TcpStream;
 
 use crate::error::{BroadcastError, MpcNetworkError, SetupError};
 
+use self::{
+    identity::{IDENTITY_BYTES, SIGNATURE_BYTES},
+    rate_limiter::RateLimiter,
+};
+
 pub type PartyId = u64;
 
 const BYTES_PER_POINT: usize = 32;
 const BYTES_PER_SCALAR: usize = 32;
+/// The largest payload sent as a single datagram, payloads larger than this fall back to the stream.
+/// Chosen below the minimum QUIC datagram size so that both parties agree on the transport for a
+/// given message without negotiating the path MTU
+const MAX_DATAGRAM_PAYLOAD: usize = 1024;
+/// The number of bytes prefixed to each datagram; its sequence number as a u64
+const DATAGRAM_SEQUENCE_BYTES: usize = 8;
+/// The duration after which a datagram receive fails if no receive timeout is set; a lost
+/// datagram is never retransmitted, so the receive would otherwise wait forever
+const DEFAULT_DATAGRAM_RECEIVE_TIMEOUT: Duration = Duration::from_secs(10);
+/// The byte written by the king to open the bidirectional stream
+const HANDSHAKE_BYTE: u8 = 1;
+/// The number of bytes exchanged in the resync handshake; the bytes sent and received as u64s
+const RESYNC_BYTES: usize = 16;
+/// The number of times a peer is dialed over TCP before giving up, the peer may not yet be listening
+const MAX_DIAL_ATTEMPTS: usize = 10;
+/// The delay between attempts to dial a peer over TCP
+const DIAL_BACKOFF: Duration = Duration::from_millis(100);
+/// The version of the wire protocol, incremented whenever the encoding of messages changes
+pub const PROTOCOL_VERSION: u16 = 3;
+/// The bytes that open the hello each party sends on connecting
+const HELLO_MAGIC: [u8; 4] = *b"MPCR";
+/// The number of bytes in the header of the hello; the magic, the protocol version as a u16,
+/// and the length of the capabilities that follow as a u16
+const HELLO_HEADER_BYTES: usize = 8;
+/// The number of bytes in the header of a message; the sequence number as a u64, the kind as a
+/// byte, and the number of values as a u64
+const MESSAGE_HEADER_BYTES: usize = 17;
+/// The number of bytes each party advertises its capabilities in; the compression threshold
+/// as a u32, zero if the party does not compress, followed by a byte of capability flags
+const CAPABILITY_BYTES: usize = 5;
+/// The capability flag set by a party that signs the handshake with an identity key
+const CAPABILITY_IDENTITY: u8 = 1;
+/// The label under which each party exports keying material from the TLS session to sign
+const IDENTITY_EXPORT_LABEL: &[u8] = b"mpc-ristretto identity";
+/// The number of bytes a party signs the handshake in; its identity followed by the signature
+const SIGNED_IDENTITY_BYTES: usize = IDENTITY_BYTES + SIGNATURE_BYTES;
+/// The application error code with which a party closes the connection after the shutdown
+/// handshake completes; distinct from the code with which a connection is dropped to reconnect
+const CLEAN_CLOSE_CODE: u32 = 1;
+/// The reason sent alongside the code when the connection is closed cleanly
+const CLEAN_CLOSE_REASON: &[u8] = b"done";
+/// The number of bytes read at a time when draining the values left unread at close
+const CLOSE_DRAIN_CHUNK_BYTES: usize = 1 << 12;
+/// The most bytes left unread at close that a party drains before giving up on the shutdown
+/// handshake, so that a peer that keeps sending cannot hold the close open
+const MAX_CLOSE_DRAIN_BYTES: usize = 1 << 20;
 
 /**
  * Helpers
@@ -80,8 +158,119 @@ fn bytes_to_points(bytes: &[u8]) -> Result<Vec<RistrettoPoint>, MpcNetworkError>
         .collect::<Result<Vec<RistrettoPoint>, MpcNetworkError>>()
 }
 
+/// Dials a peer over TCP, retrying while the peer is not yet listening
+async fn dial_tcp(peer_addr: SocketAddr) -> Result<TcpStream, MpcNetworkError> {
+    let mut attempt = 1;
+    loop {
+        match TcpStream::connect(peer_addr).await {
+            Ok(tcp_stream) => return Ok(tcp_stream),
+            Err(_) if attempt >= MAX_DIAL_ATTEMPTS => {
+                return Err(MpcNetworkError::ConnectionSetupError(
+                    SetupError::NoIncomingConnection,
+                ))
+            }
+            Err(_) => {
+                async_std::task::sleep(DIAL_BACKOFF).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Encodes the hello sent on connecting, advertising the protocol version and capabilities
+fn encode_hello(capabilities: &[u8]) -> Vec<u8> {
+    let mut hello = Vec::with_capacity(HELLO_HEADER_BYTES + capabilities.len());
+    hello.extend_from_slice(&HELLO_MAGIC);
+    hello.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    hello.extend_from_slice(&(capabilities.len() as u16).to_le_bytes());
+    hello.extend_from_slice(capabilities);
+    hello
+}
+
+/// Decodes the header of the peer's hello into its protocol version and the length of its
+/// capabilities
+fn decode_hello_header(header: &[u8]) -> Result<(u16, usize), MpcNetworkError> {
+    if header[..HELLO_MAGIC.len()] != HELLO_MAGIC {
+        return Err(MpcNetworkError::ConnectionSetupError(
+            SetupError::HandshakeError,
+        ));
+    }
+
+    let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+    let capabilities_length = u16::from_le_bytes(header[6..8].try_into().unwrap());
+    Ok((version, capabilities_length as usize))
+}
+
+/// Opens the bidirectional stream on a new connection, writing the handshake byte
+async fn open_stream(connection: &Connection) -> Result<(SendStream, RecvStream), MpcNetworkError> {
+    let (mut send, recv) = connection
+        .open_bi()
+        .await
+        .map_err(|err| MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err)))?;
+    send.write_all(&[HANDSHAKE_BYTE])
+        .await
+        .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?;
+
+    Ok((send, recv))
+}
+
+/// Maps the loss of the connection to `PeerUnreachable` if the connection timed out, otherwise
+/// to the given error
+fn connection_lost_error(err: &ConnectionError, otherwise: MpcNetworkError) -> MpcNetworkError {
+    match err {
+        ConnectionError::TimedOut => MpcNetworkError::PeerUnreachable,
+        _ if is_clean_close(err) => MpcNetworkError::PeerClosed,
+        _ => otherwise,
+    }
+}
+
+/// Whether the connection was closed by the peer at the end of the shutdown handshake
+fn is_clean_close(err: &ConnectionError) -> bool {
+    match err {
+        ConnectionError::ApplicationClosed(close) => {
+            close.error_code == VarInt::from_u32(CLEAN_CLOSE_CODE)
+        }
+        _ => false,
+    }
+}
+
+/// Reads and discards the rest of the stream until the peer finishes it, in chunks of
+/// `CLOSE_DRAIN_CHUNK_BYTES`; fails with `ConnectionTeardownError` if more than
+/// `MAX_CLOSE_DRAIN_BYTES` are left unread
+async fn drain_stream(mut stream: RecvStream) -> Result<(), MpcNetworkError> {
+    let mut buffer = vec![0u8; CLOSE_DRAIN_CHUNK_BYTES];
+    let mut num_drained = 0;
+    loop {
+        match stream.read(&mut buffer).await {
+            Ok(Some(num_read)) => {
+                num_drained += num_read;
+                if num_drained > MAX_CLOSE_DRAIN_BYTES {
+                    return Err(MpcNetworkError::ConnectionTeardownError);
+                }
+            }
+            Ok(None) => return Ok(()),
+            Err(ReadError::ConnectionLost(err)) if is_clean_close(&err) => return Ok(()),
+            Err(_) => return Err(MpcNetworkError::ConnectionTeardownError),
+        }
+    }
+}
+
+/// Awaits a future, failing with `MpcNetworkError::Timeout` if a timeout is given and elapses
+async fn with_timeout<F: Future>(
+    timeout: Option<Duration>,
+    future: F,
+) -> Result<F::Output, MpcNetworkError> {
+    match timeout {
+        Some(timeout) => async_std::future::timeout(timeout, future)
+            .await
+            .map_err(|_| MpcNetworkError::Timeout),
+        None => Ok(future.await),
+    }
+}
+
 /// MpcNetwork represents the network functionality needed for 2PC execution
-/// Note that only two party computation is implemented here
+/// Note that only two party computation is implemented here; a computation among more parties
+/// runs over a `MultiPartyNetwork`
 #[async_trait]
 pub trait MpcNetwork {
     /// Returns the ID of the given party in the MPC computation
@@ -141,8 +330,178 @@ pub trait MpcNetwork {
     ) -> Result<RistrettoPoint, MpcNetworkError> {
         Ok(self.broadcast_points(&[point]).await?[0])
     }
+    /// Both parties exchange commitments, then the openings of those commitments
+    ///
+    /// The king sends its commitment, the peer replies with its commitment and its opening
+    /// together, then the king sends its opening; three messages where broadcasting the
+    /// commitments and then the openings takes four. Neither party sees the other's opening
+    /// before it has committed. Returns the peer's commitment and opening
+    async fn exchange_commitments(
+        &mut self,
+        commitment: &Payload,
+        opening: &Payload,
+    ) -> Result<(Payload, Payload), MpcNetworkError> {
+        if self.am_king() {
+            self.send_payload(commitment).await?;
+            let peer_commitment = self.receive_payload(commitment).await?;
+            let peer_opening = self.receive_payload(opening).await?;
+            self.send_payload(opening).await?;
+            // The peer awaits the opening, which ends the exchange
+            self.flush().await?;
+
+            Ok((peer_commitment, peer_opening))
+        } else {
+            let peer_commitment = self.receive_payload(commitment).await?;
+            self.send_payload(commitment).await?;
+            self.send_payload(opening).await?;
+            let peer_opening = self.receive_payload(opening).await?;
+
+            Ok((peer_commitment, peer_opening))
+        }
+    }
+    /// The local party sends the scalars and then the points of a payload, omitting either
+    /// if empty
+    async fn send_payload(&mut self, payload: &Payload) -> Result<(), MpcNetworkError> {
+        if !payload.scalars.is_empty() {
+            self.send_scalars(&payload.scalars).await?;
+        }
+        if !payload.points.is_empty() {
+            self.send_points(&payload.points).await?;
+        }
+
+        Ok(())
+    }
+    /// The local party receives a payload of the same shape as `expected` from the peer
+    async fn receive_payload(&mut self, expected: &Payload) -> Result<Payload, MpcNetworkError> {
+        let mut payload = Payload::default();
+        if !expected.scalars.is_empty() {
+            payload.scalars = self.receive_scalars(expected.scalars.len()).await?;
+        }
+        if !expected.points.is_empty() {
+            payload.points = self.receive_points(expected.points.len()).await?;
+        }
+
+        Ok(payload)
+    }
     /// Closes the connections opened in the handshake phase
     async fn close(&mut self) -> Result<(), MpcNetworkError>;
+    /// Sends any values queued by a network that coalesces sends
+    ///
+    /// Such a network flushes before every receive, so values are never withheld from a peer
+    /// that the local party awaits; explicit flushes are only needed when the local party stops
+    /// receiving, e.g. before a long local computation
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
+        Ok(())
+    }
+    /// Absorbs the bytes of a value opened in the session into the output digest, if the
+    /// network maintains one
+    fn record_opening(&mut self, _opened_bytes: &[u8]) {}
+    /// Returns the hash chain over every value opened in the session, or `None` if the
+    /// network does not maintain one
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        None
+    }
+    /// Sets the duration after which a receive fails with `MpcNetworkError::Timeout`, or
+    /// `None` to wait indefinitely; networks that do not support timeouts ignore this
+    fn set_receive_timeout(&mut self, _timeout: Option<Duration>) {}
+    /// Returns the identity the peer proved in the connection handshake, or `None` if the peer
+    /// did not sign the handshake or the network does not support identities
+    ///
+    /// Any identity may sign the handshake; callers decide whether to trust the identity
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        None
+    }
+    /// Returns the usage of the network's rate limit, or `None` if the network is not rate
+    /// limited; reported to applications through `InstrumentedNetwork::stats`
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        None
+    }
+}
+
+/// A network whose transport is chosen at runtime, e.g. from configuration
+///
+/// Values allocated over a `DynMpcNetwork` have the same type whichever transport is boxed, so
+/// applications need not be generic over the network; see the `Dyn*` aliases of the value types
+pub type DynMpcNetwork = Box<dyn MpcNetwork + Send>;
+
+#[async_trait]
+impl<N: MpcNetwork + Send + ?Sized> MpcNetwork for Box<N> {
+    fn party_id(&self) -> u64 {
+        (**self).party_id()
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        (**self).send_scalars(scalars).await
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        (**self).receive_scalars(num_expected).await
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        (**self).broadcast_scalars(scalars).await
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        (**self).send_points(points).await
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        (**self).receive_points(num_expected).await
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        (**self).broadcast_points(points).await
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        (**self).close().await
+    }
+
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
+        (**self).flush().await
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        (**self).record_opening(opened_bytes)
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        (**self).output_digest()
+    }
+
+    fn set_receive_timeout(&mut self, timeout: Option<Duration>) {
+        (**self).set_receive_timeout(timeout)
+    }
+
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        (**self).peer_identity()
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        (**self).rate_limit_stats()
+    }
+}
+
+/// The scalars and points a party sends in one step of a protocol
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Payload {
+    /// The scalars of the payload, sent as one message
+    pub scalars: Vec<Scalar>,
+    /// The points of the payload, sent as one message after the scalars
+    pub points: Vec<RistrettoPoint>,
 }
 
 /// The order in which the local party should read when exchanging values
@@ -152,6 +511,255 @@ pub enum ReadWriteOrder {
     WriteFirst,
 }
 
+impl ReadWriteOrder {
+    /// Returns the read order for the given party; king is write first
+    fn of<N: MpcNetwork + ?Sized>(network: &N) -> Self {
+        if network.am_king() {
+            ReadWriteOrder::WriteFirst
+        } else {
+            ReadWriteOrder::ReadFirst
+        }
+    }
+}
+
+/// A network that exchanges values as raw bytes over a reliable stream to the peer, from which
+/// its broadcasts are built
+///
+/// Each message of values is prefixed with a `MessageHeader`, checked by the receiver against
+/// the operation it runs
+#[async_trait]
+pub(crate) trait ByteTransport: MpcNetwork + Send {
+    /// Write a stream of bytes to the peer
+    async fn write_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError>;
+    /// Read exactly `n` bytes from the peer
+    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError>;
+    /// The sequence numbers of the messages exchanged with the peer
+    fn message_sequence(&mut self) -> &mut MessageSequence;
+    /// Write a message of `num_values` serialized values to the peer after its header
+    async fn write_message(
+        &mut self,
+        kind: MessageKind,
+        num_values: usize,
+        payload: &[u8],
+    ) -> Result<(), MpcNetworkError> {
+        let header = self.message_sequence().next_sent(kind, num_values);
+
+        let mut message = Vec::with_capacity(MESSAGE_HEADER_BYTES + payload.len());
+        message.extend_from_slice(&header.to_bytes());
+        message.extend_from_slice(payload);
+        self.write_bytes(&message).await
+    }
+    /// Read a message of `num_values` serialized values from the peer, after checking that its
+    /// header matches the operation the local party expects
+    async fn read_message(
+        &mut self,
+        kind: MessageKind,
+        num_values: usize,
+        bytes_per_value: usize,
+    ) -> Result<Vec<u8>, MpcNetworkError> {
+        let header = self.read_bytes(MESSAGE_HEADER_BYTES).await?;
+        self.message_sequence()
+            .check_received(kind, num_values, &header)?;
+        self.read_bytes(num_values * bytes_per_value).await
+    }
+    /// Write a message of values to the network, then expect a message of as many values back
+    /// from the connected peer
+    async fn write_then_read_message(
+        &mut self,
+        kind: MessageKind,
+        num_values: usize,
+        bytes_per_value: usize,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, MpcNetworkError> {
+        Ok(match ReadWriteOrder::of(self) {
+            ReadWriteOrder::ReadFirst => {
+                let bytes_read = self.read_message(kind, num_values, bytes_per_value).await?;
+                self.write_message(kind, num_values, payload).await?;
+                bytes_read
+            }
+            ReadWriteOrder::WriteFirst => {
+                self.write_message(kind, num_values, payload).await?;
+                self.read_message(kind, num_values, bytes_per_value).await?
+            }
+        })
+    }
+}
+
+/// The operation with which a party sent a message of values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Values sent with `send_scalars`, received with `receive_scalars`
+    SendScalars,
+    /// Values exchanged with `broadcast_scalars`
+    BroadcastScalars,
+    /// Values sent with `send_points`, received with `receive_points`
+    SendPoints,
+    /// Values exchanged with `broadcast_points`
+    BroadcastPoints,
+}
+
+impl MessageKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            MessageKind::SendScalars => 1,
+            MessageKind::BroadcastScalars => 2,
+            MessageKind::SendPoints => 3,
+            MessageKind::BroadcastPoints => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, MpcNetworkError> {
+        match byte {
+            1 => Ok(MessageKind::SendScalars),
+            2 => Ok(MessageKind::BroadcastScalars),
+            3 => Ok(MessageKind::SendPoints),
+            4 => Ok(MessageKind::BroadcastPoints),
+            _ => Err(MpcNetworkError::SerializationError),
+        }
+    }
+}
+
+/// The header tagging each message of values sent over a two party network
+///
+/// A party checks the header of each message against the operation it runs, so that parties
+/// whose code paths diverge fail with `MpcNetworkError::ProtocolDesync` rather than
+/// misinterpreting each other's values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageHeader {
+    /// The index of the message among those sent by the party on the stream
+    pub sequence: u64,
+    /// The operation that sent the message
+    pub kind: MessageKind,
+    /// The number of values in the message
+    pub num_values: u64,
+}
+
+impl MessageHeader {
+    fn to_bytes(self) -> [u8; MESSAGE_HEADER_BYTES] {
+        let mut bytes = [0u8; MESSAGE_HEADER_BYTES];
+        bytes[..8].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes[8] = self.kind.to_byte();
+        bytes[9..].copy_from_slice(&self.num_values.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MpcNetworkError> {
+        Ok(Self {
+            sequence: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            kind: MessageKind::from_byte(bytes[8])?,
+            num_values: u64::from_le_bytes(bytes[9..].try_into().unwrap()),
+        })
+    }
+}
+
+/// A hash chain over every value opened in a session, kept by a network built with
+/// `with_output_digest`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct OutputDigest([u8; 32]);
+
+impl OutputDigest {
+    /// Absorbs the bytes of an opened value into the chain
+    pub(crate) fn absorb(&mut self, opened_bytes: &[u8]) {
+        // digest = H(digest || opened_bytes)
+        let mut hasher = Sha3_256::new();
+        hasher.input(&self.0[..]);
+        hasher.input(opened_bytes);
+        self.0.copy_from_slice(&hasher.result());
+    }
+
+    /// Returns the current head of the chain
+    pub(crate) fn value(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+/// The sequence numbers of the messages of values exchanged with the peer, from which the
+/// header of each message is built and checked
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct MessageSequence {
+    /// The sequence number of the next message sent to the peer
+    next_sent: u64,
+    /// The sequence number of the next message expected from the peer
+    next_received: u64,
+}
+
+impl MessageSequence {
+    /// Returns the header of the next message sent to the peer
+    fn next_sent(&mut self, kind: MessageKind, num_values: usize) -> MessageHeader {
+        let header = MessageHeader {
+            sequence: self.next_sent,
+            kind,
+            num_values: num_values as u64,
+        };
+        self.next_sent += 1;
+        header
+    }
+
+    /// Checks the header of a message received from the peer against the message the local
+    /// party expects
+    fn check_received(
+        &mut self,
+        kind: MessageKind,
+        num_values: usize,
+        header_bytes: &[u8],
+    ) -> Result<(), MpcNetworkError> {
+        let expected = MessageHeader {
+            sequence: self.next_received,
+            kind,
+            num_values: num_values as u64,
+        };
+        let received = MessageHeader::from_bytes(header_bytes)?;
+        self.next_received += 1;
+        if received != expected {
+            return Err(MpcNetworkError::ProtocolDesync(expected, received));
+        }
+
+        Ok(())
+    }
+}
+
+/// The transport used for messages sent over QUIC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuicMode {
+    /// All messages are sent over a reliable, ordered bidirectional stream
+    Stream,
+    /// Messages that fit in a datagram are sent as QUIC datagrams for lower latency, larger
+    /// messages fall back to the bidirectional stream.
+    ///
+    /// Datagrams are not retransmitted; this mode is only suitable for links on which loss
+    /// and reordering are not expected. Each datagram carries a sequence number, and a receive
+    /// fails with `MpcNetworkError::DatagramLost` if a datagram was lost or reordered. A receive
+    /// awaiting a lost datagram fails with `MpcNetworkError::Timeout` after the receive timeout,
+    /// or after `DEFAULT_DATAGRAM_RECEIVE_TIMEOUT` if none is set
+    Datagram,
+}
+
+/// The policy used to re-establish the connection to the peer after a transient failure
+///
+/// After reconnecting, the parties exchange the number of bytes each has sent and received on
+/// the stream, and each replays the bytes that the peer did not receive. A protocol in flight
+/// therefore resumes where it failed, so long as the peer is not missing more bytes than were
+/// retained for replay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts made to establish a connection, including the first
+    pub max_attempts: usize,
+    /// The delay before the first retry, doubled after each failed attempt
+    pub initial_backoff: Duration,
+    /// The number of most recently sent bytes retained to replay to the peer after a reconnect
+    pub replay_window: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            replay_window: 1 << 16,
+        }
+    }
+}
+
 /// Implements an MpcNetwork on top of QUIC
 #[derive(Debug)]
 pub struct QuicTwoPartyNet {
@@ -159,6 +767,8 @@ pub struct QuicTwoPartyNet {
     party_id: PartyId,
     /// Whether the network has been bootstrapped yet
     connected: bool,
+    /// The transport used for messages, both parties must use the same mode
+    mode: QuicMode,
     /// The address of the local peer
     local_addr: SocketAddr,
     /// Addresses of the counterparties in the MPC
@@ -167,6 +777,56 @@ pub struct QuicTwoPartyNet {
     send_stream: Option<SendStream>,
     /// The receive side of the bidirecitonal stream
     recv_stream: Option<RecvStream>,
+    /// The connection to the peer, used to send datagrams
+    connection: Option<Connection>,
+    /// A hash chain over the values opened in the session, if enabled
+    output_digest: Option<OutputDigest>,
+    /// The local endpoint, kept to re-establish the connection
+    endpoint: Option<Endpoint>,
+    /// The policy used to reconnect after a failure, if enabled
+    retry_policy: Option<RetryPolicy>,
+    /// The number of bytes written to the stream in the session
+    bytes_sent: u64,
+    /// The number of bytes read from the stream in the session
+    bytes_received: u64,
+    /// The most recently sent bytes, replayed to the peer after a reconnect
+    replay_buffer: VecDeque<u8>,
+    /// Whether stream writes are queued and coalesced into a single write on flush
+    coalesce_writes: bool,
+    /// The queued stream writes awaiting a flush
+    pending_writes: BytesMut,
+    /// The identities used to authenticate the peer, if enabled
+    auth_config: Option<PeerAuthConfig>,
+    /// The duration after which a receive fails, if set
+    receive_timeout: Option<Duration>,
+    /// The largest frame a batch is split into, if batches are streamed
+    max_frame_size: Option<usize>,
+    /// The frame size from which the local party offers to compress frames, if enabled
+    #[cfg(feature = "compression")]
+    compression_threshold: Option<usize>,
+    /// The frame size from which both parties compress frames, if both enabled compression
+    #[cfg(feature = "compression")]
+    negotiated_compression_threshold: Option<usize>,
+    /// The ID of the next logical channel opened on the connection, the main stream is channel 0
+    next_channel_id: u64,
+    /// The keepalive pings and idle timeout of the connection
+    keep_alive: KeepAliveConfig,
+    /// The cache from which sessions are resumed, if enabled
+    session_cache: Option<SessionCache>,
+    /// Whether the current connection resumed a session and sent early data
+    zero_rtt_accepted: bool,
+    /// The long-term key with which the local party signs the handshake, if enabled
+    identity: Option<IdentityKeypair>,
+    /// The identity the peer proved in the handshake, if it signed the handshake
+    peer_identity: Option<PeerIdentity>,
+    /// The sequence numbers of the messages of values exchanged with the peer
+    message_sequence: MessageSequence,
+    /// The sequence number of the next datagram sent to the peer
+    next_datagram_sent: u64,
+    /// The sequence number of the next datagram expected from the peer
+    next_datagram_received: u64,
+    /// The limit on the rate of sends, shared with the channels opened on the connection
+    rate_limiter: Option<RateLimiter>,
 }
 
 #[allow(clippy::redundant_closure)] // For readability of error handling
@@ -178,20 +838,160 @@ impl<'a> QuicTwoPartyNet {
             local_addr,
             peer_addr,
             connected: false,
+            mode: QuicMode::Stream,
             send_stream: None,
             recv_stream: None,
+            connection: None,
+            output_digest: None,
+            endpoint: None,
+            retry_policy: None,
+            bytes_sent: 0,
+            bytes_received: 0,
+            replay_buffer: VecDeque::new(),
+            coalesce_writes: false,
+            pending_writes: BytesMut::new(),
+            auth_config: None,
+            receive_timeout: None,
+            max_frame_size: None,
+            #[cfg(feature = "compression")]
+            compression_threshold: None,
+            #[cfg(feature = "compression")]
+            negotiated_compression_threshold: None,
+            next_channel_id: 1,
+            keep_alive: KeepAliveConfig::default(),
+            session_cache: None,
+            zero_rtt_accepted: false,
+            identity: None,
+            peer_identity: None,
+            message_sequence: MessageSequence::default(),
+            next_datagram_sent: 0,
+            next_datagram_received: 0,
+            rate_limiter: None,
         }
     }
 
-    /// Returns the read order for the local peer; king is write first
-    fn read_order(&self) -> ReadWriteOrder {
-        if self.am_king() {
-            ReadWriteOrder::WriteFirst
+    /// Selects the transport used for messages; defaults to `QuicMode::Stream`
+    pub fn with_mode(mut self, mode: QuicMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Maintains a hash chain over every value opened in the session, see `output_digest`
+    pub fn with_output_digest(mut self) -> Self {
+        self.output_digest = Some(OutputDigest::default());
+        self
+    }
+
+    /// Reconnects to the peer under the given policy when the connection fails, rather than
+    /// failing the computation; both parties must enable the same policy
+    ///
+    /// Datagrams cannot be replayed, so the policy is only applied in `QuicMode::Stream`
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Queues stream writes and coalesces them into a single write, flushed before the local
+    /// party next reads from the peer; e.g. the shares sent one by one in a batch are written
+    /// once at the following open
+    ///
+    /// Coalescing saves writes rather than round trips, as the peer may await any queued value.
+    /// Round trips are saved where a protocol sends consecutive messages, e.g. the peer's
+    /// commitment and opening in `MpcNetwork::exchange_commitments` are written at once
+    pub fn with_coalesced_writes(mut self) -> Self {
+        self.coalesce_writes = true;
+        self
+    }
+
+    /// Authenticates the peer when connecting, failing with an `AuthenticationError` if the
+    /// peer does not present the pinned certificate
+    pub fn with_peer_auth(mut self, auth_config: PeerAuthConfig) -> Self {
+        self.auth_config = Some(auth_config);
+        self
+    }
+
+    /// Fails receives with `MpcNetworkError::Timeout` if the peer does not send within the
+    /// given duration; see `MpcNetwork::set_receive_timeout`
+    ///
+    /// A timed out receive may have consumed part of a message, so the session should be
+    /// abandoned after a timeout
+    pub fn with_receive_timeout(mut self, timeout: Duration) -> Self {
+        self.receive_timeout = Some(timeout);
+        self
+    }
+
+    /// Streams large batches as a sequence of frames of at most `max_frame_size` bytes, rather
+    /// than serializing each batch into a single message. A frame is only serialized once the
+    /// previous frame has been accepted by the transport, so the memory used for buffers stays
+    /// flat however large the batch, and flow control applies backpressure to the sender
+    ///
+    /// Both parties must use the same frame size in `QuicMode::Datagram`, where small frames are
+    /// sent as datagrams; over the stream the framing is invisible to the peer
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = Some(max_frame_size);
+        self
+    }
+
+    /// Offers to compress scalar and point frames of at least `threshold` bytes
+    ///
+    /// Compression is negotiated when connecting and applied only if both parties offer it,
+    /// from the larger of the two thresholds. Frames small enough to be sent as datagrams are
+    /// never compressed, and compressed frames are always written to the stream
+    ///
+    /// A threshold of zero is advertised to the peer as not compressing, so it is rejected
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, threshold: usize) -> Result<Self, MpcNetworkError> {
+        if threshold == 0 || threshold > u32::MAX as usize {
+            return Err(MpcNetworkError::InvalidConfig(format!(
+                "compression threshold must be in 1..={}, got {}",
+                u32::MAX,
+                threshold
+            )));
+        }
+
+        self.compression_threshold = Some(threshold);
+        Ok(self)
+    }
+
+    /// Returns whether a frame of the given length is compressed on the wire
+    #[cfg(feature = "compression")]
+    fn compress_frame(&self, frame_length: usize) -> bool {
+        self.negotiated_compression_threshold
+            .is_some_and(|threshold| frame_length >= threshold)
+            && !self.use_datagram(frame_length)
+    }
+
+    /// Returns an error if the peer on the connection did not present the pinned certificate
+    fn authenticate_peer(&self, connection: &Connection) -> Result<(), MpcNetworkError> {
+        let auth_config = match self.auth_config.as_ref() {
+            Some(auth_config) => auth_config,
+            None => return Ok(()),
+        };
+
+        let peer_certs = connection
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<rustls::Certificate>>().ok())
+            .ok_or(MpcNetworkError::AuthenticationError)?;
+        match peer_certs.first() {
+            Some(cert) if cert.0 == auth_config.peer_cert => Ok(()),
+            _ => Err(MpcNetworkError::AuthenticationError),
+        }
+    }
+
+    /// Returns the retry policy if the network reconnects after a failure
+    fn active_retry_policy(&self) -> Option<RetryPolicy> {
+        if self.mode == QuicMode::Stream {
+            self.retry_policy
         } else {
-            ReadWriteOrder::ReadFirst
+            None
         }
     }
 
+    /// Returns whether a payload of the given length is sent as a datagram
+    fn use_datagram(&self, payload_length: usize) -> bool {
+        self.mode == QuicMode::Datagram && payload_length <= MAX_DATAGRAM_PAYLOAD
+    }
+
     /// Returns an error if the network is not connected
     fn assert_connected(&self) -> Result<(), MpcNetworkError> {
         if self.connected {
@@ -201,29 +1001,199 @@ impl<'a> QuicTwoPartyNet {
         }
     }
 
+    /// Configures the keepalive pings sent on the connection and the silence after which the peer
+    /// is considered unreachable; defaults to `KeepAliveConfig::default()`
+    pub fn with_keep_alive(mut self, keep_alive: KeepAliveConfig) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Caches the TLS session of each connection, and resumes a cached session when connecting
+    ///
+    /// A resumed session skips the certificate exchange, and the king opens the stream in 0-RTT
+    /// data alongside the handshake. Only the handshake byte is sent as early data, as early data
+    /// may be replayed by an attacker; values are exchanged once the handshake completes
+    pub fn with_session_cache(mut self, session_cache: SessionCache) -> Self {
+        self.session_cache = Some(session_cache);
+        self
+    }
+
+    /// Signs the connection handshake with the given long-term identity, proving it to the peer
+    ///
+    /// The peer exposes the identity through `MpcNetwork::peer_identity`. A party without an
+    /// identity still verifies the identity of a peer that has one
+    pub fn with_identity(mut self, identity: IdentityKeypair) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Requires the peer to sign the connection handshake with the given identity, failing with
+    /// an `AuthenticationError` if it signs with another identity or does not sign at all
+    ///
+    /// Without a pinned identity the peer is held to the identity it proves on its first
+    /// connection, so that a reconnect must reach the same peer
+    pub fn with_pinned_identity(mut self, peer_identity: PeerIdentity) -> Self {
+        self.peer_identity = Some(peer_identity);
+        self
+    }
+
+    /// Limits the rate at which the network sends to the peer, e.g. so that many computations run
+    /// by a relayer share its link; channels opened on the network share the same limit
+    ///
+    /// The limit applies to everything sent on the connection other than QUIC's own framing
+    /// and acknowledgements
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(limit));
+        self
+    }
+
+    /// Whether the current connection resumed a cached session with 0-RTT data, as seen by the
+    /// king; the peer accepts early data but cannot tell it from a full handshake
+    pub fn zero_rtt_accepted(&self) -> bool {
+        self.zero_rtt_accepted
+    }
+
+    /// Opens a logical channel to the peer, multiplexed over the connection as a new stream
+    ///
+    /// The returned network sends and receives independently of this network and of any other
+    /// channel, so that independent subprotocols may be interleaved without their messages
+    /// colliding. Both parties must open their channels in the same order; the king writes the
+    /// channel ID at the head of the stream and the peer checks it against its own count.
+    ///
+    /// A channel always uses `QuicMode::Stream` as datagrams are shared by the connection, does
+    /// not reconnect, and does not maintain an output digest; the remaining options are inherited
+    pub async fn open_channel(&mut self) -> Result<QuicTwoPartyNet, MpcNetworkError> {
+        self.assert_connected()?;
+
+        let channel_id = self.next_channel_id;
+        self.next_channel_id += 1;
+
+        let connection = self.connection.as_ref().unwrap().clone();
+        let (send, recv) = if self.am_king() {
+            let (mut send, recv) = connection.open_bi().await.map_err(|err| {
+                MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err))
+            })?;
+            send.write_all(&channel_id.to_le_bytes())
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?;
+
+            (send, recv)
+        } else {
+            let (send, mut recv) = connection.accept_bi().await.map_err(|err| {
+                MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err))
+            })?;
+
+            let mut header = [0u8; 8];
+            recv.read_exact(&mut header)
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?;
+            if u64::from_le_bytes(header) != channel_id {
+                return Err(MpcNetworkError::ConnectionSetupError(
+                    SetupError::HandshakeError,
+                ));
+            }
+
+            (send, recv)
+        };
+
+        let mut channel = Self::new(self.party_id, self.local_addr, self.peer_addr);
+        channel.connected = true;
+        channel.send_stream = Some(send);
+        channel.recv_stream = Some(recv);
+        channel.connection = Some(connection);
+        channel.coalesce_writes = self.coalesce_writes;
+        channel.receive_timeout = self.receive_timeout;
+        channel.max_frame_size = self.max_frame_size;
+        channel.peer_identity = self.peer_identity;
+        channel.rate_limiter = self.rate_limiter.clone();
+        #[cfg(feature = "compression")]
+        {
+            channel.negotiated_compression_threshold = self.negotiated_compression_threshold;
+        }
+
+        Ok(channel)
+    }
+
     /// Establishes connections to the peer
     pub async fn connect(&mut self) -> Result<(), MpcNetworkError> {
         // Build the client and server configs
-        let (client_config, server_config) =
-            config::build_configs().map_err(|err| MpcNetworkError::ConnectionSetupError(err))?;
+        let (client_config, server_config) = config::build_configs(
+            self.auth_config.as_ref(),
+            &self.keep_alive,
+            self.session_cache.as_ref(),
+        )
+        .map_err(|err| MpcNetworkError::ConnectionSetupError(err))?;
 
         // Create a quinn server
         let mut local_endpoint = Endpoint::server(server_config, self.local_addr)
             .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::ServerSetupError))?;
         local_endpoint.set_default_client_config(client_config);
+        self.endpoint = Some(local_endpoint);
+
+        self.establish_connection_with_retry().await?;
+        self.connected = true;
 
-        // The king dials the peer who awaits connection
+        Ok(())
+    }
+
+    /// Establishes a connection, retrying with exponential backoff if a retry policy is active
+    async fn establish_connection_with_retry(&mut self) -> Result<(), MpcNetworkError> {
+        let policy = match self.active_retry_policy() {
+            Some(policy) => policy,
+            None => return self.establish_connection().await,
+        };
+
+        let mut backoff = policy.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.establish_connection().await {
+                Ok(()) => return Ok(()),
+                // A peer that fails authentication will not pass on a retry
+                Err(MpcNetworkError::AuthenticationError) => {
+                    return Err(MpcNetworkError::AuthenticationError)
+                }
+                Err(err) if attempt >= policy.max_attempts => return Err(err),
+                Err(_) => {
+                    async_std::task::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Dials or accepts a connection to the peer and opens a bidirectional stream on it
+    async fn establish_connection(&mut self) -> Result<(), MpcNetworkError> {
+        let local_endpoint = self.endpoint.as_ref().unwrap();
+
+        // The king dials the peer who awaits connection, resuming a cached session in 0-RTT if
+        // the session cache holds one
+        let mut early_stream = None;
+        self.zero_rtt_accepted = false;
         let connection = {
             if self.am_king() {
-                local_endpoint
+                let connecting = local_endpoint
                     .connect(self.peer_addr, config::SERVER_NAME)
                     .map_err(|err| {
                         MpcNetworkError::ConnectionSetupError(SetupError::ConnectError(err))
-                    })?
-                    .await
-                    .map_err(|err| {
+                    })?;
+
+                match connecting.into_0rtt() {
+                    Ok((connection, zero_rtt_accepted)) => {
+                        // If the peer rejects the early data the stream is discarded, and
+                        // reopened below once the handshake completes
+                        let stream = open_stream(&connection).await?;
+                        self.zero_rtt_accepted = zero_rtt_accepted.await;
+                        if self.zero_rtt_accepted {
+                            early_stream = Some(stream);
+                        }
+
+                        connection
+                    }
+                    Err(connecting) => connecting.await.map_err(|err| {
                         MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err))
-                    })?
+                    })?,
+                }
             } else {
                 local_endpoint
                     .accept()
@@ -238,73 +1208,548 @@ impl<'a> QuicTwoPartyNet {
             }
         };
 
+        // Check the peer identity before any data beyond the early handshake byte is exchanged
+        if let Err(err) = self.authenticate_peer(&connection) {
+            connection.close(VarInt::from_u32(0), b"authentication failed");
+            return Err(err);
+        }
+
         // King opens a bidirectional stream on top of the connection
+        // The peer only sees the stream once data is written to it, so the king writes a handshake
+        // byte; otherwise the peer would not finish connecting if the first message were a datagram
         let (send, recv) = {
             if self.am_king() {
-                connection.open_bi().await.map_err(|err| {
-                    MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err))
-                })?
+                match early_stream {
+                    Some(stream) => stream,
+                    None => open_stream(&connection).await?,
+                }
             } else {
-                connection.accept_bi().await.map_err(|err| {
+                let (send, mut recv) = connection.accept_bi().await.map_err(|err| {
                     MpcNetworkError::ConnectionSetupError(SetupError::ConnectionError(err))
-                })?
+                })?;
+
+                let mut handshake = [0u8; 1];
+                recv.read_exact(&mut handshake).await.map_err(|_| {
+                    MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError)
+                })?;
+                if handshake[0] != HANDSHAKE_BYTE {
+                    return Err(MpcNetworkError::ConnectionSetupError(
+                        SetupError::HandshakeError,
+                    ));
+                }
+
+                (send, recv)
             }
         };
 
         // Update MpcNet state
-        self.connected = true;
         self.send_stream = Some(send);
         self.recv_stream = Some(recv);
+        self.connection = Some(connection);
+
+        let peer_signs = self.negotiate_capabilities().await?;
+        self.exchange_identities(peer_signs).await
+    }
+
+    /// Exchanges hellos carrying the protocol version and the optional capabilities of the
+    /// parties on the new stream, the king writes first
+    ///
+    /// Parties always exchange capabilities, so that a party built without an optional feature
+    /// interoperates with one that has it. The capabilities are length prefixed, so that a later
+    /// version may extend them and still report a version mismatch to an earlier one.
+    ///
+    /// Returns whether the peer signs the handshake
+    async fn negotiate_capabilities(&mut self) -> Result<bool, MpcNetworkError> {
+        #[cfg(feature = "compression")]
+        let compression_threshold = self.compression_threshold.unwrap_or_default() as u32;
+        #[cfg(not(feature = "compression"))]
+        let compression_threshold = 0u32;
+        let flags = if self.identity.is_some() {
+            CAPABILITY_IDENTITY
+        } else {
+            0
+        };
 
+        let mut capabilities = compression_threshold.to_le_bytes().to_vec();
+        capabilities.push(flags);
+        let hello = encode_hello(&capabilities);
+        let (peer_version, peer_capabilities) = match ReadWriteOrder::of(self) {
+            ReadWriteOrder::ReadFirst => {
+                let peer_hello = self.read_hello().await?;
+                // Respond before checking the version, so that the king sees the mismatch too
+                self.write_stream(&hello).await?;
+                peer_hello
+            }
+            ReadWriteOrder::WriteFirst => {
+                self.write_stream(&hello).await?;
+                self.read_hello().await?
+            }
+        };
+
+        if peer_version != PROTOCOL_VERSION {
+            return Err(MpcNetworkError::ProtocolVersionMismatch(
+                PROTOCOL_VERSION,
+                peer_version,
+            ));
+        }
+        if peer_capabilities.len() != CAPABILITY_BYTES {
+            return Err(MpcNetworkError::ConnectionSetupError(
+                SetupError::HandshakeError,
+            ));
+        }
+
+        #[cfg(feature = "compression")]
+        {
+            let peer_threshold = u32::from_le_bytes(peer_capabilities[..4].try_into().unwrap());
+            self.negotiated_compression_threshold =
+                if compression_threshold > 0 && peer_threshold > 0 {
+                    Some(u32::max(compression_threshold, peer_threshold) as usize)
+                } else {
+                    None
+                };
+        }
+
+        Ok(peer_capabilities[4] & CAPABILITY_IDENTITY != 0)
+    }
+
+    /// Signs the handshake with the local identity and verifies the peer's signature, if the
+    /// respective party has an identity; the king writes first
+    ///
+    /// Each party signs keying material exported from the TLS session under its party ID, so
+    /// that a signature is bound to both the session and the role of the signer, and cannot be
+    /// relayed to another session by an attacker in the middle
+    async fn exchange_identities(&mut self, peer_signs: bool) -> Result<(), MpcNetworkError> {
+        let signed_identity = match self.identity.as_ref() {
+            Some(identity) => {
+                let transcript = self.handshake_transcript(self.party_id)?;
+                let mut signed_identity = identity.public().to_bytes().to_vec();
+                signed_identity.extend_from_slice(&identity.sign(&transcript).to_bytes());
+                Some(signed_identity)
+            }
+            None => None,
+        };
+
+        let mut peer_signed_identity = None;
+        match ReadWriteOrder::of(self) {
+            ReadWriteOrder::ReadFirst => {
+                if peer_signs {
+                    peer_signed_identity = Some(self.read_stream(SIGNED_IDENTITY_BYTES).await?);
+                }
+                if let Some(signed_identity) = signed_identity {
+                    self.write_stream(&signed_identity).await?;
+                }
+            }
+            ReadWriteOrder::WriteFirst => {
+                if let Some(signed_identity) = signed_identity {
+                    self.write_stream(&signed_identity).await?;
+                }
+                if peer_signs {
+                    peer_signed_identity = Some(self.read_stream(SIGNED_IDENTITY_BYTES).await?);
+                }
+            }
+        }
+
+        let peer_signed_identity = match peer_signed_identity {
+            Some(peer_signed_identity) => peer_signed_identity,
+            // A peer whose identity is pinned or was proven on an earlier connection must sign
+            None if self.peer_identity.is_some() => {
+                return Err(MpcNetworkError::AuthenticationError)
+            }
+            None => return Ok(()),
+        };
+        let peer_identity = PeerIdentity::from_bytes(&peer_signed_identity[..IDENTITY_BYTES])
+            .map_err(|_| MpcNetworkError::AuthenticationError)?;
+        let signature = Signature::from_bytes(&peer_signed_identity[IDENTITY_BYTES..])
+            .map_err(|_| MpcNetworkError::AuthenticationError)?;
+
+        let peer_transcript = self.handshake_transcript(1 - self.party_id)?;
+        if !peer_identity.verify(&peer_transcript, &signature) {
+            return Err(MpcNetworkError::AuthenticationError);
+        }
+        // A reconnect must reach the same peer
+        if self
+            .peer_identity
+            .is_some_and(|known_identity| known_identity != peer_identity)
+        {
+            return Err(MpcNetworkError::AuthenticationError);
+        }
+
+        self.peer_identity = Some(peer_identity);
         Ok(())
     }
 
-    /// Write a stream of bytes to the stream
+    /// Exports the keying material of the TLS session that the given party signs
+    fn handshake_transcript(&self, signer: PartyId) -> Result<[u8; 32], MpcNetworkError> {
+        let mut transcript = [0u8; 32];
+        self.connection
+            .as_ref()
+            .unwrap()
+            .export_keying_material(
+                &mut transcript,
+                IDENTITY_EXPORT_LABEL,
+                &signer.to_le_bytes(),
+            )
+            .map_err(|_| MpcNetworkError::AuthenticationError)?;
+
+        Ok(transcript)
+    }
+
+    /// Reads the peer's hello, returning its protocol version and capabilities
+    async fn read_hello(&mut self) -> Result<(u16, Vec<u8>), MpcNetworkError> {
+        let header = self.read_stream(HELLO_HEADER_BYTES).await?;
+        let (version, capabilities_length) = decode_hello_header(&header)?;
+        let capabilities = self.read_stream(capabilities_length).await?;
+
+        Ok((version, capabilities))
+    }
+
+    /// Write a stream of bytes to the peer, as a datagram if the mode and payload size allow
     async fn write_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        if self.use_datagram(payload.len()) {
+            // Keep datagrams ordered after the queued stream writes
+            self.flush_pending().await?;
+
+            let mut datagram = BytesMut::with_capacity(DATAGRAM_SEQUENCE_BYTES + payload.len());
+            datagram.extend_from_slice(&self.next_datagram_sent.to_le_bytes());
+            datagram.extend_from_slice(payload);
+            self.next_datagram_sent += 1;
+
+            self.throttle(datagram.len()).await;
+            return self
+                .connection
+                .as_ref()
+                .unwrap()
+                .send_datagram(datagram.freeze())
+                .map_err(|err| match err {
+                    SendDatagramError::ConnectionLost(err) => {
+                        connection_lost_error(&err, MpcNetworkError::SendError)
+                    }
+                    _ => MpcNetworkError::SendError,
+                });
+        }
+
+        self.write_stream_bytes(payload).await
+    }
+
+    /// Write a payload to the peer over the stream, however small
+    async fn write_stream_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        if self.coalesce_writes {
+            self.pending_writes.extend_from_slice(payload);
+            return Ok(());
+        }
+
+        self.write_stream_recoverable(payload).await
+    }
+
+    /// Write the queued stream writes to the peer as a single payload
+    async fn flush_pending(&mut self) -> Result<(), MpcNetworkError> {
+        if self.pending_writes.is_empty() {
+            return Ok(());
+        }
+
+        let payload = self.pending_writes.split().freeze();
+        self.write_stream_recoverable(&payload).await
+    }
+
+    /// Write a payload to the stream, reconnecting on failure if a retry policy is active
+    async fn write_stream_recoverable(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        let res = self.write_stream(payload).await;
+        if self.active_retry_policy().is_none() {
+            return res;
+        }
+
+        // The payload may have been partially delivered before a failure, so it is recorded
+        // either way and the resync after reconnecting replays what the peer did not receive
+        self.record_sent(payload);
+        match res {
+            Ok(()) => Ok(()),
+            Err(_) => self.reconnect().await,
+        }
+    }
+
+    /// Read exactly `n` bytes from the peer, from a datagram if the mode and payload size allow
+    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        if self.use_datagram(num_bytes) {
+            // The peer may await queued writes before sending what the local party reads
+            self.flush_pending().await?;
+
+            let timeout = self
+                .receive_timeout
+                .unwrap_or(DEFAULT_DATAGRAM_RECEIVE_TIMEOUT);
+            let read = self.connection.as_ref().unwrap().read_datagram();
+            let datagram = with_timeout(Some(timeout), read)
+                .await?
+                .map_err(|err| connection_lost_error(&err, MpcNetworkError::RecvError))?;
+
+            if datagram.len() < DATAGRAM_SEQUENCE_BYTES {
+                return Err(MpcNetworkError::BroadcastError(BroadcastError::TooFewBytes));
+            }
+            let sequence =
+                u64::from_le_bytes(datagram[..DATAGRAM_SEQUENCE_BYTES].try_into().unwrap());
+            if sequence != self.next_datagram_received {
+                return Err(MpcNetworkError::DatagramLost(
+                    self.next_datagram_received,
+                    sequence,
+                ));
+            }
+            self.next_datagram_received += 1;
+
+            if datagram.len() - DATAGRAM_SEQUENCE_BYTES != num_bytes {
+                return Err(MpcNetworkError::BroadcastError(BroadcastError::TooFewBytes));
+            }
+
+            return Ok(datagram[DATAGRAM_SEQUENCE_BYTES..].to_vec());
+        }
+
+        self.read_stream_bytes(num_bytes).await
+    }
+
+    /// Read a payload of the given length from the peer over the stream, however small
+    async fn read_stream_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        // The peer may await queued writes before sending what the local party reads
+        self.flush_pending().await?;
+
+        let max_attempts = self
+            .active_retry_policy()
+            .map(|policy| policy.max_attempts)
+            .unwrap_or_default();
+        let mut attempt = 0;
+        loop {
+            match self.read_stream(num_bytes).await {
+                Ok(bytes) => {
+                    self.bytes_received += num_bytes as u64;
+                    return Ok(bytes);
+                }
+                // Any bytes read from the failed stream are replayed by the peer after the resync
+                Err(MpcNetworkError::RecvError | MpcNetworkError::PeerUnreachable)
+                    if attempt < max_attempts =>
+                {
+                    self.reconnect().await?;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Waits until the rate limit, if any, allows a send of the given length
+    async fn throttle(&self, num_bytes: usize) {
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            rate_limiter.acquire(num_bytes).await;
+        }
+    }
+
+    /// Write a stream of bytes to the bidirectional stream
+    async fn write_stream(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        self.throttle(payload.len()).await;
         self.send_stream
             .as_mut()
             .unwrap()
             .write_all(payload)
             .await
-            .map_err(|_| MpcNetworkError::SendError)
+            .map_err(|err| match err {
+                WriteError::ConnectionLost(err) => {
+                    connection_lost_error(&err, MpcNetworkError::SendError)
+                }
+                _ => MpcNetworkError::SendError,
+            })
     }
 
-    /// Read exactly `n` bytes from the stream
-    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+    /// Read exactly `n` bytes from the bidirectional stream
+    async fn read_stream(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        // Large payloads may arrive in multiple chunks, wait for the full payload
         let mut read_buffer = vec![0u8; num_bytes];
-        let bytes_read = self
+        let read = self
             .recv_stream
             .as_mut()
             .unwrap()
-            .read(&mut read_buffer)
-            .await
-            .map_err(|_| MpcNetworkError::RecvError)?
-            .ok_or(MpcNetworkError::RecvError)?;
+            .read_exact(&mut read_buffer);
+        with_timeout(self.receive_timeout, read)
+            .await?
+            .map_err(|err| match err {
+                // The peer finishes its stream to begin the shutdown handshake
+                ReadExactError::FinishedEarly => MpcNetworkError::PeerClosed,
+                ReadExactError::ReadError(ReadError::ConnectionLost(err)) => {
+                    connection_lost_error(&err, MpcNetworkError::RecvError)
+                }
+                ReadExactError::ReadError(_) => MpcNetworkError::RecvError,
+            })?;
+
+        Ok(read_buffer)
+    }
+
+    /// Counts a payload written to the stream and retains it for replay
+    fn record_sent(&mut self, payload: &[u8]) {
+        let replay_window = self.retry_policy.unwrap_or_default().replay_window;
+
+        self.bytes_sent += payload.len() as u64;
+        self.replay_buffer.extend(payload);
+        let num_expired = self.replay_buffer.len().saturating_sub(replay_window);
+        self.replay_buffer.drain(..num_expired);
+    }
+
+    /// Re-establishes a failed connection and resynchronizes the stream with the peer
+    ///
+    /// The parties exchange the number of bytes each has sent and received, then each replays
+    /// the bytes that the peer is missing. Returns a `ResyncError` if the positions are
+    /// inconsistent or the peer is missing bytes that have left the replay buffer
+    async fn reconnect(&mut self) -> Result<(), MpcNetworkError> {
+        if let Some(connection) = self.connection.take() {
+            connection.close(VarInt::from_u32(0), b"reconnect");
+        }
+        self.send_stream = None;
+        self.recv_stream = None;
+
+        self.establish_connection_with_retry().await?;
 
-        if bytes_read != num_bytes {
-            return Err(MpcNetworkError::BroadcastError(BroadcastError::TooFewBytes));
+        // Exchange stream positions, the king writes first
+        let mut positions = Vec::with_capacity(RESYNC_BYTES);
+        positions.extend_from_slice(&self.bytes_sent.to_le_bytes());
+        positions.extend_from_slice(&self.bytes_received.to_le_bytes());
+        let peer_positions = match ReadWriteOrder::of(self) {
+            ReadWriteOrder::ReadFirst => {
+                let peer_positions = self.read_stream(RESYNC_BYTES).await?;
+                self.write_stream(&positions).await?;
+                peer_positions
+            }
+            ReadWriteOrder::WriteFirst => {
+                self.write_stream(&positions).await?;
+                self.read_stream(RESYNC_BYTES).await?
+            }
+        };
+
+        let peer_sent = u64::from_le_bytes(peer_positions[..8].try_into().unwrap());
+        let peer_received = u64::from_le_bytes(peer_positions[8..].try_into().unwrap());
+        if peer_sent < self.bytes_received || peer_received > self.bytes_sent {
+            return Err(MpcNetworkError::ResyncError);
         }
 
-        Ok(read_buffer.to_vec())
+        // Replay the suffix of the sent bytes that the peer did not receive
+        let num_missing = (self.bytes_sent - peer_received) as usize;
+        if num_missing > self.replay_buffer.len() {
+            return Err(MpcNetworkError::ResyncError);
+        }
+
+        let replay = self
+            .replay_buffer
+            .iter()
+            .skip(self.replay_buffer.len() - num_missing)
+            .copied()
+            .collect::<Vec<u8>>();
+        self.write_stream(&replay).await
+    }
+
+    /// Returns the number of values of the given size written per frame
+    fn values_per_frame(&self, bytes_per_value: usize, num_values: usize) -> usize {
+        match self.max_frame_size {
+            Some(max_frame_size) => usize::max(1, max_frame_size / bytes_per_value),
+            None => usize::max(1, num_values),
+        }
     }
 
-    /// Write a stream of bytes to the network, then expect the same back from the connected peer
-    async fn write_then_read_bytes(
+    /// Serialize and write a batch of values to the peer, one frame at a time, after a header
+    /// tagging the message
+    async fn write_values<T>(
         &mut self,
-        order: ReadWriteOrder,
-        payload: &[u8],
-    ) -> Result<Vec<u8>, MpcNetworkError> {
-        let payload_length = payload.len();
+        kind: MessageKind,
+        values: &[T],
+        bytes_per_value: usize,
+        to_bytes: fn(&[T]) -> Bytes,
+    ) -> Result<(), MpcNetworkError> {
+        let header = self.message_sequence.next_sent(kind, values.len());
+        self.write_bytes(&header.to_bytes()).await?;
+
+        for frame in values.chunks(self.values_per_frame(bytes_per_value, values.len())) {
+            let payload = to_bytes(frame);
+
+            #[cfg(feature = "compression")]
+            if self.compress_frame(payload.len()) {
+                self.write_stream_bytes(&compression::compress(&payload))
+                    .await?;
+                continue;
+            }
+
+            self.write_bytes(&payload).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a frame of the given uncompressed length from the peer, decompressing if needed
+    async fn read_frame(&mut self, frame_length: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        #[cfg(feature = "compression")]
+        if self.compress_frame(frame_length) {
+            let prefix = self
+                .read_stream_bytes(compression::LENGTH_PREFIX_BYTES)
+                .await?;
+            let block_length = u32::from_le_bytes(prefix[..].try_into().unwrap()) as usize;
+
+            // A zero length prefixes a frame that did not compress
+            if block_length == 0 {
+                return self.read_stream_bytes(frame_length).await;
+            }
+
+            // A block is only sent if it is shorter than the frame, so a longer length is not
+            // read from a peer that could otherwise force an arbitrary allocation
+            if block_length >= frame_length {
+                return Err(MpcNetworkError::SerializationError);
+            }
+
+            let block = self.read_stream_bytes(block_length).await?;
+            return compression::decompress(&block, frame_length);
+        }
+
+        self.read_bytes(frame_length).await
+    }
+
+    /// Read and deserialize a batch of values from the peer, one frame at a time, after checking
+    /// that the header of the message matches the operation the local party expects
+    async fn read_values<T>(
+        &mut self,
+        kind: MessageKind,
+        num_values: usize,
+        bytes_per_value: usize,
+        from_bytes: fn(&[u8]) -> Result<Vec<T>, MpcNetworkError>,
+    ) -> Result<Vec<T>, MpcNetworkError> {
+        let header = self.read_bytes(MESSAGE_HEADER_BYTES).await?;
+        self.message_sequence
+            .check_received(kind, num_values, &header)?;
 
-        Ok(match order {
+        let values_per_frame = self.values_per_frame(bytes_per_value, num_values);
+
+        let mut values = Vec::with_capacity(num_values);
+        while values.len() < num_values {
+            let frame_length = usize::min(values_per_frame, num_values - values.len());
+            let bytes_read = self.read_frame(frame_length * bytes_per_value).await?;
+            values.append(&mut from_bytes(&bytes_read)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Write a batch of values to the network, then expect the same number back from the peer
+    async fn write_then_read_values<T>(
+        &mut self,
+        kind: MessageKind,
+        values: &[T],
+        bytes_per_value: usize,
+        to_bytes: fn(&[T]) -> Bytes,
+        from_bytes: fn(&[u8]) -> Result<Vec<T>, MpcNetworkError>,
+    ) -> Result<Vec<T>, MpcNetworkError> {
+        Ok(match ReadWriteOrder::of(self) {
             ReadWriteOrder::ReadFirst => {
-                let bytes_read = self.read_bytes(payload_length).await?;
-                self.write_bytes(payload).await?;
-                bytes_read
+                let values_read = self
+                    .read_values(kind, values.len(), bytes_per_value, from_bytes)
+                    .await?;
+                self.write_values(kind, values, bytes_per_value, to_bytes)
+                    .await?;
+                values_read
             }
             ReadWriteOrder::WriteFirst => {
-                self.write_bytes(payload).await?;
-                self.read_bytes(payload_length).await?
+                self.write_values(kind, values, bytes_per_value, to_bytes)
+                    .await?;
+                self.read_values(kind, values.len(), bytes_per_value, from_bytes)
+                    .await?
             }
         })
     }
@@ -318,12 +1763,13 @@ impl MpcNetwork for QuicTwoPartyNet {
 
     async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
         self.assert_connected()?;
-
-        // To byte buffer
-        let payload = scalars_to_bytes(scalars);
-        self.write_bytes(&payload).await?;
-
-        Ok(())
+        self.write_values(
+            MessageKind::SendScalars,
+            scalars,
+            BYTES_PER_SCALAR,
+            scalars_to_bytes,
+        )
+        .await
     }
 
     async fn receive_scalars(
@@ -331,9 +1777,13 @@ impl MpcNetwork for QuicTwoPartyNet {
         num_scalars: usize,
     ) -> Result<Vec<Scalar>, MpcNetworkError> {
         self.assert_connected()?;
-        let bytes_read = self.read_bytes(num_scalars * BYTES_PER_SCALAR).await?;
-
-        bytes_to_scalars(&bytes_read)
+        self.read_values(
+            MessageKind::SendScalars,
+            num_scalars,
+            BYTES_PER_SCALAR,
+            bytes_to_scalars,
+        )
+        .await
     }
 
     async fn broadcast_scalars(
@@ -341,28 +1791,37 @@ impl MpcNetwork for QuicTwoPartyNet {
         scalars: &[Scalar],
     ) -> Result<Vec<Scalar>, MpcNetworkError> {
         self.assert_connected()?;
-
-        // To byte buffer
-        let payload = scalars_to_bytes(scalars);
-
-        let read_buffer = self
-            .write_then_read_bytes(self.read_order(), &payload)
-            .await?;
-
-        bytes_to_scalars(&read_buffer)
+        self.write_then_read_values(
+            MessageKind::BroadcastScalars,
+            scalars,
+            BYTES_PER_SCALAR,
+            scalars_to_bytes,
+            bytes_to_scalars,
+        )
+        .await
     }
 
     async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
-        let payload = points_to_bytes(points);
-        self.write_bytes(&payload).await
+        self.write_values(
+            MessageKind::SendPoints,
+            points,
+            BYTES_PER_POINT,
+            points_to_bytes,
+        )
+        .await
     }
 
     async fn receive_points(
         &mut self,
         num_points: usize,
     ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
-        let read_buffer = self.read_bytes(BYTES_PER_POINT * num_points).await?;
-        bytes_to_points(&read_buffer)
+        self.read_values(
+            MessageKind::SendPoints,
+            num_points,
+            BYTES_PER_POINT,
+            bytes_to_points,
+        )
+        .await
     }
 
     async fn broadcast_points(
@@ -370,38 +1829,105 @@ impl MpcNetwork for QuicTwoPartyNet {
         points: &[RistrettoPoint],
     ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
         self.assert_connected()?;
+        self.write_then_read_values(
+            MessageKind::BroadcastPoints,
+            points,
+            BYTES_PER_POINT,
+            points_to_bytes,
+            bytes_to_points,
+        )
+        .await
+    }
 
-        // To byte buffer
-        let payload = points_to_bytes(points);
-        let read_buffer = self
-            .write_then_read_bytes(self.read_order(), &payload)
-            .await?;
+    /// Runs the shutdown handshake with the peer, then closes the connection
+    ///
+    /// Finishing the local stream requests the shutdown, and completes once the peer has
+    /// received every value sent to it; the peer acknowledges by finishing its own stream. Both
+    /// parties close the connection once the handshake completes, so that either party can
+    /// tell a clean close from the loss of the connection. Values the peer sent that were never
+    /// read are drained up to `MAX_CLOSE_DRAIN_BYTES`, beyond which the close fails with
+    /// `ConnectionTeardownError`. A channel only finishes its stream, leaving the shared
+    /// connection open
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.assert_connected()?;
+        self.flush_pending().await?;
+
+        // The peer may close the connection as soon as it has both finished streams, before the
+        // local party observes the acknowledgement of its own
+        let finish = self.send_stream.as_mut().unwrap().finish();
+        match with_timeout(self.receive_timeout, finish).await? {
+            Ok(()) => {}
+            Err(WriteError::ConnectionLost(err)) if is_clean_close(&err) => {}
+            Err(_) => return Err(MpcNetworkError::ConnectionTeardownError),
+        }
+
+        // Await the peer's acknowledgement, draining any values it sent that were never read
+        let drain = drain_stream(self.recv_stream.take().unwrap());
+        with_timeout(self.receive_timeout, drain).await??;
+        self.connected = false;
+
+        if let Some(endpoint) = self.endpoint.as_ref() {
+            self.connection
+                .as_ref()
+                .unwrap()
+                .close(VarInt::from_u32(CLEAN_CLOSE_CODE), CLEAN_CLOSE_REASON);
+            // Give the close a chance to reach the peer before the endpoint is dropped
+            endpoint.wait_idle().await;
+        }
 
-        // Deserialize back to Ristretto points
-        bytes_to_points(&read_buffer)
+        Ok(())
     }
 
-    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
         self.assert_connected()?;
+        self.flush_pending().await
+    }
 
-        self.send_stream
-            .as_mut()
-            .unwrap()
-            .finish()
-            .await
-            .map_err(|_| MpcNetworkError::ConnectionTeardownError)
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        if let Some(digest) = self.output_digest.as_mut() {
+            digest.absorb(opened_bytes);
+        }
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.output_digest.map(|digest| digest.value())
+    }
+
+    fn set_receive_timeout(&mut self, timeout: Option<Duration>) {
+        self.receive_timeout = timeout;
+    }
+
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        self.peer_identity
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        self.rate_limiter.as_ref().map(RateLimiter::stats)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::net::SocketAddr;
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
 
-    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
     use rand_core::OsRng;
     use tokio;
 
-    use super::{MpcNetwork, QuicTwoPartyNet};
+    use quinn::{ConnectionError, VarInt};
+
+    use crate::error::{MpcNetworkError, SetupError};
+
+    use super::{
+        connection_lost_error, decode_hello_header, encode_hello,
+        instrumented_network::InstrumentedNetwork, IdentityKeypair, KeepAliveConfig, MessageHeader,
+        MessageKind, MpcNetwork, Payload, PeerAuthConfig, QuicMode, QuicTwoPartyNet, RateLimit,
+        RetryPolicy, SessionCache, BYTES_PER_SCALAR, HELLO_HEADER_BYTES, MAX_CLOSE_DRAIN_BYTES,
+        MAX_DATAGRAM_PAYLOAD, MESSAGE_HEADER_BYTES, PROTOCOL_VERSION,
+    };
 
     #[tokio::test]
     async fn test_errors() {
@@ -416,4 +1942,809 @@ mod test {
             .await
             .is_err())
     }
+
+    #[tokio::test]
+    async fn test_datagram_mode() {
+        let addr1: SocketAddr = "127.0.0.1:7100".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7101".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_mode(QuicMode::Datagram);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_mode(QuicMode::Datagram);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let mut rng = OsRng {};
+
+        // A small message is sent as a datagram
+        let small_message = vec![Scalar::random(&mut rng); 2];
+        let (send_res, recv_res) = tokio::join!(
+            king.send_scalars(&small_message),
+            peer.receive_scalars(small_message.len())
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), small_message);
+
+        // A message larger than a datagram falls back to the stream
+        let num_scalars = 4 * MAX_DATAGRAM_PAYLOAD / BYTES_PER_SCALAR;
+        let large_message = (0..num_scalars)
+            .map(|_| Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let (send_res, recv_res) = tokio::join!(
+            peer.send_scalars(&large_message),
+            king.receive_scalars(large_message.len())
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), large_message);
+
+        // Broadcasts of both sizes
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_scalars(&small_message),
+            peer.broadcast_scalars(&small_message)
+        );
+        assert_eq!(king_res.unwrap(), small_message);
+        assert_eq!(peer_res.unwrap(), small_message);
+
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_scalars(&large_message),
+            peer.broadcast_scalars(&large_message)
+        );
+        assert_eq!(king_res.unwrap(), large_message);
+        assert_eq!(peer_res.unwrap(), large_message);
+    }
+
+    #[tokio::test]
+    async fn test_datagram_lost() {
+        let addr1: SocketAddr = "127.0.0.1:7104".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7105".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_mode(QuicMode::Datagram);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1)
+            .with_mode(QuicMode::Datagram)
+            .with_receive_timeout(Duration::from_millis(500));
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let mut rng = OsRng {};
+        let message = vec![Scalar::random(&mut rng); 2];
+        king.send_scalars(&message).await.unwrap();
+        assert_eq!(peer.receive_scalars(message.len()).await.unwrap(), message);
+
+        // A receive awaiting a datagram that never arrives times out rather than hanging
+        assert_eq!(
+            peer.receive_scalars(message.len()).await,
+            Err(MpcNetworkError::Timeout)
+        );
+
+        // Skip a sequence number as though the header datagram of the next message were lost
+        king.next_datagram_sent += 1;
+        king.send_scalars(&message).await.unwrap();
+        assert_eq!(
+            peer.receive_scalars(message.len()).await,
+            Err(MpcNetworkError::DatagramLost(2, 3))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnect() {
+        let addr1: SocketAddr = "127.0.0.1:7200".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7201".parse().unwrap();
+        let mut king =
+            QuicTwoPartyNet::new(0, addr1, addr2).with_retry_policy(RetryPolicy::default());
+        let mut peer =
+            QuicTwoPartyNet::new(1, addr2, addr1).with_retry_policy(RetryPolicy::default());
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let mut rng = OsRng {};
+        let king_message = vec![Scalar::random(&mut rng); 3];
+        let peer_message = vec![Scalar::random(&mut rng); 3];
+
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_scalars(&king_message),
+            peer.broadcast_scalars(&peer_message)
+        );
+        assert_eq!(king_res.unwrap(), peer_message);
+        assert_eq!(peer_res.unwrap(), king_message);
+
+        // Drop the connection, both parties should reconnect and resume the broadcast
+        king.connection
+            .as_ref()
+            .unwrap()
+            .close(VarInt::from_u32(0), b"blip");
+
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_scalars(&king_message),
+            peer.broadcast_scalars(&peer_message)
+        );
+        assert_eq!(king_res.unwrap(), peer_message);
+        assert_eq!(peer_res.unwrap(), king_message);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_writes() {
+        let addr1: SocketAddr = "127.0.0.1:7300".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7301".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_coalesced_writes();
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_coalesced_writes();
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // Single sends are queued until the next read
+        let mut rng = OsRng {};
+        let message = (0..3).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        for scalar in message.iter() {
+            king.send_single_scalar(*scalar).await.unwrap();
+        }
+        assert_eq!(
+            king.pending_writes.len(),
+            message.len() * (MESSAGE_HEADER_BYTES + BYTES_PER_SCALAR)
+        );
+
+        // The peer replies with an explicit flush, as it does not read again
+        let peer_fut = async {
+            let mut received = Vec::with_capacity(2 * message.len());
+            for _ in 0..message.len() {
+                received.push(peer.receive_single_scalar().await.unwrap());
+            }
+            received.append(&mut peer.broadcast_scalars(&message).await.unwrap());
+            peer.flush().await.unwrap();
+            received
+        };
+        let (king_res, peer_received) = tokio::join!(king.broadcast_scalars(&message), peer_fut);
+        assert!(king.pending_writes.is_empty());
+        assert_eq!(peer_received, [message.clone(), message.clone()].concat());
+        assert_eq!(king_res.unwrap(), message);
+
+        // The exchange of commitments leaves nothing queued for either party
+        let commitment = Payload {
+            points: vec![RistrettoPoint::random(&mut rng)],
+            ..Default::default()
+        };
+        let opening = Payload {
+            scalars: message.clone(),
+            ..Default::default()
+        };
+        let (king_res, peer_res) = tokio::join!(
+            king.exchange_commitments(&commitment, &opening),
+            peer.exchange_commitments(&commitment, &opening)
+        );
+        assert_eq!(king_res.unwrap(), (commitment.clone(), opening.clone()));
+        assert_eq!(peer_res.unwrap(), (commitment, opening));
+        assert!(king.pending_writes.is_empty() && peer.pending_writes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_peer_auth() {
+        let (king_cert, king_key) = PeerAuthConfig::generate_identity().unwrap();
+        let (peer_cert, peer_key) = PeerAuthConfig::generate_identity().unwrap();
+        let (other_cert, _) = PeerAuthConfig::generate_identity().unwrap();
+
+        let king_auth = PeerAuthConfig {
+            local_cert: king_cert.clone(),
+            local_key: king_key,
+            peer_cert: peer_cert.clone(),
+        };
+        let peer_auth = PeerAuthConfig {
+            local_cert: peer_cert,
+            local_key: peer_key,
+            peer_cert: king_cert,
+        };
+
+        // Both parties present the pinned identities
+        let addr1: SocketAddr = "127.0.0.1:7400".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7401".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_peer_auth(king_auth.clone());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_peer_auth(peer_auth.clone());
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let scalar = Scalar::one();
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_scalar(scalar),
+            peer.broadcast_single_scalar(scalar)
+        );
+        assert_eq!(king_res.unwrap(), scalar);
+        assert_eq!(peer_res.unwrap(), scalar);
+
+        // The king expects a different identity than the peer presents
+        let addr1: SocketAddr = "127.0.0.1:7402".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7403".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_peer_auth(PeerAuthConfig {
+            peer_cert: other_cert,
+            ..king_auth
+        });
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_peer_auth(peer_auth);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        assert_eq!(king_res, Err(MpcNetworkError::AuthenticationError));
+        assert!(peer_res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_receive_timeout() {
+        let addr1: SocketAddr = "127.0.0.1:7600".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7601".parse().unwrap();
+        let mut king =
+            QuicTwoPartyNet::new(0, addr1, addr2).with_receive_timeout(Duration::from_millis(50));
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // The peer never sends
+        assert_eq!(
+            king.receive_single_point().await,
+            Err(MpcNetworkError::Timeout)
+        );
+
+        // The timeout may be lifted through the trait
+        king.set_receive_timeout(None);
+        let (send_res, recv_res) = tokio::join!(
+            peer.send_single_scalar(Scalar::one()),
+            king.receive_single_scalar()
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), Scalar::one());
+    }
+
+    #[tokio::test]
+    async fn test_framed_transfer() {
+        let addr1: SocketAddr = "127.0.0.1:7700".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7701".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_max_frame_size(100);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // Batches that do not divide evenly into frames
+        let mut rng = OsRng {};
+        let scalars = (0..10)
+            .map(|_| Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let (send_res, recv_res) = tokio::join!(
+            king.send_scalars(&scalars),
+            peer.receive_scalars(scalars.len())
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), scalars);
+
+        let points = (0..7)
+            .map(|_| RistrettoPoint::random(&mut rng))
+            .collect::<Vec<_>>();
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_points(&points),
+            peer.broadcast_points(&points)
+        );
+        assert_eq!(king_res.unwrap(), points);
+        assert_eq!(peer_res.unwrap(), points);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_compression() {
+        let addr1: SocketAddr = "127.0.0.1:7800".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7801".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2)
+            .with_compression(64)
+            .unwrap();
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1)
+            .with_compression(128)
+            .unwrap();
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert_eq!(king.negotiated_compression_threshold, Some(128));
+        assert_eq!(peer.negotiated_compression_threshold, Some(128));
+
+        // A compressible batch, an incompressible batch, and a batch below the threshold
+        let mut rng = OsRng {};
+        let zeros = vec![Scalar::zero(); 100];
+        let random = (0..100)
+            .map(|_| Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        for batch in [zeros, random.clone(), random[..2].to_vec()] {
+            let (king_res, peer_res) = tokio::join!(
+                king.broadcast_scalars(&batch),
+                peer.broadcast_scalars(&batch)
+            );
+            assert_eq!(king_res.unwrap(), batch);
+            assert_eq!(peer_res.unwrap(), batch);
+        }
+
+        // Compression is not applied unless both parties offer it
+        let addr1: SocketAddr = "127.0.0.1:7802".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7803".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2)
+            .with_compression(64)
+            .unwrap();
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert_eq!(king.negotiated_compression_threshold, None);
+
+        let (send_res, recv_res) = tokio::join!(
+            king.send_scalars(&random),
+            peer.receive_scalars(random.len())
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), random);
+
+        // A threshold of zero would be advertised as not compressing
+        assert!(matches!(
+            QuicTwoPartyNet::new(0, addr1, addr2).with_compression(0),
+            Err(MpcNetworkError::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_compression_datagram_mode() {
+        let addr1: SocketAddr = "127.0.0.1:7804".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7805".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2)
+            .with_mode(QuicMode::Datagram)
+            .with_compression(64)
+            .unwrap();
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1)
+            .with_mode(QuicMode::Datagram)
+            .with_compression(64)
+            .unwrap();
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A frame too large for a datagram that compresses to fit in one, interleaved with
+        // frames sent as datagrams
+        let mut rng = OsRng {};
+        let zeros = vec![Scalar::zero(); 100];
+        let random = (0..2).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        assert!(zeros.len() * BYTES_PER_SCALAR > MAX_DATAGRAM_PAYLOAD);
+        for batch in [zeros.clone(), random, zeros] {
+            let (king_res, peer_res) = tokio::join!(
+                king.broadcast_scalars(&batch),
+                peer.broadcast_scalars(&batch)
+            );
+            assert_eq!(king_res.unwrap(), batch);
+            assert_eq!(peer_res.unwrap(), batch);
+        }
+
+        // A block length no shorter than the frame is rejected before the block is read
+        let header = king
+            .message_sequence
+            .next_sent(MessageKind::SendScalars, 100);
+        king.write_bytes(&header.to_bytes()).await.unwrap();
+        king.write_stream_bytes(&u32::MAX.to_le_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            peer.receive_scalars(100).await,
+            Err(MpcNetworkError::SerializationError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channels() {
+        let addr1: SocketAddr = "127.0.0.1:7900".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7901".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let (king_res, peer_res) = tokio::join!(king.open_channel(), peer.open_channel());
+        let (mut king_channel1, mut peer_channel1) = (king_res.unwrap(), peer_res.unwrap());
+        let (king_res, peer_res) = tokio::join!(king.open_channel(), peer.open_channel());
+        let (mut king_channel2, mut peer_channel2) = (king_res.unwrap(), peer_res.unwrap());
+
+        // Messages sent on each channel are received on the same channel, whatever the order
+        let (scalar1, scalar2, scalar3) =
+            (Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64));
+        king_channel2.send_single_scalar(scalar2).await.unwrap();
+        king_channel1.send_single_scalar(scalar1).await.unwrap();
+        king.send_single_scalar(scalar3).await.unwrap();
+
+        assert_eq!(
+            peer_channel1.receive_single_scalar().await.unwrap(),
+            scalar1
+        );
+        assert_eq!(peer.receive_single_scalar().await.unwrap(), scalar3);
+        assert_eq!(
+            peer_channel2.receive_single_scalar().await.unwrap(),
+            scalar2
+        );
+
+        // Broadcasts on separate channels may be interleaved
+        let (king_res1, peer_res1, king_res2, peer_res2) = tokio::join!(
+            king_channel1.broadcast_single_scalar(scalar1),
+            peer_channel1.broadcast_single_scalar(scalar2),
+            king_channel2.broadcast_single_scalar(scalar3),
+            peer_channel2.broadcast_single_scalar(scalar1),
+        );
+        assert_eq!(king_res1.unwrap(), scalar2);
+        assert_eq!(peer_res1.unwrap(), scalar1);
+        assert_eq!(king_res2.unwrap(), scalar1);
+        assert_eq!(peer_res2.unwrap(), scalar3);
+    }
+
+    #[test]
+    fn test_connection_lost_error() {
+        assert_eq!(
+            connection_lost_error(&ConnectionError::TimedOut, MpcNetworkError::RecvError),
+            MpcNetworkError::PeerUnreachable
+        );
+        assert_eq!(
+            connection_lost_error(&ConnectionError::LocallyClosed, MpcNetworkError::RecvError),
+            MpcNetworkError::RecvError
+        );
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown() {
+        let addr1: SocketAddr = "127.0.0.1:7970".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7971".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A value the peer never reads is drained by the handshake
+        king.send_single_scalar(Scalar::one()).await.unwrap();
+        let (king_res, peer_res) = tokio::join!(king.close(), peer.close());
+        king_res.unwrap();
+        peer_res.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_peer_closed() {
+        let addr1: SocketAddr = "127.0.0.1:7972".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7973".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A party awaiting values sees the shutdown rather than a failed connection, and
+        // completes the handshake in turn
+        let (king_res, peer_res) = tokio::join!(king.close(), async {
+            assert_eq!(
+                peer.receive_single_scalar().await,
+                Err(MpcNetworkError::PeerClosed)
+            );
+            peer.close().await
+        });
+        king_res.unwrap();
+        peer_res.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_close_drain_limit() {
+        let addr1: SocketAddr = "127.0.0.1:7996".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7997".parse().unwrap();
+        let mut king =
+            QuicTwoPartyNet::new(0, addr1, addr2).with_receive_timeout(Duration::from_millis(500));
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A peer that closes with more values unread than it drains gives up on the handshake
+        let num_scalars = 2 * MAX_CLOSE_DRAIN_BYTES / BYTES_PER_SCALAR;
+        let (_, peer_res) = tokio::join!(
+            async {
+                let _ = king.send_scalars(&vec![Scalar::one(); num_scalars]).await;
+                king.close().await
+            },
+            peer.close()
+        );
+        assert_eq!(peer_res, Err(MpcNetworkError::ConnectionTeardownError));
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive() {
+        let keep_alive = KeepAliveConfig {
+            interval: Duration::from_millis(20),
+            idle_timeout: Duration::from_millis(100),
+        };
+
+        let addr1: SocketAddr = "127.0.0.1:7950".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7951".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_keep_alive(keep_alive);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_keep_alive(keep_alive);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // The pings keep the connection open through a silence longer than the idle timeout
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_scalar(Scalar::one()),
+            peer.broadcast_single_scalar(Scalar::one())
+        );
+        assert_eq!(king_res.unwrap(), Scalar::one());
+        assert_eq!(peer_res.unwrap(), Scalar::one());
+    }
+
+    #[tokio::test]
+    async fn test_peer_unreachable() {
+        assert!(!KeepAliveConfig::default().idle_timeout.is_zero());
+        let keep_alive = KeepAliveConfig {
+            interval: Duration::from_millis(20),
+            idle_timeout: Duration::from_millis(200),
+        };
+
+        // The peer runs on a runtime of its own, which stops driving its connection once the
+        // peer's thread blocks; the peer then goes silent without closing the connection
+        let addr1: SocketAddr = "127.0.0.1:7952".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7953".parse().unwrap();
+        let (connected_send, connected_recv) = tokio::sync::oneshot::channel();
+        let peer_thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_keep_alive(keep_alive);
+            runtime.block_on(async {
+                peer.connect().await.unwrap();
+                // Drive the connection until the king has also connected
+                connected_recv.await.unwrap();
+            });
+
+            std::thread::sleep(Duration::from_secs(1));
+            peer
+        });
+
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_keep_alive(keep_alive);
+        king.connect().await.unwrap();
+        connected_send.send(()).unwrap();
+
+        assert_eq!(
+            king.receive_single_scalar().await,
+            Err(MpcNetworkError::PeerUnreachable)
+        );
+        peer_thread.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_resumption() {
+        let king_cache = SessionCache::new();
+        let peer_cache = SessionCache::new();
+
+        // The first connection runs the full handshake and caches the session
+        let addr1: SocketAddr = "127.0.0.1:7980".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7981".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_session_cache(king_cache.clone());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_session_cache(peer_cache.clone());
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert!(!king.zero_rtt_accepted());
+
+        let (king_res, peer_res) = tokio::join!(king.close(), peer.close());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A later computation restores the persisted sessions and resumes in 0-RTT
+        let king_cache = SessionCache::import(&king_cache.export()).unwrap();
+        let peer_cache = SessionCache::import(&peer_cache.export()).unwrap();
+        assert!(!king_cache.is_empty() && !peer_cache.is_empty());
+
+        let addr1: SocketAddr = "127.0.0.1:7982".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7983".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_session_cache(king_cache);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_session_cache(peer_cache);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert!(king.zero_rtt_accepted());
+
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_scalar(Scalar::one()),
+            peer.broadcast_single_scalar(Scalar::zero())
+        );
+        assert_eq!(king_res.unwrap(), Scalar::zero());
+        assert_eq!(peer_res.unwrap(), Scalar::one());
+    }
+
+    #[test]
+    fn test_hello() {
+        let hello = encode_hello(&[1, 2, 3]);
+        assert_eq!(
+            decode_hello_header(&hello[..HELLO_HEADER_BYTES]),
+            Ok((PROTOCOL_VERSION, 3))
+        );
+        assert_eq!(hello[HELLO_HEADER_BYTES..], [1, 2, 3]);
+
+        // A peer that does not open with the magic is not speaking the protocol at all
+        assert_eq!(
+            decode_hello_header(&[0u8; HELLO_HEADER_BYTES]),
+            Err(MpcNetworkError::ConnectionSetupError(
+                SetupError::HandshakeError
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_identity_handshake() {
+        let mut rng = OsRng {};
+        let king_identity = IdentityKeypair::generate(&mut rng);
+        let peer_identity = IdentityKeypair::generate(&mut rng);
+
+        let addr1: SocketAddr = "127.0.0.1:7984".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7985".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_identity(king_identity.clone());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1).with_identity(peer_identity.clone());
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert_eq!(king.peer_identity(), Some(peer_identity.public()));
+        assert_eq!(peer.peer_identity(), Some(king_identity.public()));
+
+        // A party without an identity still learns the identity of a peer with one
+        let addr1: SocketAddr = "127.0.0.1:7986".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7987".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2).with_identity(king_identity.clone());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert_eq!(king.peer_identity(), None);
+        assert_eq!(peer.peer_identity(), Some(king_identity.public()));
+
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_scalar(Scalar::one()),
+            peer.broadcast_single_scalar(Scalar::zero())
+        );
+        assert_eq!(king_res.unwrap(), Scalar::zero());
+        assert_eq!(peer_res.unwrap(), Scalar::one());
+
+        // A peer that does not sign fails to connect to a party that pins its identity
+        let addr1: SocketAddr = "127.0.0.1:7994".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7995".parse().unwrap();
+        let mut king =
+            QuicTwoPartyNet::new(0, addr1, addr2).with_pinned_identity(peer_identity.public());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, _) = tokio::join!(king.connect(), peer.connect());
+        assert_eq!(king_res, Err(MpcNetworkError::AuthenticationError));
+    }
+
+    #[tokio::test]
+    async fn test_identity_reconnect() {
+        let mut rng = OsRng {};
+        let peer_identity = IdentityKeypair::generate(&mut rng);
+
+        let addr1: SocketAddr = "127.0.0.1:7992".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7993".parse().unwrap();
+        let mut king =
+            QuicTwoPartyNet::new(0, addr1, addr2).with_retry_policy(RetryPolicy::default());
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1)
+            .with_retry_policy(RetryPolicy::default())
+            .with_identity(peer_identity.clone());
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert_eq!(king.peer_identity(), Some(peer_identity.public()));
+
+        // The peer stops signing and the connection drops; the king must not keep trusting the
+        // identity proven on the first connection
+        peer.identity = None;
+        king.connection
+            .as_ref()
+            .unwrap()
+            .close(VarInt::from_u32(0), b"blip");
+
+        let (king_res, _) = tokio::join!(
+            king.broadcast_single_scalar(Scalar::one()),
+            async_std::future::timeout(
+                Duration::from_secs(2),
+                peer.broadcast_single_scalar(Scalar::zero())
+            )
+        );
+        assert_eq!(king_res, Err(MpcNetworkError::AuthenticationError));
+    }
+
+    #[tokio::test]
+    async fn test_protocol_desync() {
+        let addr1: SocketAddr = "127.0.0.1:7988".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7989".parse().unwrap();
+        let mut king = QuicTwoPartyNet::new(0, addr1, addr2);
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        king.send_single_scalar(Scalar::one()).await.unwrap();
+        assert_eq!(peer.receive_single_scalar().await.unwrap(), Scalar::one());
+
+        // The peer expects a point where the king sent scalars
+        king.send_scalars(&[Scalar::one(); 2]).await.unwrap();
+        assert_eq!(
+            peer.receive_single_point().await,
+            Err(MpcNetworkError::ProtocolDesync(
+                MessageHeader {
+                    sequence: 1,
+                    kind: MessageKind::SendPoints,
+                    num_values: 1,
+                },
+                MessageHeader {
+                    sequence: 1,
+                    kind: MessageKind::SendScalars,
+                    num_values: 2,
+                },
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let addr1: SocketAddr = "127.0.0.1:7990".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7991".parse().unwrap();
+        let limit = RateLimit {
+            bytes_per_second: 1 << 16,
+            burst_bytes: 1 << 14,
+        };
+        let mut king =
+            InstrumentedNetwork::new(QuicTwoPartyNet::new(0, addr1, addr2).with_rate_limit(limit));
+        let mut peer = InstrumentedNetwork::new(QuicTwoPartyNet::new(1, addr2, addr1));
+
+        let (king_res, peer_res) =
+            tokio::join!(king.inner_mut().connect(), peer.inner_mut().connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert!(peer.stats().rate_limit.is_none());
+
+        // Three times the burst is sent at the limited rate, taking half a second after the burst
+        let mut rng = OsRng {};
+        let scalars = (0..3 * (1 << 14) / BYTES_PER_SCALAR)
+            .map(|_| Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        let (king_res, peer_res) = tokio::join!(
+            king.send_scalars(&scalars),
+            peer.receive_scalars(scalars.len())
+        );
+        king_res.unwrap();
+        assert_eq!(peer_res.unwrap(), scalars);
+        assert!(start.elapsed() >= Duration::from_millis(450));
+
+        let stats = king.stats().rate_limit.unwrap();
+        assert!(stats.bytes_sent >= 3 << 14);
+        assert!(stats.throttled >= Duration::from_millis(450));
+        assert!(stats.utilization > 0.5);
+
+        // The usage is counted from the last reset, as are the other stats
+        king.reset_stats();
+        let stats = king.stats().rate_limit.unwrap();
+        assert_eq!(stats.bytes_sent, 0);
+        assert_eq!(stats.throttled, Duration::ZERO);
+    }
 }
@@ -0,0 +1,228 @@
+//! Implements a network wrapper that holds sends locally until they are flushed, so that the
+//! caller controls the boundaries of the messages sent to the peer
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::error::MpcNetworkError;
+
+use super::{MpcNetwork, PeerIdentity, RateLimitStats};
+
+/// A send held until the next flush, sent to the peer as its own message
+#[derive(Clone, Debug)]
+enum PendingSend {
+    Scalars(Vec<Scalar>),
+    Points(Vec<RistrettoPoint>),
+}
+
+/// Wraps a network and holds the values sent through it until an explicit `flush`, or until
+/// the local party must hear from the peer
+///
+/// Each send remains a message of its own, so the peer receives values exactly as they were
+/// sent and networks that tag messages, such as `QuicTwoPartyNet`, check every message as
+/// usual. To write the held messages to the transport at once, wrap a network that buffers
+/// bytes until flushed, e.g. a `QuicTwoPartyNet` built `with_coalesced_writes`
+#[derive(Debug)]
+pub struct BufferedNetwork<N: MpcNetwork + Send> {
+    /// The underlying network
+    inner: N,
+    /// The sends held since the last flush, in the order they were made
+    pending: Vec<PendingSend>,
+}
+
+impl<N: MpcNetwork + Send> BufferedNetwork<N> {
+    pub fn new(inner: N) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the number of values held since the last flush
+    pub fn num_pending(&self) -> usize {
+        self.pending
+            .iter()
+            .map(|send| match send {
+                PendingSend::Scalars(scalars) => scalars.len(),
+                PendingSend::Points(points) => points.len(),
+            })
+            .sum()
+    }
+
+    /// Returns a reference to the underlying network
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying network
+    pub fn inner_mut(&mut self) -> &mut N {
+        &mut self.inner
+    }
+
+    /// Sends the held values to the peer, one message per send
+    async fn send_pending(&mut self) -> Result<(), MpcNetworkError> {
+        for send in std::mem::take(&mut self.pending) {
+            match send {
+                PendingSend::Scalars(scalars) => self.inner.send_scalars(&scalars).await?,
+                PendingSend::Points(points) => self.inner.send_points(&points).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> MpcNetwork for BufferedNetwork<N> {
+    fn party_id(&self) -> u64 {
+        self.inner.party_id()
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        self.pending.push(PendingSend::Scalars(scalars.to_vec()));
+        Ok(())
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        // The peer may await the held values before sending what the local party receives
+        self.flush().await?;
+        self.inner.receive_scalars(num_expected).await
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        self.flush().await?;
+        self.inner.broadcast_scalars(scalars).await
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        self.pending.push(PendingSend::Points(points.to_vec()));
+        Ok(())
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        self.flush().await?;
+        self.inner.receive_points(num_expected).await
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        self.flush().await?;
+        self.inner.broadcast_points(points).await
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.flush().await?;
+        self.inner.close().await
+    }
+
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
+        self.send_pending().await?;
+        self.inner.flush().await
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        self.inner.record_opening(opened_bytes)
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.inner.output_digest()
+    }
+
+    fn set_receive_timeout(&mut self, timeout: Option<Duration>) {
+        self.inner.set_receive_timeout(timeout)
+    }
+
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        self.inner.peer_identity()
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        self.inner.rate_limit_stats()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+    use futures::executor::block_on;
+
+    use crate::network::{
+        dummy_network::DummyMpcNetwork, instrumented_network::InstrumentedNetwork, MpcNetwork,
+        QuicTwoPartyNet,
+    };
+
+    use super::BufferedNetwork;
+
+    #[test]
+    fn test_buffered_sends() {
+        let mut network = BufferedNetwork::new(InstrumentedNetwork::new(DummyMpcNetwork::new()));
+
+        // Single sends are held, then flushed as one message each
+        for _ in 0..3 {
+            block_on(network.send_single_scalar(Scalar::one())).unwrap();
+        }
+        assert_eq!(network.num_pending(), 3);
+        assert_eq!(network.inner().stats().messages_sent, 0);
+
+        block_on(network.flush()).unwrap();
+        assert_eq!(network.num_pending(), 0);
+        assert_eq!(network.inner().stats().messages_sent, 3);
+        assert_eq!(network.inner().stats().bytes_sent, 96);
+
+        // Sends of either type keep their boundaries, and a receive flushes the held sends
+        network.inner_mut().reset_stats();
+        block_on(network.send_single_scalar(Scalar::one())).unwrap();
+        block_on(network.send_single_point(RISTRETTO_BASEPOINT_POINT)).unwrap();
+        block_on(network.send_single_point(RISTRETTO_BASEPOINT_POINT)).unwrap();
+        block_on(network.send_single_scalar(Scalar::one())).unwrap();
+        block_on(network.receive_scalars(0)).unwrap();
+
+        assert_eq!(network.num_pending(), 0);
+        assert_eq!(network.inner().stats().messages_sent, 4);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_tagged_messages() {
+        let addr1: SocketAddr = "127.0.0.1:7810".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7811".parse().unwrap();
+        let mut king = BufferedNetwork::new(QuicTwoPartyNet::new(0, addr1, addr2));
+        let mut peer = QuicTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.inner_mut().connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // The peer receives the held sends one at a time, as they were sent
+        let scalars = (1..4u64).map(Scalar::from).collect::<Vec<_>>();
+        for scalar in scalars.iter() {
+            king.send_single_scalar(*scalar).await.unwrap();
+        }
+        king.send_single_point(RISTRETTO_BASEPOINT_POINT)
+            .await
+            .unwrap();
+        king.flush().await.unwrap();
+
+        for scalar in scalars.iter() {
+            assert_eq!(peer.receive_single_scalar().await.unwrap(), *scalar);
+        }
+        assert_eq!(
+            peer.receive_single_point().await.unwrap(),
+            RISTRETTO_BASEPOINT_POINT
+        );
+    }
+}
@@ -1,8 +1,13 @@
-//! Implements a dummy certificate verifier that simply passes through the request
-//! We do not care about the authenticity of certificates during the course of a
-//! p2p MPC
+//! Implements dummy certificate verifiers that simply pass through the request
+//! Certificates are not validated against a root of trust during the course of a
+//! p2p MPC; when the peer is authenticated its certificate is pinned instead, and
+//! checked once the connection is established
 
-use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    server::{ClientCertVerified, ClientCertVerifier},
+    DistinguishedNames,
+};
 
 /// Responds Ok() to any certificate verification request
 pub(crate) struct PassThroughCertVerifier;
@@ -26,3 +31,27 @@ impl ServerCertVerifier for PassThroughCertVerifier {
         Ok(ServerCertVerified::assertion())
     }
 }
+
+/// Requires the client to present a certificate, and responds Ok() to any such certificate
+pub(crate) struct PassThroughClientCertVerifier;
+
+impl PassThroughClientCertVerifier {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ClientCertVerifier for PassThroughClientCertVerifier {
+    fn client_auth_root_subjects(&self) -> Option<DistinguishedNames> {
+        Some(DistinguishedNames::new())
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _now: std::time::SystemTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        Ok(ClientCertVerified::assertion())
+    }
+}
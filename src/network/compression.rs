@@ -0,0 +1,42 @@
+//! Groups the helpers used to compress large batch messages on the wire
+//!
+//! A compressed frame is the length of the compressed block as a little endian u32 followed by
+//! the LZ4 block. A length of zero indicates that the block did not compress and the payload
+//! follows uncompressed. The receiver knows the uncompressed length of every frame it reads, so
+//! no other framing is needed
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::error::MpcNetworkError;
+
+/// The number of bytes in the length prefix of a compressed frame
+pub(crate) const LENGTH_PREFIX_BYTES: usize = 4;
+
+/// Compress a payload into a length prefixed frame, falling back to the uncompressed payload
+/// if compression does not shrink it
+pub(crate) fn compress(payload: &[u8]) -> Bytes {
+    let compressed = lz4_flex::block::compress(payload);
+
+    let mut frame = BytesMut::with_capacity(LENGTH_PREFIX_BYTES + payload.len());
+    if compressed.len() < payload.len() {
+        frame.put_u32_le(compressed.len() as u32);
+        frame.extend_from_slice(&compressed);
+    } else {
+        frame.put_u32_le(0);
+        frame.extend_from_slice(payload);
+    }
+
+    frame.freeze()
+}
+
+/// Decompress a block into a payload of the given length
+pub(crate) fn decompress(block: &[u8], payload_length: usize) -> Result<Vec<u8>, MpcNetworkError> {
+    let payload = lz4_flex::block::decompress(block, payload_length)
+        .map_err(|_| MpcNetworkError::SerializationError)?;
+
+    if payload.len() != payload_length {
+        return Err(MpcNetworkError::SerializationError);
+    }
+
+    Ok(payload)
+}
@@ -7,48 +7,145 @@ use rcgen::RcgenError;
 use rustls::{Certificate, ClientConfig as CryptoClientConfig};
 
 use crate::error::SetupError;
-use crate::network::cert_verifier::PassThroughCertVerifier;
+use crate::network::cert_verifier::{PassThroughCertVerifier, PassThroughClientCertVerifier};
+use crate::network::session_cache::SessionCache;
 
-#[cfg(not(test))]
 const MAX_IDLE_TIMEOUT: u32 = 10_000; // milliseconds
-#[cfg(test)]
-const MAX_IDLE_TIMEOUT: u32 = 0; // No timeout
 const KEEP_ALIVE_INTERVAL: u64 = 3_000; // milliseconds
 pub(crate) const SERVER_NAME: &str = "otter.cash"; // dummy value
 
+/// The keepalive behaviour of the connection
+///
+/// Keepalive pings keep NAT bindings open through long silences in the computation, e.g. while
+/// preprocessing; if the peer stops acknowledging them for the idle timeout the connection is
+/// dropped and operations fail with `MpcNetworkError::PeerUnreachable`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeepAliveConfig {
+    /// The interval at which pings are sent while the connection is otherwise silent
+    pub interval: Duration,
+    /// The silence after which the peer is considered unreachable, zero to never time out
+    pub idle_timeout: Duration,
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(KEEP_ALIVE_INTERVAL),
+            idle_timeout: Duration::from_millis(MAX_IDLE_TIMEOUT as u64),
+        }
+    }
+}
+
+/// The identities used to authenticate the parties to one another when connecting
+///
+/// Each party presents its own certificate, both as server and as client, and requires the
+/// peer to present the pinned certificate; the certificates are exchanged out of band, e.g.
+/// generated with `PeerAuthConfig::generate_identity`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerAuthConfig {
+    /// The DER encoded certificate presented by the local party
+    pub local_cert: Vec<u8>,
+    /// The DER encoded private key of the local certificate
+    pub local_key: Vec<u8>,
+    /// The DER encoded certificate that the peer must present
+    pub peer_cert: Vec<u8>,
+}
+
+impl PeerAuthConfig {
+    /// Generates a self-signed identity, returned as the DER encoded certificate and key
+    pub fn generate_identity() -> Result<(Vec<u8>, Vec<u8>), SetupError> {
+        let (cert, key) = generate_cert().map_err(|_| SetupError::KeygenError)?;
+        Ok((cert.0, key.0))
+    }
+}
+
 /// Builds the configs for quinn p2p communication
-pub fn build_configs() -> Result<(ClientConfig, ServerConfig), SetupError> {
+///
+/// Without an authentication config each party generates a fresh certificate and the peer is
+/// not authenticated; with one, the local identity is used and the peer must present a client
+/// certificate. With a session cache, sessions are stored in and resumed from the cache, and
+/// the client sends early data on resumption
+pub fn build_configs(
+    auth_config: Option<&PeerAuthConfig>,
+    keep_alive: &KeepAliveConfig,
+    session_cache: Option<&SessionCache>,
+) -> Result<(ClientConfig, ServerConfig), SetupError> {
     // 1. Transport config
     let mut transport_config = TransportConfig::default();
-    transport_config.max_idle_timeout(Some(IdleTimeout::from(VarInt::from_u32(MAX_IDLE_TIMEOUT))));
-
-    transport_config.keep_alive_interval(Some(Duration::from_millis(KEEP_ALIVE_INTERVAL)));
+    // A zero timeout disables the idle timeout altogether
+    let idle_timeout = if keep_alive.idle_timeout.is_zero() {
+        None
+    } else {
+        Some(IdleTimeout::from(
+            VarInt::from_u64(keep_alive.idle_timeout.as_millis() as u64)
+                .map_err(|_| SetupError::ServerSetupError)?,
+        ))
+    };
+    transport_config.max_idle_timeout(idle_timeout);
+
+    transport_config.keep_alive_interval(Some(keep_alive.interval));
 
     let transport: Arc<TransportConfig> = Arc::new(transport_config);
 
     // 2. Cryptography setup
-    // Generate a self-signed server certificate for the QUIC connection
-    let (cert, key) = generate_cert().map_err(|_| SetupError::KeygenError)?;
+    // Use the local identity if authenticating, otherwise generate a self-signed server certificate
+    let (cert, key) = match auth_config {
+        Some(auth_config) => (
+            Certificate(auth_config.local_cert.clone()),
+            rustls::PrivateKey(auth_config.local_key.clone()),
+        ),
+        None => generate_cert().map_err(|_| SetupError::KeygenError)?,
+    };
 
     // Setup the certificate root
     let mut roots = rustls::RootCertStore::empty();
     roots.add(&cert).map_err(|_| SetupError::ServerSetupError)?;
 
-    // Pass the self-signed cert to the client, and disable auth; p2p auth should happen at a higher layer
-    let mut client_crypto_config = CryptoClientConfig::builder()
+    // Pass the self-signed cert to the client; the peer certificate is checked against the pinned
+    // certificate once connected, if at all
+    let client_builder = CryptoClientConfig::builder()
         .with_safe_defaults()
-        .with_root_certificates(roots)
-        .with_no_client_auth();
+        .with_root_certificates(roots);
+    let mut client_crypto_config = match auth_config {
+        Some(_) => client_builder
+            .with_single_cert(vec![cert.clone()], key.clone())
+            .map_err(|_| SetupError::ServerSetupError)?,
+        None => client_builder.with_no_client_auth(),
+    };
     client_crypto_config
         .dangerous()
         .set_certificate_verifier(Arc::new(PassThroughCertVerifier::new()));
+    if let Some(session_cache) = session_cache {
+        client_crypto_config.session_storage = session_cache.client_sessions.clone();
+        client_crypto_config.enable_early_data = true;
+    }
 
     // 3. Client and server setup
     let mut client_config = ClientConfig::new(Arc::new(client_crypto_config));
     client_config.transport_config(transport.clone());
 
-    let mut server_config = ServerConfig::with_single_cert(vec![cert], key)
+    // Mirrors the crypto config built by `ServerConfig::with_single_cert`, with client
+    // certificates required when authenticating
+    let server_builder = rustls::ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[&rustls::version::TLS13])
         .map_err(|_| SetupError::ServerSetupError)?;
+    let server_builder = match auth_config {
+        Some(_) => {
+            server_builder.with_client_cert_verifier(Arc::new(PassThroughClientCertVerifier::new()))
+        }
+        None => server_builder.with_no_client_auth(),
+    };
+    let mut server_crypto_config = server_builder
+        .with_single_cert(vec![cert], key)
+        .map_err(|_| SetupError::ServerSetupError)?;
+    server_crypto_config.max_early_data_size = u32::MAX;
+    if let Some(session_cache) = session_cache {
+        server_crypto_config.session_storage = session_cache.server_sessions.clone();
+    }
+
+    let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto_config));
     server_config.transport = transport;
 
     Ok((client_config, server_config))
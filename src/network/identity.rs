@@ -0,0 +1,225 @@
+//! Long-term identities of the parties
+//!
+//! Each party holds a Schnorr signing key over the Ristretto group, and signs the transcript of
+//! the connection handshake so that its peer learns a verified identity for the session
+
+use std::convert::TryInto;
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use sha3::{Digest, Sha3_512};
+use zeroize::Zeroize;
+
+use crate::error::MpcNetworkError;
+
+/// The number of bytes in an encoded identity
+pub const IDENTITY_BYTES: usize = 32;
+/// The number of bytes in an encoded signature
+pub const SIGNATURE_BYTES: usize = 64;
+
+/// Separates the challenge hash of the signature scheme from any other use of the hash function
+const CHALLENGE_DOMAIN: &[u8] = b"mpc-ristretto identity challenge";
+/// Separates the nonce derivation of the signature scheme from any other use of the hash function
+const NONCE_DOMAIN: &[u8] = b"mpc-ristretto identity nonce";
+
+/// The public identity of a party; the public half of its identity keypair
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PeerIdentity(CompressedRistretto);
+
+impl PeerIdentity {
+    /// Returns the encoded identity
+    pub fn to_bytes(&self) -> [u8; IDENTITY_BYTES] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes an identity, failing if the bytes do not encode a valid group element
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MpcNetworkError> {
+        if bytes.len() != IDENTITY_BYTES {
+            return Err(MpcNetworkError::SerializationError);
+        }
+
+        let point = CompressedRistretto::from_slice(bytes);
+        point
+            .decompress()
+            .ok_or(MpcNetworkError::SerializationError)?;
+        Ok(Self(point))
+    }
+
+    /// Verifies a signature by the party over the message
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        let public_key = match self.0.decompress() {
+            Some(public_key) => public_key,
+            None => return false,
+        };
+        let nonce_commitment = match signature.nonce_commitment.decompress() {
+            Some(nonce_commitment) => nonce_commitment,
+            None => return false,
+        };
+
+        // s * G == R + c * X
+        let challenge = challenge(&signature.nonce_commitment, &self.0, message);
+        RistrettoPoint::vartime_double_scalar_mul_basepoint(
+            &challenge,
+            &-public_key,
+            &signature.response,
+        ) == nonce_commitment
+    }
+}
+
+/// The long-term signing key of a party, and its public identity
+#[derive(Clone)]
+pub struct IdentityKeypair {
+    /// The secret signing key
+    secret: Scalar,
+    /// The identity corresponding to the secret key
+    public: PeerIdentity,
+}
+
+impl std::fmt::Debug for IdentityKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Elide the secret key
+        f.debug_struct("IdentityKeypair")
+            .field("public", &self.public)
+            .finish()
+    }
+}
+
+impl Drop for IdentityKeypair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl IdentityKeypair {
+    /// Generates a fresh keypair
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::from_secret(Scalar::random(rng))
+    }
+
+    /// Restores a keypair from the secret key encoded by `IdentityKeypair::secret_bytes`
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Result<Self, MpcNetworkError> {
+        Scalar::from_canonical_bytes(bytes)
+            .map(Self::from_secret)
+            .ok_or(MpcNetworkError::SerializationError)
+    }
+
+    /// Returns the encoded secret key, e.g. to persist the keypair
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.secret.to_bytes()
+    }
+
+    /// Returns the public identity of the keypair
+    pub fn public(&self) -> PeerIdentity {
+        self.public
+    }
+
+    /// Signs the message
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        // Hedge the nonce with the secret key and the message, so that a weak source of
+        // randomness does not reveal the key
+        let mut rng = OsRng {};
+        let mut randomness = [0u8; 32];
+        rng.fill_bytes(&mut randomness);
+
+        let mut hasher = Sha3_512::new();
+        hasher.input(NONCE_DOMAIN);
+        hasher.input(self.secret.as_bytes());
+        hasher.input(randomness);
+        hasher.input(message);
+        let mut nonce = Scalar::from_hash(hasher);
+
+        let nonce_commitment = (nonce * RISTRETTO_BASEPOINT_POINT).compress();
+        let response = nonce + challenge(&nonce_commitment, &self.public.0, message) * self.secret;
+        nonce.zeroize();
+
+        Signature {
+            nonce_commitment,
+            response,
+        }
+    }
+
+    fn from_secret(secret: Scalar) -> Self {
+        let public = PeerIdentity((secret * RISTRETTO_BASEPOINT_POINT).compress());
+        Self { secret, public }
+    }
+}
+
+/// A Schnorr signature by an identity keypair
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    /// The commitment to the nonce, R = r * G
+    nonce_commitment: CompressedRistretto,
+    /// The response to the challenge, s = r + c * x
+    response: Scalar,
+}
+
+impl Signature {
+    /// Returns the encoded signature
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_BYTES] {
+        let mut bytes = [0u8; SIGNATURE_BYTES];
+        bytes[..32].copy_from_slice(self.nonce_commitment.as_bytes());
+        bytes[32..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    /// Decodes a signature, failing if the response is not a canonical scalar
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MpcNetworkError> {
+        if bytes.len() != SIGNATURE_BYTES {
+            return Err(MpcNetworkError::SerializationError);
+        }
+
+        let response = Scalar::from_canonical_bytes(bytes[32..].try_into().unwrap())
+            .ok_or(MpcNetworkError::SerializationError)?;
+        Ok(Self {
+            nonce_commitment: CompressedRistretto::from_slice(&bytes[..32]),
+            response,
+        })
+    }
+}
+
+/// Computes the challenge of a signature, c = H(R || X || m)
+fn challenge(
+    nonce_commitment: &CompressedRistretto,
+    public_key: &CompressedRistretto,
+    message: &[u8],
+) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.input(CHALLENGE_DOMAIN);
+    hasher.input(nonce_commitment.as_bytes());
+    hasher.input(public_key.as_bytes());
+    hasher.input(message);
+    Scalar::from_hash(hasher)
+}
+
+#[cfg(test)]
+mod test {
+    use rand_core::OsRng;
+
+    use super::{IdentityKeypair, PeerIdentity, Signature};
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = OsRng {};
+        let keypair = IdentityKeypair::generate(&mut rng);
+        let identity = keypair.public();
+
+        let signature = keypair.sign(b"transcript");
+        assert!(identity.verify(b"transcript", &signature));
+        assert!(!identity.verify(b"other transcript", &signature));
+        assert!(!IdentityKeypair::generate(&mut rng)
+            .public()
+            .verify(b"transcript", &signature));
+
+        // Identities, signatures, and keys survive encoding
+        let decoded = Signature::from_bytes(&signature.to_bytes()).unwrap();
+        assert!(PeerIdentity::from_bytes(&identity.to_bytes())
+            .unwrap()
+            .verify(b"transcript", &decoded));
+        let restored = IdentityKeypair::from_secret_bytes(keypair.secret_bytes()).unwrap();
+        assert_eq!(restored.public(), identity);
+    }
+}
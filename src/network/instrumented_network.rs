@@ -0,0 +1,246 @@
+//! Implements a network wrapper that counts the communication of the MPC, for benchmarking
+//! the cost of a protocol
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::error::MpcNetworkError;
+
+use super::{MpcNetwork, PeerIdentity, RateLimitStats, BYTES_PER_POINT, BYTES_PER_SCALAR};
+
+/// The communication counted by an `InstrumentedNetwork`
+///
+/// A message is a single send or receive of any number of values, a broadcast both sends and
+/// receives a message. A round is counted each time the local party waits on the peer after
+/// sending; i.e. on every broadcast, and on the first of any consecutive receives
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkStats {
+    /// The number of bytes sent to the peer
+    pub bytes_sent: u64,
+    /// The number of bytes received from the peer
+    pub bytes_received: u64,
+    /// The number of messages sent to the peer
+    pub messages_sent: u64,
+    /// The number of messages received from the peer
+    pub messages_received: u64,
+    /// The number of logical rounds of communication
+    pub rounds: u64,
+    /// The usage of the underlying network's rate limit, or `None` if it is not rate limited;
+    /// the bytes sent and time throttled are counted from the same point as the other fields
+    pub rate_limit: Option<RateLimitStats>,
+}
+
+/// Wraps a network and counts the bytes, messages, and rounds of communication through it
+#[derive(Debug)]
+pub struct InstrumentedNetwork<N: MpcNetwork + Send> {
+    /// The underlying network
+    inner: N,
+    /// The communication counted since construction or the last reset
+    stats: NetworkStats,
+    /// Whether the last operation received from the peer, so that a run of receives is one round
+    last_was_receive: bool,
+    /// The usage of the rate limit at the last reset, which the reported usage is counted from
+    rate_limit_baseline: Option<RateLimitStats>,
+}
+
+impl<N: MpcNetwork + Send> InstrumentedNetwork<N> {
+    pub fn new(inner: N) -> Self {
+        Self {
+            inner,
+            stats: NetworkStats::default(),
+            last_was_receive: false,
+            rate_limit_baseline: None,
+        }
+    }
+
+    /// Returns the communication counted since construction or the last reset
+    pub fn stats(&self) -> NetworkStats {
+        let rate_limit =
+            self.inner
+                .rate_limit_stats()
+                .map(|usage| match self.rate_limit_baseline {
+                    Some(baseline) => RateLimitStats {
+                        bytes_sent: usage.bytes_sent - baseline.bytes_sent,
+                        throttled: usage.throttled - baseline.throttled,
+                        utilization: usage.utilization,
+                    },
+                    None => usage,
+                });
+
+        NetworkStats {
+            rate_limit,
+            ..self.stats
+        }
+    }
+
+    /// Resets the counters, e.g. between the phases of a protocol
+    pub fn reset_stats(&mut self) {
+        self.stats = NetworkStats::default();
+        self.last_was_receive = false;
+        self.rate_limit_baseline = self.inner.rate_limit_stats();
+    }
+
+    /// Returns a reference to the underlying network
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying network
+    pub fn inner_mut(&mut self) -> &mut N {
+        &mut self.inner
+    }
+
+    /// Counts a message of the given length sent to the peer
+    fn count_send(&mut self, num_bytes: usize) {
+        self.stats.bytes_sent += num_bytes as u64;
+        self.stats.messages_sent += 1;
+        self.last_was_receive = false;
+    }
+
+    /// Counts a message of the given length received from the peer
+    fn count_receive(&mut self, num_bytes: usize) {
+        self.stats.bytes_received += num_bytes as u64;
+        self.stats.messages_received += 1;
+        if !self.last_was_receive {
+            self.stats.rounds += 1;
+        }
+        self.last_was_receive = true;
+    }
+
+    /// Counts a broadcast of the given length, each broadcast is a round
+    fn count_broadcast(&mut self, num_bytes: usize) {
+        self.count_send(num_bytes);
+        self.count_receive(num_bytes);
+        self.last_was_receive = false;
+    }
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> MpcNetwork for InstrumentedNetwork<N> {
+    fn party_id(&self) -> u64 {
+        self.inner.party_id()
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        self.inner.send_scalars(scalars).await?;
+        self.count_send(scalars.len() * BYTES_PER_SCALAR);
+        Ok(())
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let scalars = self.inner.receive_scalars(num_expected).await?;
+        self.count_receive(num_expected * BYTES_PER_SCALAR);
+        Ok(scalars)
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let received = self.inner.broadcast_scalars(scalars).await?;
+        self.count_broadcast(scalars.len() * BYTES_PER_SCALAR);
+        Ok(received)
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        self.inner.send_points(points).await?;
+        self.count_send(points.len() * BYTES_PER_POINT);
+        Ok(())
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let points = self.inner.receive_points(num_expected).await?;
+        self.count_receive(num_expected * BYTES_PER_POINT);
+        Ok(points)
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let received = self.inner.broadcast_points(points).await?;
+        self.count_broadcast(points.len() * BYTES_PER_POINT);
+        Ok(received)
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.inner.close().await
+    }
+
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
+        self.inner.flush().await
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        self.inner.record_opening(opened_bytes)
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.inner.output_digest()
+    }
+
+    fn set_receive_timeout(&mut self, timeout: Option<Duration>) {
+        self.inner.set_receive_timeout(timeout)
+    }
+
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        self.inner.peer_identity()
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        self.inner.rate_limit_stats()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::{
+        beaver::DummySharedScalarSource, mpc_scalar::MpcScalar,
+        network::dummy_network::DummyMpcNetwork,
+    };
+
+    use super::{InstrumentedNetwork, NetworkStats};
+
+    #[test]
+    fn test_stats() {
+        let mut mock_network = DummyMpcNetwork::new();
+        mock_network.add_mock_scalars(vec![Scalar::zero(); 3]);
+
+        let network = Rc::new(RefCell::new(InstrumentedNetwork::new(mock_network)));
+        let beaver_source = Rc::new(RefCell::new(DummySharedScalarSource::new()));
+
+        // A share from the king is a single send, a share from the peer a single receive
+        let value = MpcScalar::from_private_u64(2, network.clone(), beaver_source.clone());
+        let lhs = value.share_secret(0 /* party_id */).unwrap();
+        let rhs = MpcScalar::receive_value(network.clone(), beaver_source).unwrap();
+
+        assert_eq!(
+            network.borrow().stats(),
+            NetworkStats {
+                bytes_sent: 32,
+                bytes_received: 32,
+                messages_sent: 1,
+                messages_received: 1,
+                rounds: 1,
+                rate_limit: None,
+            }
+        );
+
+        // An opening is a single broadcast round
+        network.borrow_mut().reset_stats();
+        MpcScalar::batch_open(&[lhs, rhs]).unwrap();
+        assert_eq!(network.borrow().stats().rounds, 1);
+    }
+}
@@ -0,0 +1,358 @@
+//! Implements an in-memory two party network, allowing both parties of an MPC to run in a
+//! single process; e.g. on two threads of a unit test
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    StreamExt,
+};
+
+use crate::error::MpcNetworkError;
+
+use super::{multi_party::MeshNetwork, MpcNetwork, PartyId};
+
+/// A two party network backed by in-memory channels
+///
+/// Each party holds one end of a channel for scalars and one for points in each direction. The
+/// channels are unbounded, so a party never blocks on a send, and a broadcast is a send followed
+/// by a receive.
+#[derive(Debug)]
+pub struct LocalTwoPartyNet {
+    /// The index of the local party in the participants
+    party_id: PartyId,
+    /// The channel on which scalars are sent to the peer
+    scalar_sender: UnboundedSender<Scalar>,
+    /// The channel on which scalars are received from the peer
+    scalar_receiver: UnboundedReceiver<Scalar>,
+    /// The channel on which points are sent to the peer
+    point_sender: UnboundedSender<RistrettoPoint>,
+    /// The channel on which points are received from the peer
+    point_receiver: UnboundedReceiver<RistrettoPoint>,
+}
+
+impl LocalTwoPartyNet {
+    /// Create a connected pair of networks, the first for party 0 and the second for party 1
+    pub fn new_pair() -> (Self, Self) {
+        let (scalar_sender0, scalar_receiver1) = unbounded();
+        let (scalar_sender1, scalar_receiver0) = unbounded();
+        let (point_sender0, point_receiver1) = unbounded();
+        let (point_sender1, point_receiver0) = unbounded();
+
+        (
+            Self {
+                party_id: 0,
+                scalar_sender: scalar_sender0,
+                scalar_receiver: scalar_receiver0,
+                point_sender: point_sender0,
+                point_receiver: point_receiver0,
+            },
+            Self {
+                party_id: 1,
+                scalar_sender: scalar_sender1,
+                scalar_receiver: scalar_receiver1,
+                point_sender: point_sender1,
+                point_receiver: point_receiver1,
+            },
+        )
+    }
+
+    /// Create a mesh network for each of the given number of parties, indexed by party ID,
+    /// connected by a pair of networks between each pair of parties
+    pub fn new_mesh(num_parties: usize) -> Vec<MeshNetwork<Self>> {
+        let mut links = (0..num_parties).map(|_| Vec::new()).collect::<Vec<_>>();
+        for i in 0..num_parties {
+            for j in i + 1..num_parties {
+                let (link_i, link_j) = Self::new_pair();
+                links[i].push((j as PartyId, link_i));
+                links[j].push((i as PartyId, link_j));
+            }
+        }
+
+        links
+            .into_iter()
+            .enumerate()
+            .map(|(party_id, links)| {
+                MeshNetwork::new(party_id as PartyId, links).expect("mesh links are well formed")
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl MpcNetwork for LocalTwoPartyNet {
+    fn party_id(&self) -> u64 {
+        self.party_id
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        scalars.iter().try_for_each(|scalar| {
+            self.scalar_sender
+                .unbounded_send(*scalar)
+                .map_err(|_| MpcNetworkError::SendError)
+        })
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_scalars: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let mut scalars = Vec::with_capacity(num_scalars);
+        for _ in 0..num_scalars {
+            scalars.push(
+                self.scalar_receiver
+                    .next()
+                    .await
+                    .ok_or(MpcNetworkError::RecvError)?,
+            );
+        }
+
+        Ok(scalars)
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        self.send_scalars(scalars).await?;
+        self.receive_scalars(scalars.len()).await
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        points.iter().try_for_each(|point| {
+            self.point_sender
+                .unbounded_send(*point)
+                .map_err(|_| MpcNetworkError::SendError)
+        })
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_points: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let mut points = Vec::with_capacity(num_points);
+        for _ in 0..num_points {
+            points.push(
+                self.point_receiver
+                    .next()
+                    .await
+                    .ok_or(MpcNetworkError::RecvError)?,
+            );
+        }
+
+        Ok(points)
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        self.send_points(points).await?;
+        self.receive_points(points.len()).await
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.scalar_sender.close_channel();
+        self.point_sender.close_channel();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc, thread};
+
+    use curve25519_dalek::{
+        constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    };
+    use futures::executor::block_on;
+
+    use crate::{
+        beaver::SharedValueSource,
+        mpc_ristretto::MpcRistrettoPoint,
+        mpc_scalar::MpcScalar,
+        network::{instrumented_network::InstrumentedNetwork, DynMpcNetwork, MpcNetwork, PartyId},
+    };
+
+    use super::LocalTwoPartyNet;
+
+    /// A source whose shares sum to valid Beaver triplets (2, 3, 6) across the two parties
+    #[derive(Debug)]
+    struct PartyIDBeaverSource {
+        party_id: PartyId,
+    }
+
+    impl SharedValueSource<Scalar> for PartyIDBeaverSource {
+        fn next_shared_bit(&mut self) -> Scalar {
+            Scalar::from(self.party_id)
+        }
+
+        fn next_shared_value(&mut self) -> Scalar {
+            Scalar::from(self.party_id)
+        }
+
+        fn next_shared_inverse_pair(&mut self) -> (Scalar, Scalar) {
+            (Scalar::from(self.party_id), Scalar::from(self.party_id))
+        }
+
+        fn next_square_pair(&mut self) -> (Scalar, Scalar) {
+            (Scalar::from(self.party_id), Scalar::from(self.party_id))
+        }
+
+        fn next_triplet(&mut self) -> (Scalar, Scalar, Scalar) {
+            if self.party_id == 0 {
+                (Scalar::from(1u64), Scalar::from(1u64), Scalar::from(3u64))
+            } else {
+                (Scalar::from(1u64), Scalar::from(2u64), Scalar::from(3u64))
+            }
+        }
+    }
+
+    /// Runs a party's share of a multiplication and a point opening
+    fn run_party<N: MpcNetwork + Send>(network: N) -> (Scalar, RistrettoPoint) {
+        let party_id = network.party_id();
+        let network = Rc::new(RefCell::new(network));
+        let beaver_source = Rc::new(RefCell::new(PartyIDBeaverSource { party_id }));
+
+        // Each party shares one input
+        let my_value =
+            MpcScalar::from_private_u64(3 + 2 * party_id, network.clone(), beaver_source.clone());
+        let lhs = my_value.share_secret(0 /* party_id */).unwrap();
+        let rhs = my_value.share_secret(1 /* party_id */).unwrap();
+        let product = (lhs * rhs).open().unwrap();
+
+        let point = MpcRistrettoPoint::from_private_u64(7, network.clone(), beaver_source.clone())
+            .share_secret(1 /* party_id */)
+            .unwrap()
+            .open()
+            .unwrap();
+
+        block_on(network.borrow_mut().close()).unwrap();
+        (product.value(), point.value())
+    }
+
+    #[test]
+    fn test_local_two_party_net() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_party(network0));
+        let party1 = thread::spawn(move || run_party(network1));
+
+        let expected = (
+            Scalar::from(15u64),
+            RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64),
+        );
+        assert_eq!(party0.join().unwrap(), expected);
+        assert_eq!(party1.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_dyn_network() {
+        // The parties' transports differ in type but the values allocated over them do not
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let network0: DynMpcNetwork = Box::new(network0);
+        let network1: DynMpcNetwork = Box::new(InstrumentedNetwork::new(network1));
+        let party0 = thread::spawn(move || run_party(network0));
+        let party1 = thread::spawn(move || run_party(network1));
+
+        let expected = (
+            Scalar::from(15u64),
+            RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64),
+        );
+        assert_eq!(party0.join().unwrap(), expected);
+        assert_eq!(party1.join().unwrap(), expected);
+    }
+
+    /// Runs a party's share of a batch multiplication and a point product through the async methods
+    async fn run_party_async(network: LocalTwoPartyNet) -> (Vec<Scalar>, RistrettoPoint) {
+        let party_id = network.party_id();
+        let network = Rc::new(RefCell::new(network));
+        let beaver_source = Rc::new(RefCell::new(PartyIDBeaverSource { party_id }));
+
+        let my_values = MpcScalar::from_private_u64_slice(
+            &[2 + party_id, 4 + party_id],
+            network.clone(),
+            beaver_source.clone(),
+        );
+        let lhs = MpcScalar::batch_share_secrets_async(0 /* party_id */, &my_values)
+            .await
+            .unwrap();
+        let rhs = MpcScalar::batch_share_secrets_async(1 /* party_id */, &my_values)
+            .await
+            .unwrap();
+        let products = MpcScalar::batch_mul_async(&lhs, &rhs).await.unwrap();
+
+        let mut opened = Vec::with_capacity(products.len());
+        for product in products.iter() {
+            opened.push(product.open_async().await.unwrap().value());
+        }
+
+        let point = MpcRistrettoPoint::from_private_u64(7, network.clone(), beaver_source)
+            .share_secret_async(1 /* party_id */)
+            .await
+            .unwrap();
+        let point_product = MpcRistrettoPoint::batch_mul_async(&lhs[..1], &[point])
+            .await
+            .unwrap()
+            .remove(0)
+            .open_async()
+            .await
+            .unwrap();
+
+        crate::await_network(&network, |net| net.close())
+            .await
+            .unwrap();
+        (opened, point_product.value())
+    }
+
+    #[test]
+    fn test_async_ops() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || block_on(run_party_async(network0)));
+        let party1 = thread::spawn(move || block_on(run_party_async(network1)));
+
+        let expected = (
+            vec![Scalar::from(6u64), Scalar::from(20u64)],
+            RISTRETTO_BASEPOINT_POINT * Scalar::from(14u64),
+        );
+        assert_eq!(party0.join().unwrap(), expected);
+        assert_eq!(party1.join().unwrap(), expected);
+    }
+
+    /// Runs a party's share of a commit and open, returning the value and the party's rounds
+    fn run_commit_and_open(network: LocalTwoPartyNet) -> (Scalar, u64) {
+        let party_id = network.party_id();
+        let network = Rc::new(RefCell::new(InstrumentedNetwork::new(network)));
+        let beaver_source = Rc::new(RefCell::new(PartyIDBeaverSource { party_id }));
+
+        let opened = MpcScalar::from_private_u64(3 + party_id, network.clone(), beaver_source)
+            .share_secret(0 /* party_id */)
+            .unwrap()
+            .commit_and_open()
+            .unwrap();
+        let rounds = network.borrow().stats().rounds;
+
+        block_on(network.borrow_mut().close()).unwrap();
+        (opened.value(), rounds)
+    }
+
+    #[test]
+    fn test_commit_and_open_rounds() {
+        let (network0, network1) = LocalTwoPartyNet::new_pair();
+        let party0 = thread::spawn(move || run_commit_and_open(network0));
+        let party1 = thread::spawn(move || run_commit_and_open(network1));
+
+        // The king's commitment is answered by the peer's commitment and opening in one round
+        // trip, then the king's opening completes the exchange
+        assert_eq!(party0.join().unwrap(), (Scalar::from(3u64), 1));
+        assert_eq!(party1.join().unwrap(), (Scalar::from(3u64), 2));
+    }
+
+    #[test]
+    fn test_closed_peer() {
+        let (mut network0, mut network1) = LocalTwoPartyNet::new_pair();
+        block_on(network1.close()).unwrap();
+
+        assert!(block_on(network0.receive_scalars(1)).is_err());
+        assert!(block_on(network1.send_scalars(&[Scalar::one()])).is_err());
+    }
+}
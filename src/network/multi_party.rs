@@ -0,0 +1,211 @@
+//! Implements a network among more than two parties, as a mesh of two party networks between
+//! each pair of parties
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+use crate::error::MpcNetworkError;
+
+use super::{MpcNetwork, PartyId};
+
+/// MultiPartyNetwork represents the network functionality needed for an MPC among any number
+/// of parties, indexed from 0 to `num_parties() - 1`
+#[async_trait]
+pub trait MultiPartyNetwork {
+    /// Returns the ID of the local party in the MPC computation
+    fn party_id(&self) -> PartyId;
+    /// Returns the number of parties in the MPC computation, including the local party
+    fn num_parties(&self) -> usize;
+    /// Returns whether the local party is the king of the MPC (party 0)
+    fn am_king(&self) -> bool {
+        self.party_id() == 0
+    }
+    /// The local party sends a vector of scalars to the given party
+    async fn send_scalars_to(
+        &mut self,
+        party_id: PartyId,
+        scalars: &[Scalar],
+    ) -> Result<(), MpcNetworkError>;
+    /// The local party receives exactly `num_expected` scalars from the given party
+    async fn receive_scalars_from(
+        &mut self,
+        party_id: PartyId,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError>;
+    /// Every party broadcasts a vector of scalars to every other; returns the vectors indexed
+    /// by party, the local party's own included
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Vec<Scalar>>, MpcNetworkError>;
+    /// The local party sends a vector of Ristretto points to the given party
+    async fn send_points_to(
+        &mut self,
+        party_id: PartyId,
+        points: &[RistrettoPoint],
+    ) -> Result<(), MpcNetworkError>;
+    /// The local party receives exactly `num_expected` Ristretto points from the given party
+    async fn receive_points_from(
+        &mut self,
+        party_id: PartyId,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError>;
+    /// Every party broadcasts a vector of points to every other; returns the vectors indexed
+    /// by party, the local party's own included
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<Vec<RistrettoPoint>>, MpcNetworkError>;
+    /// Closes the connections to every other party
+    async fn close(&mut self) -> Result<(), MpcNetworkError>;
+}
+
+/// A network among any number of parties, connected by a two party network between each pair
+///
+/// Within the link between parties i < j, party i plays party 0 of the two party network and
+/// party j plays party 1. Broadcasts visit the links in the order of the other party's ID, so
+/// every party exchanges values over the pairs in the same order and no broadcast waits on a
+/// party that is itself waiting
+#[derive(Debug)]
+pub struct MeshNetwork<N: MpcNetwork + Send> {
+    /// The ID of the local party
+    party_id: PartyId,
+    /// The network to each other party indexed by its ID, `None` at the local party's index
+    links: Vec<Option<N>>,
+}
+
+impl<N: MpcNetwork + Send> MeshNetwork<N> {
+    /// Creates a mesh from the network to each other party, given with that party's ID
+    ///
+    /// Returns `InvalidConfig` unless the links cover every other party exactly once, and the
+    /// local party plays party 0 in its links to the parties with greater IDs
+    pub fn new(party_id: PartyId, links: Vec<(PartyId, N)>) -> Result<Self, MpcNetworkError> {
+        let num_parties = links.len() + 1;
+        if party_id as usize >= num_parties {
+            return Err(MpcNetworkError::InvalidConfig(format!(
+                "party {} is not among the {} parties",
+                party_id, num_parties
+            )));
+        }
+
+        let mut mesh_links = (0..num_parties).map(|_| None).collect::<Vec<Option<N>>>();
+        for (peer_id, link) in links.into_iter() {
+            if peer_id == party_id || peer_id as usize >= num_parties {
+                return Err(MpcNetworkError::InvalidConfig(format!(
+                    "party {} is not a peer among the {} parties",
+                    peer_id, num_parties
+                )));
+            }
+            if mesh_links[peer_id as usize].is_some() {
+                return Err(MpcNetworkError::InvalidConfig(format!(
+                    "more than one link to party {} was given",
+                    peer_id
+                )));
+            }
+            if link.am_king() != (party_id < peer_id) {
+                return Err(MpcNetworkError::InvalidConfig(format!(
+                    "the party with the lower ID must be the king of the link to party {}",
+                    peer_id
+                )));
+            }
+
+            mesh_links[peer_id as usize] = Some(link);
+        }
+
+        Ok(Self {
+            party_id,
+            links: mesh_links,
+        })
+    }
+
+    /// Mutably borrow the network to the given party
+    fn link(&mut self, party_id: PartyId) -> Result<&mut N, MpcNetworkError> {
+        self.links
+            .get_mut(party_id as usize)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| {
+                MpcNetworkError::InvalidConfig(format!("party {} is not a peer", party_id))
+            })
+    }
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> MultiPartyNetwork for MeshNetwork<N> {
+    fn party_id(&self) -> PartyId {
+        self.party_id
+    }
+
+    fn num_parties(&self) -> usize {
+        self.links.len()
+    }
+
+    async fn send_scalars_to(
+        &mut self,
+        party_id: PartyId,
+        scalars: &[Scalar],
+    ) -> Result<(), MpcNetworkError> {
+        self.link(party_id)?.send_scalars(scalars).await
+    }
+
+    async fn receive_scalars_from(
+        &mut self,
+        party_id: PartyId,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        self.link(party_id)?.receive_scalars(num_expected).await
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Vec<Scalar>>, MpcNetworkError> {
+        let mut received = Vec::with_capacity(self.links.len());
+        for link in self.links.iter_mut() {
+            match link {
+                Some(link) => received.push(link.broadcast_scalars(scalars).await?),
+                None => received.push(scalars.to_vec()),
+            }
+        }
+
+        Ok(received)
+    }
+
+    async fn send_points_to(
+        &mut self,
+        party_id: PartyId,
+        points: &[RistrettoPoint],
+    ) -> Result<(), MpcNetworkError> {
+        self.link(party_id)?.send_points(points).await
+    }
+
+    async fn receive_points_from(
+        &mut self,
+        party_id: PartyId,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        self.link(party_id)?.receive_points(num_expected).await
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<Vec<RistrettoPoint>>, MpcNetworkError> {
+        let mut received = Vec::with_capacity(self.links.len());
+        for link in self.links.iter_mut() {
+            match link {
+                Some(link) => received.push(link.broadcast_points(points).await?),
+                None => received.push(points.to_vec()),
+            }
+        }
+
+        Ok(received)
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        for link in self.links.iter_mut().flatten() {
+            link.close().await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,429 @@
+//! Implements a two party network over TCP secured by the Noise protocol framework
+//!
+//! The parties run the Noise XX handshake, in which each transmits its static key encrypted
+//! under ephemeral keys. Each party checks the peer's static key against the key it expects, so
+//! the peers authenticate one another without a certificate infrastructure, and the session
+//! keys derived from the ephemeral keys give forward secrecy
+
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use bytes::{Buf, BytesMut};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use snow::{Builder, HandshakeState, TransportState};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::error::{MpcNetworkError, SetupError};
+
+use super::{
+    bytes_to_points, bytes_to_scalars, dial_tcp, points_to_bytes, scalars_to_bytes, ByteTransport,
+    MessageKind, MessageSequence, MpcNetwork, OutputDigest, PartyId, BYTES_PER_POINT,
+    BYTES_PER_SCALAR,
+};
+
+/// The Noise protocol run between the parties
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// The largest Noise message, including the authentication tag
+const MAX_NOISE_MESSAGE: usize = 65535;
+/// The length of the authentication tag appended to each transport message
+const TAG_BYTES: usize = 16;
+/// The number of bytes in the length prefix of each message on the wire
+const LENGTH_PREFIX_BYTES: usize = 2;
+
+/// A static Noise keypair identifying a party
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoiseKeypair {
+    /// The private key, kept by the party
+    pub private: Vec<u8>,
+    /// The public key, given to the peer out of band
+    pub public: Vec<u8>,
+}
+
+impl NoiseKeypair {
+    /// Generates a fresh static keypair
+    pub fn generate() -> Result<Self, SetupError> {
+        let keypair = Builder::new(NOISE_PARAMS.parse().unwrap())
+            .generate_keypair()
+            .map_err(|_| SetupError::KeygenError)?;
+
+        Ok(Self {
+            private: keypair.private,
+            public: keypair.public,
+        })
+    }
+}
+
+/// Implements an MpcNetwork over TCP, encrypted and authenticated with the Noise protocol
+///
+/// The king dials the peer and initiates the handshake. A send, its header included, is split
+/// into as many Noise messages as needed, and received plaintext is buffered so that the header
+/// and the values of a send can be read across Noise messages
+pub struct NoiseTwoPartyNet {
+    /// The index of the local party in the participants
+    party_id: PartyId,
+    /// The address of the local peer
+    local_addr: SocketAddr,
+    /// Addresses of the counterparties in the MPC
+    peer_addr: SocketAddr,
+    /// The static keypair of the local party
+    keypair: NoiseKeypair,
+    /// The static public key the peer must present in the handshake
+    peer_public_key: Vec<u8>,
+    /// The TCP connection to the peer
+    stream: Option<TcpStream>,
+    /// The session keys established by the handshake
+    transport: Option<TransportState>,
+    /// Plaintext received from the peer that has not yet been read
+    read_buffer: BytesMut,
+    /// The sequence numbers of the messages of values exchanged with the peer
+    message_sequence: MessageSequence,
+    /// A hash chain over the values opened in the session, if enabled
+    output_digest: Option<OutputDigest>,
+}
+
+impl std::fmt::Debug for NoiseTwoPartyNet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Elide the private key
+        f.debug_struct("NoiseTwoPartyNet")
+            .field("party_id", &self.party_id)
+            .field("local_addr", &self.local_addr)
+            .field("peer_addr", &self.peer_addr)
+            .field("peer_public_key", &self.peer_public_key)
+            .field("connected", &self.transport.is_some())
+            .finish()
+    }
+}
+
+impl NoiseTwoPartyNet {
+    pub fn new(
+        party_id: PartyId,
+        local_addr: SocketAddr,
+        peer_addr: SocketAddr,
+        keypair: NoiseKeypair,
+        peer_public_key: Vec<u8>,
+    ) -> Self {
+        Self {
+            party_id,
+            local_addr,
+            peer_addr,
+            keypair,
+            peer_public_key,
+            stream: None,
+            transport: None,
+            read_buffer: BytesMut::new(),
+            message_sequence: MessageSequence::default(),
+            output_digest: None,
+        }
+    }
+
+    /// Maintains a hash chain over every value opened in the session, see
+    /// `MpcNetwork::output_digest`
+    pub fn with_output_digest(mut self) -> Self {
+        self.output_digest = Some(OutputDigest::default());
+        self
+    }
+
+    /// Establishes a connection to the peer and runs the handshake
+    ///
+    /// Fails with `MpcNetworkError::AuthenticationError` if the peer does not present the
+    /// expected static key
+    pub async fn connect(&mut self) -> Result<(), MpcNetworkError> {
+        let mut stream = if self.am_king() {
+            dial_tcp(self.peer_addr).await?
+        } else {
+            let listener = TcpListener::bind(self.local_addr)
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::ServerSetupError))?;
+            let (stream, _) = listener.accept().await.map_err(|_| {
+                MpcNetworkError::ConnectionSetupError(SetupError::NoIncomingConnection)
+            })?;
+
+            stream
+        };
+
+        let builder =
+            Builder::new(NOISE_PARAMS.parse().unwrap()).local_private_key(&self.keypair.private);
+        let handshake = if self.am_king() {
+            builder.build_initiator()
+        } else {
+            builder.build_responder()
+        }
+        .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?;
+
+        let transport = self.handshake(&mut stream, handshake).await?;
+        if transport.get_remote_static() != Some(&self.peer_public_key[..]) {
+            return Err(MpcNetworkError::AuthenticationError);
+        }
+
+        self.stream = Some(stream);
+        self.transport = Some(transport);
+        Ok(())
+    }
+
+    /// Runs the three messages of the XX handshake, the initiator writes first
+    async fn handshake(
+        &self,
+        stream: &mut TcpStream,
+        mut handshake: HandshakeState,
+    ) -> Result<TransportState, MpcNetworkError> {
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE];
+        while !handshake.is_handshake_finished() {
+            if handshake.is_my_turn() {
+                let length = handshake
+                    .write_message(&[], &mut buffer)
+                    .map_err(handshake_error)?;
+                write_message(stream, &buffer[..length])
+                    .await
+                    .map_err(handshake_error)?;
+            } else {
+                let message = read_message(stream).await.map_err(handshake_error)?;
+                handshake
+                    .read_message(&message, &mut buffer)
+                    .map_err(handshake_error)?;
+            }
+        }
+
+        handshake.into_transport_mode().map_err(handshake_error)
+    }
+}
+
+#[async_trait]
+impl ByteTransport for NoiseTwoPartyNet {
+    /// Encrypt and write a payload to the peer, split into as many Noise messages as needed
+    async fn write_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        let (stream, transport) = match (self.stream.as_mut(), self.transport.as_mut()) {
+            (Some(stream), Some(transport)) => (stream, transport),
+            _ => return Err(MpcNetworkError::NetworkUninitialized),
+        };
+
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE];
+        for chunk in payload.chunks(MAX_NOISE_MESSAGE - TAG_BYTES) {
+            let length = transport
+                .write_message(chunk, &mut buffer)
+                .map_err(|_| MpcNetworkError::SendError)?;
+            write_message(stream, &buffer[..length])
+                .await
+                .map_err(|_| MpcNetworkError::SendError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read exactly `n` bytes of plaintext from the peer, buffering any excess for the next read
+    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        let (stream, transport) = match (self.stream.as_mut(), self.transport.as_mut()) {
+            (Some(stream), Some(transport)) => (stream, transport),
+            _ => return Err(MpcNetworkError::NetworkUninitialized),
+        };
+
+        let mut buffer = vec![0u8; MAX_NOISE_MESSAGE];
+        while self.read_buffer.len() < num_bytes {
+            let message = read_message(stream)
+                .await
+                .map_err(|_| MpcNetworkError::RecvError)?;
+            let length = transport
+                .read_message(&message, &mut buffer)
+                .map_err(|_| MpcNetworkError::RecvError)?;
+            self.read_buffer.extend_from_slice(&buffer[..length]);
+        }
+
+        let bytes = self.read_buffer[..num_bytes].to_vec();
+        self.read_buffer.advance(num_bytes);
+        Ok(bytes)
+    }
+
+    fn message_sequence(&mut self) -> &mut MessageSequence {
+        &mut self.message_sequence
+    }
+}
+
+/// Maps a failure during the handshake to a setup error
+fn handshake_error<E>(_: E) -> MpcNetworkError {
+    MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError)
+}
+
+/// Write a length prefixed message to the stream
+async fn write_message(stream: &mut TcpStream, message: &[u8]) -> std::io::Result<()> {
+    stream
+        .write_all(&(message.len() as u16).to_be_bytes())
+        .await?;
+    stream.write_all(message).await
+}
+
+/// Read a length prefixed message from the stream
+async fn read_message(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut prefix = [0u8; LENGTH_PREFIX_BYTES];
+    stream.read_exact(&mut prefix).await?;
+
+    let mut message = vec![0u8; u16::from_be_bytes(prefix) as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(message)
+}
+
+#[async_trait]
+impl MpcNetwork for NoiseTwoPartyNet {
+    fn party_id(&self) -> u64 {
+        self.party_id
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendScalars,
+            scalars.len(),
+            &scalars_to_bytes(scalars),
+        )
+        .await
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_scalars: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendScalars, num_scalars, BYTES_PER_SCALAR)
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastScalars,
+                scalars.len(),
+                BYTES_PER_SCALAR,
+                &scalars_to_bytes(scalars),
+            )
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendPoints,
+            points.len(),
+            &points_to_bytes(points),
+        )
+        .await
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_points: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendPoints, num_points, BYTES_PER_POINT)
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastPoints,
+                points.len(),
+                BYTES_PER_POINT,
+                &points_to_bytes(points),
+            )
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        if let Some(digest) = self.output_digest.as_mut() {
+            digest.absorb(opened_bytes);
+        }
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.output_digest.map(|digest| digest.value())
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.stream
+            .as_mut()
+            .ok_or(MpcNetworkError::NetworkUninitialized)?
+            .shutdown()
+            .await
+            .map_err(|_| MpcNetworkError::ConnectionTeardownError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+    use rand_core::OsRng;
+
+    use crate::{error::MpcNetworkError, network::MpcNetwork};
+
+    use super::{NoiseKeypair, NoiseTwoPartyNet};
+
+    #[tokio::test]
+    async fn test_noise_net() {
+        let king_keypair = NoiseKeypair::generate().unwrap();
+        let peer_keypair = NoiseKeypair::generate().unwrap();
+
+        let addr1: SocketAddr = "127.0.0.1:7960".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7961".parse().unwrap();
+        let mut king = NoiseTwoPartyNet::new(
+            0,
+            addr1,
+            addr2,
+            king_keypair.clone(),
+            peer_keypair.public.clone(),
+        );
+        let mut peer = NoiseTwoPartyNet::new(1, addr2, addr1, peer_keypair, king_keypair.public);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        // A batch larger than a single Noise message
+        let mut rng = OsRng {};
+        let scalars = (0..3000)
+            .map(|_| Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let (send_res, recv_res) = tokio::join!(
+            king.send_scalars(&scalars),
+            peer.receive_scalars(scalars.len())
+        );
+        send_res.unwrap();
+        assert_eq!(recv_res.unwrap(), scalars);
+
+        let king_point = RistrettoPoint::random(&mut rng);
+        let peer_point = RistrettoPoint::random(&mut rng);
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_point(king_point),
+            peer.broadcast_single_point(peer_point)
+        );
+        assert_eq!(king_res.unwrap(), peer_point);
+        assert_eq!(peer_res.unwrap(), king_point);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_peer_key() {
+        let king_keypair = NoiseKeypair::generate().unwrap();
+        let peer_keypair = NoiseKeypair::generate().unwrap();
+        let other_keypair = NoiseKeypair::generate().unwrap();
+
+        let addr1: SocketAddr = "127.0.0.1:7962".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7963".parse().unwrap();
+        let mut king =
+            NoiseTwoPartyNet::new(0, addr1, addr2, king_keypair.clone(), other_keypair.public);
+        let mut peer = NoiseTwoPartyNet::new(1, addr2, addr1, peer_keypair, king_keypair.public);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        assert_eq!(king_res, Err(MpcNetworkError::AuthenticationError));
+        peer_res.unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! Implements a token bucket limiting the rate at which a network sends to the peer, so that
+//! many computations running side by side share the link rather than one saturating it
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A limit on the rate at which a network sends to its peer
+///
+/// Sends spend tokens from a bucket of `burst_bytes` that refills at `bytes_per_second`; a send
+/// larger than the tokens available waits until the bucket has refilled enough to cover it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The sustained rate at which bytes are sent
+    pub bytes_per_second: u64,
+    /// The number of bytes that may be sent at once after the network has been idle
+    pub burst_bytes: u64,
+}
+
+/// The usage of a network's rate limit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitStats {
+    /// The number of bytes sent under the limit
+    pub bytes_sent: u64,
+    /// The total time sends have waited on the limit
+    pub throttled: Duration,
+    /// The fraction of the burst currently spent; near 0 when idle and 1 when sending at the limit
+    pub utilization: f64,
+}
+
+/// A token bucket shared by a network and the channels opened on it
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimiter {
+    /// The configured limit
+    limit: RateLimit,
+    /// The state of the bucket
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+/// The mutable state of a `RateLimiter`
+#[derive(Debug)]
+struct TokenBucket {
+    /// The tokens available, negative while sends are waiting on the bucket to refill
+    tokens: f64,
+    /// When the tokens were last refilled
+    last_refill: Instant,
+    /// The number of bytes sent under the limit
+    bytes_sent: u64,
+    /// The total time sends have waited on the limit
+    throttled: Duration,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: limit.burst_bytes as f64,
+                last_refill: Instant::now(),
+                bytes_sent: 0,
+                throttled: Duration::ZERO,
+            })),
+        }
+    }
+
+    /// Spends tokens for a send of the given length, waiting until the bucket covers it
+    pub(crate) async fn acquire(&self, num_bytes: usize) {
+        let delay = {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+
+            // Spending ahead of the refill queues later sends behind this one
+            bucket.tokens -= num_bytes as f64;
+            bucket.bytes_sent += num_bytes as u64;
+            let delay = if bucket.tokens < 0. {
+                Duration::from_secs_f64(-bucket.tokens / self.rate())
+            } else {
+                Duration::ZERO
+            };
+            bucket.throttled += delay;
+
+            delay
+        };
+
+        if !delay.is_zero() {
+            async_std::task::sleep(delay).await;
+        }
+    }
+
+    /// Returns the usage of the limit
+    pub(crate) fn stats(&self) -> RateLimitStats {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+
+        let burst_bytes = u64::max(1, self.limit.burst_bytes) as f64;
+        RateLimitStats {
+            bytes_sent: bucket.bytes_sent,
+            throttled: bucket.throttled,
+            utilization: (1. - bucket.tokens / burst_bytes).clamp(0., 1.),
+        }
+    }
+
+    /// Adds the tokens accrued since the last refill, up to the burst
+    fn refill(&self, bucket: &mut TokenBucket) {
+        let now = Instant::now();
+        let accrued = now.duration_since(bucket.last_refill).as_secs_f64() * self.rate();
+        bucket.tokens = f64::min(bucket.tokens + accrued, self.limit.burst_bytes as f64);
+        bucket.last_refill = now;
+    }
+
+    /// Returns the refill rate in bytes per second, a zero rate is treated as one
+    fn rate(&self) -> f64 {
+        u64::max(1, self.limit.bytes_per_second) as f64
+    }
+}
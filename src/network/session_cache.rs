@@ -0,0 +1,155 @@
+//! Implements a cache of TLS sessions, so that a party reconnecting to the same counterparty
+//! resumes its previous session rather than running the full handshake
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::{Arc, Mutex},
+};
+
+use rustls::{client::StoresClientSessions, server::StoresServerSessions};
+
+use crate::error::MpcNetworkError;
+
+/// The number of bytes in the length prefixes of the exported cache
+const LENGTH_BYTES: usize = 4;
+
+/// The sessions cached by a party, for resuming them in later connections to the same peer
+///
+/// The king dials the peer, so the king caches the sessions it resumes as a client and the
+/// peer the sessions it resumes as a server; both parties must use a cache for a session to be
+/// resumed. Clones share the same sessions, so a single cache may be given to the networks of
+/// many sequential computations. A cache should only be used with a single counterparty
+#[derive(Clone, Debug, Default)]
+pub struct SessionCache {
+    /// The sessions offered to the peer when dialing
+    pub(crate) client_sessions: Arc<SessionStore>,
+    /// The sessions the peer may resume when accepting a connection
+    pub(crate) server_sessions: Arc<SessionStore>,
+}
+
+impl SessionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serializes the cached sessions, e.g. to persist them across restarts of the process
+    ///
+    /// The serialized sessions contain the secrets of the sessions and must be stored as
+    /// securely as the key material of the party
+    pub fn export(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.client_sessions.export_into(&mut bytes);
+        self.server_sessions.export_into(&mut bytes);
+        bytes
+    }
+
+    /// Restores a cache from sessions serialized with `SessionCache::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, MpcNetworkError> {
+        let mut cursor = bytes;
+        let client_sessions = SessionStore::import_from(&mut cursor)?;
+        let server_sessions = SessionStore::import_from(&mut cursor)?;
+        if !cursor.is_empty() {
+            return Err(MpcNetworkError::SerializationError);
+        }
+
+        Ok(Self {
+            client_sessions: Arc::new(client_sessions),
+            server_sessions: Arc::new(server_sessions),
+        })
+    }
+
+    /// Whether any sessions are cached
+    pub fn is_empty(&self) -> bool {
+        self.client_sessions.is_empty() && self.server_sessions.is_empty()
+    }
+}
+
+/// An in-memory map of session keys to the encoded session, as stored by rustls
+#[derive(Debug, Default)]
+pub(crate) struct SessionStore {
+    /// The encoded sessions
+    sessions: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl SessionStore {
+    fn is_empty(&self) -> bool {
+        self.sessions.lock().unwrap().is_empty()
+    }
+
+    /// Appends the sessions as a count followed by length prefixed keys and values
+    fn export_into(&self, bytes: &mut Vec<u8>) {
+        let sessions = self.sessions.lock().unwrap();
+        bytes.extend_from_slice(&(sessions.len() as u32).to_le_bytes());
+        for (key, value) in sessions.iter() {
+            bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(key);
+            bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(value);
+        }
+    }
+
+    /// Reads sessions written by `export_into` from the head of the cursor, advancing it
+    fn import_from(cursor: &mut &[u8]) -> Result<Self, MpcNetworkError> {
+        let num_sessions = read_length(cursor)?;
+        let mut sessions = HashMap::new();
+        for _ in 0..num_sessions {
+            let key_length = read_length(cursor)?;
+            let key = read_bytes(cursor, key_length)?;
+            let value_length = read_length(cursor)?;
+            let value = read_bytes(cursor, value_length)?;
+            sessions.insert(key, value);
+        }
+
+        Ok(Self {
+            sessions: Mutex::new(sessions),
+        })
+    }
+}
+
+/// Reads a length prefix from the head of the cursor
+fn read_length(cursor: &mut &[u8]) -> Result<usize, MpcNetworkError> {
+    let bytes = read_bytes(cursor, LENGTH_BYTES)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+}
+
+/// Reads the given number of bytes from the head of the cursor
+fn read_bytes(cursor: &mut &[u8], num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+    if cursor.len() < num_bytes {
+        return Err(MpcNetworkError::SerializationError);
+    }
+
+    let (head, tail) = cursor.split_at(num_bytes);
+    *cursor = tail;
+    Ok(head.to_vec())
+}
+
+impl StoresClientSessions for SessionStore {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        self.sessions.lock().unwrap().insert(key, value);
+        true
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.sessions.lock().unwrap().get(key).cloned()
+    }
+}
+
+impl StoresServerSessions for SessionStore {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        self.sessions.lock().unwrap().insert(key, value);
+        true
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.sessions.lock().unwrap().get(key).cloned()
+    }
+
+    fn take(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.sessions.lock().unwrap().remove(key)
+    }
+
+    fn can_cache(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,228 @@
+//! Implements a network wrapper that simulates the latency and bandwidth of a wide area network,
+//! for evaluating protocols under realistic conditions on a local network
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{OsRng, RngCore};
+
+use crate::error::MpcNetworkError;
+
+use super::{MpcNetwork, PeerIdentity, BYTES_PER_POINT, BYTES_PER_SCALAR};
+
+/// The link conditions simulated by a `SimulatedNetwork`
+///
+/// The default simulates a perfect link, adding no delay
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SimulationConfig {
+    /// The one way delay added to every message
+    pub latency: Duration,
+    /// The upper bound of a uniformly random delay added to every message on top of the latency
+    pub jitter: Duration,
+    /// The bandwidth of the link in bytes per second, or `None` for unlimited bandwidth
+    pub bandwidth: Option<u64>,
+}
+
+impl SimulationConfig {
+    /// Samples the delay for a message of the given length
+    fn sample_delay(&self, num_bytes: usize) -> Duration {
+        let jitter = if self.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            let jitter_nanos = self.jitter.as_nanos() as u64;
+            let mut rng = OsRng {};
+            Duration::from_nanos(rng.next_u64() % (jitter_nanos + 1))
+        };
+
+        let transmission_time = match self.bandwidth {
+            Some(bandwidth) => Duration::from_secs_f64(num_bytes as f64 / bandwidth as f64),
+            None => Duration::ZERO,
+        };
+
+        self.latency + jitter + transmission_time
+    }
+}
+
+/// Wraps a network and delays each message sent through it to simulate the configured link
+///
+/// The delay is applied before a message is sent, so the peer observes it when receiving; both
+/// parties should wrap their network in the same configuration for a symmetric link
+#[derive(Debug)]
+pub struct SimulatedNetwork<N: MpcNetwork + Send> {
+    /// The underlying network
+    inner: N,
+    /// The link conditions to simulate
+    config: SimulationConfig,
+}
+
+impl<N: MpcNetwork + Send> SimulatedNetwork<N> {
+    /// Wraps the network in the given link conditions
+    ///
+    /// A bandwidth of zero would never transmit a message, so it is rejected; `None` simulates
+    /// unlimited bandwidth
+    pub fn new(inner: N, config: SimulationConfig) -> Result<Self, MpcNetworkError> {
+        if config.bandwidth == Some(0) {
+            return Err(MpcNetworkError::InvalidConfig(
+                "simulated bandwidth must be positive".to_string(),
+            ));
+        }
+
+        Ok(Self { inner, config })
+    }
+
+    /// Returns the simulated link conditions
+    pub fn config(&self) -> SimulationConfig {
+        self.config
+    }
+
+    /// Returns a reference to the underlying network
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying network
+    pub fn inner_mut(&mut self) -> &mut N {
+        &mut self.inner
+    }
+}
+
+/// Waits out a simulated delay
+async fn wait(delay: Duration) {
+    if !delay.is_zero() {
+        async_std::task::sleep(delay).await;
+    }
+}
+
+#[async_trait]
+impl<N: MpcNetwork + Send> MpcNetwork for SimulatedNetwork<N> {
+    fn party_id(&self) -> u64 {
+        self.inner.party_id()
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        wait(self.config.sample_delay(scalars.len() * BYTES_PER_SCALAR)).await;
+        self.inner.send_scalars(scalars).await
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        self.inner.receive_scalars(num_expected).await
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        wait(self.config.sample_delay(scalars.len() * BYTES_PER_SCALAR)).await;
+        self.inner.broadcast_scalars(scalars).await
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        wait(self.config.sample_delay(points.len() * BYTES_PER_POINT)).await;
+        self.inner.send_points(points).await
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_expected: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        self.inner.receive_points(num_expected).await
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        wait(self.config.sample_delay(points.len() * BYTES_PER_POINT)).await;
+        self.inner.broadcast_points(points).await
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.inner.close().await
+    }
+
+    async fn flush(&mut self) -> Result<(), MpcNetworkError> {
+        self.inner.flush().await
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        self.inner.record_opening(opened_bytes)
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.inner.output_digest()
+    }
+
+    fn set_receive_timeout(&mut self, timeout: Option<Duration>) {
+        self.inner.set_receive_timeout(timeout)
+    }
+
+    fn peer_identity(&self) -> Option<PeerIdentity> {
+        self.inner.peer_identity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use curve25519_dalek::scalar::Scalar;
+    use futures::executor::block_on;
+
+    use crate::{
+        error::MpcNetworkError,
+        network::{dummy_network::DummyMpcNetwork, MpcNetwork},
+    };
+
+    use super::{SimulatedNetwork, SimulationConfig};
+
+    #[test]
+    fn test_sample_delay() {
+        let config = SimulationConfig {
+            latency: Duration::from_millis(10),
+            jitter: Duration::from_millis(5),
+            bandwidth: Some(1000),
+        };
+
+        // 500 bytes at 1000 bytes per second take half a second to transmit
+        let delay = config.sample_delay(500);
+        assert!(delay >= Duration::from_millis(510));
+        assert!(delay <= Duration::from_millis(515));
+
+        assert_eq!(
+            SimulationConfig::default().sample_delay(500),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_delayed_send() {
+        let latency = Duration::from_millis(20);
+        let mut network = SimulatedNetwork::new(
+            DummyMpcNetwork::new(),
+            SimulationConfig {
+                latency,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let start = Instant::now();
+        block_on(network.send_single_scalar(Scalar::one())).unwrap();
+        assert!(start.elapsed() >= latency);
+
+        assert!(matches!(
+            SimulatedNetwork::new(
+                DummyMpcNetwork::new(),
+                SimulationConfig {
+                    bandwidth: Some(0),
+                    ..Default::default()
+                },
+            ),
+            Err(MpcNetworkError::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+//! Implements a two party network over a Unix domain socket, for parties running on the same
+//! host; e.g. an enclave and its host process
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+};
+
+use crate::error::{MpcNetworkError, SetupError};
+
+use super::{
+    bytes_to_points, bytes_to_scalars, points_to_bytes, scalars_to_bytes, ByteTransport,
+    MessageKind, MessageSequence, MpcNetwork, OutputDigest, PartyId, BYTES_PER_POINT,
+    BYTES_PER_SCALAR, DIAL_BACKOFF, MAX_DIAL_ATTEMPTS,
+};
+
+/// Implements an MpcNetwork on top of a Unix domain socket
+///
+/// The peer listens on the socket path and the king dials it. The socket is a reliable byte
+/// stream, so each message is written as its header followed by the values, without further
+/// framing
+#[derive(Debug)]
+pub struct UdsTwoPartyNet {
+    /// The index of the local party in the participants
+    party_id: PartyId,
+    /// The path of the socket the peer listens on
+    socket_path: PathBuf,
+    /// The connection to the peer
+    stream: Option<UnixStream>,
+    /// The sequence numbers of the messages of values exchanged with the peer
+    message_sequence: MessageSequence,
+    /// A hash chain over the values opened in the session, if enabled
+    output_digest: Option<OutputDigest>,
+}
+
+impl UdsTwoPartyNet {
+    pub fn new(party_id: PartyId, socket_path: PathBuf) -> Self {
+        Self {
+            party_id,
+            socket_path,
+            stream: None,
+            message_sequence: MessageSequence::default(),
+            output_digest: None,
+        }
+    }
+
+    /// Maintains a hash chain over every value opened in the session, see
+    /// `MpcNetwork::output_digest`
+    pub fn with_output_digest(mut self) -> Self {
+        self.output_digest = Some(OutputDigest::default());
+        self
+    }
+
+    /// Returns the connection to the peer, or an error if the network is not connected
+    fn stream(&mut self) -> Result<&mut UnixStream, MpcNetworkError> {
+        self.stream
+            .as_mut()
+            .ok_or(MpcNetworkError::NetworkUninitialized)
+    }
+
+    /// Establishes a connection to the peer
+    ///
+    /// The peer replaces any socket left at the path by an earlier run
+    pub async fn connect(&mut self) -> Result<(), MpcNetworkError> {
+        let stream = if self.am_king() {
+            self.dial().await?
+        } else {
+            let _ = std::fs::remove_file(&self.socket_path);
+            let listener = UnixListener::bind(&self.socket_path)
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::ServerSetupError))?;
+            let (stream, _) = listener.accept().await.map_err(|_| {
+                MpcNetworkError::ConnectionSetupError(SetupError::NoIncomingConnection)
+            })?;
+
+            stream
+        };
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// Dials the peer, retrying while the peer is not yet listening
+    async fn dial(&self) -> Result<UnixStream, MpcNetworkError> {
+        let mut attempt = 1;
+        loop {
+            match UnixStream::connect(&self.socket_path).await {
+                Ok(stream) => return Ok(stream),
+                Err(_) if attempt >= MAX_DIAL_ATTEMPTS => {
+                    return Err(MpcNetworkError::ConnectionSetupError(
+                        SetupError::NoIncomingConnection,
+                    ))
+                }
+                Err(_) => {
+                    async_std::task::sleep(DIAL_BACKOFF).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ByteTransport for UdsTwoPartyNet {
+    /// Write a stream of bytes to the peer
+    async fn write_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        self.stream()?
+            .write_all(payload)
+            .await
+            .map_err(|_| MpcNetworkError::SendError)
+    }
+
+    /// Read exactly `n` bytes from the peer
+    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        let mut read_buffer = vec![0u8; num_bytes];
+        self.stream()?
+            .read_exact(&mut read_buffer)
+            .await
+            .map_err(|_| MpcNetworkError::RecvError)?;
+
+        Ok(read_buffer)
+    }
+
+    fn message_sequence(&mut self) -> &mut MessageSequence {
+        &mut self.message_sequence
+    }
+}
+
+#[async_trait]
+impl MpcNetwork for UdsTwoPartyNet {
+    fn party_id(&self) -> u64 {
+        self.party_id
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendScalars,
+            scalars.len(),
+            &scalars_to_bytes(scalars),
+        )
+        .await
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_scalars: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendScalars, num_scalars, BYTES_PER_SCALAR)
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastScalars,
+                scalars.len(),
+                BYTES_PER_SCALAR,
+                &scalars_to_bytes(scalars),
+            )
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendPoints,
+            points.len(),
+            &points_to_bytes(points),
+        )
+        .await
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_points: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendPoints, num_points, BYTES_PER_POINT)
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastPoints,
+                points.len(),
+                BYTES_PER_POINT,
+                &points_to_bytes(points),
+            )
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        if let Some(digest) = self.output_digest.as_mut() {
+            digest.absorb(opened_bytes);
+        }
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.output_digest.map(|digest| digest.value())
+    }
+
+    /// Shuts down the local side of the connection; the peer also removes the socket file
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.stream()?
+            .shutdown()
+            .await
+            .map_err(|_| MpcNetworkError::ConnectionTeardownError)?;
+
+        if !self.am_king() {
+            std::fs::remove_file(&self.socket_path)
+                .map_err(|_| MpcNetworkError::ConnectionTeardownError)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+    use rand_core::OsRng;
+
+    use crate::{
+        error::MpcNetworkError,
+        network::{ByteTransport, MessageHeader, MessageKind, MpcNetwork, BYTES_PER_SCALAR},
+    };
+
+    use super::UdsTwoPartyNet;
+
+    #[tokio::test]
+    async fn test_uds_net() {
+        let socket_path = std::env::temp_dir().join(format!("mpc-uds-{}", std::process::id()));
+        let mut king = UdsTwoPartyNet::new(0, socket_path.clone()).with_output_digest();
+        let mut peer = UdsTwoPartyNet::new(1, socket_path.clone()).with_output_digest();
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let mut rng = OsRng {};
+        let scalars = (0..3).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        king.send_scalars(&scalars).await.unwrap();
+        assert_eq!(peer.receive_scalars(3).await.unwrap(), scalars);
+
+        // The peer expects a point where the king sent scalars
+        king.send_scalars(&scalars[..2]).await.unwrap();
+        assert_eq!(
+            peer.receive_single_point().await,
+            Err(MpcNetworkError::ProtocolDesync(
+                MessageHeader {
+                    sequence: 1,
+                    kind: MessageKind::SendPoints,
+                    num_values: 1,
+                },
+                MessageHeader {
+                    sequence: 1,
+                    kind: MessageKind::SendScalars,
+                    num_values: 2,
+                },
+            ))
+        );
+        // The desynced message is abandoned; both parties move on to the next
+        peer.read_bytes(2 * BYTES_PER_SCALAR).await.unwrap();
+
+        let king_point = RistrettoPoint::random(&mut rng);
+        let peer_point = RistrettoPoint::random(&mut rng);
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_point(king_point),
+            peer.broadcast_single_point(peer_point)
+        );
+        assert_eq!(king_res.unwrap(), peer_point);
+        assert_eq!(peer_res.unwrap(), king_point);
+
+        // Both parties absorb the same openings into their digests
+        let opened = (king_point + peer_point).compress();
+        king.record_opening(opened.as_bytes());
+        peer.record_opening(opened.as_bytes());
+        assert!(king.output_digest().is_some());
+        assert_eq!(king.output_digest(), peer.output_digest());
+
+        let (king_res, peer_res) = tokio::join!(king.close(), peer.close());
+        king_res.unwrap();
+        peer_res.unwrap();
+        assert!(!socket_path.exists());
+    }
+}
@@ -0,0 +1,268 @@
+//! Implements a two party network over WebSockets, for deployments in which only HTTP traffic
+//! reaches the parties, e.g. behind a reverse proxy
+
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use bytes::{Buf, BytesMut};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{accept_async, client_async, tungstenite::Message, WebSocketStream};
+
+use crate::error::{MpcNetworkError, SetupError};
+
+use super::{
+    bytes_to_points, bytes_to_scalars, dial_tcp, points_to_bytes, scalars_to_bytes, ByteTransport,
+    MessageKind, MessageSequence, MpcNetwork, OutputDigest, PartyId, BYTES_PER_POINT,
+    BYTES_PER_SCALAR,
+};
+
+/// Implements an MpcNetwork on top of a WebSocket connection
+///
+/// The king dials the peer, who listens for the connection. Each send is written as a single
+/// binary message holding its header and values, and received bytes are buffered so that the
+/// header and the values can be read separately
+#[derive(Debug)]
+pub struct WsTwoPartyNet {
+    /// The index of the local party in the participants
+    party_id: PartyId,
+    /// The address of the local peer
+    local_addr: SocketAddr,
+    /// Addresses of the counterparties in the MPC
+    peer_addr: SocketAddr,
+    /// The WebSocket connection to the peer
+    stream: Option<WebSocketStream<TcpStream>>,
+    /// Bytes received from the peer that have not yet been read
+    read_buffer: BytesMut,
+    /// The sequence numbers of the messages of values exchanged with the peer
+    message_sequence: MessageSequence,
+    /// A hash chain over the values opened in the session, if enabled
+    output_digest: Option<OutputDigest>,
+}
+
+impl WsTwoPartyNet {
+    pub fn new(party_id: PartyId, local_addr: SocketAddr, peer_addr: SocketAddr) -> Self {
+        Self {
+            party_id,
+            local_addr,
+            peer_addr,
+            stream: None,
+            read_buffer: BytesMut::new(),
+            message_sequence: MessageSequence::default(),
+            output_digest: None,
+        }
+    }
+
+    /// Maintains a hash chain over every value opened in the session, see
+    /// `MpcNetwork::output_digest`
+    pub fn with_output_digest(mut self) -> Self {
+        self.output_digest = Some(OutputDigest::default());
+        self
+    }
+
+    /// Returns the connection to the peer, or an error if the network is not connected
+    fn stream(&mut self) -> Result<&mut WebSocketStream<TcpStream>, MpcNetworkError> {
+        self.stream
+            .as_mut()
+            .ok_or(MpcNetworkError::NetworkUninitialized)
+    }
+
+    /// Establishes a connection to the peer
+    pub async fn connect(&mut self) -> Result<(), MpcNetworkError> {
+        let stream = if self.am_king() {
+            let tcp_stream = dial_tcp(self.peer_addr).await?;
+            let url = format!("ws://{}/", self.peer_addr);
+            let (stream, _) = client_async(url, tcp_stream)
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?;
+
+            stream
+        } else {
+            let listener = TcpListener::bind(self.local_addr)
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::ServerSetupError))?;
+            let (tcp_stream, _) = listener.accept().await.map_err(|_| {
+                MpcNetworkError::ConnectionSetupError(SetupError::NoIncomingConnection)
+            })?;
+
+            accept_async(tcp_stream)
+                .await
+                .map_err(|_| MpcNetworkError::ConnectionSetupError(SetupError::HandshakeError))?
+        };
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ByteTransport for WsTwoPartyNet {
+    /// Write a payload to the peer as a single binary message
+    async fn write_bytes(&mut self, payload: &[u8]) -> Result<(), MpcNetworkError> {
+        self.stream()?
+            .send(Message::Binary(payload.to_vec()))
+            .await
+            .map_err(|_| MpcNetworkError::SendError)
+    }
+
+    /// Read exactly `n` bytes from the peer, buffering any excess for the next read
+    async fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, MpcNetworkError> {
+        while self.read_buffer.len() < num_bytes {
+            let message = self
+                .stream()?
+                .next()
+                .await
+                .ok_or(MpcNetworkError::RecvError)?
+                .map_err(|_| MpcNetworkError::RecvError)?;
+
+            match message {
+                Message::Binary(bytes) => self.read_buffer.extend_from_slice(&bytes),
+                // Control frames are answered by the WebSocket implementation
+                Message::Ping(_) | Message::Pong(_) => {}
+                _ => return Err(MpcNetworkError::RecvError),
+            }
+        }
+
+        let bytes = self.read_buffer[..num_bytes].to_vec();
+        self.read_buffer.advance(num_bytes);
+        Ok(bytes)
+    }
+
+    fn message_sequence(&mut self) -> &mut MessageSequence {
+        &mut self.message_sequence
+    }
+}
+
+#[async_trait]
+impl MpcNetwork for WsTwoPartyNet {
+    fn party_id(&self) -> u64 {
+        self.party_id
+    }
+
+    async fn send_scalars(&mut self, scalars: &[Scalar]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendScalars,
+            scalars.len(),
+            &scalars_to_bytes(scalars),
+        )
+        .await
+    }
+
+    async fn receive_scalars(
+        &mut self,
+        num_scalars: usize,
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendScalars, num_scalars, BYTES_PER_SCALAR)
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn broadcast_scalars(
+        &mut self,
+        scalars: &[Scalar],
+    ) -> Result<Vec<Scalar>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastScalars,
+                scalars.len(),
+                BYTES_PER_SCALAR,
+                &scalars_to_bytes(scalars),
+            )
+            .await?;
+        bytes_to_scalars(&bytes_read)
+    }
+
+    async fn send_points(&mut self, points: &[RistrettoPoint]) -> Result<(), MpcNetworkError> {
+        self.write_message(
+            MessageKind::SendPoints,
+            points.len(),
+            &points_to_bytes(points),
+        )
+        .await
+    }
+
+    async fn receive_points(
+        &mut self,
+        num_points: usize,
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .read_message(MessageKind::SendPoints, num_points, BYTES_PER_POINT)
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    async fn broadcast_points(
+        &mut self,
+        points: &[RistrettoPoint],
+    ) -> Result<Vec<RistrettoPoint>, MpcNetworkError> {
+        let bytes_read = self
+            .write_then_read_message(
+                MessageKind::BroadcastPoints,
+                points.len(),
+                BYTES_PER_POINT,
+                &points_to_bytes(points),
+            )
+            .await?;
+        bytes_to_points(&bytes_read)
+    }
+
+    fn record_opening(&mut self, opened_bytes: &[u8]) {
+        if let Some(digest) = self.output_digest.as_mut() {
+            digest.absorb(opened_bytes);
+        }
+    }
+
+    fn output_digest(&self) -> Option<[u8; 32]> {
+        self.output_digest.map(|digest| digest.value())
+    }
+
+    async fn close(&mut self) -> Result<(), MpcNetworkError> {
+        self.stream()?
+            .close(None)
+            .await
+            .map_err(|_| MpcNetworkError::ConnectionTeardownError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+    use rand_core::OsRng;
+
+    use crate::network::MpcNetwork;
+
+    use super::WsTwoPartyNet;
+
+    #[tokio::test]
+    async fn test_websocket_net() {
+        let addr1: SocketAddr = "127.0.0.1:7500".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:7501".parse().unwrap();
+        let mut king = WsTwoPartyNet::new(0, addr1, addr2);
+        let mut peer = WsTwoPartyNet::new(1, addr2, addr1);
+
+        let (king_res, peer_res) = tokio::join!(king.connect(), peer.connect());
+        king_res.unwrap();
+        peer_res.unwrap();
+
+        let mut rng = OsRng {};
+        let scalars = (0..3).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        king.send_scalars(&scalars).await.unwrap();
+        assert_eq!(peer.receive_scalars(3).await.unwrap(), scalars);
+
+        let king_point = RistrettoPoint::random(&mut rng);
+        let peer_point = RistrettoPoint::random(&mut rng);
+        let (king_res, peer_res) = tokio::join!(
+            king.broadcast_single_point(king_point),
+            peer.broadcast_single_point(peer_point)
+        );
+        assert_eq!(king_res.unwrap(), peer_point);
+        assert_eq!(peer_res.unwrap(), king_point);
+
+        king.close().await.unwrap();
+        assert!(peer.receive_single_scalar().await.is_err());
+    }
+}
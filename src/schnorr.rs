@@ -0,0 +1,80 @@
+//! Implements a Schnorr proof of knowledge of a discrete log whose witness is secret shared
+//! between the parties
+//!
+//! For a shared witness [x] and a public generator G the parties jointly prove knowledge of x
+//! such that X = x * G:
+//!     1. The parties sample a shared nonce [k] and commit to and open R = [k] * G
+//!     2. The parties jointly sample the challenge c with a coin flip
+//!     3. The parties commit to and open the response s = [k] + c * [x]
+//!
+//! Anyone holding the proof may then check s * G == R + c * X
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::OsRng;
+
+use crate::{
+    beaver::SharedValueSource, error::MpcError, mpc_scalar::MpcScalar, network::MpcNetwork,
+    Visibility,
+};
+
+/// A Schnorr proof of knowledge of the discrete log of a statement with respect to a generator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Proof {
+    /// The generator that the statement is a multiple of
+    pub generator: RistrettoPoint,
+    /// The commitment to the nonce, R = k * G
+    pub commitment: RistrettoPoint,
+    /// The jointly sampled challenge
+    pub challenge: Scalar,
+    /// The response to the challenge, s = k + c * x
+    pub response: Scalar,
+}
+
+/// Prove knowledge of the shared witness x such that the statement is x * generator
+///
+/// The challenge is sampled by a coin flip after the nonce commitment is opened, so the proof
+/// convinces the parties that took part in the protocol. The nonce is a fresh shared random value,
+/// so the opened response reveals nothing about the witness.
+///
+/// Cost: six rounds of communication; two each to open the nonce commitment, sample the
+/// challenge, and open the response
+pub fn prove_shared<N: MpcNetwork + Send, S: SharedValueSource<Scalar>>(
+    witness: &MpcScalar<N, S>,
+    generator: RistrettoPoint,
+) -> Result<Proof, MpcError> {
+    if witness.is_private() {
+        return Err(MpcError::VisibilityError(
+            "Private witnesses may not be used in a shared proof...".to_string(),
+        ));
+    }
+
+    let network = witness.network();
+    let beaver_source = witness.beaver_source();
+
+    // Each party samples an additive share of the nonce locally
+    let mut rng = OsRng {};
+    let nonce = MpcScalar::from_scalar_with_visibility(
+        Scalar::random(&mut rng),
+        Visibility::Shared,
+        network.clone(),
+        beaver_source.clone(),
+    );
+    let commitment = (&nonce * generator).commit_and_open()?;
+
+    let challenge = MpcScalar::coin_flip(network, beaver_source)?;
+    let response = (&nonce + witness * &challenge).commit_and_open()?;
+
+    Ok(Proof {
+        generator,
+        commitment: commitment.value(),
+        challenge: challenge.value(),
+        response: response.value(),
+    })
+}
+
+/// Verify a proof of knowledge of the discrete log of the statement
+///
+/// This is a public check and requires no communication
+pub fn verify(proof: &Proof, statement: RistrettoPoint) -> bool {
+    proof.response * proof.generator == proof.commitment + proof.challenge * statement
+}
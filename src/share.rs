@@ -0,0 +1,64 @@
+//! Groups the detached shares of values allocated in an MPC network, i.e. a party's share of a
+//! value without the network and value source it was allocated in
+//!
+//! A detached share may be persisted or routed over an application's own transport, then
+//! re-attached to a network and value source, e.g. of a later session with the same peer
+
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Visibility;
+
+/// A party's share of a scalar, detached with `MpcScalar::detach` or
+/// `AuthenticatedScalar::detach`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScalarShare {
+    /// The party's share of the value, or the value itself if it is private or public
+    pub value: Scalar,
+    /// The visibility of the value within the network
+    pub visibility: Visibility,
+    /// The party's share of the value's MAC, if the value was authenticated
+    pub mac_share: Option<Scalar>,
+}
+
+/// A party's share of a Ristretto point, detached with `MpcRistrettoPoint::detach` or
+/// `AuthenticatedRistretto::detach`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PointShare {
+    /// The party's share of the point, or the point itself if it is private or public
+    pub value: RistrettoPoint,
+    /// The visibility of the point within the network
+    pub visibility: Visibility,
+    /// The party's share of the point's MAC, if the point was authenticated
+    pub mac_share: Option<RistrettoPoint>,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+
+    use crate::Visibility;
+
+    use super::{PointShare, ScalarShare};
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let scalar_share = ScalarShare {
+            value: Scalar::from(3u64),
+            visibility: Visibility::Shared,
+            mac_share: Some(Scalar::from(5u64)),
+        };
+        let point_share = PointShare {
+            value: RISTRETTO_BASEPOINT_POINT,
+            visibility: Visibility::Public,
+            mac_share: None,
+        };
+
+        let bytes = bincode::serialize(&(scalar_share, point_share)).unwrap();
+        let res: (ScalarShare, PointShare) = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(res, (scalar_share, point_share));
+    }
+}